@@ -0,0 +1,159 @@
+use std::process::ExitCode;
+
+use crate::commands::index::save_index;
+use crate::index::{self, Function, Index};
+use crate::sanitize::sanitize_summary;
+
+/// Changes `aria annotate` makes to one function
+pub struct AnnotateOptions {
+    /// Replace the summary with this text
+    pub summary: Option<String>,
+    /// Keep the summary as it is through reindexing and summarization
+    pub lock: bool,
+    /// Let summarization regenerate the summary again
+    pub unlock: bool,
+}
+
+pub fn run(qualified_name: &str, opts: &AnnotateOptions) -> ExitCode {
+    let mut index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let report = match annotate(&mut index, qualified_name, opts) {
+        Ok(func) => describe(func, opts),
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Err(e) = save_index(index::index_path(), &index) {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    println!("{report}");
+    ExitCode::SUCCESS
+}
+
+/// Apply `opts` to the function named exactly `qualified_name`
+fn annotate<'a>(index: &'a mut Index, qualified_name: &str, opts: &AnnotateOptions) -> Result<&'a Function, String> {
+    let mut matches: Vec<(&str, &mut Function)> = index
+        .files
+        .iter_mut()
+        .flat_map(|(path, entry)| entry.functions.iter_mut().map(move |f| (path.as_str(), f)))
+        .filter(|(_, f)| f.qualified_name == qualified_name)
+        .collect();
+
+    let func = match matches.len() {
+        0 => return Err(format!("no function named '{qualified_name}' (annotate takes an exact qualified name)")),
+        1 => matches.pop().unwrap().1,
+        _ => {
+            let mut files: Vec<&str> = matches.iter().map(|(path, _)| *path).collect();
+            files.sort();
+            return Err(format!("'{qualified_name}' is defined in several files: {}", files.join(", ")));
+        }
+    };
+
+    if let Some(summary) = &opts.summary {
+        let summary = sanitize_summary(summary.trim());
+        if summary.is_empty() {
+            return Err("summary is empty".to_string());
+        }
+        func.summary = Some(summary.into_owned());
+        func.summary_hash = Some(func.ast_hash.clone());
+        func.summary_shared_from = None;
+    }
+
+    if opts.lock {
+        if func.summary.is_none() {
+            return Err(format!("{qualified_name} has no summary to lock (pass --summary)"));
+        }
+        func.summary_locked = true;
+    } else if opts.unlock {
+        func.summary_locked = false;
+    }
+
+    Ok(func)
+}
+
+fn describe(func: &Function, opts: &AnnotateOptions) -> String {
+    let mut changes = Vec::new();
+    if opts.summary.is_some() {
+        changes.push("summary updated");
+    }
+    if opts.lock {
+        changes.push("locked");
+    } else if opts.unlock {
+        changes.push("unlocked");
+    }
+    format!("{}: {}", func.qualified_name, changes.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::index::preserve_summaries;
+    use crate::index::FileEntry;
+
+    fn func(name: &str, ast_hash: &str) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("pkg.{}", name),
+            ast_hash: ast_hash.to_string(),
+            line_start: 1,
+            line_end: 2,
+            summary: Some(format!("Generated summary of {name}.")),
+            summary_hash: Some(ast_hash.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn index_of(functions: Vec<Function>) -> Index {
+        let mut index = Index::new();
+        let entry = FileEntry {
+            functions,
+            ..Default::default()
+        };
+        index.files.insert("./pkg/a.go".to_string(), entry);
+        index
+    }
+
+    #[test]
+    fn test_locked_summary_survives_body_change() {
+        let mut old = index_of(vec![func("Parse", "h1"), func("Other", "h2")]);
+        let opts = AnnotateOptions { summary: Some("Parses a  header.\n".to_string()), lock: true, unlock: false };
+        let annotated = annotate(&mut old, "pkg.Parse", &opts).unwrap();
+        assert_eq!(annotated.summary.as_deref(), Some("Parses a header."));
+        assert!(annotated.summary_locked);
+
+        // Both bodies changed: the locked summary is kept (and now stale), the other is dropped
+        let mut new = index_of(vec![func("Parse", "h1b"), func("Other", "h2b")]);
+        for f in &mut new.files.get_mut("./pkg/a.go").unwrap().functions {
+            f.summary = None;
+            f.summary_hash = None;
+        }
+        preserve_summaries(&mut new, Some(&old));
+        let functions = &new.files["./pkg/a.go"].functions;
+        assert_eq!(functions[0].summary.as_deref(), Some("Parses a header."));
+        assert_eq!(functions[0].summary_hash.as_deref(), Some("h1"));
+        assert!(functions[0].summary_locked);
+        assert!(functions[1].summary.is_none());
+    }
+
+    #[test]
+    fn test_annotate_errors() {
+        let mut index = index_of(vec![func("Parse", "h1")]);
+        index.files.insert("./pkg/b.go".to_string(), index.files["./pkg/a.go"].clone());
+        let lock = AnnotateOptions { summary: None, lock: true, unlock: false };
+
+        let err = annotate(&mut index, "Parse", &lock).err().unwrap();
+        assert!(err.starts_with("no function named 'Parse'"));
+        let err = annotate(&mut index, "pkg.Parse", &lock).err().unwrap();
+        assert_eq!(err, "'pkg.Parse' is defined in several files: ./pkg/a.go, ./pkg/b.go");
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::commands::index::save_index;
+use crate::externals::{self, KINDS, UserExternal, external_name, is_external_target};
+use crate::index::{self, ExternalEntry, Index};
+use crate::sanitize::sanitize_summary;
+
+/// Close matches listed when the target is not in the index
+const MAX_SUGGESTIONS: usize = 5;
+
+/// What `aria annotate-external` records for one external
+pub struct AnnotateExternalOptions {
+    /// Description shown wherever the external is called
+    pub summary: String,
+    /// Recategorize the external; keeps its current kind when None
+    pub kind: Option<String>,
+}
+
+pub fn run(target: &str, opts: &AnnotateExternalOptions) -> ExitCode {
+    let mut index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let aria_dir = Path::new(".aria");
+    let mut user = match externals::load_user_externals(aria_dir) {
+        Ok(user) => user,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (name, external, call_sites) = match annotate(&mut index, target, opts) {
+        Ok(annotation) => annotation,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let kind = external.kind.clone();
+    user.insert(name.clone(), external);
+
+    let saved = save_index(index::index_path(), &index).and_then(|_| externals::save_user_externals(aria_dir, &user));
+    if let Err(e) = saved {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    let plural = if call_sites == 1 { "" } else { "s" };
+    println!(
+        "[{kind}:{name}]: summary updated ({call_sites} call site{plural}), saved to .aria/{}",
+        externals::USER_EXTERNALS_FILE
+    );
+    ExitCode::SUCCESS
+}
+
+/// Record `opts` for the external `target` ("[kind:name]" or a bare name) in the index.
+/// Returns the external's name as called, its new description, and the number of calls to it.
+fn annotate(
+    index: &mut Index,
+    target: &str,
+    opts: &AnnotateExternalOptions,
+) -> Result<(String, UserExternal, usize), String> {
+    let target = target.trim();
+    let name = external_name(target);
+    if let Some(kind) = &opts.kind
+        && !KINDS.contains(&kind.as_str())
+    {
+        return Err(format!("unknown kind '{kind}' (expected {})", KINDS.join(", ")));
+    }
+    let summary = sanitize_summary(opts.summary.trim());
+    if summary.is_empty() {
+        return Err("summary is empty".to_string());
+    }
+
+    // Calls to the external, matched on the full "[kind:name]" when one was given
+    let matching: Vec<&str> = index
+        .files
+        .values()
+        .flat_map(|e| e.functions.iter().flat_map(|f| f.calls.iter()))
+        .filter(|c| is_external_target(&c.target) && external_name(&c.target) == name && (!target.starts_with('[') || c.target == target))
+        .map(|c| c.target.as_str())
+        .collect();
+    let Some(current) = matching.first() else {
+        return Err(not_found(index, target));
+    };
+
+    let kind = match &opts.kind {
+        Some(kind) => kind.clone(),
+        None => current.trim_start_matches('[').split(':').next().unwrap_or("external").to_string(),
+    };
+    let call_sites = matching.len();
+    let external = UserExternal { kind: kind.clone(), summary: summary.into_owned() };
+
+    // Indexes that predate the call showing up in the externals table still get an entry
+    index.externals.entry(name.to_string()).or_insert_with(|| ExternalEntry {
+        kind,
+        summary: None,
+        references: call_sites as u32,
+    });
+    externals::apply_user_externals(index, &BTreeMap::from([(name.to_string(), external.clone())]));
+
+    Ok((name.to_string(), external, call_sites))
+}
+
+/// Error for a target no call in the index has, with the closest external targets
+fn not_found(index: &Index, target: &str) -> String {
+    let name = external_name(target);
+    let targets: BTreeSet<&str> = index
+        .files
+        .values()
+        .flat_map(|e| e.functions.iter().flat_map(|f| f.calls.iter()))
+        .filter(|c| is_external_target(&c.target))
+        .map(|c| c.target.as_str())
+        .collect();
+
+    let limit = (name.chars().count() / 3).max(2);
+    let mut close: Vec<(usize, &str)> = targets
+        .into_iter()
+        .filter_map(|t| {
+            let candidate = external_name(t);
+            let distance = edit_distance(&candidate.to_lowercase(), &name.to_lowercase());
+            let contains = candidate.contains(name) || name.contains(candidate);
+            (distance <= limit || contains).then_some((distance, t))
+        })
+        .collect();
+    close.sort();
+    close.truncate(MAX_SUGGESTIONS);
+
+    let mut message = format!("no external call '{target}' in the index");
+    if !close.is_empty() {
+        let names: Vec<&str> = close.iter().map(|(_, t)| *t).collect();
+        message.push_str(&format!("; did you mean: {}?", names.join(", ")));
+    }
+    message
+}
+
+/// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb)).min(above + 1).min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{CallSite, FileEntry, Function};
+    use crate::resolver::Resolver;
+
+    fn func(name: &str, calls: &[&str]) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("pkg.{name}"),
+            line_start: 1,
+            line_end: 9,
+            calls: calls
+                .iter()
+                .enumerate()
+                .map(|(i, raw)| CallSite {
+                    target: "[unresolved]".to_string(),
+                    raw: raw.to_string(),
+                    callee_name: raw.to_string(),
+                    line: i as u32 + 2,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// A resolved C index: compute_hmac is external, malloc is libc
+    fn resolved_index() -> Index {
+        let mut index = Index::new();
+        index.files.insert(
+            "./src/sign.c".to_string(),
+            FileEntry {
+                functions: vec![func("sign", &["compute_hmac", "malloc"]), func("verify", &["compute_hmac"])],
+                ..Default::default()
+            },
+        );
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+        index
+    }
+
+    fn opts(summary: &str, kind: Option<&str>) -> AnnotateExternalOptions {
+        AnnotateExternalOptions { summary: summary.to_string(), kind: kind.map(String::from) }
+    }
+
+    #[test]
+    fn test_annotate_external_updates_index_and_survives_reresolve() {
+        let mut index = resolved_index();
+        let summary = "Computes HMAC-SHA256 via the vendored crypto shim";
+
+        let (name, external, call_sites) =
+            annotate(&mut index, "[external:compute_hmac]", &opts(summary, Some("libc"))).unwrap();
+        assert_eq!((name.as_str(), external.kind.as_str(), call_sites), ("compute_hmac", "libc", 2));
+        assert_eq!(index.externals["compute_hmac"].summary.as_deref(), Some(summary));
+        assert_eq!(index.files["./src/sign.c"].functions[1].calls[0].target, "[libc:compute_hmac]");
+
+        // A rebuilt index loses the annotation until the user file is applied again
+        let mut rebuilt = resolved_index();
+        externals::apply_user_externals(&mut rebuilt, &BTreeMap::from([(name, external)]));
+        assert_eq!(rebuilt.externals["compute_hmac"].kind, "libc");
+        assert_eq!(rebuilt.externals["compute_hmac"].summary.as_deref(), Some(summary));
+        assert_eq!(rebuilt.files["./src/sign.c"].functions[0].calls[0].target, "[libc:compute_hmac]");
+    }
+
+    #[test]
+    fn test_annotate_external_validates_target() {
+        let mut index = resolved_index();
+
+        // A bare name keeps the current kind
+        let (_, external, _) = annotate(&mut index, "malloc", &opts("Allocates.", None)).unwrap();
+        assert_eq!(external.kind, "libc");
+
+        let e = annotate(&mut index, "[external:compute_hmc]", &opts("x", None)).unwrap_err();
+        assert_eq!(e, "no external call '[external:compute_hmc]' in the index; did you mean: [external:compute_hmac]?");
+        // The kind is part of a bracketed target
+        assert!(annotate(&mut index, "[libc:compute_hmac]", &opts("x", None)).is_err());
+        assert!(annotate(&mut index, "compute_hmac", &opts("x", Some("ffi"))).unwrap_err().contains("unknown kind"));
+        assert_eq!(annotate(&mut index, "compute_hmac", &opts("  ", None)).unwrap_err(), "summary is empty");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("compute_hmac", "compute_hmac"), 0);
+        assert_eq!(edit_distance("compute_hmc", "compute_hmac"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,152 @@
+use std::hint::black_box;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::commands::{callstack, stats, usages};
+use crate::format::Formatter;
+use crate::index::{self, Function, Index};
+
+/// Depth of the benchmarked forward trace
+const TRACE_DEPTH: usize = 3;
+
+/// Options controlling `aria bench`
+pub struct BenchOptions {
+    /// Times each operation is run
+    pub iterations: usize,
+    /// Emit JSON instead of a table
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Timing {
+    name: String,
+    iterations: usize,
+    mean_ms: f64,
+    min_ms: f64,
+}
+
+pub fn run(opts: &BenchOptions) -> ExitCode {
+    let iterations = opts.iterations.max(1);
+
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut timings = Vec::new();
+    let fmt = Formatter::new(false);
+
+    // Named for the format and layout on disk, as `aria stats` reports them
+    let load_label = match stats::storage(index::index_path(), &index.shards, 0.0) {
+        Some(storage) => match storage.shards {
+            0 => format!("load index ({})", storage.format),
+            1 => format!("load index ({}, 1 shard)", storage.format),
+            n => format!("load index ({}, {n} shards)", storage.format),
+        },
+        None => "load index".to_string(),
+    };
+    timings.push(measure(&load_label, iterations, || {
+        let _ = black_box(index::load_index());
+    }));
+    timings.push(measure("build function map", iterations, || {
+        black_box(index::build_function_map(&index));
+    }));
+
+    if let Some(func) = max_by_key(&index, |f| f.calls.len()) {
+        let query = func.qualified_name.clone();
+        timings.push(measure(&format!("lookup {query}"), iterations, || {
+            let _ = black_box(index::lookup_functions(&index, &query));
+        }));
+
+        let func_map = index::build_function_map(&index);
+        let (file, func) = func_map[query.as_str()];
+        timings.push(measure(&format!("trace -f -d {TRACE_DEPTH} {query}"), iterations, || {
+            black_box(callstack::render_forward(&func_map, &index, file, func, TRACE_DEPTH, &fmt, false));
+        }));
+    }
+
+    if let Some(func) = max_by_key(&index, |f| f.called_by.len()) {
+        let func_map = index::build_function_map(&index);
+        let (file, func) = func_map[func.qualified_name.as_str()];
+        timings.push(measure(&format!("usages {}", func.qualified_name), iterations, || {
+            black_box(usages::render(&func_map, file, func, false, usages::TestCallers::Include, &fmt));
+        }));
+    }
+
+    if opts.json {
+        return match serde_json::to_string_pretty(&timings) {
+            Ok(out) => {
+                println!("{out}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let width = timings.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    println!("{:<width$}  {:>10}  {:>10}", "operation", "mean", "min");
+    for t in &timings {
+        println!("{:<width$}  {:>8.3}ms  {:>8.3}ms", t.name, t.mean_ms, t.min_ms);
+    }
+    println!();
+    println!("{} iterations, {} files", iterations, index.files.len());
+
+    ExitCode::SUCCESS
+}
+
+/// Run `op` `iterations` times and record mean and minimum wall time
+fn measure(name: &str, iterations: usize, mut op: impl FnMut()) -> Timing {
+    let mut total = Duration::ZERO;
+    let mut min = Duration::MAX;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        op();
+        let elapsed = start.elapsed();
+        total += elapsed;
+        min = min.min(elapsed);
+    }
+
+    Timing {
+        name: name.to_string(),
+        iterations,
+        mean_ms: round_us(total.as_secs_f64() * 1000.0 / iterations as f64),
+        min_ms: round_us(min.as_secs_f64() * 1000.0),
+    }
+}
+
+/// Round milliseconds to microsecond precision
+fn round_us(ms: f64) -> f64 {
+    (ms * 1000.0).round() / 1000.0
+}
+
+/// Function with the largest `key`, ties broken by qualified name for stable output
+fn max_by_key(index: &Index, key: impl Fn(&Function) -> usize) -> Option<&Function> {
+    index
+        .files
+        .values()
+        .flat_map(|e| e.functions.iter())
+        .max_by(|a, b| key(a).cmp(&key(b)).then_with(|| b.qualified_name.cmp(&a.qualified_name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_counts_iterations() {
+        let mut runs = 0;
+        let timing = measure("noop", 4, || runs += 1);
+        assert_eq!(runs, 4);
+        assert_eq!(timing.iterations, 4);
+        assert!(timing.min_ms <= timing.mean_ms);
+        assert_eq!(round_us(1.23456), 1.235);
+    }
+}
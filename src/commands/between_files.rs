@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+use crate::index::{self, Function, Index};
+
+/// Call edges crossing the boundary between two files, in both directions
+#[derive(Debug, Serialize)]
+struct Boundary<'a> {
+    a: &'a str,
+    b: &'a str,
+    a_to_b: Vec<Edge<'a>>,
+    b_to_a: Vec<Edge<'a>>,
+}
+
+/// One call site in one file whose target is defined in the other
+#[derive(Debug, PartialEq, Serialize)]
+struct Edge<'a> {
+    caller: &'a str,
+    /// Line of the call site
+    line: u32,
+    callee: &'a str,
+    callee_line: u32,
+    /// The call matched several functions and this is one of the candidates
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    ambiguous: bool,
+}
+
+pub fn run(a: &str, b: &str, json: bool, ambiguous: bool) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let resolve = |input: &str| index::resolve_file_key(&index, input).ok_or(format!("file '{input}' is not indexed"));
+    let (a, b) = match (resolve(a), resolve(b)) {
+        (Ok(a), Ok(b)) if a == b => {
+            eprintln!("error: both paths name {a}");
+            return ExitCode::FAILURE;
+        }
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let func_map = index::build_function_map(&index);
+    let boundary = Boundary {
+        a,
+        b,
+        a_to_b: edges(&index, &func_map, a, b, ambiguous),
+        b_to_a: edges(&index, &func_map, b, a, ambiguous),
+    };
+
+    if json {
+        return match serde_json::to_string_pretty(&boundary) {
+            Ok(out) => {
+                println!("{out}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    for (i, (from, to, edges)) in [(a, b, &boundary.a_to_b), (b, a, &boundary.b_to_a)].into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{from} -> {to} ({} {})", edges.len(), if edges.len() == 1 { "call" } else { "calls" });
+        for edge in edges {
+            let flag = if edge.ambiguous { " [ambiguous]" } else { "" };
+            println!("  {} (line {}) -> {} ({to}:{}){flag}", edge.caller, edge.line, edge.callee, edge.callee_line);
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Call sites in `from` whose target is defined in `to`, in caller then line order. With
+/// `ambiguous`, a call matching several functions counts once for each candidate in `to`.
+fn edges<'a>(
+    index: &'a Index,
+    func_map: &HashMap<&str, (&'a str, &'a Function)>,
+    from: &str,
+    to: &str,
+    ambiguous: bool,
+) -> Vec<Edge<'a>> {
+    let mut edges = Vec::new();
+    for func in &index.files[from].functions {
+        for call in &func.calls {
+            let targets: &[String] = if ambiguous && !call.candidates.is_empty() {
+                &call.candidates
+            } else {
+                std::slice::from_ref(&call.target)
+            };
+            for target in targets {
+                if let Some((file, callee)) = func_map.get(target.as_str())
+                    && *file == to
+                {
+                    edges.push(Edge {
+                        caller: &func.qualified_name,
+                        line: call.line,
+                        callee: &callee.qualified_name,
+                        callee_line: callee.line_start,
+                        ambiguous: !call.candidates.is_empty(),
+                    });
+                }
+            }
+        }
+    }
+    edges.sort_by(|x, y| (x.caller, x.line, x.callee).cmp(&(y.caller, y.line, y.callee)));
+    edges
+}
+
+#[cfg(all(test, feature = "lang-go"))]
+mod tests {
+    use super::*;
+    use crate::resolver::Resolver;
+
+    #[test]
+    fn test_edges_in_both_directions() {
+        let sources = [
+            ("./app/a.go", "package app\n\nfunc Run() {\n\thelper()\n\tlocal()\n\tSave()\n}\n\nfunc local() {}\n"),
+            ("./app/b.go", "package app\n\nfunc helper() {\n\tlocal()\n}\n\nfunc (s *Store) Save() {}\n"),
+            ("./app/c.go", "package app\n\nfunc (f *File) Save() {}\n"),
+        ];
+        let mut parser = crate::parser::GoParser::new();
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+        let func_map = index::build_function_map(&index);
+
+        let a_to_b = edges(&index, &func_map, "./app/a.go", "./app/b.go", false);
+        let edge = |caller, line, callee, callee_line, ambiguous| Edge { caller, line, callee, callee_line, ambiguous };
+        assert_eq!(a_to_b, vec![edge("app.Run", 4, "app.helper", 3, false)]);
+        assert_eq!(edges(&index, &func_map, "./app/b.go", "./app/a.go", false), vec![
+            edge("app.helper", 4, "app.local", 9, false)
+        ]);
+
+        // Save() matches both receivers; only --ambiguous shows the candidate in b.go
+        let with_candidates = edges(&index, &func_map, "./app/a.go", "./app/b.go", true);
+        assert_eq!(with_candidates[1], edge("app.Run", 6, "app.Store.Save", 7, true));
+    }
+}
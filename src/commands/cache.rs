@@ -0,0 +1,290 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::SystemTime;
+
+use serde::Serialize;
+use walkdir::WalkDir;
+
+/// Summaries generated by `aria index` and `aria update`, one file per model and prompt
+const SUMMARIES: &str = "summaries";
+
+/// Cache categories, each a directory under `.aria/cache/`, with what they hold
+const CATEGORIES: &[(&str, &str)] = &[(SUMMARIES, "generated summaries, keyed by model and prompt")];
+
+/// Size and entry count of one cache category
+#[derive(Debug, PartialEq, Serialize)]
+struct CategoryStats {
+    name: &'static str,
+    path: String,
+    description: &'static str,
+    entries: usize,
+    bytes: u64,
+}
+
+/// One cached file, with its last use for eviction
+struct Entry {
+    path: PathBuf,
+    bytes: u64,
+    /// `read_summary` touches an entry on every hit, so the modification time is its last use
+    last_used: SystemTime,
+}
+
+/// Print size and entry count per category, with the directory each lives in
+pub fn stats(json: bool) -> ExitCode {
+    let cache_dir = cache_dir();
+    let stats = category_stats(&cache_dir);
+
+    if json {
+        return match serde_json::to_string_pretty(&stats) {
+            Ok(out) => {
+                println!("{out}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    println!("Cache: {}", cache_dir.display());
+    for category in &stats {
+        println!(
+            "  {:<11} {:<26} {:>6} {:>9}  {}",
+            category.name,
+            category.path,
+            plural(category.entries, "entry", "entries"),
+            format_size(category.bytes),
+            category.description
+        );
+    }
+    let entries: usize = stats.iter().map(|c| c.entries).sum();
+    let bytes: u64 = stats.iter().map(|c| c.bytes).sum();
+    println!("Total: {}, {}", plural(entries, "entry", "entries"), format_size(bytes));
+    ExitCode::SUCCESS
+}
+
+/// Remove every entry of `category`, or of all categories for "all"
+pub fn clear(category: &str) -> ExitCode {
+    let names: Vec<&str> = match category {
+        "all" => CATEGORIES.iter().map(|(name, _)| *name).collect(),
+        name if CATEGORIES.iter().any(|(known, _)| *known == name) => vec![name],
+        _ => {
+            eprintln!("error: unknown cache category '{category}' (expected summaries or all)");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let cache_dir = cache_dir();
+    let mut removed = Vec::new();
+    for name in names {
+        let entries = entries(&cache_dir.join(name));
+        if let Err(e) = remove_entries(&entries) {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+        removed.extend(entries);
+    }
+
+    let bytes: u64 = removed.iter().map(|e| e.bytes).sum();
+    println!("Removed {} ({})", plural(removed.len(), "entry", "entries"), format_size(bytes));
+    ExitCode::SUCCESS
+}
+
+/// Evict least recently used entries until the cache is at most `max_mb` megabytes
+pub fn gc(max_mb: u64) -> ExitCode {
+    let cache_dir = cache_dir();
+    let (evicted, remaining) = match evict(&cache_dir, max_mb * 1024 * 1024) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let bytes: u64 = evicted.iter().map(|e| e.bytes).sum();
+    println!(
+        "Evicted {} ({}); cache is now {} of {} MB",
+        plural(evicted.len(), "entry", "entries"),
+        format_size(bytes),
+        format_size(remaining),
+        max_mb
+    );
+    ExitCode::SUCCESS
+}
+
+pub fn cache_dir() -> PathBuf {
+    Path::new(".aria").join("cache")
+}
+
+/// Key of the summary `model` gave for `prompt`
+pub fn summary_key(model: &str, prompt: &str) -> String {
+    crate::parser::content_hash(format!("{model}\n{prompt}").as_bytes())
+}
+
+/// The cached summary under `key`, touched so that gc evicts it last
+pub fn read_summary(cache_dir: &Path, key: &str) -> Option<String> {
+    let path = summary_path(cache_dir, key);
+    let summary = fs::read_to_string(&path).ok()?;
+    let _ = fs::File::options().write(true).open(&path).and_then(|file| file.set_modified(SystemTime::now()));
+    Some(summary)
+}
+
+/// Cache a generated summary under `key`; a failed write only costs a request later
+pub fn write_summary(cache_dir: &Path, key: &str, summary: &str) {
+    let path = summary_path(cache_dir, key);
+    if let Some(dir) = path.parent()
+        && fs::create_dir_all(dir).is_ok()
+    {
+        let _ = fs::write(&path, summary);
+    }
+}
+
+/// Entries are spread over subdirectories by the first two characters of their key
+fn summary_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(SUMMARIES).join(&key[..2]).join(format!("{key}.txt"))
+}
+
+fn category_stats(cache_dir: &Path) -> Vec<CategoryStats> {
+    CATEGORIES
+        .iter()
+        .map(|(name, description)| {
+            let dir = cache_dir.join(name);
+            let entries = entries(&dir);
+            CategoryStats {
+                name,
+                path: format!("{}/", dir.display()),
+                description,
+                entries: entries.len(),
+                bytes: entries.iter().map(|e| e.bytes).sum(),
+            }
+        })
+        .collect()
+}
+
+/// Every file under `dir`; a missing directory is an empty category
+fn entries(dir: &Path) -> Vec<Entry> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some(Entry {
+                path: e.into_path(),
+                bytes: meta.len(),
+                last_used: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            })
+        })
+        .collect()
+}
+
+/// Remove the least recently used entries across all categories until the total is at
+/// most `max_bytes`. Returns the evicted entries and the size left.
+fn evict(cache_dir: &Path, max_bytes: u64) -> Result<(Vec<Entry>, u64), String> {
+    let mut all: Vec<Entry> = CATEGORIES.iter().flat_map(|(name, _)| entries(&cache_dir.join(name))).collect();
+    all.sort_by(|a, b| a.last_used.cmp(&b.last_used).then_with(|| a.path.cmp(&b.path)));
+
+    let mut total: u64 = all.iter().map(|e| e.bytes).sum();
+    let mut evicted = Vec::new();
+    for entry in all {
+        if total <= max_bytes {
+            break;
+        }
+        total -= entry.bytes;
+        evicted.push(entry);
+    }
+
+    remove_entries(&evicted)?;
+    Ok((evicted, total))
+}
+
+fn remove_entries(entries: &[Entry]) -> Result<(), String> {
+    for entry in entries {
+        fs::remove_file(&entry.path).map_err(|e| format!("failed to remove {}: {e}", entry.path.display()))?;
+    }
+    Ok(())
+}
+
+fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{n} {}", if n == 1 { one } else { many })
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{b} B"),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn write_entry(path: &Path, bytes: usize, age_secs: u64) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![b'x'; bytes]).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age_secs)).unwrap();
+    }
+
+    #[test]
+    fn test_stats_and_lru_eviction() {
+        let dir = std::env::temp_dir().join(format!("aria-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        write_entry(&dir.join("summaries/ab/abcd.txt"), 400, 30);
+        write_entry(&dir.join("summaries/cd/cdef.txt"), 400, 10);
+        write_entry(&dir.join("summaries/12/1234.txt"), 300, 20);
+
+        let stats = category_stats(&dir);
+        let counts: Vec<(&str, usize, u64)> = stats.iter().map(|c| (c.name, c.entries, c.bytes)).collect();
+        assert_eq!(counts, vec![("summaries", 3, 1100)]);
+        assert_eq!(stats[0].path, format!("{}/", dir.join("summaries").display()));
+
+        // Oldest first until under the bound
+        let (evicted, remaining) = evict(&dir, 500).unwrap();
+        let names: Vec<_> = evicted.iter().map(|e| e.path.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, vec!["abcd.txt", "1234.txt"]);
+        assert_eq!(remaining, 400);
+        assert!(dir.join("summaries/cd/cdef.txt").exists());
+        assert!(!dir.join("summaries/12/1234.txt").exists());
+
+        assert!(evict(&dir, 500).unwrap().0.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_summary_hits_refresh_last_use() {
+        let dir = std::env::temp_dir().join(format!("aria-cache-summaries-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let old = summary_key("haiku", "Function: func Old()");
+        let hit = summary_key("haiku", "Function: func Hit()");
+        assert_ne!(summary_key("sonnet", "Function: func Hit()"), hit);
+        assert_eq!(read_summary(&dir, &hit), None);
+
+        write_summary(&dir, &old, "Does old things.");
+        write_summary(&dir, &hit, "Does hit things.");
+        for key in [&old, &hit] {
+            let file = fs::File::options().write(true).open(summary_path(&dir, key)).unwrap();
+            file.set_modified(SystemTime::now() - Duration::from_secs(60)).unwrap();
+        }
+
+        // Reading an entry makes it the most recently used, so gc evicts the other one
+        assert_eq!(read_summary(&dir, &hit).as_deref(), Some("Does hit things."));
+        let (evicted, _) = evict(&dir, 20).unwrap();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].path, summary_path(&dir, &old));
+        assert!(read_summary(&dir, &hit).is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+    }
+}
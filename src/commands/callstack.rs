@@ -1,10 +1,41 @@
-use std::collections::HashSet;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::process::ExitCode;
 
-use crate::externals::ExternalDb;
-use crate::index::{self, Function, Index};
+use serde::Serialize;
 
-pub fn run(name: &str, forward: bool, backward: bool, depth: usize) -> ExitCode {
+use crate::commands::context::estimate_tokens;
+use crate::commands::filetrace;
+use crate::commands::topo::build_call_graph;
+use crate::externals::{ExternalDb, Language};
+use crate::format::Formatter;
+use crate::freshness;
+use crate::index::{self, CallSite, Function, Index};
+use crate::topo;
+
+/// Options controlling what `aria trace` prints
+pub struct TraceOptions {
+    /// Only show the forward trace (callees)
+    pub forward: bool,
+    /// Only show the backward trace (callers)
+    pub backward: bool,
+    /// Depth limit, 0 = unlimited
+    pub depth: usize,
+    /// Aggregate the forward trace by file
+    pub files: bool,
+    /// Emit JSON instead of text
+    pub json: bool,
+    /// Do not truncate long names
+    pub full: bool,
+    /// Append where in the caller each call happens
+    pub call_sites: bool,
+    /// Only show forward branches that reach this function
+    pub to: Option<String>,
+    /// Expand the forward trace breadth-first until about this many tokens
+    pub fit_tokens: Option<usize>,
+}
+
+pub fn run(name: &str, opts: &TraceOptions) -> ExitCode {
     let index = match index::load_index() {
         Ok(idx) => idx,
         Err(e) => {
@@ -14,70 +45,193 @@ pub fn run(name: &str, forward: bool, backward: bool, depth: usize) -> ExitCode
     };
 
     let func_map = index::build_function_map(&index);
-    let matches = index::find_functions(&index, name);
+    let matches = match index::lookup_functions(&index, name).and_then(|m| index::prefer_entry_points(m, name)) {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    freshness::check(&index, matches.iter().map(|(file, _)| *file));
+    let max_depth = if opts.depth == 0 { usize::MAX } else { opts.depth };
+
+    let fmt = Formatter::new(opts.full);
+
+    if opts.files {
+        return filetrace::print(&func_map, &matches, max_depth, &fmt, opts.json);
+    }
 
-    if matches.is_empty() {
-        eprintln!("No function found matching '{name}'");
-        return ExitCode::FAILURE;
+    if let Some(to) = &opts.to {
+        return print_paths_to(&func_map, &index, &matches, to, max_depth, &fmt, opts.call_sites);
     }
 
-    let max_depth = if depth == 0 { usize::MAX } else { depth };
-    let show_both = !forward && !backward;
+    if let Some(budget) = opts.fit_tokens {
+        // Matches share the budget evenly
+        let budget = budget / matches.len();
+        for (i, (file_path, func)) in matches.iter().enumerate() {
+            if matches.len() > 1 {
+                if i > 0 {
+                    println!();
+                }
+                println!("=== {} ({}:{}-{}) ===", fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end);
+            }
+            let trace = ForwardTrace::new(&func_map, &index, max_depth, &fmt, None, opts.call_sites);
+            for line in forward_lines_within(&trace, file_path, func, budget) {
+                println!("{line}");
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let show_both = !opts.forward && !opts.backward;
+
+    if opts.json {
+        let traces: Vec<TraceJson> = matches
+            .iter()
+            .map(|(file, func)| TraceJson {
+                function: &func.qualified_name,
+                file,
+                line_start: func.line_start,
+                line_end: func.line_end,
+                callers: (opts.backward || show_both).then(|| {
+                    let walk = CallerWalk { func_map: &func_map, max_depth, fmt: &fmt, call_sites: true };
+                    let mut visited = HashSet::from([func.qualified_name.as_str()]);
+                    callers_json(&walk, func, 1, &mut visited)
+                }),
+                calls: (opts.forward || show_both).then(|| {
+                    let trace = ForwardTrace::new(&func_map, &index, max_depth, &fmt, None, true);
+                    let mut visited = HashSet::from([func.qualified_name.as_str()]);
+                    calls_json(&trace, func, 1, &mut visited)
+                }),
+            })
+            .collect();
+        return match serde_json::to_string_pretty(&traces) {
+            Ok(out) => {
+                println!("{out}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
 
     for (i, (file_path, func)) in matches.iter().enumerate() {
         if matches.len() > 1 {
             if i > 0 {
                 println!();
             }
-            println!("=== {} ({}:{}-{}) ===", func.qualified_name, file_path, func.line_start, func.line_end);
+            println!("=== {} ({}:{}-{}) ===", fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end);
+        }
+
+        if opts.backward || show_both {
+            for line in render_backward(&func_map, file_path, func, max_depth, &fmt, opts.call_sites) {
+                println!("{line}");
+            }
+        }
+
+        if opts.forward || show_both {
+            if (opts.backward || show_both) && !func.called_by.is_empty() {
+                println!();
+            }
+            for line in render_forward(&func_map, &index, file_path, func, max_depth, &fmt, opts.call_sites) {
+                println!("{line}");
+            }
         }
+    }
 
-        if backward || show_both {
-            print_backward(&func_map, file_path, func, max_depth);
+    ExitCode::SUCCESS
+}
+
+/// Print the forward trace of each match, pruned to the branches that reach `to`
+fn print_paths_to(
+    func_map: &HashMap<&str, (&str, &Function)>,
+    index: &Index,
+    matches: &[(&str, &Function)],
+    to: &str,
+    max_depth: usize,
+    fmt: &Formatter,
+    call_sites: bool,
+) -> ExitCode {
+    let targets: HashSet<String> = match index::lookup_functions(index, to) {
+        Ok(found) => found.iter().map(|(_, f)| f.qualified_name.clone()).collect(),
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
         }
+    };
+    let (_, calls) = build_call_graph(index);
+    let reaching = topo::reaching(&targets, &calls);
 
-        if forward || show_both {
-            if (backward || show_both) && !func.called_by.is_empty() {
+    for (i, (file_path, func)) in matches.iter().enumerate() {
+        if matches.len() > 1 {
+            if i > 0 {
                 println!();
             }
-            print_forward(&func_map, &index, file_path, func, max_depth);
+            println!("=== {} ({}:{}-{}) ===", fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end);
+        }
+        let target = TraceTarget { names: &targets, reaching: &reaching, reached: Cell::new(false) };
+        let trace = ForwardTrace::new(func_map, index, max_depth, fmt, Some(target), call_sites);
+        for line in forward_lines(&trace, file_path, func) {
+            println!("{line}");
         }
     }
 
     ExitCode::SUCCESS
 }
 
-fn print_backward(
-    func_map: &std::collections::HashMap<&str, (&str, &Function)>,
+/// Lines of the backward trace: `func` and its callers as a tree
+pub fn render_backward(
+    func_map: &HashMap<&str, (&str, &Function)>,
     file_path: &str,
     func: &Function,
     max_depth: usize,
-) {
-    println!(
+    fmt: &Formatter,
+    call_sites: bool,
+) -> Vec<String> {
+    let mut out = Vec::new();
+    out.push(format!(
         "{} ({}:{}-{})",
-        func.qualified_name, file_path, func.line_start, func.line_end
-    );
+        fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end
+    ));
 
     if func.called_by.is_empty() {
-        println!("  (no callers found)");
-        return;
+        out.push("  (no callers found)".to_string());
+        return out;
     }
 
-    println!("  called by:");
+    out.push("  called by:".to_string());
     let mut visited = HashSet::new();
     visited.insert(func.qualified_name.as_str());
-    print_callers(func_map, func, "  ", max_depth, 1, &mut visited);
+    let walk = CallerWalk { func_map, max_depth, fmt, call_sites };
+    render_callers(&walk, func, "  ", 1, &mut visited, &mut out);
+    out
 }
 
-fn print_callers<'a>(
-    func_map: &std::collections::HashMap<&'a str, (&'a str, &'a Function)>,
+/// Shared state for a backward trace traversal
+struct CallerWalk<'a> {
+    func_map: &'a HashMap<&'a str, (&'a str, &'a Function)>,
+    max_depth: usize,
+    fmt: &'a Formatter,
+    call_sites: bool,
+}
+
+/// Lines in `caller` where it calls `callee`
+fn call_lines(caller: &Function, callee: &str) -> Vec<u32> {
+    sorted_calls(caller).iter().filter(|c| c.target == callee).map(|c| c.line).collect()
+}
+
+fn render_callers<'a>(
+    walk: &CallerWalk<'a>,
     func: &'a Function,
     prefix: &str,
-    max_depth: usize,
     current_depth: usize,
     visited: &mut HashSet<&'a str>,
+    out: &mut Vec<String>,
 ) {
-    if current_depth > max_depth {
+    if current_depth > walk.max_depth {
         return;
     }
 
@@ -90,119 +244,670 @@ fn print_callers<'a>(
         let child_prefix = if is_last { "    " } else { "│   " };
         let new_prefix = format!("{}{}", prefix, child_prefix);
 
-        if let Some((caller_file, caller_func)) = func_map.get(caller_name.as_str()) {
+        if let Some((caller_file, caller_func)) = walk.func_map.get(caller_name.as_str()) {
             if visited.contains(caller_name.as_str()) {
-                println!("{}{}[cycle] {}", prefix, connector, caller_name);
+                out.push(format!("{}{}[cycle] {}", prefix, connector, walk.fmt.name(caller_name)));
                 continue;
             }
 
-            println!(
-                "{}{}{} ({}:{}-{})",
-                prefix, connector, caller_func.qualified_name, caller_file,
-                caller_func.line_start, caller_func.line_end
-            );
+            let called_at = match call_lines(caller_func, &func.qualified_name).as_slice() {
+                lines @ [_, ..] if walk.call_sites => {
+                    let lines: Vec<String> = lines.iter().map(u32::to_string).collect();
+                    format!(" [called at {}:{}]", caller_file, lines.join(", "))
+                }
+                _ => String::new(),
+            };
+            out.push(format!(
+                "{}{}{} ({}:{}-{}){}",
+                prefix, connector, walk.fmt.name(&caller_func.qualified_name), caller_file,
+                caller_func.line_start, caller_func.line_end, called_at
+            ));
 
             visited.insert(caller_name.as_str());
-            print_callers(func_map, caller_func, &new_prefix, max_depth, current_depth + 1, visited);
+            render_callers(walk, caller_func, &new_prefix, current_depth + 1, visited, out);
             visited.remove(caller_name.as_str());
         } else {
-            println!("{}{}[external] {}", prefix, connector, caller_name);
+            out.push(format!("{}{}[external] {}", prefix, connector, walk.fmt.name(caller_name)));
         }
     }
 }
 
-fn print_forward(
-    func_map: &std::collections::HashMap<&str, (&str, &Function)>,
+/// Shared state for a forward trace traversal
+struct ForwardTrace<'a> {
+    func_map: &'a HashMap<&'a str, (&'a str, &'a Function)>,
+    index: &'a Index,
+    max_depth: usize,
+    external_db: ExternalDb,
+    fmt: &'a Formatter,
+    target: Option<TraceTarget<'a>>,
+    call_sites: bool,
+}
+
+impl<'a> ForwardTrace<'a> {
+    fn new(
+        func_map: &'a HashMap<&'a str, (&'a str, &'a Function)>,
+        index: &'a Index,
+        max_depth: usize,
+        fmt: &'a Formatter,
+        target: Option<TraceTarget<'a>>,
+        call_sites: bool,
+    ) -> Self {
+        ForwardTrace { func_map, index, max_depth, external_db: ExternalDb::new(), fmt, target, call_sites }
+    }
+
+    /// " [called at file:line:column]" for a call made in `file`, when call sites are shown
+    fn called_at(&self, file: &str, call: &CallSite) -> String {
+        if self.call_sites { format!(" [called at {}:{}]", file, call.position()) } else { String::new() }
+    }
+}
+
+/// Restricts a forward trace to the branches that reach one of `names`
+struct TraceTarget<'a> {
+    names: &'a HashSet<String>,
+    /// Functions from which a target is reachable
+    reaching: &'a HashSet<String>,
+    reached: Cell<bool>,
+}
+
+/// Lines of the forward trace: `func` and everything it calls, by depth
+pub fn render_forward(
+    func_map: &HashMap<&str, (&str, &Function)>,
     index: &Index,
     file_path: &str,
     func: &Function,
     max_depth: usize,
-) {
-    let external_db = ExternalDb::new();
+    fmt: &Formatter,
+    call_sites: bool,
+) -> Vec<String> {
+    let trace = ForwardTrace::new(func_map, index, max_depth, fmt, None, call_sites);
+    forward_lines(&trace, file_path, func)
+}
+
+fn forward_lines<'a>(trace: &ForwardTrace<'a>, file_path: &str, func: &'a Function) -> Vec<String> {
+    let mut out = Vec::new();
     let mut seen_externals = HashSet::new();
+    let marker = match &trace.target {
+        Some(target) if target.names.contains(&func.qualified_name) => {
+            target.reached.set(true);
+            "[target] "
+        }
+        _ => "",
+    };
 
-    println!(
-        "[0] {} ({}:{}-{})",
-        func.qualified_name, file_path, func.line_start, func.line_end
-    );
+    out.push(format!(
+        "[0] {}{} ({}:{}-{})",
+        marker, trace.fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end
+    ));
 
     let mut visited = HashSet::new();
     visited.insert(func.qualified_name.as_str());
-    print_forward_level(func_map, index, func, 1, max_depth, 1, &mut visited, &mut seen_externals, &external_db);
+    if marker.is_empty() {
+        render_forward_level(trace, file_path, func, 1, &mut visited, &mut seen_externals, &mut out);
+    }
+
+    if let Some(target) = &trace.target
+        && !target.reached.get()
+    {
+        let names: Vec<&str> = target.names.iter().map(String::as_str).collect();
+        let within = if trace.max_depth == usize::MAX {
+            String::new()
+        } else {
+            format!(" within depth {} (try -d 0)", trace.max_depth)
+        };
+        out.push(format!("  ({} not reached{})", names.join(", "), within));
+    }
+    out
 }
 
-fn print_forward_level<'a>(
-    func_map: &std::collections::HashMap<&'a str, (&'a str, &'a Function)>,
-    index: &'a Index,
+fn render_forward_level<'a>(
+    trace: &ForwardTrace<'a>,
+    file: &str,
     func: &'a Function,
     level: usize,
-    max_depth: usize,
-    current_depth: usize,
     visited: &mut HashSet<&'a str>,
     seen_externals: &mut HashSet<String>,
-    external_db: &ExternalDb,
+    out: &mut Vec<String>,
 ) {
-    if current_depth > max_depth {
+    if level > trace.max_depth {
         return;
     }
 
     let dashes = "-".repeat(level);
 
-    for call in &func.calls {
-        if call.target == "[unresolved]" {
-            println!("[{}] {} [unresolved] {}", level, dashes, call.raw);
-            continue;
-        }
-
-        if let Some((child_file, child_func)) = func_map.get(call.target.as_str()) {
-            if visited.contains(call.target.as_str()) {
-                println!("[{}] {} [cycle] {}", level, dashes, call.target);
+    for call in sorted_calls(func) {
+        let called_at = trace.called_at(file, call);
+        if let Some(target) = &trace.target {
+            // Only calls into functions that lead to the target survive pruning;
+            // unresolved, ambiguous, external and cyclic calls never add a new path
+            if !target.reaching.contains(&call.target) || visited.contains(call.target.as_str()) {
                 continue;
             }
+            if target.names.contains(&call.target)
+                && let Some((child_file, child_func)) = trace.func_map.get(call.target.as_str())
+            {
+                target.reached.set(true);
+                out.push(format!(
+                    "[{}] {} [target] {} ({}:{}-{}){}",
+                    level, dashes, trace.fmt.name(&child_func.qualified_name), child_file,
+                    child_func.line_start, child_func.line_end, called_at
+                ));
+                continue;
+            }
+        }
 
-            println!(
-                "[{}] {} {} ({}:{}-{})",
-                level, dashes, child_func.qualified_name, child_file,
-                child_func.line_start, child_func.line_end
-            );
-
+        let cyclic = visited.contains(call.target.as_str());
+        let (line, child) = forward_edge(trace, file, call, level, cyclic, seen_externals);
+        out.push(line);
+        if let Some((child_file, child_func)) = child {
             visited.insert(call.target.as_str());
-            print_forward_level(func_map, index, child_func, level + 1, max_depth, current_depth + 1, visited, seen_externals, external_db);
+            render_forward_level(trace, child_file, child_func, level + 1, visited, seen_externals, out);
             visited.remove(call.target.as_str());
-        } else {
-            let first_occurrence = seen_externals.insert(call.target.clone());
-            let summary_suffix = if first_occurrence {
-                get_external_summary(index, &call.target, external_db)
-            } else {
-                String::new()
-            };
-            println!("[{}] {} [external] {}{}", level, dashes, call.target, summary_suffix);
         }
     }
 }
 
-fn get_external_summary(index: &Index, target: &str, external_db: &ExternalDb) -> String {
-    if let Some(ext) = index.externals.get(target) {
-        if let Some(summary) = &ext.summary {
-            return format!(" : \"{}\"", summary);
+/// Line for one call in a forward trace at `level`, and the function to descend into when the
+/// call resolves to an indexed function that is not already on the branch (`cyclic`)
+fn forward_edge<'a>(
+    trace: &ForwardTrace<'a>,
+    file: &str,
+    call: &CallSite,
+    level: usize,
+    cyclic: bool,
+    seen_externals: &mut HashSet<String>,
+) -> (String, Option<(&'a str, &'a Function)>) {
+    let dashes = "-".repeat(level);
+    let called_at = trace.called_at(file, call);
+
+    if call.target == "[unresolved]" {
+        return (format!("[{}] {} [unresolved] {}{}", level, dashes, call.raw, called_at), None);
+    }
+
+    if !call.candidates.is_empty() {
+        let candidates: Vec<_> = call.candidates.iter().map(|c| trace.fmt.name(c)).collect();
+        let kind = if call.target.starts_with("[iface:") { "[iface]" } else { "[ambiguous]" };
+        let line = format!("[{}] {} {} {} ({}){}", level, dashes, kind, call.raw, candidates.join(", "), called_at);
+        return (line, None);
+    }
+
+    if let Some(&(child_file, child_func)) = trace.func_map.get(call.target.as_str()) {
+        if cyclic {
+            return (format!("[{}] {} [cycle] {}{}", level, dashes, trace.fmt.name(&call.target), called_at), None);
         }
+
+        // Reference (vendored) files are indexed for navigation only
+        let tag = if trace.index.files.get(child_file).is_some_and(|e| e.reference) { "[vendored] " } else { "" };
+        let line = format!(
+            "[{}] {} {}{} ({}:{}-{}){}",
+            level, dashes, tag, trace.fmt.name(&child_func.qualified_name), child_file,
+            child_func.line_start, child_func.line_end, called_at
+        );
+        return (line, Some((child_file, child_func)));
     }
 
-    let func_name = if target.starts_with('[') && target.contains(':') {
-        target.trim_start_matches('[')
-            .trim_end_matches(']')
-            .split(':')
-            .nth(1)
-            .unwrap_or(target)
+    let first_occurrence = seen_externals.insert(call.target.clone());
+    let summary_suffix = if first_occurrence {
+        get_external_summary(trace.index, &call.target, &trace.external_db, trace.fmt)
     } else {
-        target.rsplit('.').next().unwrap_or(target)
+        String::new()
     };
+    (format!("[{}] {} [external] {}{}{}", level, dashes, call.target, summary_suffix, called_at), None)
+}
+
+/// A line of a forward trace expanded breadth-first under a token budget
+struct BudgetNode<'a> {
+    line: String,
+    level: usize,
+    /// Function this line can be expanded into, with its file
+    func: Option<(&'a str, &'a Function)>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Calls the trace would show below this line: those of `func` unless it is at the depth limit
+    calls: usize,
+}
 
-    let (_, summary) = external_db.categorize(func_name);
+/// Lines of the forward trace of `func`, expanded level by level while it fits in about `budget`
+/// tokens. Every branch cut short ends in a marker counting the calls left out, and a final line
+/// gives the depth reached and the estimated size.
+fn forward_lines_within<'a>(trace: &ForwardTrace<'a>, file_path: &'a str, func: &'a Function, budget: usize) -> Vec<String> {
+    let nodes = expand_breadth_first(trace, file_path, func, budget);
 
-    if let Some(s) = summary {
-        format!(" : \"{}\"", s)
-    } else {
-        String::new()
+    // Markers are only known once the expansion stops, so drop lines from the end of the
+    // breadth-first order until the output, markers included, fits
+    let mut shown = nodes.len();
+    let mut out = budget_lines(&nodes, shown);
+    while shown > 1 && estimate_tokens(&out.join("\n")) > budget {
+        shown -= 1;
+        out = budget_lines(&nodes, shown);
+    }
+
+    let depth = nodes[..shown].iter().map(|n| n.level).max().unwrap_or(0);
+    let tokens = estimate_tokens(&out.join("\n"));
+    out.push(format!("(fit to {budget} tokens: depth {depth}, ~{tokens} tokens)"));
+    out
+}
+
+/// Trace lines in breadth-first order, stopping at the first one that would pass `budget` tokens.
+/// A node's parent always comes before it, so any prefix is a trace of its own.
+fn expand_breadth_first<'a>(
+    trace: &ForwardTrace<'a>,
+    file_path: &'a str,
+    func: &'a Function,
+    budget: usize,
+) -> Vec<BudgetNode<'a>> {
+    let line = format!(
+        "[0] {} ({}:{}-{})",
+        trace.fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end
+    );
+    let mut used = estimate_tokens(&line);
+    let calls = if trace.max_depth > 0 { sorted_calls(func).len() } else { 0 };
+    let root = BudgetNode { line, level: 0, func: Some((file_path, func)), parent: None, children: Vec::new(), calls };
+    let mut nodes = vec![root];
+    let mut queue = VecDeque::from([0]);
+    let mut seen_externals = HashSet::new();
+
+    while let Some(node) = queue.pop_front() {
+        let level = nodes[node].level + 1;
+        let Some((file, func)) = nodes[node].func else { continue };
+        if level > trace.max_depth {
+            continue;
+        }
+        for call in sorted_calls(func) {
+            let cyclic = on_branch(&nodes, node, &call.target);
+            let (line, child) = forward_edge(trace, file, call, level, cyclic, &mut seen_externals);
+            used += estimate_tokens(&line);
+            if used > budget {
+                return nodes;
+            }
+            let calls = child.filter(|_| level < trace.max_depth).map_or(0, |(_, f)| sorted_calls(f).len());
+            nodes.push(BudgetNode { line, level, func: child, parent: Some(node), children: Vec::new(), calls });
+            let child = nodes.len() - 1;
+            nodes[node].children.push(child);
+            queue.push_back(child);
+        }
+    }
+    nodes
+}
+
+/// Whether `name` is the function of `node` or of one of its ancestors
+fn on_branch(nodes: &[BudgetNode], node: usize, name: &str) -> bool {
+    let mut current = Some(node);
+    while let Some(n) = current {
+        if nodes[n].func.is_some_and(|(_, f)| f.qualified_name == name) {
+            return true;
+        }
+        current = nodes[n].parent;
+    }
+    false
+}
+
+/// Depth-first lines of the first `shown` nodes, with a marker under every node missing calls
+fn budget_lines(nodes: &[BudgetNode], shown: usize) -> Vec<String> {
+    fn push(nodes: &[BudgetNode], shown: usize, node: usize, out: &mut Vec<String>) {
+        out.push(nodes[node].line.clone());
+        let children: Vec<usize> = nodes[node].children.iter().copied().filter(|&c| c < shown).collect();
+        for &child in &children {
+            push(nodes, shown, child, out);
+        }
+        let omitted = nodes[node].calls - children.len();
+        if omitted > 0 {
+            let level = nodes[node].level + 1;
+            let noun = if omitted == 1 { "node" } else { "nodes" };
+            out.push(format!("[{}] {} (depth limited to fit budget: {omitted} {noun} omitted)", level, "-".repeat(level)));
+        }
+    }
+    let mut out = Vec::new();
+    push(nodes, shown, 0, &mut out);
+    out
+}
+
+/// JSON shape of a trace: the traced function with its callers and callees as trees
+#[derive(Serialize)]
+struct TraceJson<'a> {
+    function: &'a str,
+    file: &'a str,
+    line_start: u32,
+    line_end: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callers: Option<Vec<EdgeJson<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    calls: Option<Vec<EdgeJson<'a>>>,
+}
+
+/// JSON shape of one trace edge; `call_line` is the line of the call inside the caller
+#[derive(Serialize)]
+struct EdgeJson<'a> {
+    /// "function", "cycle", "external", "unresolved", "ambiguous", or "interface"
+    kind: &'static str,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_start: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_end: Option<u32>,
+    call_line: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    vendored: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    candidates: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    /// Callees of a forward edge, callers of a backward one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<EdgeJson<'a>>,
+}
+
+impl<'a> EdgeJson<'a> {
+    fn new(kind: &'static str, name: &'a str, call_line: Option<u32>) -> Self {
+        EdgeJson {
+            kind,
+            name,
+            file: None,
+            line_start: None,
+            line_end: None,
+            call_line,
+            vendored: false,
+            candidates: Vec::new(),
+            summary: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn function(file: &'a str, func: &'a Function, call_line: Option<u32>) -> Self {
+        EdgeJson {
+            file: Some(file),
+            line_start: Some(func.line_start),
+            line_end: Some(func.line_end),
+            ..EdgeJson::new("function", &func.qualified_name, call_line)
+        }
+    }
+}
+
+/// Forward trace edges below `func`, mirroring `render_forward_level`
+fn calls_json<'a>(
+    trace: &ForwardTrace<'a>,
+    func: &'a Function,
+    level: usize,
+    visited: &mut HashSet<&'a str>,
+) -> Vec<EdgeJson<'a>> {
+    let mut edges = Vec::new();
+    if level > trace.max_depth {
+        return edges;
+    }
+    for call in sorted_calls(func) {
+        let line = Some(call.line);
+        let edge = if call.target == "[unresolved]" {
+            EdgeJson::new("unresolved", &call.raw, line)
+        } else if !call.candidates.is_empty() {
+            let candidates = call.candidates.iter().map(String::as_str).collect();
+            let kind = if call.target.starts_with("[iface:") { "interface" } else { "ambiguous" };
+            EdgeJson { candidates, ..EdgeJson::new(kind, &call.raw, line) }
+        } else if let Some((child_file, child_func)) = trace.func_map.get(call.target.as_str()) {
+            if visited.contains(call.target.as_str()) {
+                EdgeJson::new("cycle", &call.target, line)
+            } else {
+                visited.insert(call.target.as_str());
+                let children = calls_json(trace, child_func, level + 1, visited);
+                visited.remove(call.target.as_str());
+                let vendored = trace.index.files.get(*child_file).is_some_and(|e| e.reference);
+                EdgeJson { vendored, children, ..EdgeJson::function(child_file, child_func, line) }
+            }
+        } else {
+            let summary = external_summary(trace.index, &call.target, &trace.external_db);
+            EdgeJson { summary, ..EdgeJson::new("external", &call.target, line) }
+        };
+        edges.push(edge);
+    }
+    edges
+}
+
+/// Backward trace edges above `func`, mirroring `render_callers`
+fn callers_json<'a>(
+    walk: &CallerWalk<'a>,
+    func: &'a Function,
+    depth: usize,
+    visited: &mut HashSet<&'a str>,
+) -> Vec<EdgeJson<'a>> {
+    let mut edges = Vec::new();
+    if depth > walk.max_depth {
+        return edges;
+    }
+    for caller_name in &func.called_by {
+        let Some((caller_file, caller_func)) = walk.func_map.get(caller_name.as_str()) else {
+            edges.push(EdgeJson::new("external", caller_name, None));
+            continue;
+        };
+        let line = call_lines(caller_func, &func.qualified_name).first().copied();
+        if visited.contains(caller_name.as_str()) {
+            edges.push(EdgeJson::new("cycle", caller_name, line));
+            continue;
+        }
+        visited.insert(caller_name.as_str());
+        let children = callers_json(walk, caller_func, depth + 1, visited);
+        visited.remove(caller_name.as_str());
+        edges.push(EdgeJson { children, ..EdgeJson::function(caller_file, caller_func, line) });
+    }
+    edges
+}
+
+/// Calls of a function in call-site line order (stable within a line), including the Rust
+/// closures it defines that are indexed as functions of their own, since they run as part of it
+pub fn sorted_calls(func: &Function) -> Vec<&CallSite> {
+    let closures = func.references.iter().filter(|r| r.raw.starts_with("{closure@"));
+    let mut calls: Vec<&CallSite> = func.calls.iter().chain(closures).collect();
+    calls.sort_by_key(|c| c.line);
+    calls
+}
+
+fn get_external_summary(index: &Index, target: &str, external_db: &ExternalDb, fmt: &Formatter) -> String {
+    match external_summary(index, target, external_db) {
+        Some(s) => format!(" : \"{}\"", fmt.summary(&s)),
+        None => String::new(),
+    }
+}
+
+/// Summary of an external call target from the index or the built-in database
+pub fn external_summary(index: &Index, target: &str, external_db: &ExternalDb) -> Option<String> {
+    let kind_and_name = target.strip_prefix('[').and_then(|t| t.strip_suffix(']')).and_then(|t| t.split_once(':'));
+
+    // The externals table is keyed by the name as called
+    let key = kind_and_name.map_or(target, |(_, name)| name);
+    if let Some(ext) = index.externals.get(key)
+        && let Some(summary) = &ext.summary
+    {
+        return Some(summary.clone());
+    }
+
+    // The kind in "[kind:name]" was decided from the caller's language when the index was built
+    match kind_and_name {
+        Some((kind, name)) => external_db.describe(kind, name).map(String::from),
+        None => {
+            let func_name = target.rsplit('.').next().unwrap_or(target);
+            external_db.categorize(func_name, Language::C).1.map(String::from)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileEntry;
+
+    fn func(name: &str, calls: &[&str]) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: name.to_string(),
+            line_start: 1,
+            line_end: 2,
+            calls: calls
+                .iter()
+                .enumerate()
+                .map(|(i, target)| CallSite {
+                    target: target.to_string(),
+                    raw: target.to_string(),
+                    callee_name: target.to_string(),
+                    line: i as u32 + 1,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trace_to_prunes_branches_and_cycles() {
+        // main -> {load, run, log}; load -> write; run -> {step, load}; step -> {run, write}; log -> fmt
+        let mut index = Index::new();
+        index.files.insert(
+            "./app.go".to_string(),
+            FileEntry {
+                functions: vec![
+                    func("main", &["load", "run", "log", "[unresolved]"]),
+                    func("load", &["write"]),
+                    func("run", &["step", "load"]),
+                    func("step", &["run", "write", "os.Exit"]),
+                    func("log", &["fmt"]),
+                    func("fmt", &[]),
+                    func("write", &["flush"]),
+                    func("flush", &[]),
+                ],
+                ..Default::default()
+            },
+        );
+
+        let func_map = index::build_function_map(&index);
+        let targets: HashSet<String> = ["write".to_string()].into();
+        let (_, calls) = build_call_graph(&index);
+        let reaching = topo::reaching(&targets, &calls);
+        let fmt = Formatter::full();
+
+        let (file, main) = func_map["main"];
+        let target = TraceTarget { names: &targets, reaching: &reaching, reached: Cell::new(false) };
+        let trace = ForwardTrace::new(&func_map, &index, usize::MAX, &fmt, Some(target), false);
+        let lines = forward_lines(&trace, file, main);
+        assert_eq!(
+            lines,
+            vec![
+                "[0] main (./app.go:1-2)",
+                "[1] - load (./app.go:1-2)",
+                "[2] -- [target] write (./app.go:1-2)",
+                "[1] - run (./app.go:1-2)",
+                "[2] -- step (./app.go:1-2)",
+                "[3] --- [target] write (./app.go:1-2)",
+                "[2] -- load (./app.go:1-2)",
+                "[3] --- [target] write (./app.go:1-2)",
+            ]
+        );
+
+        let (file, log) = func_map["log"];
+        let target = TraceTarget { names: &targets, reaching: &reaching, reached: Cell::new(false) };
+        let trace = ForwardTrace::new(&func_map, &index, 1, &fmt, Some(target), false);
+        assert_eq!(
+            forward_lines(&trace, file, log),
+            vec!["[0] log (./app.go:1-2)", "  (write not reached within depth 1 (try -d 0))"]
+        );
+    }
+    #[test]
+    fn test_call_sites_annotate_edges() {
+        let mut main = func("main", &["helper", "ext.Thing", "[unresolved]"]);
+        main.called_by = vec!["helper".to_string()];
+        let mut helper = func("helper", &["main"]);
+        helper.called_by = vec!["main".to_string()];
+        let mut index = Index::new();
+        for (path, f) in [("./main.go", main), ("./util.go", helper)] {
+            let entry = FileEntry {
+                functions: vec![f],
+                ..Default::default()
+            };
+            index.files.insert(path.to_string(), entry);
+        }
+        let func_map = index::build_function_map(&index);
+        let fmt = Formatter::full();
+
+        let (file, main) = func_map["main"];
+        assert_eq!(
+            render_forward(&func_map, &index, file, main, 2, &fmt, true),
+            vec![
+                "[0] main (./main.go:1-2)",
+                "[1] - helper (./util.go:1-2) [called at ./main.go:1]",
+                "[2] -- [cycle] main [called at ./util.go:1]",
+                "[1] - [external] ext.Thing [called at ./main.go:2]",
+                "[1] - [unresolved] [unresolved] [called at ./main.go:3]",
+            ]
+        );
+        assert_eq!(render_forward(&func_map, &index, file, main, 1, &fmt, false)[1], "[1] - helper (./util.go:1-2)");
+
+        let (file, helper) = func_map["helper"];
+        assert_eq!(
+            render_backward(&func_map, file, helper, 1, &fmt, true),
+            vec!["helper (./util.go:1-2)", "  called by:", "  └── main (./main.go:1-2) [called at ./main.go:1]"]
+        );
+
+        // JSON carries the call line whether or not --call-sites is given
+        let trace = ForwardTrace::new(&func_map, &index, 2, &fmt, None, false);
+        let calls = serde_json::to_value(calls_json(&trace, main, 1, &mut HashSet::from(["main"]))).unwrap();
+        let lines: Vec<_> = calls.as_array().unwrap().iter().map(|e| (e["kind"].clone(), e["call_line"].clone())).collect();
+        assert_eq!(lines, vec![("function".into(), 1.into()), ("external".into(), 2.into()), ("unresolved".into(), 3.into())]);
+        assert_eq!(calls[0]["children"][0]["kind"], "cycle");
+    }
+    #[test]
+    fn test_fit_tokens_expands_breadth_first() {
+        let mut index = Index::new();
+        let functions = vec![
+            func("main", &["load", "run"]),
+            func("load", &["parse"]),
+            func("run", &["step", "main", "log", "flush"]),
+            func("parse", &[]),
+            func("step", &[]),
+            func("log", &[]),
+            func("flush", &[]),
+        ];
+        let entry =
+            FileEntry {
+                functions,
+                ..Default::default()
+            };
+        index.files.insert("./app.go".to_string(), entry);
+        let func_map = index::build_function_map(&index);
+        let fmt = Formatter::full();
+        let trace = ForwardTrace::new(&func_map, &index, usize::MAX, &fmt, None, false);
+        let (file, main) = func_map["main"];
+
+        assert_eq!(
+            forward_lines_within(&trace, file, main, 100),
+            vec![
+                "[0] main (./app.go:1-2)",
+                "[1] - load (./app.go:1-2)",
+                "[2] -- parse (./app.go:1-2)",
+                "[1] - run (./app.go:1-2)",
+                "[2] -- step (./app.go:1-2)",
+                "[2] -- [cycle] main",
+                "[2] -- log (./app.go:1-2)",
+                "[2] -- flush (./app.go:1-2)",
+                "(fit to 100 tokens: depth 2, ~51 tokens)",
+            ]
+        );
+
+        // Level 1 is expanded before level 2, and each branch cut short ends in a marker
+        assert_eq!(
+            forward_lines_within(&trace, file, main, 45),
+            vec![
+                "[0] main (./app.go:1-2)",
+                "[1] - load (./app.go:1-2)",
+                "[2] -- parse (./app.go:1-2)",
+                "[1] - run (./app.go:1-2)",
+                "[2] -- (depth limited to fit budget: 4 nodes omitted)",
+                "(fit to 45 tokens: depth 2, ~39 tokens)",
+            ]
+        );
+        assert_eq!(
+            forward_lines_within(&trace, file, main, 20),
+            vec![
+                "[0] main (./app.go:1-2)",
+                "[1] - (depth limited to fit budget: 2 nodes omitted)",
+                "(fit to 20 tokens: depth 0, ~19 tokens)",
+            ]
+        );
     }
 }
@@ -0,0 +1,154 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::ExitCode;
+
+use clap_complete::Shell;
+
+use crate::index;
+
+/// Qualified function names written next to the index for fast completion
+pub const NAMES_FILE: &str = "names.txt";
+
+/// Subcommands whose `name` argument is a function name
+const FUNCTION_COMMANDS: &[&str] = &["function", "trace", "usages"];
+
+/// zsh completer for function names, backed by `aria __complete-functions`
+const ZSH_FUNCTION_NAMES: &str = r#"(( $+functions[_aria_function_names] )) ||
+_aria_function_names() {
+    local -a names
+    names=(${(f)"$(aria __complete-functions "$PREFIX" 2>/dev/null)"})
+    compadd -a names
+}
+
+"#;
+
+pub fn run(shell: Shell, mut cmd: clap::Command) -> ExitCode {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, "aria", &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    let script = match shell {
+        Shell::Zsh => add_zsh_function_names(&script),
+        Shell::Fish => add_fish_function_names(&script),
+        _ => script.into_owned(),
+    };
+
+    let _ = std::io::stdout().write_all(script.as_bytes());
+    ExitCode::SUCCESS
+}
+
+/// Complete `name` arguments of function commands with `_aria_function_names`
+fn add_zsh_function_names(script: &str) -> String {
+    let mut out = String::with_capacity(script.len() + ZSH_FUNCTION_NAMES.len());
+    let mut current = "";
+
+    for line in script.lines() {
+        let trimmed = line.trim();
+        if let Some(label) = trimmed.strip_prefix('(').and_then(|l| l.strip_suffix(')')) {
+            current = label;
+        }
+
+        if FUNCTION_COMMANDS.contains(&current)
+            && line.starts_with("':name -- ")
+            && let Some(spec) = line.strip_suffix(":_default' \\")
+        {
+            out.push_str(spec);
+            out.push_str(":_aria_function_names' \\\n");
+            continue;
+        }
+
+        // Define the completer before the script registers or invokes _aria
+        if line.starts_with("if [ \"$funcstack[1]\" = \"_aria\" ]") {
+            out.push_str(ZSH_FUNCTION_NAMES);
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    out
+}
+
+fn add_fish_function_names(script: &str) -> String {
+    format!(
+        "{}complete -c aria -n \"__fish_aria_using_subcommand {}\" -f -a \"(aria __complete-functions (commandline -ct))\"\n",
+        script,
+        FUNCTION_COMMANDS.join(" ")
+    )
+}
+
+/// Print function names starting with `prefix`, one per line, for shell completion
+pub fn complete_functions(prefix: &str) -> ExitCode {
+    let names = match fs::read_to_string(Path::new(".aria").join(NAMES_FILE)) {
+        Ok(content) => content.lines().map(String::from).collect(),
+        // Older indexes have no names file
+        Err(_) => match index::load_index() {
+            Ok(index) => function_names(&index),
+            Err(_) => return ExitCode::FAILURE,
+        },
+    };
+
+    let mut out = std::io::stdout().lock();
+    for name in matching_names(&names, prefix) {
+        let _ = writeln!(out, "{name}");
+    }
+    ExitCode::SUCCESS
+}
+
+/// Sorted, deduplicated qualified names of all indexed functions
+pub fn function_names(index: &index::Index) -> Vec<String> {
+    let names: BTreeSet<&str> = index
+        .files
+        .values()
+        .flat_map(|e| e.functions.iter())
+        .map(|f| f.qualified_name.as_str())
+        .collect();
+    names.into_iter().map(String::from).collect()
+}
+
+/// Qualified names starting with `prefix`, plus bare names starting with it since lookup accepts those too
+fn matching_names<'a>(names: &'a [String], prefix: &str) -> BTreeSet<&'a str> {
+    let mut matches = BTreeSet::new();
+    for name in names {
+        if name.starts_with(prefix) {
+            matches.insert(name.as_str());
+        }
+        let bare = name.rsplit(['.', ':']).next().unwrap_or(name);
+        if bare.starts_with(prefix) {
+            matches.insert(bare);
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_names() {
+        let names: Vec<String> = ["app.main", "app.Server.Start", "commands::index::run", "store.start"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+
+        let matches: Vec<&str> = matching_names(&names, "app.").into_iter().collect();
+        assert_eq!(matches, vec!["app.Server.Start", "app.main"]);
+
+        let matches: Vec<&str> = matching_names(&names, "r").into_iter().collect();
+        assert_eq!(matches, vec!["run"]);
+
+        assert_eq!(matching_names(&names, "").len(), 8);
+    }
+
+    #[test]
+    fn test_zsh_script_completes_function_names() {
+        let script = "case $line[1] in\n    (function)\n_arguments \\\n':name -- Function name:_default' \\\n&& ret=0\n;;\n(source)\n':name -- Symbol name:_default' \\\nif [ \"$funcstack[1]\" = \"_aria\" ]; then\n";
+        let out = add_zsh_function_names(script);
+
+        assert!(out.contains("':name -- Function name:_aria_function_names' \\"));
+        assert!(out.contains("':name -- Symbol name:_default' \\"));
+        assert!(out.find("_aria_function_names() {").unwrap() < out.find("if [ \"$funcstack").unwrap());
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+use crate::card::{CardFormat, CardOptions, Verbosity, render_function_card};
+use crate::commands::index::{language_of, load_config};
+use crate::format::Formatter;
+use crate::index::{self, FileEntry, Function, Index};
+use crate::parser::ParserRegistry;
+use crate::sanitize;
+
+/// Callers or callees kept per function once the bundle is over budget
+const TRUNCATED_NEIGHBORS: usize = 5;
+
+/// Options controlling `aria context`
+pub struct ContextOptions {
+    /// Revision the working tree is compared with
+    pub since: String,
+    /// Approximate token limit for the whole bundle
+    pub budget: usize,
+    /// Emit JSON instead of markdown
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ContextBundle {
+    since: String,
+    budget_tokens: usize,
+    estimated_tokens: usize,
+    /// Whether any function was cut down to fit the budget
+    truncated: bool,
+    functions: Vec<ChangedFunction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// A function added, modified, or deleted since the base revision, with its neighborhood
+#[derive(Debug, Serialize)]
+struct ChangedFunction {
+    qualified_name: String,
+    file: String,
+    change: ChangeKind,
+    /// Current lines; the base revision's lines for deleted functions
+    line_start: u32,
+    line_end: u32,
+    /// Summary stored in the index, written for the function before this change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_summary: Option<String>,
+    /// Current source from disk, absent for deleted functions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    source_lines_omitted: usize,
+    callers: Vec<Neighbor>,
+    #[serde(skip_serializing_if = "is_zero")]
+    callers_omitted: usize,
+    callees: Vec<Neighbor>,
+    #[serde(skip_serializing_if = "is_zero")]
+    callees_omitted: usize,
+}
+
+/// A direct caller or callee and its indexed summary
+#[derive(Debug, Serialize)]
+struct Neighbor {
+    qualified_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    /// One-line markdown card, when the function is indexed
+    #[serde(skip)]
+    card: Option<String>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+pub fn run(opts: &ContextOptions) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let config = load_config(Path::new(".aria"));
+    let mut registry = match ParserRegistry::configured(&config.languages, &config.index) {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let include_tests = config.features.include_tests || index.files.values().any(|e| e.is_test);
+    let files = match changed_files(&opts.since, &registry, include_tests) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Module paths must match the index, which named them after the crates it found
+    registry.scan_root(Path::new("."));
+    let mut functions = Vec::new();
+    for key in &files {
+        let path = Path::new(key);
+        let old = git(&["show", &format!("{}:{}", opts.since, key)])
+            .ok()
+            .and_then(|s| registry.parse_file(path, &s, key));
+        // Files the index skips as too large are left out here as well
+        let current = match index::read_source(path, config.index.max_file_size()) {
+            Ok(Some(s)) => Some(s),
+            Ok(None) => continue,
+            Err(_) => None,
+        };
+        let new = current.as_deref().and_then(|s| registry.parse_file(path, s, key));
+        functions.extend(changed_functions(&index, key, old.as_ref(), new.as_ref(), current.as_deref()));
+    }
+
+    let truncated = fit_to_budget(&mut functions, opts.budget);
+    let estimated_tokens = functions.iter().map(section_tokens).sum();
+    let bundle = ContextBundle {
+        since: opts.since.clone(),
+        budget_tokens: opts.budget,
+        estimated_tokens,
+        truncated,
+        functions,
+    };
+
+    if opts.json {
+        return match serde_json::to_string_pretty(&bundle) {
+            Ok(out) => {
+                println!("{out}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    println!("# Changes since {}", bundle.since);
+    if bundle.functions.is_empty() {
+        println!();
+        println!("No indexed functions changed.");
+    }
+    for func in &bundle.functions {
+        println!();
+        print!("{}", render_markdown(func));
+    }
+    ExitCode::SUCCESS
+}
+
+/// Run git and return its stdout
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Index keys of source files that differ from `since` in the working tree, including untracked ones;
+/// test files only with `include_tests`
+fn changed_files(since: &str, registry: &ParserRegistry, include_tests: bool) -> Result<BTreeSet<String>, String> {
+    let diff = git(&["diff", "--name-only", "--relative", since, "--"])?;
+    let untracked = git(&["ls-files", "--others", "--exclude-standard"])?;
+
+    Ok(diff
+        .lines()
+        .chain(untracked.lines())
+        .filter(|path| registry.language_of(Path::new(path)).is_some() && (include_tests || !index::is_test_file(path)))
+        .map(|path| format!("./{path}"))
+        .collect())
+}
+
+/// Functions of one file that were added, changed (by ast_hash), or deleted between `old` and `new`
+fn changed_functions(
+    index: &Index,
+    key: &str,
+    old: Option<&FileEntry>,
+    new: Option<&FileEntry>,
+    current: Option<&str>,
+) -> Vec<ChangedFunction> {
+    let by_name = |entry: Option<&FileEntry>| -> BTreeMap<String, Function> {
+        entry
+            .map(|e| e.functions.iter().map(|f| (f.qualified_name.clone(), f.clone())).collect())
+            .unwrap_or_default()
+    };
+    let old_funcs = by_name(old);
+    let new_funcs = by_name(new);
+
+    let mut changed = Vec::new();
+    for (name, func) in &new_funcs {
+        let change = match old_funcs.get(name) {
+            None => ChangeKind::Added,
+            Some(old) if old.ast_hash != func.ast_hash => ChangeKind::Modified,
+            Some(_) => continue,
+        };
+        let source = current.and_then(|c| func.source_text(c)).map(str::to_string);
+        changed.push(describe(index, key, func, change, source));
+    }
+    for (name, func) in &old_funcs {
+        if !new_funcs.contains_key(name) {
+            changed.push(describe(index, key, func, ChangeKind::Deleted, None));
+        }
+    }
+    changed
+}
+
+/// Attach the indexed summary, callers, and callees of `func`
+fn describe(index: &Index, key: &str, func: &Function, change: ChangeKind, source: Option<String>) -> ChangedFunction {
+    let indexed = index
+        .files
+        .get(key)
+        .and_then(|e| e.functions.iter().find(|f| f.qualified_name == func.qualified_name));
+    let fmt = Formatter::full();
+    let card_opts = CardOptions { verbosity: Verbosity::OneLine, format: CardFormat::Markdown, fmt: &fmt, raw: false };
+    let neighbor = |qualified_name: &str| {
+        let found = index::find_functions(index, qualified_name)
+            .into_iter()
+            .find(|(_, f)| f.qualified_name == qualified_name);
+        Neighbor {
+            qualified_name: qualified_name.to_string(),
+            summary: found
+                .and_then(|(_, f)| f.summary.as_deref())
+                .map(|s| sanitize::sanitize_summary(s).into_owned()),
+            card: found.map(|(file, f)| render_function_card(f, file, &card_opts)),
+        }
+    };
+
+    let callers = indexed.map(|f| f.called_by.iter().map(|c| neighbor(c)).collect()).unwrap_or_default();
+    let callees = if change == ChangeKind::Deleted {
+        Vec::new()
+    } else {
+        callee_names(index, indexed, func).iter().map(|c| neighbor(c)).collect()
+    };
+
+    ChangedFunction {
+        qualified_name: func.qualified_name.clone(),
+        file: key.to_string(),
+        change,
+        line_start: func.line_start,
+        line_end: func.line_end,
+        old_summary: indexed.and_then(|f| f.summary.as_deref()).map(|s| sanitize::sanitize_summary(s).into_owned()),
+        source,
+        source_lines_omitted: 0,
+        callers,
+        callers_omitted: 0,
+        callees,
+        callees_omitted: 0,
+    }
+}
+
+/// Qualified names the current body calls. Calls already resolved in the index keep their target;
+/// others resolve when exactly one indexed function has the called name.
+fn callee_names(index: &Index, indexed: Option<&Function>, func: &Function) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for call in &func.calls {
+        let resolved = indexed
+            .and_then(|f| f.calls.iter().find(|c| c.raw == call.raw && !c.target.starts_with('[')))
+            .map(|c| c.target.clone());
+        let target = resolved.or_else(|| {
+            let matches: Vec<_> = index::find_functions(index, &call.callee_name)
+                .into_iter()
+                .filter(|(_, f)| f.name == call.callee_name)
+                .collect();
+            match matches.as_slice() {
+                [(_, f)] => Some(f.qualified_name.clone()),
+                _ => None,
+            }
+        });
+        if let Some(target) = target {
+            names.insert(target);
+        }
+    }
+    names
+}
+
+/// Rough token count, about four characters per token
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn section_tokens(func: &ChangedFunction) -> usize {
+    estimate_tokens(&render_markdown(func))
+}
+
+/// Shrink functions until the bundle fits `budget`: each gets an equal share, and one over its
+/// share first keeps only `TRUNCATED_NEIGHBORS` callers and callees, then loses source lines from
+/// the end. Returns whether anything was cut.
+fn fit_to_budget(functions: &mut [ChangedFunction], budget: usize) -> bool {
+    let total: usize = functions.iter().map(section_tokens).sum();
+    if total <= budget || functions.is_empty() {
+        return false;
+    }
+
+    let share = budget / functions.len();
+    for func in functions.iter_mut() {
+        if section_tokens(func) <= share {
+            continue;
+        }
+        if func.callers.len() > TRUNCATED_NEIGHBORS {
+            func.callers_omitted = func.callers.len() - TRUNCATED_NEIGHBORS;
+            func.callers.truncate(TRUNCATED_NEIGHBORS);
+        }
+        if func.callees.len() > TRUNCATED_NEIGHBORS {
+            func.callees_omitted = func.callees.len() - TRUNCATED_NEIGHBORS;
+            func.callees.truncate(TRUNCATED_NEIGHBORS);
+        }
+
+        while section_tokens(func) > share
+            && let Some(source) = &mut func.source
+        {
+            match source.rfind('\n') {
+                Some(cut) => source.truncate(cut),
+                None => func.source = None,
+            }
+            func.source_lines_omitted += 1;
+        }
+    }
+    true
+}
+
+fn render_markdown(func: &ChangedFunction) -> String {
+    let change = match func.change {
+        ChangeKind::Added => "added",
+        ChangeKind::Modified => "modified",
+        ChangeKind::Deleted => "deleted",
+    };
+    let mut out = format!(
+        "## {} ({}) — {}:{}-{}\n",
+        func.qualified_name, change, func.file, func.line_start, func.line_end
+    );
+
+    if let Some(summary) = &func.old_summary {
+        out.push_str(&format!("\nPrevious summary: {summary}\n"));
+    }
+    if let Some(source) = &func.source {
+        let lang = language_of(Path::new(&func.file)).unwrap_or("");
+        out.push_str(&format!("\n```{lang}\n{source}\n```\n"));
+    }
+    if func.source_lines_omitted > 0 {
+        out.push_str(&format!("({} more lines omitted)\n", func.source_lines_omitted));
+    }
+
+    for (label, neighbors, omitted) in
+        [("Callers", &func.callers, func.callers_omitted), ("Callees", &func.callees, func.callees_omitted)]
+    {
+        if neighbors.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{label}:\n"));
+        for n in neighbors {
+            match (&n.card, &n.summary) {
+                (Some(card), _) => out.push_str(&format!("{card}\n")),
+                (None, Some(summary)) => out.push_str(&format!("- {} — {}\n", n.qualified_name, summary)),
+                (None, None) => out.push_str(&format!("- {}\n", n.qualified_name)),
+            }
+        }
+        if omitted > 0 {
+            out.push_str(&format!("- … {omitted} more\n"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "lang-go")]
+    const OLD: &str = "package app\n\nfunc keep() {}\n\nfunc edit() {\n\tkeep()\n}\n\nfunc gone() {}\n";
+    #[cfg(feature = "lang-go")]
+    const NEW: &str = "package app\n\nfunc keep() {}\n\nfunc edit() {\n\tkeep()\n\tfresh()\n}\n\nfunc fresh() {}\n";
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_changed_functions_added_modified_deleted() {
+        let key = "./app/app.go";
+        let mut index = Index::new();
+        let mut parser = crate::parser::GoParser::new();
+        let mut entry = parser.parse_file(OLD, key).unwrap();
+        for func in &mut entry.functions {
+            func.summary = Some(format!("Old {}.", func.name));
+        }
+        entry.functions[0].called_by = vec!["app.edit".to_string()];
+        index.files.insert(key.to_string(), entry);
+
+        let old = parser.parse_file(OLD, key).unwrap();
+        let new = parser.parse_file(NEW, key).unwrap();
+        let changed = changed_functions(&index, key, Some(&old), Some(&new), Some(NEW));
+        let kinds: Vec<(&str, ChangeKind)> = changed.iter().map(|f| (f.qualified_name.as_str(), f.change)).collect();
+        assert_eq!(
+            kinds,
+            vec![("app.edit", ChangeKind::Modified), ("app.fresh", ChangeKind::Added), ("app.gone", ChangeKind::Deleted)]
+        );
+
+        let edit = &changed[0];
+        assert_eq!(edit.old_summary.as_deref(), Some("Old edit."));
+        assert_eq!(edit.source.as_deref(), Some("func edit() {\n\tkeep()\n\tfresh()\n}"));
+        let callees: Vec<&str> = edit.callees.iter().map(|c| c.qualified_name.as_str()).collect();
+        assert_eq!(callees, vec!["app.keep"]);
+        assert_eq!(edit.callees[0].summary.as_deref(), Some("Old keep."));
+        assert!(render_markdown(edit).contains("\nCallees:\n- `app.keep` (./app/app.go:3-3) — Old keep.\n"));
+
+        let gone = &changed[2];
+        assert!(gone.source.is_none());
+        assert!(render_markdown(gone).starts_with("## app.gone (deleted) — ./app/app.go:9-9\n\nPrevious summary: Old gone."));
+    }
+
+    #[test]
+    fn test_fit_to_budget_truncates_neighbors_then_source() {
+        let neighbors = |n: usize| (0..n).map(|i| Neighbor { qualified_name: format!("app.f{i}"), summary: None, card: None }).collect();
+        let mut functions = vec![ChangedFunction {
+            qualified_name: "app.big".to_string(),
+            file: "./app/big.go".to_string(),
+            change: ChangeKind::Modified,
+            line_start: 1,
+            line_end: 200,
+            old_summary: None,
+            source: Some((0..200).map(|i| format!("\tstep{i}()")).collect::<Vec<_>>().join("\n")),
+            source_lines_omitted: 0,
+            callers: neighbors(12),
+            callers_omitted: 0,
+            callees: neighbors(3),
+            callees_omitted: 0,
+        }];
+
+        assert!(!fit_to_budget(&mut functions, 100_000));
+        assert!(fit_to_budget(&mut functions, 300));
+        let func = &functions[0];
+        assert_eq!((func.callers.len(), func.callers_omitted), (TRUNCATED_NEIGHBORS, 7));
+        assert_eq!((func.callees.len(), func.callees_omitted), (3, 0));
+        assert!(func.source_lines_omitted > 0);
+        assert!(section_tokens(func) <= 300);
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+use crate::index::{self, Index};
+
+/// Function-level differences between two index files
+#[derive(Debug, Default, Serialize)]
+struct IndexDiff {
+    before: Coverage,
+    after: Coverage,
+    /// Qualified names only in the second index
+    added: Vec<FunctionRef>,
+    /// Qualified names only in the first index
+    removed: Vec<FunctionRef>,
+    /// Qualified names in both whose ast_hash changed
+    modified: Vec<FunctionRef>,
+}
+
+/// Counts used for the resolution and summary percentages of one index
+#[derive(Debug, Default, Serialize)]
+struct Coverage {
+    commit: String,
+    functions: usize,
+    calls: usize,
+    resolved_calls: usize,
+    summaries: usize,
+    resolved_pct: f64,
+    summary_pct: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionRef {
+    qualified_name: String,
+    file: String,
+    line: u32,
+}
+
+pub fn run(before: &Path, after: &Path, json: bool) -> ExitCode {
+    let load = |path: &Path| {
+        if !path.exists() && !index::shard_manifest(path).exists() {
+            return Err(format!("index not found at {}", path.display()));
+        }
+        index::load_index_from(path)
+    };
+    let (old, new) = match (load(before), load(after)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if old.hash_version != new.hash_version {
+        eprintln!(
+            "warning: the indexes hash function bodies differently, so every function compares as modified; \
+             reindex the older one with this build first"
+        );
+    }
+    let diff = diff(&old, &new);
+
+    if json {
+        return match serde_json::to_string_pretty(&diff) {
+            Ok(out) => {
+                println!("{out}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    print_text(&diff, before, after);
+    ExitCode::SUCCESS
+}
+
+fn diff(old: &Index, new: &Index) -> IndexDiff {
+    let old_funcs = functions_by_name(old);
+    let new_funcs = functions_by_name(new);
+    let mut diff = IndexDiff { before: coverage(old), after: coverage(new), ..Default::default() };
+
+    for (name, entries) in &new_funcs {
+        match old_funcs.get(name) {
+            None => diff.added.push(function_ref(name, entries)),
+            Some(old_entries) if hashes(old_entries) != hashes(entries) => {
+                diff.modified.push(function_ref(name, entries))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, entries) in &old_funcs {
+        if !new_funcs.contains_key(name) {
+            diff.removed.push(function_ref(name, entries));
+        }
+    }
+
+    diff
+}
+
+/// (file, line, ast_hash) of every function, grouped by qualified name. A name can appear
+/// more than once, e.g. C static functions or Go build-tagged variants.
+fn functions_by_name(index: &Index) -> BTreeMap<&str, Vec<(&str, u32, &str)>> {
+    let mut by_name: BTreeMap<&str, Vec<(&str, u32, &str)>> = BTreeMap::new();
+    for (file, entry) in &index.files {
+        for func in &entry.functions {
+            by_name.entry(func.qualified_name.as_str()).or_default().push((
+                file.as_str(),
+                func.line_start,
+                func.ast_hash.as_str(),
+            ));
+        }
+    }
+    for entries in by_name.values_mut() {
+        entries.sort();
+    }
+    by_name
+}
+
+fn hashes<'a>(entries: &[(&str, u32, &'a str)]) -> Vec<&'a str> {
+    let mut hashes: Vec<&str> = entries.iter().map(|(_, _, hash)| *hash).collect();
+    hashes.sort();
+    hashes
+}
+
+fn function_ref(name: &str, entries: &[(&str, u32, &str)]) -> FunctionRef {
+    let (file, line, _) = entries[0];
+    FunctionRef { qualified_name: name.to_string(), file: file.to_string(), line }
+}
+
+fn coverage(index: &Index) -> Coverage {
+    let mut coverage = Coverage { commit: index.commit.clone(), ..Default::default() };
+    for func in index.files.values().flat_map(|e| e.functions.iter()) {
+        coverage.functions += 1;
+        coverage.calls += func.calls.len();
+        coverage.resolved_calls += func.calls.iter().filter(|c| !c.target.starts_with('[')).count();
+        if func.summary.is_some() {
+            coverage.summaries += 1;
+        }
+    }
+    coverage.resolved_pct = percent(coverage.resolved_calls, coverage.calls);
+    coverage.summary_pct = percent(coverage.summaries, coverage.functions);
+    coverage
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+fn print_text(diff: &IndexDiff, before: &Path, after: &Path) {
+    let label = |path: &Path, c: &Coverage| match c.commit.get(..7) {
+        Some(short) => format!("{} ({short})", path.display()),
+        None => path.display().to_string(),
+    };
+    println!("{} -> {}", label(before, &diff.before), label(after, &diff.after));
+    println!();
+    println!(
+        "Functions:  {} -> {} ({} added, {} removed, {} modified)",
+        diff.before.functions,
+        diff.after.functions,
+        diff.added.len(),
+        diff.removed.len(),
+        diff.modified.len()
+    );
+    println!(
+        "Resolved:   {:.1}% -> {:.1}% ({:+.1})",
+        diff.before.resolved_pct,
+        diff.after.resolved_pct,
+        diff.after.resolved_pct - diff.before.resolved_pct
+    );
+    println!(
+        "Summaries:  {:.1}% -> {:.1}% ({:+.1})",
+        diff.before.summary_pct,
+        diff.after.summary_pct,
+        diff.after.summary_pct - diff.before.summary_pct
+    );
+
+    for (label, marker, funcs) in
+        [("Added", '+', &diff.added), ("Removed", '-', &diff.removed), ("Modified", '~', &diff.modified)]
+    {
+        if funcs.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{label}:");
+        for func in funcs {
+            println!("  {marker} {} ({}:{})", func.qualified_name, func.file, func.line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{FileEntry, Function};
+
+    fn func(name: &str, ast_hash: &str, summarized: bool) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("pkg.{}", name),
+            ast_hash: ast_hash.to_string(),
+            line_start: 1,
+            line_end: 2,
+            summary: summarized.then(|| format!("does {}", name)),
+            ..Default::default()
+        }
+    }
+
+    fn index_of(functions: Vec<Function>) -> Index {
+        let mut index = Index::new();
+        index.files.insert(
+            "./pkg/a.go".to_string(),
+            FileEntry {
+                functions,
+                ..Default::default()
+            },
+        );
+        index
+    }
+
+    #[test]
+    fn test_diff_added_removed_modified() {
+        let old = index_of(vec![func("kept", "h1", true), func("changed", "h2", true), func("gone", "h3", false)]);
+        let new = index_of(vec![func("kept", "h1", true), func("changed", "h9", false), func("new", "h4", true)]);
+
+        let diff = diff(&old, &new);
+        let names = |funcs: &[FunctionRef]| funcs.iter().map(|f| f.qualified_name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&diff.added), vec!["pkg.new"]);
+        assert_eq!(names(&diff.removed), vec!["pkg.gone"]);
+        assert_eq!(names(&diff.modified), vec!["pkg.changed"]);
+
+        assert_eq!((diff.before.summaries, diff.after.summaries), (2, 2));
+        assert!((diff.before.summary_pct - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(diff.after.resolved_pct, 0.0);
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::list::language_of;
+use crate::commands::package::{normalize_prefix, package_of};
+use crate::index::{self, FileEntry, Index, Scope};
+
+/// Options shared by `aria export edges` and `aria export nodes`
+pub struct ExportOptions {
+    /// Record format: csv or jsonl
+    pub format: String,
+    /// Only functions in this package directory or below it
+    pub package: Option<String>,
+    /// Also export calls to external symbols (edges only)
+    pub include_external: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Csv,
+    Jsonl,
+}
+
+/// A row of an export: a JSONL object with a `schema` field, or a CSV line under a header
+trait Record: Serialize {
+    const COLUMNS: &'static [&'static str];
+    fn csv_row(&self) -> Vec<String>;
+}
+
+/// One resolved call or function reference: caller to callee, at a line of the caller's file
+#[derive(Debug, Serialize)]
+struct EdgeRecord<'a> {
+    schema: &'static str,
+    caller: &'a str,
+    /// Qualified name, or the "[external:name]" tag of an external symbol
+    callee: &'a str,
+    caller_file: &'a str,
+    line: u32,
+    /// "normal" for a call, "reference" for a function used as a value (passed, stored, or
+    /// returned); the parser does not tell deferred or spawned calls apart
+    kind: &'static str,
+}
+
+impl Record for EdgeRecord<'_> {
+    const COLUMNS: &'static [&'static str] = &["caller", "callee", "caller_file", "line", "kind"];
+
+    fn csv_row(&self) -> Vec<String> {
+        let fields = [self.caller, self.callee, self.caller_file, &self.line.to_string(), self.kind];
+        fields.iter().map(|f| f.to_string()).collect()
+    }
+}
+
+/// One indexed function with its place in the call graph
+#[derive(Debug, Serialize)]
+struct NodeRecord<'a> {
+    schema: &'static str,
+    qualified_name: &'a str,
+    file: &'a str,
+    line_start: u32,
+    line_end: u32,
+    scope: Scope,
+    language: &'static str,
+    /// Functions calling this one
+    fan_in: usize,
+    /// Distinct indexed functions this one calls
+    fan_out: usize,
+    /// Dependency level from `.aria/rank.json`, if `aria rank` was run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rank: Option<usize>,
+}
+
+impl Record for NodeRecord<'_> {
+    const COLUMNS: &'static [&'static str] =
+        &["qualified_name", "file", "line_start", "line_end", "scope", "language", "fan_in", "fan_out", "rank"];
+
+    fn csv_row(&self) -> Vec<String> {
+        let scope = self.scope.name();
+        vec![
+            self.qualified_name.to_string(),
+            self.file.to_string(),
+            self.line_start.to_string(),
+            self.line_end.to_string(),
+            scope.to_string(),
+            self.language.to_string(),
+            self.fan_in.to_string(),
+            self.fan_out.to_string(),
+            self.rank.map(|r| r.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+#[derive(Deserialize)]
+struct RankFile {
+    levels: Vec<Vec<String>>,
+}
+
+/// Stream one record per resolved call edge to stdout
+pub fn edges(opts: &ExportOptions) -> ExitCode {
+    export(opts, |files, format, out| write_records(out, format, edge_records(files, opts.include_external)))
+}
+
+/// Stream one record per indexed function to stdout
+pub fn nodes(opts: &ExportOptions) -> ExitCode {
+    let ranks = match load_ranks(&Path::new(".aria").join("rank.json")) {
+        Ok(ranks) => ranks,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    export(opts, |files, format, out| write_records(out, format, node_records(files, &ranks)))
+}
+
+/// Load the index, select the files of `opts.package`, and run `write` against buffered stdout
+fn export(
+    opts: &ExportOptions,
+    write: impl FnOnce(&[(&String, &FileEntry)], Format, &mut dyn Write) -> io::Result<()>,
+) -> ExitCode {
+    let format = match opts.format.as_str() {
+        "csv" => Format::Csv,
+        "jsonl" => Format::Jsonl,
+        other => {
+            eprintln!("error: unknown format '{other}' (expected csv or jsonl)");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let files = selected_files(&index, opts.package.as_deref());
+    if files.is_empty()
+        && let Some(package) = &opts.package
+    {
+        eprintln!("error: no package found matching '{package}'");
+        return ExitCode::FAILURE;
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    match write(&files, format, &mut out).and_then(|()| out.flush()) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The reader stopped early (e.g. `| head`); nothing is wrong with the export
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: failed to write export: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Indexed files in path order, limited to a package directory and those below it.
+/// Reference files are left out, as their calls are not recorded.
+fn selected_files<'a>(index: &'a Index, package: Option<&str>) -> Vec<(&'a String, &'a FileEntry)> {
+    let prefix = package.map(normalize_prefix);
+    let mut files: Vec<(&String, &FileEntry)> = index
+        .files
+        .iter()
+        .filter(|(_, entry)| !entry.reference)
+        .filter(|(file, _)| {
+            prefix.as_deref().is_none_or(|p| {
+                let package = package_of(file);
+                p == "." || package == p || package.starts_with(&format!("{p}/"))
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.0.cmp(b.0));
+    files
+}
+
+/// Edges in file, function, and line order, calls and references interleaved; unresolved
+/// and ambiguous calls and references are left out
+fn edge_records<'a>(
+    files: &'a [(&'a String, &'a FileEntry)],
+    include_external: bool,
+) -> impl Iterator<Item = EdgeRecord<'a>> {
+    files.iter().flat_map(move |(file, entry)| {
+        entry.functions.iter().flat_map(move |func| {
+            let calls = func.calls.iter().map(|c| (c, "normal"));
+            let references = func.references.iter().map(|r| (r, "reference"));
+            let mut edges: Vec<EdgeRecord> = calls
+                .chain(references)
+                .filter(|(c, _)| !c.target.starts_with('[') || (include_external && c.target.starts_with("[external:")))
+                .map(|(c, kind)| EdgeRecord {
+                    schema: "aria.edge.v1",
+                    caller: &func.qualified_name,
+                    callee: &c.target,
+                    caller_file: file,
+                    line: c.line,
+                    kind,
+                })
+                .collect();
+            // Stable, so a call and a reference on one line keep calls first
+            edges.sort_by_key(|e| e.line);
+            edges
+        })
+    })
+}
+
+/// Nodes in file and source order
+fn node_records<'a>(
+    files: &'a [(&'a String, &'a FileEntry)],
+    ranks: &'a HashMap<String, usize>,
+) -> impl Iterator<Item = NodeRecord<'a>> {
+    files.iter().flat_map(move |(file, entry)| {
+        entry.functions.iter().map(move |func| {
+            let callees: HashSet<&str> =
+                func.calls.iter().map(|c| c.target.as_str()).filter(|t| !t.starts_with('[')).collect();
+            NodeRecord {
+                schema: "aria.node.v1",
+                qualified_name: &func.qualified_name,
+                file,
+                line_start: func.line_start,
+                line_end: func.line_end,
+                scope: func.scope,
+                language: language_of(file),
+                fan_in: func.called_by.len(),
+                fan_out: callees.len(),
+                rank: ranks.get(&func.qualified_name).copied(),
+            }
+        })
+    })
+}
+
+/// Dependency level of each function from rank.json; empty when `aria rank` has not been run
+fn load_ranks(path: &Path) -> Result<HashMap<String, usize>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(format!("failed to read {}: {e}", path.display())),
+    };
+    let rank: RankFile =
+        serde_json::from_str(&content).map_err(|e| format!("failed to parse {}: {e}", path.display()))?;
+    Ok(rank
+        .levels
+        .into_iter()
+        .enumerate()
+        .flat_map(|(level, names)| names.into_iter().map(move |name| (name, level)))
+        .collect())
+}
+
+/// Write records one at a time, so an export of millions of edges is never held in memory
+fn write_records<R: Record>(out: &mut dyn Write, format: Format, records: impl Iterator<Item = R>) -> io::Result<()> {
+    if let Format::Csv = format {
+        writeln!(out, "{}", R::COLUMNS.join(","))?;
+    }
+    for record in records {
+        match format {
+            Format::Csv => {
+                let row: Vec<String> = record.csv_row().iter().map(|f| csv_field(f)).collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+            Format::Jsonl => {
+                serde_json::to_writer(&mut *out, &record)?;
+                writeln!(out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quote a CSV field that holds a comma, quote, or line break (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{CallSite, Function};
+
+    fn func(name: &str, calls: &[&str], called_by: &[&str]) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: name.to_string(),
+            line_start: 3,
+            line_end: 9,
+            calls: calls
+                .iter()
+                .enumerate()
+                .map(|(i, target)| CallSite {
+                    target: target.to_string(),
+                    raw: target.to_string(),
+                    line: 4 + i as u32,
+                    ..Default::default()
+                })
+                .collect(),
+            called_by: called_by.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn add_file(index: &mut Index, path: &str, functions: Vec<Function>) {
+        let entry = FileEntry {
+            functions,
+            ..Default::default()
+        };
+        index.files.insert(path.to_string(), entry);
+    }
+
+    fn export_to_string<R: Record>(format: Format, records: impl Iterator<Item = R>) -> String {
+        let mut out = Vec::new();
+        write_records(&mut out, format, records).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_edges_and_nodes_export() {
+        let mut index = Index::new();
+        let main_calls = ["store.Open", "store.Open", "[external:fmt.Println]", "[unresolved]", "[ambiguous:get]"];
+        let mut main = func("main.main", &main_calls, &[]);
+        // store.Close passed as a value on line 5; an unresolved reference is left out
+        main.references = ["store.Close", "[unresolved]"]
+            .iter()
+            .map(|target| CallSite { target: target.to_string(), raw: target.to_string(), line: 5, ..Default::default() })
+            .collect();
+        add_file(&mut index, "./main.go", vec![main]);
+        add_file(&mut index, "./internal/store/open.go", vec![func("store.Open", &[], &["main.main"])]);
+
+        let all = selected_files(&index, None);
+        let csv = export_to_string(Format::Csv, edge_records(&all, false));
+        assert_eq!(
+            csv,
+            "caller,callee,caller_file,line,kind\n\
+             main.main,store.Open,./main.go,4,normal\n\
+             main.main,store.Open,./main.go,5,normal\n\
+             main.main,store.Close,./main.go,5,reference\n"
+        );
+
+        let jsonl = export_to_string(Format::Jsonl, edge_records(&all, true));
+        let records: Vec<serde_json::Value> = jsonl.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[2]["kind"], "reference");
+        assert_eq!(records[2]["callee"], "store.Close");
+        assert_eq!(records[3]["schema"], "aria.edge.v1");
+        assert_eq!(records[3]["callee"], "[external:fmt.Println]");
+        assert_eq!(records[3]["kind"], "normal");
+
+        // Duplicate calls count once toward fan-out; rank is empty until `aria rank` runs
+        let ranks = HashMap::from([("store.Open".to_string(), 0)]);
+        let csv = export_to_string(Format::Csv, node_records(&all, &ranks));
+        let rows: Vec<&str> = csv.lines().skip(1).collect();
+        assert_eq!(rows, ["store.Open,./internal/store/open.go,3,9,public,go,1,0,0", "main.main,./main.go,3,9,public,go,0,1,"]);
+
+        let store = selected_files(&index, Some("internal"));
+        assert_eq!(store.len(), 1);
+        assert!(edge_records(&store, true).next().is_none());
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("store.Open"), "store.Open");
+        assert_eq!(csv_field("Map<K, V>::get"), "\"Map<K, V>::get\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::{BTreeSet, HashMap};
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+use crate::commands::callstack::sorted_calls;
+use crate::format::Formatter;
+use crate::index::Function;
+
+/// A file reached by a forward trace, with the functions hit inside it
+struct FileNode<'a> {
+    file: &'a str,
+    /// Qualified names of the functions reached in this file on this branch
+    functions: BTreeSet<&'a str>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// JSON shape of a file trace node
+#[derive(Serialize)]
+struct FileNodeJson<'a> {
+    /// Traced function, set on the top node only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root: Option<&'a str>,
+    file: &'a str,
+    functions: Vec<&'a str>,
+    children: Vec<FileNodeJson<'a>>,
+}
+
+/// Forward trace collapsed by file.
+///
+/// Consecutive calls within one file stay in the same node, and a file already on
+/// the current branch is reused rather than listed again, so each file appears
+/// at most once per branch.
+struct FileTrace<'a> {
+    func_map: &'a HashMap<&'a str, (&'a str, &'a Function)>,
+    max_depth: usize,
+    nodes: Vec<FileNode<'a>>,
+    /// (node, function) -> deepest remaining depth it was expanded with
+    expanded: HashMap<(usize, &'a str), usize>,
+}
+
+impl<'a> FileTrace<'a> {
+    fn new(
+        func_map: &'a HashMap<&'a str, (&'a str, &'a Function)>,
+        root_file: &'a str,
+        root: &'a Function,
+        max_depth: usize,
+    ) -> Self {
+        let mut trace = Self {
+            func_map,
+            max_depth,
+            nodes: vec![FileNode {
+                file: root_file,
+                functions: BTreeSet::from([root.qualified_name.as_str()]),
+                parent: None,
+                children: Vec::new(),
+            }],
+            expanded: HashMap::new(),
+        };
+        trace.visit(0, root, 0);
+        trace
+    }
+
+    fn visit(&mut self, node: usize, func: &'a Function, depth: usize) {
+        let remaining = self.max_depth.saturating_sub(depth);
+        let key = (node, func.qualified_name.as_str());
+        if self.expanded.get(&key).is_some_and(|&prev| prev >= remaining) {
+            return;
+        }
+        self.expanded.insert(key, remaining);
+
+        if depth >= self.max_depth {
+            return;
+        }
+
+        for call in sorted_calls(func) {
+            let Some(&(child_file, child_func)) = self.func_map.get(call.target.as_str()) else {
+                continue;
+            };
+            let child_node = self.node_for(node, child_file);
+            self.nodes[child_node].functions.insert(child_func.qualified_name.as_str());
+            self.visit(child_node, child_func, depth + 1);
+        }
+    }
+
+    /// Node for `file` reached from `node`: the same node or an ancestor on this
+    /// branch if it is already there, otherwise a (possibly new) child
+    fn node_for(&mut self, node: usize, file: &'a str) -> usize {
+        let mut current = Some(node);
+        while let Some(idx) = current {
+            if self.nodes[idx].file == file {
+                return idx;
+            }
+            current = self.nodes[idx].parent;
+        }
+
+        if let Some(&child) = self.nodes[node].children.iter().find(|&&c| self.nodes[c].file == file) {
+            return child;
+        }
+
+        let idx = self.nodes.len();
+        self.nodes.push(FileNode {
+            file,
+            functions: BTreeSet::new(),
+            parent: Some(node),
+            children: Vec::new(),
+        });
+        self.nodes[node].children.push(idx);
+        idx
+    }
+
+    fn to_json(&self, node: usize) -> FileNodeJson<'a> {
+        let n = &self.nodes[node];
+        FileNodeJson {
+            root: None,
+            file: n.file,
+            functions: n.functions.iter().copied().collect(),
+            children: n.children.iter().map(|&c| self.to_json(c)).collect(),
+        }
+    }
+
+    /// "./a.go (2 functions: Foo, Server.Bar)"
+    fn describe(&self, node: usize) -> String {
+        let n = &self.nodes[node];
+        let names: Vec<String> = n
+            .functions
+            .iter()
+            .map(|qn| match self.func_map.get(qn) {
+                Some((_, f)) => display_name(f),
+                None => qn.to_string(),
+            })
+            .collect();
+        let noun = if names.len() == 1 { "function" } else { "functions" };
+        format!("{} ({} {}: {})", n.file, names.len(), noun, names.join(", "))
+    }
+
+    fn render_children(&self, node: usize, prefix: &str, out: &mut Vec<String>) {
+        let children = &self.nodes[node].children;
+        for (i, &child) in children.iter().enumerate() {
+            let is_last = i == children.len() - 1;
+            let connector = if is_last { "└── " } else { "├── " };
+            let child_prefix = if is_last { "    " } else { "│   " };
+
+            out.push(format!("{}{}{}", prefix, connector, self.describe(child)));
+            self.render_children(child, &format!("{}{}", prefix, child_prefix), out);
+        }
+    }
+}
+
+/// Simple name with receiver, e.g. "Server.Start"
+fn display_name(func: &Function) -> String {
+    match &func.receiver {
+        Some(recv) => format!("{}.{}", recv, func.name),
+        None => func.name.clone(),
+    }
+}
+
+/// Print the file-level forward trace for every matched function
+pub fn print(
+    func_map: &HashMap<&str, (&str, &Function)>,
+    matches: &[(&str, &Function)],
+    max_depth: usize,
+    fmt: &Formatter,
+    json: bool,
+) -> ExitCode {
+    let traces: Vec<FileTrace> = matches
+        .iter()
+        .map(|(file, func)| FileTrace::new(func_map, file, func, max_depth))
+        .collect();
+
+    if json {
+        let trees: Vec<FileNodeJson> = traces
+            .iter()
+            .zip(matches)
+            .map(|(trace, (_, func))| FileNodeJson { root: Some(func.qualified_name.as_str()), ..trace.to_json(0) })
+            .collect();
+        return match serde_json::to_string_pretty(&trees) {
+            Ok(out) => {
+                println!("{out}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    for line in render(&traces, matches, fmt) {
+        println!("{line}");
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Text lines for every trace, each under a header naming its root when the
+/// name matched more than one function
+fn render(traces: &[FileTrace], matches: &[(&str, &Function)], fmt: &Formatter) -> Vec<String> {
+    let mut out = Vec::new();
+    for (i, (trace, (file_path, func))) in traces.iter().zip(matches).enumerate() {
+        if i > 0 {
+            out.push(String::new());
+        }
+        if matches.len() > 1 {
+            out.push(format!(
+                "=== {} ({}:{}-{}) ===",
+                fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end
+            ));
+        }
+        out.push(trace.describe(0));
+        trace.render_children(0, "", &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::CallSite;
+
+    fn func(qualified: &str, calls: &[&str]) -> Function {
+        Function {
+            name: qualified.rsplit('.').next().unwrap().to_string(),
+            qualified_name: qualified.to_string(),
+            line_start: 1,
+            line_end: 1,
+            calls: calls
+                .iter()
+                .enumerate()
+                .map(|(i, t)| CallSite {
+                    target: t.to_string(),
+                    raw: t.to_string(),
+                    callee_name: t.to_string(),
+                    line: i as u32 + 1,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_file_trace_collapses_and_dedupes() {
+        // a.go: main -> helper (same file), main -> b.go:run -> a.go:util, run -> c.go:leaf, run -> c.go:leaf2
+        let funcs = [
+            ("a.go", func("a.main", &["a.helper", "b.run"])),
+            ("a.go", func("a.helper", &[])),
+            ("a.go", func("a.util", &[])),
+            ("b.go", func("b.run", &["a.util", "c.leaf", "c.leaf2", "[libc:malloc]"])),
+            ("c.go", func("c.leaf", &[])),
+            ("c.go", func("c.leaf2", &[])),
+        ];
+        let func_map: HashMap<&str, (&str, &Function)> =
+            funcs.iter().map(|(f, func)| (func.qualified_name.as_str(), (*f, func))).collect();
+
+        let trace = FileTrace::new(&func_map, "a.go", &funcs[0].1, usize::MAX);
+        let tree = trace.to_json(0);
+
+        assert_eq!(tree.file, "a.go");
+        assert_eq!(tree.functions, vec!["a.helper", "a.main", "a.util"]);
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].file, "b.go");
+        assert_eq!(tree.children[0].children.len(), 1);
+        assert_eq!(tree.children[0].children[0].file, "c.go");
+        assert_eq!(tree.children[0].children[0].functions, vec!["c.leaf", "c.leaf2"]);
+    }
+
+    #[test]
+    fn test_file_trace_respects_depth_and_cycles() {
+        let funcs = [
+            ("a.go", func("a.main", &["b.ping"])),
+            ("b.go", func("b.ping", &["c.pong"])),
+            ("c.go", func("c.pong", &["b.ping"])),
+        ];
+        let func_map: HashMap<&str, (&str, &Function)> =
+            funcs.iter().map(|(f, func)| (func.qualified_name.as_str(), (*f, func))).collect();
+
+        let shallow = FileTrace::new(&func_map, "a.go", &funcs[0].1, 1).to_json(0);
+        assert_eq!(shallow.children.len(), 1);
+        assert!(shallow.children[0].children.is_empty());
+
+        let full = FileTrace::new(&func_map, "a.go", &funcs[0].1, usize::MAX).to_json(0);
+        assert_eq!(full.children[0].file, "b.go");
+        assert_eq!(full.children[0].children[0].file, "c.go");
+        assert!(full.children[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_file_trace_heads_each_of_several_roots() {
+        let funcs = [
+            ("a.go", func("a.Server.Start", &["b.run"])),
+            ("c.go", func("c.Client.Start", &[])),
+            ("b.go", func("b.run", &[])),
+        ];
+        let func_map: HashMap<&str, (&str, &Function)> =
+            funcs.iter().map(|(f, func)| (func.qualified_name.as_str(), (*f, func))).collect();
+        let matches: Vec<(&str, &Function)> = funcs[..2].iter().map(|(f, func)| (*f, func)).collect();
+        let traces: Vec<FileTrace> =
+            matches.iter().map(|(file, func)| FileTrace::new(&func_map, file, func, usize::MAX)).collect();
+        let fmt = Formatter::full();
+
+        assert_eq!(
+            render(&traces, &matches, &fmt),
+            vec![
+                "=== a.Server.Start (a.go:1-1) ===",
+                "a.go (1 function: Start)",
+                "└── b.go (1 function: run)",
+                "",
+                "=== c.Client.Start (c.go:1-1) ===",
+                "c.go (1 function: Start)",
+            ]
+        );
+
+        assert_eq!(render(&traces[..1], &matches[..1], &fmt)[0], "a.go (1 function: Start)");
+    }
+}
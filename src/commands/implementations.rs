@@ -0,0 +1,37 @@
+use std::process::ExitCode;
+
+use crate::commands::types::{describe_link, find_types};
+use crate::freshness;
+use crate::index::{self, TypeKind};
+
+pub fn run(name: &str) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let matches: Vec<_> = find_types(&index, name).into_iter().filter(|(_, t)| t.kind == TypeKind::Interface).collect();
+    if matches.is_empty() {
+        eprintln!("error: no trait or interface found matching '{name}'");
+        return ExitCode::FAILURE;
+    }
+    freshness::check(&index, matches.iter().map(|(file, _)| *file));
+
+    for (i, (file, t)) in matches.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{} ({}:{}-{})", t.qualified_name, file, t.line_start, t.line_end);
+        if t.implemented_by.is_empty() {
+            println!("  (no implementations found)");
+        }
+        for link in &t.implemented_by {
+            println!("  {}", describe_link(link));
+        }
+    }
+
+    ExitCode::SUCCESS
+}
@@ -1,137 +1,406 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::process::ExitCode;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use serde::Serialize;
 use walkdir::WalkDir;
 
-use crate::config::Config;
-use crate::index::Index;
-use crate::parser::{CParser, GoParser, RustParser};
+use crate::commands::{cache, completions, package};
+use crate::config::{Config, IndexConfig};
+use crate::externals;
+use crate::index::{self, FileEntry, FileTiming, Function, Index, IndexFormat, Timings};
+use crate::interrupt;
+use crate::parser::{self, Grammar, ParserRegistry};
+use crate::progress;
 use crate::resolver::Resolver;
-use crate::summarizer::{Summarizer, SummaryRequest};
+use crate::sanitize;
+use crate::summarizer::{self, Summarizer, SummarizerError, SummaryRequest};
 use crate::topo;
 
 const README_MD: &str = include_str!("../../docs/README.md");
 
-pub fn run() -> ExitCode {
+/// Files with more of their bytes than this in ERROR nodes are reported while indexing
+const PARSE_ERROR_WARN_RATIO: f64 = 0.1;
+
+/// Number of slowest-to-parse files kept in the index timings
+const SLOWEST_FILES: usize = 10;
+
+/// Maps qualified_name -> (file path, index into FileEntry.functions)
+type FuncLocations = HashMap<String, (String, usize)>;
+
+/// Build the index and write it to `output`, or to the index path of this run.
+/// `include_tests` indexes test files even when the config does not ask for them.
+pub fn run(output: Option<&Path>, include_tests: bool) -> ExitCode {
     let aria_dir = Path::new(".aria");
+    let index_path = output.unwrap_or(index::index_path());
 
     if let Err(e) = ensure_aria_dir(aria_dir) {
         eprintln!("error: {e}");
         return ExitCode::FAILURE;
     }
 
+    interrupt::install();
+
     let config = load_config(aria_dir);
-    let old_index = load_existing_index(aria_dir);
+    let mut registry = match ParserRegistry::configured(&config.languages, &config.index) {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut old_index = match load_existing_index(index_path) {
+        Ok(old) => old,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(old) = &mut old_index
+        && let Some(warning) = legacy_hash_warning(migrate_legacy_hashes(old, Path::new("."), &mut registry))
+    {
+        progress::warn("legacy_hashes", &warning);
+    }
 
-    let (mut index, sources) = parse_source_files(config.features.summaries);
+    let include_tests = include_tests || config.features.include_tests;
+    let (mut index, sources) =
+        build_index(Path::new("."), config.features.summaries, None, &config.index, include_tests, &mut registry);
 
-    // Resolve call targets and populate called_by
-    let mut resolver = Resolver::new();
-    resolver.build_symbol_table(&index.files);
-    resolver.resolve(&mut index);
+    // A partially parsed tree would drop files from the index, so keep the old one
+    if interrupt::is_interrupted() {
+        eprintln!("interrupted — existing index left unchanged");
+        return ExitCode::from(interrupt::EXIT_INTERRUPTED);
+    }
+    restore_user_externals(&mut index, aria_dir);
 
     // Preserve summaries from old index for unchanged functions
-    let preserved = preserve_summaries(&mut index, &old_index);
+    let preserved = preserve_summaries(&mut index, old_index.as_ref());
     if preserved > 0 {
         println!("Preserved {} existing summaries", preserved);
     }
 
-    if config.features.summaries {
-        run_summarization(&config, &mut index, &sources);
-    }
+    let generated = if config.features.summaries {
+        run_summarization(&config, &mut index, &sources, None, &HashSet::new())
+    } else {
+        0
+    };
+
+    let package_summaries = config.features.summaries && config.features.package_summaries;
+    let packages = package::rollup_packages(&config, &mut index, old_index.as_ref(), package_summaries);
 
     index.commit = get_git_head().unwrap_or_default();
+    if let Some(old) = &old_index {
+        keep_unchanged_stamp(&mut index, old);
+    }
 
     // Write index
-    match write_index(aria_dir, &index) {
-        Ok(()) => ExitCode::SUCCESS,
+    let totals = match write_index(index_path, &index) {
+        Ok(totals) => totals,
         Err(e) => {
             eprintln!("error: {e}");
-            ExitCode::FAILURE
+            return ExitCode::FAILURE;
         }
+    };
+    progress::emit(&progress::Event::Result {
+        command: "index",
+        details: serde_json::json!({
+            "files": totals.files,
+            "functions": totals.functions,
+            "types": totals.types,
+            "calls": totals.calls,
+            "resolved_pct": totals.resolved_pct,
+            "preserved_summaries": preserved,
+            "generated_summaries": generated,
+            "package_summaries": packages,
+            "interrupted": interrupt::is_interrupted(),
+        }),
+    });
+
+    if interrupt::is_interrupted() {
+        eprintln!("interrupted — progress saved");
+        return ExitCode::from(interrupt::EXIT_INTERRUPTED);
     }
+
+    ExitCode::SUCCESS
 }
 
-/// Walk the source tree, parse all files, return the index and sources
-fn parse_source_files(store_sources: bool) -> (Index, HashMap<String, String>) {
+/// Parse every source file under `root` that `registry` has a parser for, test files only with
+/// `include_tests`, and resolve call targets across files. Files whose content hash matches an
+/// entry in `previous` reuse that entry instead of reparsing, and only the edges affected by the
+/// other files are resolved again.
+pub fn build_index(
+    root: &Path,
+    store_sources: bool,
+    previous: Option<&Index>,
+    config: &IndexConfig,
+    include_tests: bool,
+    registry: &mut ParserRegistry,
+) -> (Index, HashMap<String, String>) {
+    let (mut index, sources, mut changed) =
+        parse_source_files(root, store_sources, previous, config, include_tests, registry);
+
+    // Resolve call targets and populate called_by
+    let phase = progress::Phase::start("resolve", None);
+    let mut resolver = Resolver::new();
+    resolver.build_symbol_table(&index.files);
+    match previous {
+        Some(previous) => {
+            changed.extend(previous.files.keys().filter(|k| !index.files.contains_key(*k)).cloned());
+            resolver.resolve_changed(&mut index, previous, &changed);
+        }
+        None => resolver.resolve(&mut index),
+    }
+    let resolve_ms = phase.finish(index.files.values().map(|e| e.functions.len()).sum());
+    Timings::record(&mut index, "resolve", resolve_ms);
+
+    (index, sources)
+}
+
+/// Reapply the external descriptions written with `aria annotate-external`
+pub fn restore_user_externals(index: &mut Index, aria_dir: &Path) {
+    match externals::load_user_externals(aria_dir) {
+        Ok(user) => externals::apply_user_externals(index, &user),
+        Err(e) => progress::warn("user_externals", &e),
+    }
+}
+
+/// Language of a source file aria indexes, by extension: "go", "rust", "c", "cpp", "python", "typescript", or "java"
+pub fn language_of(path: &Path) -> Option<&'static str> {
+    parser::grammar_of(path).map(|g| g.name)
+}
+
+/// One warning per language found under the root whose grammar this build lacks, e.g.
+/// "found 3 .c/.h files but this build lacks lang-c"
+fn missing_grammar_warnings<'a>(skipped: impl Iterator<Item = (&'a Grammar, &'a Path)>) -> Vec<String> {
+    let mut found: BTreeMap<&str, (usize, BTreeSet<String>)> = BTreeMap::new();
+    for (grammar, path) in skipped {
+        let (count, extensions) = found.entry(grammar.feature).or_default();
+        *count += 1;
+        if let Some(ext) = path.extension() {
+            extensions.insert(format!(".{}", ext.to_string_lossy()));
+        }
+    }
+    found
+        .into_iter()
+        .map(|(feature, (count, extensions))| {
+            let extensions: Vec<String> = extensions.into_iter().collect();
+            let files = if count == 1 { "file" } else { "files" };
+            format!("found {count} {} {files} but this build lacks {feature}", extensions.join("/"))
+        })
+        .collect()
+}
+
+/// The `SLOWEST_FILES` longest parse times, slowest first, ties by path
+fn slowest_files(mut parse_times: Vec<(String, Duration)>) -> Vec<FileTiming> {
+    parse_times.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    parse_times
+        .into_iter()
+        .take(SLOWEST_FILES)
+        .map(|(file, took)| FileTiming { file, parse_ms: (took.as_secs_f64() * 100_000.0).round() / 100.0 })
+        .collect()
+}
+
+/// Warning for a file the grammar could largely not parse, so its symbols may be missing
+fn parse_error_warning(path: &str, language: &str, ratio: f64) -> Option<String> {
+    (ratio > PARSE_ERROR_WARN_RATIO).then(|| {
+        format!(
+            "{path}: {:.0}% of the file did not parse, so functions in it may be missing; \
+             the {} grammar in this build may predate its syntax",
+            ratio * 100.0,
+            language
+        )
+    })
+}
+
+/// Walk the source tree, parse every file `registry` has a parser for, and return the index,
+/// sources, and the files that were parsed rather than reused from `previous`
+fn parse_source_files(
+    root: &Path,
+    store_sources: bool,
+    previous: Option<&Index>,
+    config: &IndexConfig,
+    include_tests: bool,
+    registry: &mut ParserRegistry,
+) -> (Index, HashMap<String, String>, HashSet<String>) {
     let mut index = Index::new();
     let mut sources: HashMap<String, String> = HashMap::new();
-    let mut go_parser = GoParser::new();
-    let mut rust_parser = RustParser::new();
-    let mut c_parser = CParser::new();
     let mut file_count = 0;
     let mut func_count = 0;
     let mut type_count = 0;
-
-    for entry in WalkDir::new(".")
+    let mut reused_count = 0;
+    let mut too_large = 0;
+    let mut parsed_files: HashSet<String> = HashSet::new();
+    let mut parse_times: Vec<(String, Duration)> = Vec::new();
+
+    // Index keys are always "./<path relative to root>"
+    let is_reference = |path: &Path| {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        config.reference_dirs.iter().any(|dir| is_reference_dir(relative, dir))
+    };
+    for warning in config.language_warnings() {
+        progress::warn("missing_grammar", &warning);
+    }
+    let walk_started = Instant::now();
+    registry.scan_root(root);
+    let (candidates, skipped): (Vec<_>, Vec<_>) = WalkDir::new(root)
+        .sort_by_file_name()
         .into_iter()
-        .filter_entry(|e| !is_hidden(e) && !is_ignored(e))
+        .filter_entry(|e| !is_hidden(e) && (!is_ignored(e) || is_reference(e.path())))
         .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-        let ext = path.extension().and_then(|e| e.to_str());
-
-        let lang = match ext {
-            Some("go") => "go",
-            Some("rs") => "rust",
-            Some("c") | Some("h") => "c",
-            _ => continue,
-        };
-
-        let path_str = path.to_string_lossy();
-        if lang == "go" && path_str.ends_with("_test.go") {
-            continue;
+        .filter_map(|entry| {
+            // Files of a known grammar left out of this build are kept to warn about
+            let language = match registry.language_of(entry.path()) {
+                Some(name) => Ok(name),
+                None => Err(parser::grammar_of(entry.path()).filter(|g| !g.compiled)?),
+            };
+            let name = language.unwrap_or_else(|grammar| grammar.name);
+            let path_str = index_key(root, entry.path());
+            if !include_tests && index::is_test_file(&path_str) || !config.includes_language(name) {
+                return None;
+            }
+            Some((entry.into_path(), language, path_str))
+        })
+        .partition(|(_, language, _)| language.is_ok());
+    let skipped = skipped.iter().filter_map(|(path, language, _)| Some((language.err()?, path.as_path())));
+    for warning in missing_grammar_warnings(skipped) {
+        progress::warn("missing_grammar", &warning);
+    }
+    let candidates: Vec<_> =
+        candidates.into_iter().filter_map(|(path, language, key)| Some((path, language.ok()?, key))).collect();
+    let walk_ms = walk_started.elapsed().as_millis() as u64;
+    progress::report_timing("walk", walk_ms);
+
+    let phase = progress::Phase::start("parse", Some(candidates.len()));
+    for (done, (path, language, path_str)) in candidates.into_iter().enumerate() {
+        let reference = is_reference(&path);
+        if interrupt::is_interrupted() {
+            break;
         }
+        phase.progress(done + 1);
 
-        let source = match fs::read_to_string(path) {
-            Ok(s) => s,
+        let source = match index::read_source(&path, config.max_file_size()) {
+            Ok(Some(s)) => s,
+            Ok(None) => {
+                progress::warn("file_too_large", &format!("{} skipped (too large)", path_str));
+                too_large += 1;
+                continue;
+            }
             Err(e) => {
-                eprintln!("warning: failed to read {}: {}", path_str, e);
+                progress::warn("read_failed", &format!("failed to read {}: {}", path_str, e));
                 continue;
             }
         };
 
-        let parsed = match lang {
-            "go" => go_parser.parse_file(&source, &path_str),
-            "rust" => rust_parser.parse_file(&source, &path_str),
-            "c" => c_parser.parse_file(&source, &path_str),
-            _ => None,
+        let unchanged = previous
+            .and_then(|p| p.files.get(&path_str))
+            .filter(|e| e.ast_hash == parser::content_hash(source.as_bytes()) && e.reference == reference);
+
+        let parsed = if let Some(old_entry) = unchanged {
+            reused_count += 1;
+            Some(old_entry.clone())
+        } else {
+            parsed_files.insert(path_str.clone());
+            let started = Instant::now();
+            let parsed = registry.parse_file(&path, &source, &path_str);
+            parse_times.push((path_str.clone(), started.elapsed()));
+            if reference { parsed.map(into_reference) } else { parsed }
         };
 
         match parsed {
-            Some(file_entry) => {
+            Some(mut file_entry) => {
+                file_entry.mtime_ms = index::mtime_ms(&path);
+                file_entry.is_test = index::is_test_file(&path_str);
+                if let Some(warning) = parse_error_warning(&path_str, language, file_entry.parse_error_ratio) {
+                    progress::warn("parse_errors", &warning);
+                }
                 func_count += file_entry.functions.len();
                 type_count += file_entry.types.len();
                 file_count += 1;
-                if store_sources {
-                    sources.insert(path_str.to_string(), source);
+                // Reference files are never summarized, so their source is not needed
+                if store_sources && !reference {
+                    sources.insert(path_str.clone(), source);
                 }
-                index.files.insert(path_str.to_string(), file_entry);
+                index.files.insert(path_str, file_entry);
             }
             None => {
-                eprintln!("warning: failed to parse {}", path_str);
+                progress::warn("parse_failed", &format!("failed to parse {}", path_str));
             }
         }
     }
+    let parse_ms = phase.finish(file_count);
+    Timings::record(&mut index, "walk", walk_ms);
+    Timings::record(&mut index, "parse", parse_ms);
+    if let Some(timings) = &mut index.timings {
+        timings.slowest_files = slowest_files(parse_times);
+    }
 
-    println!(
-        "Parsed {} files: {} functions, {} types",
-        file_count, func_count, type_count
-    );
+    if previous.is_some() {
+        println!(
+            "Parsed {} files: {} functions, {} types ({} files unchanged)",
+            file_count, func_count, type_count, reused_count
+        );
+    } else {
+        println!(
+            "Parsed {} files: {} functions, {} types",
+            file_count, func_count, type_count
+        );
+    }
+    if too_large > 0 {
+        let files = if too_large == 1 { "file" } else { "files" };
+        println!(
+            "{too_large} {files} skipped (too large, over {} bytes); raise index.max_file_size to index them",
+            config.max_file_size()
+        );
+    }
+    let error_files = index.files.values().filter(|e| e.parse_errors > 0).count();
+    if error_files > 0 {
+        let files = if error_files == 1 { "file" } else { "files" };
+        println!("{error_files} {files} indexed with parse errors; run `aria validate` to list them");
+    }
 
-    (index, sources)
+    (index, sources, parsed_files)
+}
+
+/// Keep `old`'s `indexed_at` and timings when nothing else in the index changed, so indexing
+/// again without changes writes the same bytes. Files whose content is unchanged keep their
+/// recorded mtime too: a checkout or `touch` changes it without changing anything indexed.
+pub fn keep_unchanged_stamp(index: &mut Index, old: &Index) {
+    for (key, entry) in index.files.iter_mut() {
+        if let Some(old_entry) = old.files.get(key).filter(|o| o.ast_hash == entry.ast_hash) {
+            entry.mtime_ms = old_entry.mtime_ms;
+        }
+    }
+
+    let indexed_at = std::mem::replace(&mut index.indexed_at, old.indexed_at);
+    let timings = std::mem::replace(&mut index.timings, old.timings.clone());
+    // A sharded index loads with the manifest's shard list, which is not content
+    let shards = std::mem::replace(&mut index.shards, old.shards.clone());
+    let unchanged = matches!(
+        (serde_json::to_vec(index), serde_json::to_vec(old)),
+        (Ok(new), Ok(old)) if new == old
+    );
+    index.shards = shards;
+    if !unchanged {
+        index.indexed_at = indexed_at;
+        index.timings = timings;
+    }
 }
 
-/// Serialize and write the index to disk, print stats
-fn write_index(aria_dir: &Path, index: &Index) -> Result<(), String> {
-    let index_json = serde_json::to_string_pretty(index)
-        .map_err(|e| format!("failed to serialize index: {e}"))?;
+/// Counts printed after writing the index
+#[derive(Debug, Serialize)]
+pub struct IndexTotals {
+    pub files: usize,
+    pub functions: usize,
+    pub types: usize,
+    pub calls: usize,
+    pub resolved_pct: f64,
+}
 
-    fs::write(aria_dir.join("index.json"), index_json)
-        .map_err(|e| format!("failed to write index.json: {e}"))?;
+/// Serialize and write the index to `index_path`, print stats
+pub fn write_index(index_path: &Path, index: &Index) -> Result<IndexTotals, String> {
+    save_index(index_path, index)?;
 
     // Print stats
     let mut file_count = 0;
@@ -167,13 +436,130 @@ fn write_index(aria_dir: &Path, index: &Index) -> Result<(), String> {
         file_count, func_count, type_count, total_calls, pct
     );
 
+    Ok(IndexTotals { files: file_count, functions: func_count, types: type_count, calls: total_calls, resolved_pct: pct })
+}
+
+/// Write the index to `index_path` atomically, or as shards next to it with index.sharded,
+/// along with the completion names file
+pub fn save_index(index_path: &Path, index: &Index) -> Result<(), String> {
+    // Timed for --verbose only: the index cannot hold the time it takes to write itself
+    let started = Instant::now();
+    let config = load_config(Path::new(".aria")).index;
+    // Only one of the single file and the shards may exist, since the file is loaded first
+    if config.sharded {
+        save_shards(index_path, index, config.format)?;
+        if index_path.exists() {
+            fs::remove_file(index_path).map_err(|e| format!("failed to remove {}: {e}", index_path.display()))?;
+        }
+    } else {
+        let content = index::encode_index(index, config.format).map_err(|e| format!("failed to serialize index: {e}"))?;
+        write_atomic(index_path, &content)?;
+        if index::shard_manifest(index_path).exists() {
+            let dir = index::shard_dir(index_path);
+            fs::remove_dir_all(&dir).map_err(|e| format!("failed to remove {}: {e}", dir.display()))?;
+        }
+    }
+
+    // Plain name list so shell completion does not have to parse the index. Completion
+    // always reads the default index, so other index files get no names file.
+    if index_path == Path::new(index::DEFAULT_INDEX_PATH) {
+        let mut names = completions::function_names(index).join("\n");
+        names.push('\n');
+        fs::write(index_path.with_file_name(completions::NAMES_FILE), names)
+            .map_err(|e| format!("failed to write {}: {e}", completions::NAMES_FILE))?;
+    }
+
+    progress::report_timing("write", started.elapsed().as_millis() as u64);
     Ok(())
 }
 
-fn run_summarization(config: &Config, index: &mut Index, sources: &HashMap<String, String>) {
-    let summarizer = Summarizer::new(config.llm.batch_size, config.llm.parallel, config.debug);
+/// Write to a temp file and rename so an interrupted write never leaves a truncated file
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
 
-    let (level_groups, func_locations) = build_topology(index, config.debug);
+/// Write one shard per top-level directory and a JSON manifest listing them. Shards whose
+/// content did not change are left alone, so an update confined to one directory rewrites one.
+fn save_shards(index_path: &Path, index: &Index, format: IndexFormat) -> Result<(), String> {
+    let dir = index::shard_dir(index_path);
+    let shards_dir = dir.join(index::SHARDS_SUBDIR);
+    fs::create_dir_all(&shards_dir).map_err(|e| format!("failed to create {}: {e}", shards_dir.display()))?;
+    let manifest_path = index::shard_manifest(index_path);
+    let previous: Vec<String> = fs::read(&manifest_path)
+        .ok()
+        .and_then(|content| index::decode_index::<Index>(&content).ok())
+        .map(|manifest| manifest.shards)
+        .unwrap_or_default();
+
+    let mut shards: BTreeMap<String, BTreeMap<&String, &FileEntry>> = BTreeMap::new();
+    for (key, entry) in &index.files {
+        shards.entry(index::shard_of(key)).or_default().insert(key, entry);
+    }
+    let mut names = Vec::new();
+    for (shard, files) in &shards {
+        let name = format!("{shard}.{}", format.name());
+        let content = index::encode_index(files, format).map_err(|e| format!("failed to serialize index: {e}"))?;
+        let path = dir.join(&name);
+        if fs::read(&path).ok().as_deref() != Some(content.as_slice()) {
+            write_atomic(&path, &content)?;
+        }
+        names.push(name);
+    }
+
+    let manifest = Index {
+        schema_version: index.schema_version,
+        version: index.version.clone(),
+        hash_version: index.hash_version,
+        commit: index.commit.clone(),
+        indexed_at: index.indexed_at,
+        files: HashMap::new(),
+        externals: index.externals.clone(),
+        packages: index.packages.clone(),
+        timings: index.timings.clone(),
+        shards: names,
+    };
+    let content =
+        index::encode_index(&manifest, IndexFormat::Json).map_err(|e| format!("failed to serialize index: {e}"))?;
+    write_atomic(&manifest_path, &content)?;
+
+    // Directories that no longer have indexed files
+    for old in previous.iter().filter(|old| !manifest.shards.contains(old)) {
+        let _ = fs::remove_file(dir.join(old));
+    }
+    Ok(())
+}
+
+/// Generate summaries for functions that lack one, limited to `only` when given, and for the
+/// functions in `force` even when they have one; a forced function keeps its old summary until
+/// the new one replaces it. Returns the number of summaries generated.
+///
+/// Requests are built as soon as a function's callees from lower levels have finished and are
+/// fed to a pool of LLM workers, so the next level starts while the current one's stragglers run.
+pub fn run_summarization(
+    config: &Config,
+    index: &mut Index,
+    sources: &HashMap<String, String>,
+    only: Option<&HashSet<String>>,
+    force: &HashSet<String>,
+) -> usize {
+    let (mut level_groups, func_locations, calls_map) = build_topology(index, config.debug);
+
+    // Keep the topological order, restricted to the requested functions
+    if let Some(only) = only {
+        for group in &mut level_groups {
+            group.retain(|qn| only.contains(qn));
+        }
+    }
+
+    // Reference files (vendored code) are navigable but never summarized
+    for group in &mut level_groups {
+        group.retain(|qn| {
+            func_locations.get(qn).is_none_or(|(path, _)| !index.files.get(path).is_some_and(|e| e.reference))
+        });
+    }
 
     // Collect existing summaries for callee context
     let mut summaries: HashMap<String, String> = HashMap::new();
@@ -185,16 +571,23 @@ fn run_summarization(config: &Config, index: &mut Index, sources: &HashMap<Strin
         }
     }
 
-    let total: usize = level_groups
-        .iter()
-        .flat_map(|g| g.iter())
-        .filter(|qn| !summaries.contains_key(*qn))
-        .count();
+    for group in &mut level_groups {
+        group.retain(|qn| force.contains(qn) || !summaries.contains_key(qn));
+    }
+    let total: usize = level_groups.iter().map(|g| g.len()).sum();
 
     if total == 0 {
-        return;
+        return 0;
     }
 
+    let summarizer = match Summarizer::new(&config.llm, config.debug) {
+        Ok(summarizer) => summarizer,
+        Err(e) => {
+            progress::warn("summarize_failed", &e);
+            return 0;
+        }
+    };
+
     println!(
         "Generating summaries for {} functions in {} levels (batch={}, parallel={})...",
         total, level_groups.len(), config.llm.batch_size, config.llm.parallel
@@ -202,73 +595,423 @@ fn run_summarization(config: &Config, index: &mut Index, sources: &HashMap<Strin
 
     let mut summary_count = 0;
     let mut error_count = 0;
+    let mut recovered_count = 0;
     let summarization_start = Instant::now();
 
-    for (level, funcs_at_level) in level_groups.iter().enumerate() {
-        let level_start = Instant::now();
+    let mut queue = ReadyQueue::new(&level_groups, &calls_map);
+    let mut levels: Vec<LevelProgress> = level_groups
+        .iter()
+        .map(|g| LevelProgress { remaining: g.len(), requested: 0, with_context: 0, started: None })
+        .collect();
+
+    let phase = progress::Phase::start("summarize", Some(total));
+    let pool = summarizer.start();
+    let mut request_qnames: Vec<String> = Vec::new();
+    let mut request_batch: Vec<usize> = Vec::new();
+    let mut batch_remaining: Vec<usize> = Vec::new();
+    let mut batches_in_flight = 0;
+    let mut staged: Vec<SummaryRequest> = Vec::new();
+    let mut shared = SharedPrompts::default();
+    // Summaries already generated for the same model and prompt are not requested again
+    let cache_dir = cache::cache_dir();
+    let mut request_keys: Vec<String> = Vec::new();
+    let mut cached_count = 0;
+
+    loop {
+        if !interrupt::is_interrupted() {
+            // Build requests for everything whose callees are done; functions without a body finish immediately
+            while let Some(qualified_name) = queue.pop() {
+                let level = queue.level_of(&qualified_name);
+                let request = build_request(
+                    &qualified_name, &func_locations, &summaries, index, sources, config, level,
+                );
+                match request {
+                    Some(mut request) => {
+                        let cache_key = summary_cache_key(config, &request);
+                        if let Some(summary) = cache::read_summary(&cache_dir, &cache_key) {
+                            if store_summary(index, &func_locations, &qualified_name, &summary, None) {
+                                summary_count += 1;
+                                cached_count += 1;
+                            }
+                            summaries.insert(qualified_name.clone(), summary);
+                            queue.complete(&qualified_name);
+                            finish_level_function(&mut levels, level);
+                            continue;
+                        }
 
-        let (requests, request_qnames) = collect_level_requests(
-            funcs_at_level, &func_locations, &summaries, index, sources, config.debug, level,
-        );
+                        let key = shared_prompt_key(&qualified_name, &func_locations, index, &request);
+                        if let Some(representative) = key.as_ref().and_then(|k| shared.representative(k)) {
+                            match shared.reuse(representative) {
+                                Some(summary) => {
+                                    cache::write_summary(&cache_dir, &cache_key, &summary);
+                                    let from = &request_qnames[representative];
+                                    if store_summary(index, &func_locations, &qualified_name, &summary, Some(from)) {
+                                        summary_count += 1;
+                                    }
+                                    summaries.insert(qualified_name.clone(), summary);
+                                    queue.complete(&qualified_name);
+                                    finish_level_function(&mut levels, level);
+                                }
+                                None => shared.wait(representative, qualified_name, request),
+                            }
+                            continue;
+                        }
 
-        if requests.is_empty() {
-            continue;
+                        request.id = request_qnames.len();
+                        if let Some(key) = key {
+                            shared.add(key, request.id);
+                        }
+                        let progress = &mut levels[level];
+                        progress.started.get_or_insert_with(Instant::now);
+                        progress.requested += 1;
+                        if !request.callee_context.is_empty() {
+                            progress.with_context += 1;
+                        }
+                        request_qnames.push(qualified_name);
+                        request_keys.push(cache_key);
+                        staged.push(request);
+                    }
+                    None => {
+                        queue.complete(&qualified_name);
+                        finish_level_function(&mut levels, level);
+                    }
+                }
+            }
+
+            // Full batches go out right away; a partial one only when a worker would otherwise sit idle
+            while staged.len() >= summarizer.batch_size()
+                || (!staged.is_empty() && batches_in_flight < summarizer.parallel())
+            {
+                let rest = staged.split_off(staged.len().min(summarizer.batch_size()));
+                let batch = std::mem::replace(&mut staged, rest);
+                request_batch.extend(batch.iter().map(|_| batch_remaining.len()));
+                batch_remaining.push(batch.len());
+                batches_in_flight += 1;
+                pool.submit(batch);
+            }
         }
 
-        let funcs_in_level = requests.len();
-        let with_context = requests.iter().filter(|r| !r.callee_context.is_empty()).count();
+        if batches_in_flight == 0 {
+            break;
+        }
 
-        let results = summarizer.summarize_batch(requests);
+        let Some(first) = pool.recv() else {
+            break;
+        };
+        let mut results = vec![first];
+        results.extend(std::iter::from_fn(|| pool.try_recv()));
 
         for result in results {
-            let qualified_name = &request_qnames[result.id];
+            let qualified_name = request_qnames[result.id].clone();
 
             match result.summary {
                 Ok(summary) => {
+                    if result.fallback {
+                        recovered_count += 1;
+                    }
+                    // Summaries are shown to agents and fed back into later prompts as callee context
+                    let summary = sanitize::sanitize_summary(&summary).into_owned();
+                    cache::write_summary(&cache_dir, &request_keys[result.id], &summary);
                     summaries.insert(qualified_name.clone(), summary.clone());
+                    if store_summary(index, &func_locations, &qualified_name, &summary, None) {
+                        summary_count += 1;
+                    }
 
-                    if let Some((path, func_idx)) = func_locations.get(qualified_name) {
-                        if let Some(entry) = index.files.get_mut(path) {
-                            if let Some(func) = entry.functions.get_mut(*func_idx) {
-                                func.summary = Some(summary);
-                                summary_count += 1;
-                            }
+                    for (member, request) in shared.finish(result.id, &summary) {
+                        cache::write_summary(&cache_dir, &summary_cache_key(config, &request), &summary);
+                        if store_summary(index, &func_locations, &member, &summary, Some(&qualified_name)) {
+                            summary_count += 1;
                         }
+                        summaries.insert(member.clone(), summary.clone());
+                        let level = queue.level_of(&member);
+                        queue.complete(&member);
+                        finish_level_function(&mut levels, level);
+                    }
+                }
+                Err(SummarizerError::Interrupted) => {
+                    for (member, _) in shared.abandon(result.id) {
+                        let level = queue.level_of(&member);
+                        queue.complete(&member);
+                        finish_level_function(&mut levels, level);
                     }
                 }
                 Err(e) => {
-                    eprintln!("warning: failed to summarize {}: {}", qualified_name, e);
+                    progress::warn("summarize_failed", &format!("failed to summarize {}: {}", qualified_name, e));
                     error_count += 1;
+
+                    // Members waiting on this prompt are retried with their own requests
+                    for (member, mut request) in shared.abandon(result.id) {
+                        request.id = request_qnames.len();
+                        request_qnames.push(member);
+                        request_keys.push(summary_cache_key(config, &request));
+                        staged.push(request);
+                    }
                 }
             }
+
+            let batch = request_batch[result.id];
+            batch_remaining[batch] -= 1;
+            if batch_remaining[batch] == 0 {
+                batches_in_flight -= 1;
+            }
+
+            let level = queue.level_of(&qualified_name);
+            queue.complete(&qualified_name);
+            finish_level_function(&mut levels, level);
         }
+        phase.progress(total - levels.iter().map(|l| l.remaining).sum::<usize>());
+    }
 
-        eprint!("\r");
+    pool.finish();
+    let summarize_ms = phase.finish(summary_count);
+    Timings::record(index, "summarize", summarize_ms);
+
+    println!(
+        "Generated {} summaries ({} errors) in {:.2?}",
+        summary_count - cached_count, error_count, summarization_start.elapsed()
+    );
+    if cached_count > 0 {
+        println!("Reused {cached_count} summaries from {}", cache_dir.join("summaries").display());
+    }
+    if recovered_count > 0 {
+        println!("Recovered {recovered_count} summaries from failed batches by asking for each function on its own");
+    }
+    if shared.collapsed > 0 {
         println!(
-            "  Level {}: {} functions ({} with callee context) in {:.2?}",
-            level, funcs_in_level, with_context, level_start.elapsed()
+            "Collapsed {} duplicate bodies into {} prompts",
+            shared.collapsed,
+            shared.prompts.len()
         );
     }
 
+    summary_count
+}
+
+/// Key under which a request's summary is cached: the model and the prompt the function
+/// would get on its own, whether it is sent alone or in a batch
+fn summary_cache_key(config: &Config, request: &SummaryRequest) -> String {
+    let prompt = summarizer::build_single_prompt(
+        &request.signature, &request.body, request.doc.as_deref(), &request.callee_context, &request.raw_callees,
+    );
+    cache::summary_key(&config.llm.model, &prompt)
+}
+
+/// Record a generated summary on the function, returning whether it was found
+fn store_summary(
+    index: &mut Index,
+    func_locations: &FuncLocations,
+    qualified_name: &str,
+    summary: &str,
+    shared_from: Option<&str>,
+) -> bool {
+    let Some((path, func_idx)) = func_locations.get(qualified_name) else {
+        return false;
+    };
+    let Some(func) = index.files.get_mut(path).and_then(|e| e.functions.get_mut(*func_idx)) else {
+        return false;
+    };
+    if func.summary_locked {
+        return false;
+    }
+    func.summary = Some(summary.to_string());
+    func.summary_hash = Some(func.ast_hash.clone());
+    func.summary_shared_from = shared_from.map(String::from);
+    true
+}
+
+/// A function body and the callee context its prompt would carry
+type PromptKey = (String, Vec<(String, String)>, Vec<String>);
+
+/// Key under which a request can share a prompt, or None when the function has no ast_hash
+fn shared_prompt_key(
+    qualified_name: &str,
+    func_locations: &FuncLocations,
+    index: &Index,
+    request: &SummaryRequest,
+) -> Option<PromptKey> {
+    let (path, func_idx) = func_locations.get(qualified_name)?;
+    let func = index.files.get(path)?.functions.get(*func_idx)?;
+    if func.ast_hash.is_empty() {
+        return None;
+    }
+    Some((func.ast_hash.clone(), request.callee_context.clone(), request.raw_callees.clone()))
+}
+
+/// Prompts shared by functions with identical bodies (same ast_hash) and the same callee
+/// context. The first such function is sent as the representative; the others wait for its summary.
+#[derive(Default)]
+struct SharedPrompts {
+    /// Prompt key -> request id of the representative
+    by_key: HashMap<PromptKey, usize>,
+    /// Representative request id -> functions waiting on it, with their own requests as a fallback
+    waiting: HashMap<usize, Vec<(String, SummaryRequest)>>,
+    /// Summaries of representatives that finished
+    done: HashMap<usize, String>,
+    /// Functions that got a summary from another function's prompt
+    collapsed: usize,
+    /// Representatives whose summary was shared at least once
+    prompts: HashSet<usize>,
+}
+
+impl SharedPrompts {
+    fn add(&mut self, key: PromptKey, id: usize) {
+        self.by_key.insert(key, id);
+    }
+
+    fn representative(&self, key: &PromptKey) -> Option<usize> {
+        self.by_key.get(key).copied()
+    }
+
+    /// Summary of a finished representative, counted as shared
+    fn reuse(&mut self, id: usize) -> Option<String> {
+        let summary = self.done.get(&id)?.clone();
+        self.collapsed += 1;
+        self.prompts.insert(id);
+        Some(summary)
+    }
+
+    fn wait(&mut self, id: usize, qualified_name: String, request: SummaryRequest) {
+        self.waiting.entry(id).or_default().push((qualified_name, request));
+    }
+
+    /// Record a representative's summary and return the functions that share it
+    fn finish(&mut self, id: usize, summary: &str) -> Vec<(String, SummaryRequest)> {
+        self.done.insert(id, summary.to_string());
+        let members = self.waiting.remove(&id).unwrap_or_default();
+        if !members.is_empty() {
+            self.collapsed += members.len();
+            self.prompts.insert(id);
+        }
+        members
+    }
+
+    /// Forget a representative that produced no summary and return the functions waiting on it
+    fn abandon(&mut self, id: usize) -> Vec<(String, SummaryRequest)> {
+        self.by_key.retain(|_, rep| *rep != id);
+        self.waiting.remove(&id).unwrap_or_default()
+    }
+}
+
+/// Requests and timing for one topology level
+struct LevelProgress {
+    /// Functions not yet finished
+    remaining: usize,
+    requested: usize,
+    with_context: usize,
+    /// When the level's first request was built
+    started: Option<Instant>,
+}
+
+/// Record a finished function and print the level's line once all of its functions are done
+fn finish_level_function(levels: &mut [LevelProgress], level: usize) {
+    let progress = &mut levels[level];
+    progress.remaining -= 1;
+    if progress.remaining > 0 || progress.requested == 0 {
+        return;
+    }
+
+    let elapsed = progress.started.map(|s| s.elapsed()).unwrap_or_default();
+    if !progress::is_json() {
+        eprint!("\r");
+    }
     println!(
-        "Generated {} summaries ({} errors) in {:.2?}",
-        summary_count, error_count, summarization_start.elapsed()
+        "  Level {}: {} functions ({} with callee context) in {:.2?}",
+        level, progress.requested, progress.with_context, elapsed
     );
 }
 
+/// Functions waiting to be summarized, released once every callee they depend on has finished.
+/// A function depends on its resolved callees in lower levels; callees in the same level are
+/// members of its cycle and are summarized alongside it, as in the level-by-level order.
+struct ReadyQueue {
+    /// Level and position within the level, used to release functions in topological order
+    order: HashMap<String, (usize, usize)>,
+    /// Function -> number of unfinished callees it waits on
+    waiting: HashMap<String, usize>,
+    /// Callee -> functions waiting on it
+    dependents: HashMap<String, Vec<String>>,
+    ready: BTreeSet<(usize, usize)>,
+    by_order: HashMap<(usize, usize), String>,
+}
+
+impl ReadyQueue {
+    fn new(level_groups: &[Vec<String>], calls_map: &HashMap<String, HashSet<String>>) -> Self {
+        let mut queue = ReadyQueue {
+            order: HashMap::new(),
+            waiting: HashMap::new(),
+            dependents: HashMap::new(),
+            ready: BTreeSet::new(),
+            by_order: HashMap::new(),
+        };
+
+        for (level, group) in level_groups.iter().enumerate() {
+            for (pos, qualified_name) in group.iter().enumerate() {
+                queue.order.insert(qualified_name.clone(), (level, pos));
+                queue.by_order.insert((level, pos), qualified_name.clone());
+            }
+        }
+
+        for (qualified_name, &(level, pos)) in &queue.order {
+            let mut count = 0;
+            for callee in calls_map.get(qualified_name).into_iter().flatten() {
+                if let Some(&(callee_level, _)) = queue.order.get(callee)
+                    && callee_level < level
+                {
+                    queue.dependents.entry(callee.clone()).or_default().push(qualified_name.clone());
+                    count += 1;
+                }
+            }
+            if count == 0 {
+                queue.ready.insert((level, pos));
+            } else {
+                queue.waiting.insert(qualified_name.clone(), count);
+            }
+        }
+
+        queue
+    }
+
+    /// Next function whose callees have all finished
+    fn pop(&mut self) -> Option<String> {
+        let key = self.ready.pop_first()?;
+        self.by_order.get(&key).cloned()
+    }
+
+    fn level_of(&self, qualified_name: &str) -> usize {
+        self.order.get(qualified_name).map_or(0, |&(level, _)| level)
+    }
+
+    /// Mark a function finished (summarized or failed), releasing functions that waited on it
+    fn complete(&mut self, qualified_name: &str) {
+        for dependent in self.dependents.remove(qualified_name).unwrap_or_default() {
+            if let Some(count) = self.waiting.get_mut(&dependent) {
+                *count -= 1;
+                if *count == 0 {
+                    self.waiting.remove(&dependent);
+                    self.ready.insert(self.order[&dependent]);
+                }
+            }
+        }
+    }
+}
+
 /// Build the call graph topology and function location lookup
 fn build_topology(
     index: &Index,
     debug: bool,
-) -> (Vec<Vec<String>>, HashMap<String, (String, usize)>) {
+) -> (Vec<Vec<String>>, FuncLocations, HashMap<String, HashSet<String>>) {
     let topo_start = Instant::now();
     let mut all_functions: HashSet<String> = HashSet::new();
     let mut calls_map: HashMap<String, HashSet<String>> = HashMap::new();
     let mut total_funcs = 0;
 
-    let mut func_locations: HashMap<String, (String, usize)> = HashMap::new();
+    let mut func_locations: FuncLocations = HashMap::new();
+
+    // Sorted by path so duplicate qualified names always resolve to the same location
+    let mut files: Vec<_> = index.files.iter().collect();
+    files.sort_by(|a, b| a.0.cmp(b.0));
 
-    for (path, entry) in index.files.iter() {
+    for (path, entry) in files {
         for (func_idx, func) in entry.functions.iter().enumerate() {
             total_funcs += 1;
             if debug && all_functions.contains(&func.qualified_name) {
@@ -299,95 +1042,92 @@ fn build_topology(
         level_groups.iter().map(|g| g.len()).sum::<usize>()
     );
 
-    (level_groups, func_locations)
+    (level_groups, func_locations, calls_map)
 }
 
-/// Collect summary requests for one level of the topology
-fn collect_level_requests(
-    funcs_at_level: &[String],
-    func_locations: &HashMap<String, (String, usize)>,
+/// Build the summary request for one function, or None if it has no source body.
+/// The caller assigns the request id.
+fn build_request(
+    qualified_name: &str,
+    func_locations: &FuncLocations,
     summaries: &HashMap<String, String>,
     index: &Index,
     sources: &HashMap<String, String>,
-    debug: bool,
+    config: &Config,
     level: usize,
-) -> (Vec<SummaryRequest>, Vec<String>) {
-    let mut requests: Vec<SummaryRequest> = Vec::new();
-    let mut request_qnames: Vec<String> = Vec::new();
-
-    for qualified_name in funcs_at_level {
-        if summaries.contains_key(qualified_name) {
-            continue;
-        }
-
-        let Some((path, func_idx)) = func_locations.get(qualified_name) else {
-            continue;
-        };
-        let Some(source) = sources.get(path) else {
-            continue;
-        };
-        let Some(entry) = index.files.get(path) else {
-            continue;
-        };
-        let Some(func) = entry.functions.get(*func_idx) else {
-            continue;
-        };
-
-        let lines: Vec<&str> = source.lines().collect();
-        let body = extract_body(&lines, func.line_start, func.line_end);
-        if body.is_empty() {
-            continue;
-        }
+) -> Option<SummaryRequest> {
+    let (path, func_idx) = func_locations.get(qualified_name)?;
+    let source = sources.get(path)?;
+    let func = index.files.get(path)?.functions.get(*func_idx)?;
+
+    let body = extract_body(source, func);
+    if body.is_empty() {
+        return None;
+    }
 
-        let callee_context: Vec<(String, String)> = func
-            .calls
-            .iter()
-            .filter(|c| !c.target.starts_with('['))
-            .filter_map(|c| {
-                summaries.get(&c.target).map(|s| {
-                    let simple_name = c.target.rsplit('.').next().unwrap_or(&c.target);
-                    (simple_name.to_string(), s.clone())
-                })
+    let callee_context: Vec<(String, String)> = func
+        .calls
+        .iter()
+        .filter(|c| !c.target.starts_with('['))
+        .filter_map(|c| {
+            summaries.get(&c.target).map(|s| {
+                let simple_name = c.target.rsplit('.').next().unwrap_or(&c.target);
+                (simple_name.to_string(), s.clone())
             })
-            .collect();
-
-        if debug {
-            let resolved_count = func.calls.iter().filter(|c| !c.target.starts_with('[')).count();
-            if resolved_count > 0 {
-                if callee_context.is_empty() {
-                    let missed: Vec<_> = func.calls.iter()
-                        .filter(|c| !c.target.starts_with('['))
-                        .map(|c| &c.target)
-                        .collect();
-                    eprintln!(
-                        "debug [level {}]: {} has {} resolved calls but 0 found in summaries: {:?}",
-                        level, qualified_name, resolved_count, missed
-                    );
-                } else {
-                    eprintln!(
-                        "debug [level {}]: {} has {} callee summaries as context",
-                        level, qualified_name, callee_context.len()
-                    );
-                }
-            }
+        })
+        .collect();
+    let raw_callees = raw_callees(func, summaries, config.llm.max_raw_callees);
+
+    if config.debug {
+        let resolved_count = func.calls.iter().filter(|c| !c.target.starts_with('[')).count();
+        if resolved_count > 0 && callee_context.is_empty() {
+            let missed: Vec<_> = func.calls.iter()
+                .filter(|c| !c.target.starts_with('['))
+                .map(|c| &c.target)
+                .collect();
+            eprintln!(
+                "debug [level {}]: {} has {} resolved calls but 0 found in summaries: {:?}",
+                level, qualified_name, resolved_count, missed
+            );
+        }
+        if !callee_context.is_empty() || !raw_callees.is_empty() {
+            eprintln!(
+                "debug [level {}]: {} has {} callee summaries and {} raw callee names as context",
+                level, qualified_name, callee_context.len(), raw_callees.len()
+            );
         }
-
-        let id = requests.len();
-        requests.push(SummaryRequest {
-            id,
-            signature: func.signature.clone(),
-            body,
-            callee_context,
-        });
-        request_qnames.push(qualified_name.clone());
     }
 
-    (requests, request_qnames)
+    Some(SummaryRequest {
+        id: 0,
+        signature: func.signature.clone(),
+        body,
+        callee_context,
+        raw_callees,
+        doc: func.doc.clone(),
+    })
+}
+
+/// Calls without a summary to show the model, by raw expression in call order, at most `max` distinct
+fn raw_callees(func: &Function, summaries: &HashMap<String, String>, max: usize) -> Vec<String> {
+    let mut seen = HashSet::new();
+    func.calls
+        .iter()
+        .filter(|c| !summaries.contains_key(&c.target))
+        .filter(|c| seen.insert(c.raw.as_str()))
+        .take(max)
+        .map(|c| c.raw.clone())
+        .collect()
 }
 
-fn extract_body(lines: &[&str], line_start: u32, line_end: u32) -> String {
-    let start = (line_start as usize).saturating_sub(1);
-    let end = (line_end as usize).min(lines.len());
+/// Source text of `func`, by its byte range, or by its lines in indexes without byte ranges
+fn extract_body(source: &str, func: &Function) -> String {
+    if let Some(text) = func.source_text(source) {
+        return text.to_string();
+    }
+    let lines: Vec<&str> = source.lines().collect();
+    let start = (func.line_start as usize).saturating_sub(1);
+    let end = (func.line_end as usize).min(lines.len());
 
     if start >= end || start >= lines.len() {
         return String::new();
@@ -396,7 +1136,7 @@ fn extract_body(lines: &[&str], line_start: u32, line_end: u32) -> String {
     lines[start..end].join("\n")
 }
 
-fn load_config(aria_dir: &Path) -> Config {
+pub fn load_config(aria_dir: &Path) -> Config {
     let config_path = aria_dir.join("config.toml");
     if let Ok(content) = fs::read_to_string(&config_path) {
         toml::from_str(&content).unwrap_or_default()
@@ -405,6 +1145,12 @@ fn load_config(aria_dir: &Path) -> Config {
     }
 }
 
+/// Index key for a walked path: "./" followed by the path relative to `root`
+fn index_key(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    format!("./{}", relative.to_string_lossy())
+}
+
 fn is_hidden(entry: &walkdir::DirEntry) -> bool {
     entry
         .file_name()
@@ -412,12 +1158,34 @@ fn is_hidden(entry: &walkdir::DirEntry) -> bool {
         .is_some_and(|s| s != "." && s.starts_with('.'))
 }
 
+/// Whether `relative` (a path from the index root) is the reference directory `dir`: a bare name
+/// matches a directory of that name at any depth, a path with "/" matches from the root
+fn is_reference_dir(relative: &Path, dir: &str) -> bool {
+    let dir = dir.trim_start_matches("./").trim_end_matches('/');
+    if dir.contains('/') {
+        relative.starts_with(dir)
+    } else {
+        relative.components().any(|c| c.as_os_str() == dir)
+    }
+}
+
+/// Keep only what is needed to resolve calls into a reference file: names, signatures, and
+/// locations. Calls and type references inside it are dropped.
+fn into_reference(mut entry: FileEntry) -> FileEntry {
+    for func in &mut entry.functions {
+        func.calls.clear();
+        func.type_refs.clear();
+    }
+    entry.reference = true;
+    entry
+}
+
 fn is_ignored(entry: &walkdir::DirEntry) -> bool {
     let name = entry.file_name().to_string_lossy();
     matches!(name.as_ref(), "vendor" | "node_modules" | "target")
 }
 
-fn get_git_head() -> Option<String> {
+pub fn get_git_head() -> Option<String> {
     std::process::Command::new("git")
         .args(["rev-parse", "HEAD"])
         .output()
@@ -427,25 +1195,87 @@ fn get_git_head() -> Option<String> {
         .map(|s| s.trim().to_string())
 }
 
-fn load_existing_index(aria_dir: &Path) -> Option<Index> {
-    let index_path = aria_dir.join("index.json");
-    fs::read_to_string(index_path)
-        .ok()
-        .and_then(|content| serde_json::from_str(&content).ok())
+/// The index being replaced, to reuse entries of unchanged files; None when there is none or
+/// it cannot be read. One from a newer aria is an error, since rebuilding would drop what it holds.
+fn load_existing_index(index_path: &Path) -> Result<Option<Index>, String> {
+    match index::load_index_from(index_path) {
+        Ok(index) => Ok(Some(index)),
+        Err(e) if index::stored_schema(index_path).is_some_and(|s| s > index::SCHEMA_VERSION) => Err(e),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Rewrite the hashes of an index written before `index::HASH_VERSION`, so its summaries still
+/// match the functions they describe. Only files whose content still has the recorded hash can be
+/// rehashed, by parsing them again; the rest count as changed. Returns how many those are.
+pub fn migrate_legacy_hashes(old: &mut Index, root: &Path, registry: &mut ParserRegistry) -> usize {
+    if old.hash_version >= index::HASH_VERSION {
+        return 0;
+    }
+    registry.scan_root(root);
+    let file_hash = if old.hash_version == 0 { parser::legacy_content_hash } else { parser::content_hash };
+    let mut changed = 0;
+    for (key, entry) in old.files.iter_mut() {
+        let Some((content, path)) = index::resolve_source_path(root, key)
+            .ok()
+            .and_then(|path| Some((index::read_source(&path, u64::MAX).ok()??, path)))
+            .filter(|(content, _)| file_hash(content.as_bytes()) == entry.ast_hash)
+        else {
+            changed += 1;
+            continue;
+        };
+        let Some(parsed) = registry.parse_file(&path, &content, key) else {
+            changed += 1;
+            continue;
+        };
+        let hashes: HashMap<(&str, u32), &str> =
+            parsed.functions.iter().map(|f| ((f.qualified_name.as_str(), f.line_start), f.ast_hash.as_str())).collect();
+        for func in &mut entry.functions {
+            let Some(hash) = hashes.get(&(func.qualified_name.as_str(), func.line_start)) else {
+                continue;
+            };
+            if func.summary_hash.as_ref() == Some(&func.ast_hash) {
+                func.summary_hash = Some(hash.to_string());
+            }
+            func.ast_hash = hash.to_string();
+        }
+        entry.ast_hash = parsed.ast_hash;
+    }
+    if old.hash_version == 0 {
+        package::rehash_legacy_rollups(old);
+    }
+    old.hash_version = index::HASH_VERSION;
+    changed
+}
+
+/// Warning for an index whose legacy hashes could not all be carried over
+pub fn legacy_hash_warning(changed: usize) -> Option<String> {
+    (changed > 0).then(|| {
+        let files = if changed == 1 { "file" } else { "files" };
+        format!(
+            "the index was written with an older content hash; {changed} {files} changed since, so \
+             their summaries count as stale"
+        )
+    })
 }
 
-fn preserve_summaries(index: &mut Index, old_index: &Option<Index>) -> usize {
+pub fn preserve_summaries(index: &mut Index, old_index: Option<&Index>) -> usize {
     let Some(old) = old_index else {
         return 0;
     };
 
     let mut old_summaries: HashMap<String, String> = HashMap::new();
+    // Locked summaries follow their function by name, even when its body changed
+    let mut locked: HashMap<&str, &Function> = HashMap::new();
     for entry in old.files.values() {
         for func in &entry.functions {
-            if let Some(summary) = &func.summary {
-                if !func.ast_hash.is_empty() {
-                    old_summaries.insert(func.ast_hash.clone(), summary.clone());
-                }
+            if func.summary_locked && func.summary.is_some() {
+                locked.insert(&func.qualified_name, func);
+            }
+            if let Some(summary) = &func.summary
+                && !func.ast_hash.is_empty()
+            {
+                old_summaries.insert(func.ast_hash.clone(), summary.clone());
             }
         }
     }
@@ -457,11 +1287,18 @@ fn preserve_summaries(index: &mut Index, old_index: &Option<Index>) -> usize {
     let mut preserved = 0;
     for entry in index.files.values_mut() {
         for func in &mut entry.functions {
-            if func.summary.is_none() && !func.ast_hash.is_empty() {
-                if let Some(summary) = old_summaries.get(&func.ast_hash) {
-                    func.summary = Some(summary.clone());
-                    preserved += 1;
-                }
+            if let Some(old_func) = locked.get(func.qualified_name.as_str()) {
+                func.summary = old_func.summary.clone();
+                func.summary_hash = old_func.summary_hash.clone();
+                func.summary_locked = true;
+                preserved += 1;
+            } else if func.summary.is_none()
+                && !func.ast_hash.is_empty()
+                && let Some(summary) = old_summaries.get(&func.ast_hash)
+            {
+                func.summary = Some(summary.clone());
+                func.summary_hash = Some(func.ast_hash.clone());
+                preserved += 1;
             }
         }
     }
@@ -492,3 +1329,622 @@ fn ensure_aria_dir(aria_dir: &Path) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::LanguageParser;
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_workspace_crates_resolve_across_members() {
+        let root = std::env::temp_dir().join(format!("aria-rust-workspace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let files: &[(&str, &str)] = &[
+            ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+            ("crates/core/Cargo.toml", "[package]\nname = \"store-core\"\n"),
+            ("crates/core/src/lib.rs", "pub mod store;\npub fn open() { crate::store::init(); }\n"),
+            ("crates/core/src/store.rs", "pub fn init() {}\n"),
+            ("crates/core/tests/open.rs", "use store_core::open;\n#[test]\nfn opens() { open(); }\n"),
+            ("crates/cli/Cargo.toml", "[package]\nname = \"cli\"\n"),
+            ("crates/cli/src/main.rs", "use store_core::store::init;\nfn main() { init(); }\n"),
+        ];
+        for (path, content) in files {
+            let full = root.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+        let index = build(&root, None, &IndexConfig::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        let function = |path: &str| index.files[path].functions[0].clone();
+        assert_eq!(function("./crates/core/src/lib.rs").qualified_name, "store_core::open");
+        assert_eq!(function("./crates/core/src/lib.rs").calls[0].target, "store_core::store::init");
+        let main = function("./crates/cli/src/main.rs");
+        assert_eq!((main.qualified_name.as_str(), main.is_entry), ("cli::main", true));
+        assert_eq!(main.calls[0].target, "store_core::store::init");
+        let test = function("./crates/core/tests/open.rs");
+        assert_eq!((test.qualified_name.as_str(), test.calls[0].target.as_str()), ("store_core::tests::open::opens", "store_core::open"));
+    }
+
+    /// Index `root` with every parser in this build
+    fn build(root: &Path, previous: Option<&Index>, config: &IndexConfig) -> Index {
+        build_index(root, false, previous, config, false, &mut ParserRegistry::new()).0
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_migrate_legacy_hashes_keeps_summaries_of_unchanged_files() {
+        let root = std::env::temp_dir().join(format!("aria-legacy-hashes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let files = [("a.go", "package a\n\nfunc Open() {}\n\nfunc Close() {}\n"), ("b.go", "package a\n\nfunc Read() {}\n")];
+        for (path, content) in files {
+            fs::write(root.join(path), content).unwrap();
+        }
+        let current = build(&root, None, &IndexConfig::default());
+
+        // The same index as an older aria wrote it: legacy hashes, summaries fresh except Close's
+        let mut old = current.clone();
+        old.hash_version = 0;
+        for (path, content) in files {
+            let entry = old.files.get_mut(&format!("./{path}")).unwrap();
+            entry.ast_hash = parser::legacy_content_hash(content.as_bytes());
+            for func in &mut entry.functions {
+                func.ast_hash = format!("legacy-{}", func.name);
+                func.summary = Some(format!("{} summary", func.name));
+                func.summary_hash = Some(if func.name == "Close" { "older".to_string() } else { func.ast_hash.clone() });
+            }
+        }
+        fs::write(root.join("b.go"), "package a\n\nfunc Read() { Open() }\n").unwrap();
+
+        let changed = migrate_legacy_hashes(&mut old, &root, &mut ParserRegistry::new());
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(changed, 1);
+        assert_eq!(old.hash_version, index::HASH_VERSION);
+        let a = &old.files["./a.go"];
+        assert_eq!(a.ast_hash, current.files["./a.go"].ast_hash);
+        let open = &a.functions[0];
+        assert_eq!(open.ast_hash, current.files["./a.go"].functions[0].ast_hash);
+        assert_eq!(open.summary_hash.as_ref(), Some(&open.ast_hash));
+        assert_eq!(a.functions[1].summary_hash.as_deref(), Some("older"));
+        assert_eq!(old.files["./b.go"].functions[0].ast_hash, "legacy-Read");
+    }
+
+    /// Write a small mixed-language repo into a fresh temp directory
+    fn write_fixture_repo(name: &str) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("aria-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let files: &[(&str, &str)] = &[
+            ("cmd/app/main.go", "package main\n\nfunc main() {\n\tserve()\n\tfmt.Println(\"x\")\n}\n\nfunc serve() {\n\thelper(); other()\n}\n"),
+            ("cmd/app/util.go", "package main\n\nfunc helper() {}\n\nfunc other() {\n\thelper()\n}\n"),
+            ("internal/store/store.go", "package store\n\ntype Store struct{}\n\nfunc (s *Store) Get() {\n\tos.Open(\"f\")\n}\n"),
+            ("src/lib.rs", "pub fn run() {\n    step_one();\n    step_two();\n}\n\nfn step_one() {}\nfn step_two() { step_one(); }\n"),
+            ("src/c/io.c", "static int read_all(int fd) {\n    return read(fd, 0, 0);\n}\n\nint load(void) {\n    malloc(4);\n    return read_all(3);\n}\n"),
+        ];
+        for (path, content) in files {
+            let full = root.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+        root
+    }
+
+    // The fixture repo mixes all three languages
+    #[cfg(all(feature = "lang-go", feature = "lang-rust", feature = "lang-c"))]
+    #[test]
+    fn test_index_build_is_deterministic() {
+        let root = write_fixture_repo("determinism");
+
+        let first = build(&root, None, &IndexConfig::default());
+        // Indexing again without changes, from scratch or reusing the first index, writes the same bytes
+        let mut second = build(&root, None, &IndexConfig::default());
+        let mut third = build(&root, Some(&first), &IndexConfig::default());
+        keep_unchanged_stamp(&mut second, &first);
+        keep_unchanged_stamp(&mut third, &first);
+        let first_json = String::from_utf8(index::encode_index(&first, IndexFormat::Json).unwrap()).unwrap();
+        assert_eq!(first_json.as_bytes(), index::encode_index(&second, IndexFormat::Json).unwrap());
+        assert_eq!(first_json.as_bytes(), index::encode_index(&third, IndexFormat::Json).unwrap());
+        assert_eq!(
+            index::encode_index(&first, IndexFormat::Bin).unwrap(),
+            index::encode_index(&third, IndexFormat::Bin).unwrap()
+        );
+
+        // Touching a file, as a fresh clone does to all of them, changes nothing either
+        let touched = root.join("cmd/app/util.go");
+        for minutes in [10, 20] {
+            let mtime = std::time::SystemTime::now() + std::time::Duration::from_secs(minutes * 60);
+            fs::File::options().write(true).open(&touched).unwrap().set_modified(mtime).unwrap();
+            let mut again = build(&root, None, &IndexConfig::default());
+            assert_ne!(again.files["./cmd/app/util.go"].mtime_ms, first.files["./cmd/app/util.go"].mtime_ms);
+            keep_unchanged_stamp(&mut again, &first);
+            assert_eq!(first_json.as_bytes(), index::encode_index(&again, IndexFormat::Json).unwrap());
+        }
+
+        // A change is stamped with the time of the run that made it
+        fs::write(root.join("cmd/app/util.go"), "package main\n\nfunc helper() {}\n").unwrap();
+        let mut changed = build(&root, Some(&first), &IndexConfig::default());
+        let stamp = changed.indexed_at;
+        keep_unchanged_stamp(&mut changed, &first);
+        assert_eq!(changed.indexed_at, stamp);
+        assert_ne!(changed.indexed_at, first.indexed_at);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(first.files.contains_key("./cmd/app/main.go"));
+        assert!(!first.externals.is_empty());
+
+        // Keys are emitted in sorted order regardless of HashMap iteration
+        let positions: Vec<usize> = ["./cmd/app/main.go", "./cmd/app/util.go", "./internal/store/store.go", "./src/c/io.c", "./src/lib.rs"]
+            .iter()
+            .map(|k| first_json.find(&format!("\"{k}\"")).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[cfg(all(feature = "lang-go", feature = "lang-rust"))]
+    #[test]
+    fn test_progress_events_for_index_build() {
+        let root = write_fixture_repo("progress");
+        fs::write(root.join("src/bad.rs"), "// filler\n".repeat(200)).unwrap();
+
+        // Leave out the C file so the events are the same whether or not lang-c is built
+        let config = IndexConfig {
+            languages: vec!["go".to_string(), "rust".to_string()],
+            max_file_size: Some(1024),
+            ..Default::default()
+        };
+        let (index, events) = progress::capture(|| build(&root, None, &config));
+        fs::remove_dir_all(&root).unwrap();
+
+        let kinds: Vec<(&str, &str)> = events
+            .iter()
+            .map(|e| (e["event"].as_str().unwrap(), e["phase"].as_str().or(e["code"].as_str()).unwrap_or("")))
+            .collect();
+        let mut expected = vec![("phase_start", "parse")];
+        for i in 0..5 {
+            expected.push(("progress", "parse"));
+            // src/bad.rs sorts after cmd/ and internal/ but before src/lib.rs
+            if i == 3 {
+                expected.push(("warning", "file_too_large"));
+            }
+        }
+        expected.extend([("phase_end", "parse"), ("phase_start", "resolve"), ("phase_end", "resolve")]);
+        assert_eq!(kinds, expected);
+
+        let phases: Vec<&str> = index.timings.as_ref().unwrap().phases_ms.keys().map(String::as_str).collect();
+        assert_eq!(phases, vec!["parse", "resolve", "walk"]);
+        assert_eq!(events[0]["total"], 5);
+        let currents: Vec<u64> = events.iter().filter_map(|e| e.get("current")?.as_u64()).collect();
+        assert_eq!(currents, vec![1, 2, 3, 4, 5]);
+        assert_eq!(events[7]["count"], 4);
+        assert_eq!(events[9]["count"], index.files.values().map(|e| e.functions.len()).sum::<usize>());
+    }
+
+    #[test]
+    fn test_slowest_files_sorted_and_capped() {
+        let times: Vec<(String, Duration)> =
+            (0..12).map(|i| (format!("./f{i:02}.go"), Duration::from_micros(1000 + (i % 4) * 250))).collect();
+        let slowest = slowest_files(times);
+        assert_eq!(slowest.len(), SLOWEST_FILES);
+        assert_eq!((slowest[0].file.as_str(), slowest[0].parse_ms), ("./f03.go", 1.75));
+        // Ties keep path order
+        assert_eq!(slowest[1].file, "./f07.go");
+        assert!(slowest.windows(2).all(|w| w[0].parse_ms >= w[1].parse_ms));
+    }
+
+    #[test]
+    fn test_calls_sorted_by_line() {
+        let root = write_fixture_repo("call-order");
+        let index = build(&root, None, &IndexConfig::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        for entry in index.files.values() {
+            for func in &entry.functions {
+                assert!(func.calls.windows(2).all(|w| w[0].line <= w[1].line), "{}", func.qualified_name);
+                assert!(func.called_by.windows(2).all(|w| w[0] < w[1]), "{}", func.qualified_name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_build_matches_full_build() {
+        let root = write_fixture_repo("incremental");
+        let previous = build(&root, None, &IndexConfig::default());
+
+        fs::write(root.join("cmd/app/util.go"), "package main\n\nfunc helper() {\n\tother()\n}\n\nfunc other() {}\n").unwrap();
+        let mut incremental = build(&root, Some(&previous), &IndexConfig::default());
+        let full = build(&root, None, &IndexConfig::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        incremental.indexed_at = full.indexed_at;
+        incremental.timings = full.timings.clone();
+        assert_eq!(
+            serde_json::to_string_pretty(&incremental).unwrap(),
+            serde_json::to_string_pretty(&full).unwrap()
+        );
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_reference_dirs_index_symbols_only() {
+        let root = write_fixture_repo("reference");
+        fs::create_dir_all(root.join("vendor/strs")).unwrap();
+        fs::write(root.join("vendor/strs/strs.go"), "package strs\n\nfunc Clean() {\n\ttrim()\n}\n").unwrap();
+        let config = IndexConfig { reference_dirs: vec!["vendor".to_string()], ..Default::default() };
+
+        let previous = build(&root, None, &IndexConfig::default());
+        let index = build(&root, Some(&previous), &config);
+        fs::remove_dir_all(&root).unwrap();
+
+        let vendored = &index.files["./vendor/strs/strs.go"];
+        assert!(vendored.reference);
+        assert_eq!(vendored.functions[0].qualified_name, "vendor/strs.Clean");
+        assert!(vendored.functions[0].calls.is_empty());
+        assert!(!index.files["./cmd/app/main.go"].reference);
+
+        assert!(is_reference_dir(Path::new("third_party/vendor/x"), "vendor"));
+        assert!(is_reference_dir(Path::new("third_party/zlib"), "third_party/zlib"));
+        assert!(!is_reference_dir(Path::new("src/third_party/zlib"), "third_party/zlib"));
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_include_tests_indexes_test_callers() {
+        let root = write_fixture_repo("tests");
+        let test_source = "package store\n\nfunc TestGet(t *testing.T) {\n\ts := &Store{}\n\ts.Get()\n}\n";
+        fs::write(root.join("internal/store/store_test.go"), test_source).unwrap();
+
+        let without = build(&root, None, &IndexConfig::default());
+        let with = build_index(&root, false, None, &IndexConfig::default(), true, &mut ParserRegistry::new()).0;
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(!without.files.contains_key("./internal/store/store_test.go"));
+        assert!(with.files["./internal/store/store_test.go"].is_test);
+        assert!(!with.files["./internal/store/store.go"].is_test);
+        let get = &with.files["./internal/store/store.go"].functions[0];
+        assert_eq!(get.called_by, vec!["internal/store.TestGet"]);
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_use_items_resolve_across_modules() {
+        let root = std::env::temp_dir().join(format!("aria-rust-uses-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let files: &[(&str, &str)] = &[
+            (
+                "src/main.rs",
+                "mod cache;\nmod store;\nmod util;\n\
+                 use crate::store::{self, Store};\nuse store::open as open_store;\nuse crate::util::*;\n\n\
+                 fn main() {\n    let s = Store::new();\n    s.save();\n    store::open();\n    open_store();\n    helper();\n}\n",
+            ),
+            ("src/store.rs", "pub struct Store;\nimpl Store {\n    pub fn new() -> Self { Store }\n    pub fn save(&self) {}\n}\npub fn open() {}\n"),
+            // Same names in other modules, which only the use items tell apart
+            ("src/cache.rs", "pub struct Store;\nimpl Store {\n    pub fn new() -> Self { Store }\n    pub fn save(&self) {}\n}\npub fn helper() {}\n"),
+            ("src/util.rs", "use super::cache;\npub fn helper() { cache::helper(); }\n"),
+        ];
+        for (path, content) in files {
+            let full = root.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+        let index = build(&root, None, &IndexConfig::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        let main = &index.files["./src/main.rs"];
+        let imports: Vec<(&str, &str)> = main.imports.iter().map(|i| (i.alias.as_str(), i.path.as_str())).collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("store", "crate::store"),
+                ("Store", "crate::store::Store"),
+                ("open_store", "store::open"),
+                ("*", "crate::util"),
+            ]
+        );
+        let targets: Vec<&str> = main.functions[0].calls.iter().map(|c| c.target.as_str()).collect();
+        assert_eq!(targets, vec!["store::Store::new", "store::Store::save", "store::open", "store::open", "util::helper"]);
+        let util = &index.files["./src/util.rs"];
+        assert_eq!(util.imports[0].path, "crate::cache");
+        assert_eq!(util.functions[0].calls[0].target, "cache::helper");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_targets_reach_the_lib_by_package_name() {
+        let root = std::env::temp_dir().join(format!("aria-rust-targets-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let files: &[(&str, &str)] = &[
+            ("Cargo.toml", "[package]\nname = \"demo\"\n\n[workspace]\nmembers = [\"crates/*\"]\n"),
+            ("src/lib.rs", "pub mod store;\npub fn run() {}\n"),
+            ("src/store/mod.rs", "pub mod helpers;\n"),
+            ("src/store/helpers.rs", "pub fn assist() {}\n"),
+            ("src/main.rs", "fn main() { demo::store::helpers::assist(); }\n"),
+            ("src/bin/tool.rs", "fn main() { demo::run(); }\n"),
+            ("tests/it.rs", "use demo::store::helpers;\n\n#[test]\nfn it_works() { demo::store::helpers::assist(); helpers::assist(); }\n"),
+            ("crates/util/Cargo.toml", "[package]\nname = \"util-kit\"\n"),
+            ("crates/util/src/lib.rs", "pub fn help() {}\n"),
+            ("crates/util/tests/help.rs", "#[test]\nfn helps() { util_kit::help(); }\n"),
+        ];
+        for (path, content) in files {
+            let full = root.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+        let index = build(&root, None, &IndexConfig::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        let targets = |file: &str| -> Vec<String> {
+            index.files[file].functions[0].calls.iter().map(|c| c.target.clone()).collect()
+        };
+        assert_eq!(targets("./src/main.rs"), vec!["store::helpers::assist"]);
+        assert_eq!(targets("./src/bin/tool.rs"), vec!["run"]);
+        assert_eq!(targets("./tests/it.rs"), vec!["store::helpers::assist", "store::helpers::assist"]);
+        assert_eq!(targets("./crates/util/tests/help.rs"), vec!["util_kit::help"]);
+        // The lib itself is not given its own package name
+        assert!(index.files["./src/lib.rs"].imports.is_empty());
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_languages_filter_and_missing_grammars() {
+        let root = write_fixture_repo("languages");
+        let config = IndexConfig { languages: vec!["go".to_string()], ..Default::default() };
+        let index = build(&root, None, &config);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(index.files.len(), 3);
+        assert!(index.files.keys().all(|k| k.ends_with(".go")));
+
+        let c = parser::grammar("c").unwrap();
+        let skipped = [Path::new("./a.c"), Path::new("./b/x.h"), Path::new("./b/y.c")];
+        assert_eq!(
+            missing_grammar_warnings(skipped.iter().map(|p| (c, *p))),
+            vec!["found 3 .c/.h files but this build lacks lang-c"]
+        );
+
+        let config = IndexConfig { languages: vec!["go".to_string(), "cobol".to_string()], ..Default::default() };
+        assert_eq!(config.language_warnings(), vec!["index.languages lists unknown language 'cobol'"]);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_non_utf8_and_oversized_files() {
+        let root = write_fixture_repo("lossy");
+        // A Latin-1 "é" in a comment
+        fs::write(root.join("cmd/app/latin1.go"), b"package main\n\n// caf\xe9\nfunc brew() {}\n").unwrap();
+        fs::write(root.join("cmd/app/table.go"), format!("package main\n\nvar table = \"{}\"\n", "x".repeat(300))).unwrap();
+        let config = IndexConfig { max_file_size: Some(200), ..Default::default() };
+        let index = build(&root, None, &config);
+        fs::remove_dir_all(&root).unwrap();
+
+        let latin1 = &index.files["./cmd/app/latin1.go"];
+        assert_eq!(latin1.functions[0].name, "brew");
+        assert_eq!(latin1.parse_errors, 0);
+        assert!(!index.files.contains_key("./cmd/app/table.go"));
+        assert!(index.files.contains_key("./cmd/app/main.go"));
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_binary_index_round_trip() {
+        let root = write_fixture_repo("binary");
+        let index = build(&root, None, &IndexConfig::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        let json = index::encode_index(&index, index::IndexFormat::Json).unwrap();
+        let bin = index::encode_index(&index, index::IndexFormat::Bin).unwrap();
+        assert_eq!(index::IndexFormat::detect(&json), index::IndexFormat::Json);
+        assert_eq!(index::IndexFormat::detect(&bin), index::IndexFormat::Bin);
+        assert!(bin.len() < json.len());
+
+        let decoded: Index = index::decode_index(&bin).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&index).unwrap());
+        assert!(index::decode_index::<Index>(&json).is_ok());
+    }
+
+    #[cfg(all(unix, feature = "lang-go", feature = "lang-rust"))]
+    #[test]
+    fn test_sharded_index_round_trip() {
+        use std::os::unix::fs::MetadataExt;
+
+        let root = write_fixture_repo("sharded");
+        let mut index = build(&root, None, &IndexConfig::default());
+        // Directories named like the manifest or the root shard must not clash with them
+        for key in ["./manifest/m.go", "./_root/r.go", "./top.go"] {
+            index.files.insert(key.to_string(), FileEntry::default());
+        }
+        let index_path = root.join(".aria/index.json");
+        fs::create_dir_all(root.join(".aria")).unwrap();
+        save_shards(&index_path, &index, IndexFormat::Json).unwrap();
+
+        let dir = index::shard_dir(&index_path);
+        let loaded = index::load_index_from(&index_path).unwrap();
+        assert_eq!(
+            loaded.shards,
+            vec![
+                "root.json",
+                "shards/_root.json",
+                "shards/cmd.json",
+                "shards/internal.json",
+                "shards/manifest.json",
+                "shards/src.json",
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&loaded.files).unwrap(),
+            serde_json::to_value(&index.files).unwrap()
+        );
+        assert_eq!(index::shard_of("./internal/store/store.go"), "shards/internal");
+        assert_eq!(index::shard_of("./main.go"), "root");
+
+        // A change under cmd/ rewrites only its shard; a directory left without files loses its shard
+        let inode = |name: &str| fs::metadata(dir.join(name)).unwrap().ino();
+        let internal = inode("shards/internal.json");
+        index.files.get_mut("./cmd/app/util.go").unwrap().functions.clear();
+        index.files.retain(|key, _| !key.starts_with("./src/"));
+        save_shards(&index_path, &index, IndexFormat::Json).unwrap();
+        assert_eq!(inode("shards/internal.json"), internal);
+        assert!(!dir.join("shards/src.json").exists());
+
+        let loaded = index::load_index_from(&index_path).unwrap();
+        assert_eq!(loaded.shards.len(), 5);
+        assert!(!loaded.shards.contains(&"shards/src.json".to_string()));
+        assert!(loaded.files["./cmd/app/util.go"].functions.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Records the files it is handed and indexes each as an empty entry
+    struct MockParser {
+        seen: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    }
+
+    impl LanguageParser for MockParser {
+        fn language(&self) -> &'static str {
+            "mock"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["mk", "go"]
+        }
+
+        fn parse_file(&mut self, _source: &str, path: &str) -> Option<FileEntry> {
+            self.seen.borrow_mut().push(path.to_string());
+            Some(FileEntry {
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_walk_dispatches_by_registered_extension() {
+        let root = write_fixture_repo("registry");
+        fs::write(root.join("src/extra.mk"), "all:\n").unwrap();
+        let seen = std::rc::Rc::default();
+        let mut registry = ParserRegistry::default();
+        registry.register(Box::new(MockParser { seen: std::rc::Rc::clone(&seen) }));
+
+        let (index, _, _) = parse_source_files(&root, false, None, &IndexConfig::default(), false, &mut registry);
+        fs::remove_dir_all(&root).unwrap();
+
+        // The mock took over .go; .rs and .c files have no parser in this registry
+        let expected = vec!["./cmd/app/main.go", "./cmd/app/util.go", "./internal/store/store.go", "./src/extra.mk"];
+        assert_eq!(*seen.borrow(), expected);
+        let mut indexed: Vec<&str> = index.files.keys().map(String::as_str).collect();
+        indexed.sort();
+        assert_eq!(indexed, expected);
+        assert_eq!(registry.language_of(Path::new("a/b.mk")), Some("mock"));
+        assert_eq!(registry.language_of(Path::new("a/b.rs")), None);
+    }
+
+    #[test]
+    fn test_parse_error_warning() {
+        assert_eq!(parse_error_warning("./a.go", "go", 0.02), None);
+        assert_eq!(
+            parse_error_warning("./a.go", "go", 0.25).unwrap(),
+            "./a.go: 25% of the file did not parse, so functions in it may be missing; \
+             the go grammar in this build may predate its syntax"
+        );
+    }
+
+    #[test]
+    fn test_ready_queue_waits_for_lower_level_callees() {
+        // leaf <- left, right <- top; cyc_a <-> cyc_b form one level
+        let calls_map: HashMap<String, HashSet<String>> = [
+            ("left", vec!["leaf"]),
+            ("right", vec!["leaf", "cyc_a"]),
+            ("top", vec!["left", "right"]),
+            ("cyc_a", vec!["cyc_b"]),
+            ("cyc_b", vec!["cyc_a"]),
+        ]
+        .into_iter()
+        .map(|(f, callees)| (f.to_string(), callees.into_iter().map(String::from).collect()))
+        .collect();
+        let all: HashSet<String> =
+            ["leaf", "left", "right", "top", "cyc_a", "cyc_b"].into_iter().map(String::from).collect();
+        let level_groups = topo::hierarchy(&all, &calls_map);
+
+        let mut queue = ReadyQueue::new(&level_groups, &calls_map);
+        let mut ready: Vec<String> = std::iter::from_fn(|| queue.pop()).collect();
+        ready.sort();
+        assert_eq!(ready, vec!["cyc_a", "cyc_b", "leaf"]);
+
+        // left only needs leaf; right also waits on the cycle
+        queue.complete("leaf");
+        assert_eq!(queue.pop().as_deref(), Some("left"));
+        assert_eq!(queue.pop(), None);
+
+        queue.complete("cyc_b");
+        assert_eq!(queue.pop(), None);
+        queue.complete("cyc_a");
+        assert_eq!(queue.pop().as_deref(), Some("right"));
+
+        queue.complete("left");
+        assert_eq!(queue.pop(), None);
+        queue.complete("right");
+        assert_eq!(queue.pop().as_deref(), Some("top"));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_raw_callees_skip_summarized_and_cap() {
+        let source = "package main\n\nfunc run(s *Server) {\n\thelper()\n\ts.store.Get()\n\thelper()\n\tvalidate()\n\tfmt.Println()\n}\n";
+        let mut entry = parser::GoParser::new().parse_file(source, "main.go").unwrap();
+        let func = &mut entry.functions[0];
+        func.calls[0].target = "main.helper".to_string();
+        func.calls[2].target = "main.helper".to_string();
+        let summaries = HashMap::from([("main.helper".to_string(), "Helps".to_string())]);
+
+        assert_eq!(raw_callees(func, &summaries, 10), vec!["s.store.Get", "validate", "fmt.Println"]);
+        assert_eq!(raw_callees(func, &summaries, 2), vec!["s.store.Get", "validate"]);
+        assert!(raw_callees(func, &summaries, 0).is_empty());
+        assert_eq!(raw_callees(func, &HashMap::new(), 10)[0], "helper");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_extract_body_by_byte_range() {
+        let source = "package main\r\n\r\nfunc a() {}\r\n\r\nfunc b() {\r\n\treturn\r\n}\r\n";
+        let mut entry = parser::GoParser::new().parse_file(source, "main.go").unwrap();
+        let b = &mut entry.functions[1];
+        assert_eq!((b.byte_start, b.byte_end), (31, 53));
+        assert_eq!(extract_body(source, b), "func b() {\r\n\treturn\r\n}");
+
+        // Indexes written before byte ranges fall back to the line range
+        (b.byte_start, b.byte_end) = (0, 0);
+        assert_eq!(extract_body(source, b), "func b() {\n\treturn\n}");
+    }
+
+    #[test]
+    fn test_shared_prompts_collapse_identical_bodies() {
+        let request = |id| SummaryRequest {
+            id,
+            signature: String::new(),
+            body: "fn f() {}".to_string(),
+            callee_context: vec![],
+            raw_callees: vec![],
+            doc: None,
+        };
+        let key = |hash: &str| (hash.to_string(), Vec::new(), Vec::new());
+        let mut shared = SharedPrompts::default();
+
+        shared.add(key("h1"), 0);
+        assert_eq!(shared.representative(&key("h1")), Some(0));
+        assert_eq!(shared.representative(&key("h2")), None);
+        assert_eq!(shared.reuse(0), None);
+
+        shared.wait(0, "b.f".to_string(), request(0));
+        shared.wait(0, "c.f".to_string(), request(0));
+        let members: Vec<String> = shared.finish(0, "Does f.").into_iter().map(|(qn, _)| qn).collect();
+        assert_eq!(members, vec!["b.f", "c.f"]);
+        assert_eq!(shared.reuse(0).as_deref(), Some("Does f."));
+        assert_eq!((shared.collapsed, shared.prompts.len()), (3, 1));
+
+        // A failed representative hands its members back and no longer matches its key
+        shared.add(key("h2"), 1);
+        shared.wait(1, "e.g".to_string(), request(1));
+        assert_eq!(shared.abandon(1).len(), 1);
+        assert_eq!(shared.representative(&key("h2")), None);
+    }
+}
@@ -0,0 +1,664 @@
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+use crate::format::Formatter;
+use crate::index::{self, FileEntry, Function, Index, Scope, TypeDef, TypeKind, Variable};
+use crate::parser;
+
+/// Options controlling what `aria list` prints
+pub struct ListOptions {
+    /// List indexed files instead of functions; the inner value is an optional glob
+    pub paths: Option<Option<String>>,
+    /// Only include files of this language (go, rust, c, cpp, python, typescript, java)
+    pub lang: Option<String>,
+    /// Only include functions that use this type (qualified or simple name)
+    pub uses_type: Option<String>,
+    /// File ordering: functions, size, or path
+    pub sort: String,
+    /// Emit JSON instead of text
+    pub json: bool,
+    /// Do not truncate long names
+    pub full: bool,
+    /// Also list reference-only files (vendored code)
+    pub include_reference: bool,
+    /// Only include functions the index records as public
+    pub public_only: bool,
+    /// Only include functions of this scope (public, crate, static, internal)
+    pub scope: Option<String>,
+    /// Only include async functions
+    pub is_async: bool,
+    /// Only include functions callable from C (cgo `//export`, Rust `extern "C"` or `#[no_mangle]`)
+    pub ffi: bool,
+    /// Show functions and types as a tree of modules and files
+    pub tree: bool,
+    /// Levels of the tree to show (0 = unlimited)
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct FileRow<'a> {
+    path: &'a str,
+    language: &'static str,
+    functions: usize,
+    types: usize,
+    /// Size on disk in bytes, if the file still exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionRow<'a> {
+    qualified_name: &'a str,
+    file: &'a str,
+    line_start: u32,
+    line_end: u32,
+}
+
+pub fn run(opts: &ListOptions) -> ExitCode {
+    // Listing files under one top-level directory needs only its shard of a sharded index
+    let loaded = match opts.paths.as_ref().and_then(|glob| glob.as_deref()).filter(|glob| is_in_one_shard(glob)) {
+        Some(glob) => index::load_index_for(glob),
+        None => index::load_index(),
+    };
+    let index = match loaded {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(lang) = &opts.lang
+        && parser::grammar(lang).is_none()
+    {
+        eprintln!("error: unknown language '{lang}' (expected go, rust, c, cpp, python, typescript, or java)");
+        return ExitCode::FAILURE;
+    }
+    if let Some(scope) = &opts.scope
+        && !Scope::ALL.iter().any(|s| s.name() == scope)
+    {
+        eprintln!("error: unknown scope '{scope}' (expected public, crate, static, or internal)");
+        return ExitCode::FAILURE;
+    }
+
+    let result = match &opts.paths {
+        Some(glob) => list_paths(&index, glob.as_deref(), opts),
+        None if opts.tree => list_tree(&index, opts),
+        None => list_functions(&index, opts),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Files matching the language filter and glob, sorted by path. Reference files are left out
+/// unless `include_reference` is set.
+fn matching_files<'a>(
+    index: &'a Index,
+    glob: Option<&str>,
+    lang: Option<&str>,
+    include_reference: bool,
+) -> Vec<(&'a String, &'a FileEntry)> {
+    let mut files: Vec<(&String, &FileEntry)> = index
+        .files
+        .iter()
+        .filter(|(_, entry)| include_reference || !entry.reference)
+        .filter(|(path, _)| lang.is_none_or(|l| language_of(path) == l))
+        .filter(|(path, _)| glob.is_none_or(|g| glob_match(g, path)))
+        .collect();
+    files.sort_by(|a, b| a.0.cmp(b.0));
+    files
+}
+
+fn list_paths(index: &Index, glob: Option<&str>, opts: &ListOptions) -> Result<(), String> {
+    let root = index::repo_root()?;
+    let mut rows: Vec<FileRow> = matching_files(index, glob, opts.lang.as_deref(), opts.include_reference)
+        .into_iter()
+        .map(|(path, entry)| FileRow {
+            path,
+            language: language_of(path),
+            functions: entry.functions.len(),
+            types: entry.types.len(),
+            size: index::resolve_source_path(&root, path)
+                .ok()
+                .and_then(|p| fs::metadata(p).ok())
+                .map(|m| m.len()),
+        })
+        .collect();
+
+    // Stable sorts keep path order within ties
+    match opts.sort.as_str() {
+        "path" => {}
+        "functions" => rows.sort_by_key(|r| std::cmp::Reverse(r.functions)),
+        "size" => rows.sort_by_key(|r| std::cmp::Reverse(r.size)),
+        other => return Err(format!("unknown sort '{other}' (expected functions, size, or path)")),
+    }
+
+    let total_functions: usize = rows.iter().map(|r| r.functions).sum();
+    let total_types: usize = rows.iter().map(|r| r.types).sum();
+
+    if opts.json {
+        let out = serde_json::to_string_pretty(&rows).map_err(|e| format!("failed to serialize: {e}"))?;
+        println!("{out}");
+        return Ok(());
+    }
+
+    let width = rows.iter().map(|r| r.path.len()).max().unwrap_or(0);
+    for row in &rows {
+        let size = row.size.map(format_size).unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<width$}  {:<5} {:>5} functions {:>4} types {:>9}",
+            row.path, row.language, row.functions, row.types, size
+        );
+    }
+
+    println!();
+    println!("{} files: {} functions, {} types", rows.len(), total_functions, total_types);
+    Ok(())
+}
+
+fn list_functions(index: &Index, opts: &ListOptions) -> Result<(), String> {
+    let mut rows: Vec<FunctionRow> = matching_files(index, None, opts.lang.as_deref(), opts.include_reference)
+        .into_iter()
+        .flat_map(|(path, entry)| {
+            entry
+                .functions
+                .iter()
+                .filter(|f| keep_function(f, opts))
+                .map(move |f| FunctionRow {
+                    qualified_name: &f.qualified_name,
+                    file: path,
+                    line_start: f.line_start,
+                    line_end: f.line_end,
+                })
+        })
+        .collect();
+    rows.sort_by(|a, b| a.qualified_name.cmp(b.qualified_name).then_with(|| a.file.cmp(b.file)));
+
+    if opts.json {
+        let out = serde_json::to_string_pretty(&rows).map_err(|e| format!("failed to serialize: {e}"))?;
+        println!("{out}");
+        return Ok(());
+    }
+
+    let fmt = Formatter::new(opts.full);
+    for row in &rows {
+        println!("{} ({}:{}-{})", fmt.name(row.qualified_name), row.file, row.line_start, row.line_end);
+    }
+    Ok(())
+}
+
+/// Whether `func` passes the --uses-type, --public-only, --scope, --async, and --ffi filters
+fn keep_function(func: &Function, opts: &ListOptions) -> bool {
+    opts.uses_type.as_deref().is_none_or(|t| uses_type(func, t))
+        && (!opts.public_only || func.scope == Scope::Public)
+        && opts.scope.as_deref().is_none_or(|s| func.scope.name() == s)
+        && (!opts.is_async || func.is_async)
+        && (!opts.ffi || func.exported_c)
+}
+
+/// Whether `func` uses a type with qualified name `type_name`, or simple name if unqualified
+fn uses_type(func: &Function, type_name: &str) -> bool {
+    func.uses_types
+        .iter()
+        .any(|t| t == type_name || t.rsplit(['.', ':']).next() == Some(type_name))
+}
+
+/// One node of the `--tree` view: a module, a file, a type, or a function
+#[derive(Debug, Serialize)]
+struct TreeNode {
+    /// "module", "file", "function", "const", "static", "var", or the type kind (struct, interface, typedef, enum)
+    kind: &'static str,
+    name: String,
+    /// The file a module's items all live in, once the file node was collapsed into it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    functions: usize,
+    types: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<TreeNode>,
+    /// Separator between a module and its submodules: "::" for Rust, "/" for Go and C paths
+    #[serde(skip)]
+    separator: &'static str,
+}
+
+impl TreeNode {
+    fn new(kind: &'static str, name: &str, separator: &'static str) -> Self {
+        TreeNode {
+            kind,
+            name: name.to_string(),
+            file: None,
+            line: None,
+            functions: 0,
+            types: 0,
+            children: Vec::new(),
+            separator,
+        }
+    }
+
+    /// The child of `kind` named `name`, created if missing
+    fn child(&mut self, kind: &'static str, name: &str, separator: &'static str) -> &mut TreeNode {
+        let existing = self.children.iter().position(|c| c.kind == kind && c.name == name && c.separator == separator);
+        let pos = match existing {
+            Some(pos) => pos,
+            None => {
+                self.children.push(TreeNode::new(kind, name, separator));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[pos]
+    }
+
+    fn is_item(&self) -> bool {
+        self.kind != "module" && self.kind != "file"
+    }
+}
+
+fn list_tree(index: &Index, opts: &ListOptions) -> Result<(), String> {
+    let files = matching_files(index, None, opts.lang.as_deref(), opts.include_reference);
+    // Types and variables are not functions, so a function filter leaves them out
+    let with_types = opts.uses_type.is_none() && !opts.is_async && !opts.ffi;
+    let mut root = build_tree(&files, |f| keep_function(f, opts), with_types);
+    prune(&mut root, opts.depth);
+
+    if opts.json {
+        let out = serde_json::to_string_pretty(&root.children).map_err(|e| format!("failed to serialize: {e}"))?;
+        println!("{out}");
+        return Ok(());
+    }
+
+    for line in render_tree(&root) {
+        println!("{line}");
+    }
+    println!();
+    println!("{}", counts(root.functions, root.types));
+    Ok(())
+}
+
+/// Build the module → file → type → method tree of `files`, with variables next to the types. Modules come from the qualified
+/// names, so a Rust file with inline `mod` blocks shows up under each of its modules.
+fn build_tree(files: &[(&String, &FileEntry)], keep: impl Fn(&Function) -> bool, with_types: bool) -> TreeNode {
+    let mut root = TreeNode::new("module", "", "");
+    for (path, entry) in files {
+        // C++ namespaces and classes are `::`-separated like Rust modules
+        let rust = matches!(language_of(path), "rust" | "cpp");
+
+        let types: &[TypeDef] = if with_types { &entry.types } else { &[] };
+        for t in types {
+            let (module, _) = split_qualified_name(&t.qualified_name, None, rust);
+            let node = file_node(&mut root, module, path, rust);
+            let mut item = TreeNode::new(type_kind(t.kind), &t.name, "");
+            item.line = Some(t.line_start);
+            node.children.push(item);
+        }
+        let variables: &[Variable] = if with_types { &entry.variables } else { &[] };
+        for var in variables {
+            let (module, _) = split_qualified_name(&var.qualified_name, None, rust);
+            let node = file_node(&mut root, module, path, rust);
+            let kind = match (var.is_const, language_of(path)) {
+                (true, _) => "const",
+                (false, "rust") => "static",
+                (false, _) => "var",
+            };
+            let mut item = TreeNode::new(kind, &var.name, "");
+            item.line = Some(var.line_start);
+            node.children.push(item);
+        }
+        for func in entry.functions.iter().filter(|f| keep(f)) {
+            let (module, local) = split_qualified_name(&func.qualified_name, func.receiver.as_deref(), rust);
+            let node = file_node(&mut root, module, path, rust);
+            // Methods go under their type when it is defined in the same file
+            let receiver = func.receiver.as_deref().map(|r| r.split('<').next().unwrap_or(r));
+            let owner = receiver
+                .and_then(|r| node.children.iter_mut().find(|c| c.is_item() && c.kind != "function" && c.name == r));
+            let (parent, name) = match owner {
+                Some(owner) => (owner, func.name.as_str()),
+                None => (node, local),
+            };
+            let mut item = TreeNode::new("function", name, "");
+            item.line = Some(func.line_start);
+            parent.children.push(item);
+        }
+    }
+    finish(&mut root);
+    root
+}
+
+/// Sort children, total the counts, and fold chains of single-child modules into one node
+fn finish(node: &mut TreeNode) {
+    for child in &mut node.children {
+        finish(child);
+    }
+    match node.kind {
+        "function" => node.functions += 1,
+        "module" | "file" | "const" | "static" | "var" => {}
+        _ => node.types += 1,
+    }
+    node.functions += node.children.iter().map(|c| c.functions).sum::<usize>();
+    node.types += node.children.iter().map(|c| c.types).sum::<usize>();
+    // Files before submodules, each by name; items in source order
+    node.children.sort_by(|a, b| (a.kind == "module", a.line, &a.name).cmp(&(b.kind == "module", b.line, &b.name)));
+
+    if node.kind == "module" && !node.name.is_empty() && node.children.len() == 1 {
+        let child = node.children.pop().unwrap();
+        if child.kind == "module" {
+            node.name = format!("{}{}{}", node.name, node.separator, child.name);
+            node.file = child.file;
+        } else {
+            node.file = Some(child.name);
+        }
+        node.children = child.children;
+    }
+}
+
+/// Drop the nodes more than `depth` levels below `node` (0 = unlimited); their ancestors keep the counts
+fn prune(node: &mut TreeNode, depth: usize) {
+    if depth == 0 {
+        return;
+    }
+    for child in &mut node.children {
+        if depth == 1 {
+            child.children.clear();
+        } else {
+            prune(child, depth - 1);
+        }
+    }
+}
+
+/// One line per node below `root`, indented two spaces per level
+fn render_tree(root: &TreeNode) -> Vec<String> {
+    fn push(node: &TreeNode, level: usize, out: &mut Vec<String>) {
+        let pad = "  ".repeat(level);
+        let line = match node.kind {
+            "module" | "file" => {
+                let file = node.file.as_deref().map(|f| format!("  {f}")).unwrap_or_default();
+                format!("{pad}{}{file}  ({})", node.name, counts(node.functions, node.types))
+            }
+            "function" => format!("{pad}{}()  :{}", node.name, node.line.unwrap_or_default()),
+            kind => {
+                let methods = match node.functions {
+                    0 => String::new(),
+                    1 => "  (1 method)".to_string(),
+                    n => format!("  ({n} methods)"),
+                };
+                format!("{pad}{kind} {}  :{}{methods}", node.name, node.line.unwrap_or_default())
+            }
+        };
+        out.push(line);
+        for child in &node.children {
+            push(child, level + 1, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    for child in &root.children {
+        push(child, 0, &mut out);
+    }
+    out
+}
+
+/// "3 functions, 1 type", leaving out a count of zero
+fn counts(functions: usize, types: usize) -> String {
+    let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+    match (functions, types) {
+        (f, 0) => plural(f, "function"),
+        (0, t) => plural(t, "type"),
+        (f, t) => format!("{}, {}", plural(f, "function"), plural(t, "type")),
+    }
+}
+
+/// Kind of a type as written in the index: struct, interface, typedef, enum
+fn type_kind(kind: TypeKind) -> &'static str {
+    match kind {
+        TypeKind::Struct => "struct",
+        TypeKind::Interface => "interface",
+        TypeKind::Typedef => "typedef",
+        TypeKind::Enum => "enum",
+    }
+}
+
+/// The node of file `path` under the module path `module`, created along with the modules if missing
+fn file_node<'a>(root: &'a mut TreeNode, module: &str, path: &str, rust: bool) -> &'a mut TreeNode {
+    let separator = if rust { "::" } else { "/" };
+    let mut node = root;
+    for segment in module.split(separator).filter(|s| !s.is_empty()) {
+        node = node.child("module", segment, separator);
+    }
+    node.child("file", path, separator)
+}
+
+/// Split a qualified name into its module path and the name within the module, e.g.
+/// "commands::index::Builder::run" -> ("commands::index", "Builder::run") for Rust and
+/// "internal/store.Store.Get" -> ("internal/store", "Store.Get") for Go and C
+fn split_qualified_name<'a>(qualified_name: &'a str, receiver: Option<&str>, rust: bool) -> (&'a str, &'a str) {
+    if rust {
+        let Some((scope, _)) = qualified_name.rsplit_once("::") else {
+            return ("", qualified_name);
+        };
+        let module = match receiver {
+            Some(receiver) if scope == receiver => "",
+            Some(receiver) => scope.strip_suffix(&format!("::{receiver}")).unwrap_or(scope),
+            None if scope == "crate" => "",
+            None => scope,
+        };
+        let local = if module.is_empty() { qualified_name } else { &qualified_name[module.len() + 2..] };
+        return (module, local);
+    }
+
+    // The package path ends at the first '.' after the last '/'
+    let after_slash = qualified_name.rfind('/').map_or(0, |i| i + 1);
+    match qualified_name[after_slash..].find('.') {
+        Some(dot) => (&qualified_name[..after_slash + dot], &qualified_name[after_slash + dot + 1..]),
+        None => ("", qualified_name),
+    }
+}
+
+pub fn language_of(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("go") => "go",
+        Some("rs") => "rust",
+        Some("c") | Some("h") => "c",
+        Some("py") => "python",
+        Some("ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs") => "typescript",
+        Some("java") => "java",
+        Some(ext) if parser::CPP_EXTENSIONS.contains(&ext) => "cpp",
+        _ => "other",
+    }
+}
+
+/// "512 B", "2.1 KB", "3.4 MB"
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Match an index key against a glob where `*` and `?` stay within one path
+/// segment and `**` spans segments. A leading "./" on either side is ignored.
+/// Whether every path `glob` matches is under one literal top-level directory, e.g. "internal/**"
+fn is_in_one_shard(glob: &str) -> bool {
+    let glob = glob.strip_prefix("./").unwrap_or(glob);
+    glob.split_once('/').is_some_and(|(dir, _)| !dir.contains(['*', '?']))
+}
+
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    let path = path.strip_prefix("./").unwrap_or(path);
+    glob_match_bytes(pattern.as_bytes(), path.as_bytes())
+}
+
+fn glob_match_bytes(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+            // "**/" also matches zero directories
+            let rest = &rest[1..];
+            if let Some(after_slash) = rest.strip_prefix(b"/")
+                && glob_match_bytes(after_slash, path)
+            {
+                return true;
+            }
+            (0..=path.len()).any(|i| glob_match_bytes(rest, &path[i..]))
+        }
+        Some((b'*', rest)) => {
+            let segment_len = path.iter().position(|&c| c == b'/').unwrap_or(path.len());
+            (0..=segment_len).any(|i| glob_match_bytes(rest, &path[i..]))
+        }
+        Some((b'?', rest)) => {
+            matches!(path.first(), Some(&c) if c != b'/') && glob_match_bytes(rest, &path[1..])
+        }
+        Some((&c, rest)) => path.first() == Some(&c) && glob_match_bytes(rest, &path[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("internal/**", "./internal/store/db.go"));
+        assert!(glob_match("./internal/**", "./internal/db.go"));
+        assert!(!glob_match("internal/**", "./cmd/main.go"));
+        assert!(glob_match("src/*.rs", "./src/main.rs"));
+        assert!(!glob_match("src/*.rs", "./src/commands/list.rs"));
+        assert!(glob_match("src/**/*.rs", "./src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "./src/commands/list.rs"));
+        assert!(glob_match("**/*_test.go", "./pkg/a_test.go"));
+        assert!(glob_match("src/ma?n.rs", "./src/main.rs"));
+    }
+
+    fn func(qualified_name: &str, receiver: Option<&str>, line: u32) -> Function {
+        let name = qualified_name.rsplit(['.', ':']).next().unwrap();
+        Function {
+            name: name.to_string(),
+            qualified_name: qualified_name.to_string(),
+            line_start: line,
+            line_end: line + 2,
+            receiver: receiver.map(String::from),
+            scope: if name.starts_with(char::is_lowercase) { Scope::Internal } else { Scope::Public },
+            ..Default::default()
+        }
+    }
+
+    fn file(functions: Vec<Function>, types: &[(&str, u32)]) -> FileEntry {
+        let types = types
+            .iter()
+            .map(|(qualified_name, line)| TypeDef {
+                name: qualified_name.rsplit(['.', ':']).next().unwrap().to_string(),
+                qualified_name: qualified_name.to_string(),
+                kind: TypeKind::Struct,
+                line_start: *line,
+                line_end: line + 3,
+                summary: None,
+                methods: Vec::new(),
+                implements: Vec::new(),
+                implemented_by: Vec::new(),
+                fields: Vec::new(),
+                variants: Vec::new(),
+                doc: None,
+            })
+            .collect();
+        FileEntry {
+            functions,
+            types,
+            ..Default::default()
+        }
+    }
+
+    fn tree_index() -> Index {
+        let mut index = Index::new();
+        let store = file(
+            vec![func("internal/store.Open", None, 10), func("internal/store.Store.Get", Some("Store"), 20)],
+            &[("internal/store.Store", 3)],
+        );
+        index.files.insert("./internal/store/store.go".to_string(), store);
+        let cache =
+            file(vec![func("internal/store.Store.Put", Some("Store"), 5), func("internal/store.evict", None, 30)], &[]);
+        index.files.insert("./internal/store/cache.go".to_string(), cache);
+        index.files.insert("./main.go".to_string(), file(vec![func("main.main", None, 3)], &[]));
+        let builder = file(
+            vec![
+                func("commands::index::Builder::run", Some("Builder"), 12),
+                func("commands::index::build", None, 40),
+                func("commands::index::tests::test_build", None, 80),
+            ],
+            &[("commands::index::Builder", 4)],
+        );
+        index.files.insert("./src/commands/index.rs".to_string(), builder);
+        index
+    }
+
+    #[test]
+    fn test_tree_collapses_chains_and_nests_methods() {
+        let index = tree_index();
+        let files = matching_files(&index, None, None, false);
+        let root = build_tree(&files, |_| true, true);
+        let expected = [
+            "commands::index  (3 functions, 1 type)",
+            "  ./src/commands/index.rs  (2 functions, 1 type)",
+            "    struct Builder  :4  (1 method)",
+            "      run()  :12",
+            "    build()  :40",
+            "  tests  ./src/commands/index.rs  (1 function)",
+            "    test_build()  :80",
+            "internal/store  (4 functions, 1 type)",
+            "  ./internal/store/cache.go  (2 functions)",
+            "    Store.Put()  :5",
+            "    evict()  :30",
+            "  ./internal/store/store.go  (2 functions, 1 type)",
+            "    struct Store  :3  (1 method)",
+            "      Get()  :20",
+            "    Open()  :10",
+            "main  ./main.go  (1 function)",
+            "  main()  :3",
+        ];
+        assert_eq!(render_tree(&root), expected);
+        assert_eq!((root.functions, root.types), (8, 2));
+
+        // Filtered functions and pruned levels keep their counts on the nodes above
+        let mut public = build_tree(&files, |f| f.scope == Scope::Public, false);
+        prune(&mut public, 1);
+        assert_eq!(render_tree(&public), ["internal/store  (3 functions)"]);
+        let json = serde_json::to_value(&public.children).unwrap();
+        let expected = serde_json::json!({ "kind": "module", "name": "internal/store", "functions": 3, "types": 0 });
+        assert_eq!(json[0], expected);
+    }
+
+    #[test]
+    fn test_split_qualified_name() {
+        assert_eq!(split_qualified_name("commands::index::Builder::run", Some("Builder"), true), ("commands::index", "Builder::run"));
+        assert_eq!(split_qualified_name("Index::new", Some("Index"), true), ("", "Index::new"));
+        assert_eq!(split_qualified_name("main", None, true), ("", "main"));
+        assert_eq!(split_qualified_name("internal/store.Store.Get", Some("Store"), false), ("internal/store", "Store.Get"));
+        assert_eq!(split_qualified_name("main.init@main", None, false), ("main", "init@main"));
+        assert_eq!(split_qualified_name("read_all", None, false), ("", "read_all"));
+    }
+
+    #[test]
+    fn test_language_and_size() {
+        assert_eq!(language_of("./a/b.go"), "go");
+        assert_eq!(language_of("./src/main.rs"), "rust");
+        assert_eq!(language_of("./x.h"), "c");
+        assert_eq!(language_of("./tools/gen.py"), "python");
+        assert_eq!(language_of("./web/App.jsx"), "typescript");
+        assert_eq!(language_of("./svc/Main.java"), "java");
+        assert_eq!(language_of("./net/server.cc"), "cpp");
+        assert_eq!(format_size(100), "100 B");
+        assert_eq!(format_size(2048), "2.0 KB");
+    }
+}
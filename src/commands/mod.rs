@@ -1,4 +1,24 @@
+pub mod annotate;
+pub mod annotate_external;
+pub mod bench;
+pub mod between_files;
+pub mod cache;
 pub mod callstack;
+pub mod completions;
+pub mod context;
+pub mod diff_index;
+pub mod export;
+pub mod filetrace;
+pub mod function;
+pub mod implementations;
 pub mod index;
+pub mod list;
+pub mod package;
 pub mod source;
+pub mod stats;
 pub mod topo;
+pub mod types;
+pub mod update;
+pub mod usages;
+pub mod validate;
+pub mod writers;
@@ -0,0 +1,317 @@
+use std::collections::{BTreeMap, HashMap};
+use std::process::ExitCode;
+
+use crate::config::Config;
+use crate::index::{self, Function, Index, PackageEntry, Scope, Timings};
+use crate::parser;
+use crate::progress;
+use crate::sanitize;
+use crate::summarizer::{PackageRequest, Summarizer, SummarizerError};
+
+/// Most function summaries put in one package prompt
+const MAX_PROMPT_FUNCTIONS: usize = 60;
+
+pub fn run(prefix: &str) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let prefix = normalize_prefix(prefix);
+    let packages = members(&index);
+    let matches: Vec<(&str, &Vec<&str>)> = packages
+        .iter()
+        .filter(|(name, _)| prefix == "." || **name == prefix || name.starts_with(&format!("{prefix}/")))
+        .map(|(name, files)| (*name, files))
+        .collect();
+    if matches.is_empty() {
+        eprintln!("error: no package found matching '{prefix}'");
+        return ExitCode::FAILURE;
+    }
+
+    let edges = package_edges(&index);
+    for (i, (name, files)) in matches.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        for line in render(&index, name, files, &edges) {
+            println!("{line}");
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// "./"-prefixed directory without a trailing slash, matching the form of index keys
+pub fn normalize_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim_start_matches("./").trim_end_matches('/');
+    if trimmed.is_empty() || trimmed == "." { ".".to_string() } else { format!("./{trimmed}") }
+}
+
+/// Package of an index key: the directory holding the file ("." for files at the root)
+pub fn package_of(file: &str) -> &str {
+    match file.rfind('/') {
+        Some(i) if i > 1 => &file[..i],
+        _ => ".",
+    }
+}
+
+/// Indexed files grouped by package, leaving out reference files
+fn members(index: &Index) -> BTreeMap<&str, Vec<&str>> {
+    let mut packages: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (file, entry) in &index.files {
+        if !entry.reference {
+            packages.entry(package_of(file)).or_default().push(file.as_str());
+        }
+    }
+    for files in packages.values_mut() {
+        files.sort();
+    }
+    packages
+}
+
+/// Number of resolved calls from one package into another, keyed by (caller package, callee package)
+fn package_edges(index: &Index) -> BTreeMap<(&str, &str), usize> {
+    let mut file_of: HashMap<&str, &str> = HashMap::new();
+    for (file, entry) in &index.files {
+        for func in &entry.functions {
+            file_of.entry(func.qualified_name.as_str()).or_insert(file.as_str());
+        }
+    }
+
+    let mut edges: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for (file, entry) in &index.files {
+        let from = package_of(file);
+        for call in entry.functions.iter().flat_map(|f| f.calls.iter()) {
+            if let Some(target_file) = file_of.get(call.target.as_str())
+                && package_of(target_file) != from
+            {
+                *edges.entry((from, package_of(target_file))).or_default() += 1;
+            }
+        }
+    }
+    edges
+}
+
+fn render(index: &Index, name: &str, files: &[&str], edges: &BTreeMap<(&str, &str), usize>) -> Vec<String> {
+    let functions: Vec<(&str, &Function)> = files
+        .iter()
+        .flat_map(|file| index.files[*file].functions.iter().map(move |f| (*file, f)))
+        .collect();
+    let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+
+    let mut out = vec![format!("{name} ({}, {})", plural(files.len(), "file"), plural(functions.len(), "function"))];
+    if let Some(entry) = index.packages.get(name) {
+        out.push(format!("  summary: {}", sanitize::sanitize_summary(&entry.summary)));
+    }
+
+    out.push("  files:".to_string());
+    // A Rust file's module doc is described by its first line
+    out.extend(files.iter().map(|file| match index.files[*file].doc.as_deref().and_then(|d| d.lines().next()) {
+        Some(doc) => format!("    {file}  {}", sanitize::sanitize_summary(doc)),
+        None => format!("    {file}"),
+    }));
+
+    let mut public: Vec<&(&str, &Function)> = functions.iter().filter(|(_, f)| f.scope == Scope::Public).collect();
+    public.sort_by(|a, b| a.1.qualified_name.cmp(&b.1.qualified_name));
+    if !public.is_empty() {
+        out.push("  public API:".to_string());
+        for (file, func) in public {
+            out.push(format!("    {} ({}:{})", func.qualified_name, file, func.line_start));
+        }
+    }
+
+    let depends_on: Vec<String> = edges
+        .iter()
+        .filter(|((from, _), _)| *from == name)
+        .map(|((_, to), count)| format!("    {to} ({})", plural(*count, "call")))
+        .collect();
+    if !depends_on.is_empty() {
+        out.push("  depends on:".to_string());
+        out.extend(depends_on);
+    }
+    let used_by: Vec<String> = edges
+        .iter()
+        .filter(|((_, to), _)| *to == name)
+        .map(|((from, _), count)| format!("    {from} ({})", plural(*count, "call")))
+        .collect();
+    if !used_by.is_empty() {
+        out.push("  used by:".to_string());
+        out.extend(used_by);
+    }
+    out
+}
+
+/// Prompt for one package and the hash of its member summaries, or None when none of its
+/// functions have a summary yet
+fn rollup_request(index: &Index, name: &str, files: &[&str]) -> Option<(PackageRequest, String)> {
+    let mut summarized: Vec<&Function> = files
+        .iter()
+        .flat_map(|file| index.files[*file].functions.iter())
+        .filter(|f| f.summary.is_some())
+        .collect();
+    if summarized.is_empty() {
+        return None;
+    }
+
+    let hash = parser::content_hash(rollup_input(&summarized).as_bytes());
+
+    // Public API first, so it survives the cap on large packages
+    summarized.sort_by_key(|f| (f.scope != Scope::Public, f.qualified_name.as_str()));
+    let functions = summarized
+        .iter()
+        .take(MAX_PROMPT_FUNCTIONS)
+        .map(|f| (f.qualified_name.clone(), f.summary.clone().unwrap_or_default()))
+        .collect();
+    let files = files.iter().map(|f| f.to_string()).collect();
+    Some((PackageRequest { name: name.to_string(), files, functions }, hash))
+}
+
+/// What a package's `summary_hash` is computed from: each summarized member and its summary
+fn rollup_input(summarized: &[&Function]) -> String {
+    let mut hashed = String::new();
+    for func in summarized {
+        hashed.push_str(&format!("{}\t{}\n", func.qualified_name, func.summary.as_deref().unwrap_or_default()));
+    }
+    hashed
+}
+
+/// Rewrite package rollup hashes computed with `parser::legacy_content_hash` whose member
+/// summaries are unchanged, so the rollups are kept (see index::migrate_legacy_hashes)
+pub fn rehash_legacy_rollups(index: &mut Index) {
+    let mut rehashed = Vec::new();
+    for (name, files) in members(index) {
+        let summarized: Vec<&Function> = files
+            .iter()
+            .flat_map(|file| index.files[*file].functions.iter())
+            .filter(|f| f.summary.is_some())
+            .collect();
+        let input = rollup_input(&summarized);
+        if index.packages.get(name).is_some_and(|e| e.summary_hash == parser::legacy_content_hash(input.as_bytes())) {
+            rehashed.push((name.to_string(), parser::content_hash(input.as_bytes())));
+        }
+    }
+    for (name, hash) in rehashed {
+        if let Some(entry) = index.packages.get_mut(&name) {
+            entry.summary_hash = hash;
+        }
+    }
+}
+
+/// Rebuild `index.packages`: keep rollups from `previous` whose member summaries are unchanged and,
+/// when `generate` is set, summarize the other packages. Returns the number generated.
+pub fn rollup_packages(config: &Config, index: &mut Index, previous: Option<&Index>, generate: bool) -> usize {
+    let mut packages: BTreeMap<String, PackageEntry> = BTreeMap::new();
+    let mut pending: Vec<(PackageRequest, String)> = Vec::new();
+
+    for (name, files) in members(index) {
+        let Some((req, hash)) = rollup_request(index, name, &files) else {
+            continue;
+        };
+        match previous.and_then(|p| p.packages.get(name)).filter(|e| e.summary_hash == hash) {
+            Some(entry) => {
+                packages.insert(name.to_string(), entry.clone());
+            }
+            None if generate => pending.push((req, hash)),
+            None => {}
+        }
+    }
+
+    let mut generated = 0;
+    if !pending.is_empty()
+        && let Some(summarizer) =
+            Summarizer::new(&config.llm, config.debug).inspect_err(|e| progress::warn("summarize_failed", e)).ok()
+    {
+        let phase = progress::Phase::start("packages", Some(pending.len()));
+        for (done, (req, hash)) in pending.into_iter().enumerate() {
+            match summarizer.summarize_package(&req) {
+                Ok(summary) => {
+                    let summary = sanitize::sanitize_summary(&summary).into_owned();
+                    packages.insert(req.name, PackageEntry { summary, summary_hash: hash });
+                    generated += 1;
+                }
+                Err(SummarizerError::Interrupted) => break,
+                Err(e) => progress::warn("summarize_failed", &format!("failed to summarize package {}: {e}", req.name)),
+            }
+            phase.progress(done + 1);
+        }
+        let packages_ms = phase.finish(generated);
+        Timings::record(index, "packages", packages_ms);
+        if generated > 0 {
+            println!("Generated {generated} package summaries");
+        }
+    }
+
+    index.packages = packages;
+    generated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{CallSite, FileEntry};
+
+    fn func(name: &str, summary: Option<&str>, calls: &[&str]) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: name.to_string(),
+            line_start: 1,
+            line_end: 2,
+            summary: summary.map(String::from),
+            calls: calls
+                .iter()
+                .map(|target| CallSite {
+                    target: target.to_string(),
+                    raw: target.to_string(),
+                    line: 1,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn add_file(index: &mut Index, path: &str, functions: Vec<Function>) {
+        let entry =
+            FileEntry {
+                functions,
+                ..Default::default()
+            };
+        index.files.insert(path.to_string(), entry);
+    }
+
+    #[test]
+    fn test_package_rollups_and_edges() {
+        let mut index = Index::new();
+        add_file(&mut index, "./main.go", vec![func("main.main", None, &["store.Open", "store.Get", "fmt.Println"])]);
+        add_file(&mut index, "./internal/store/open.go", vec![func("store.Open", Some("Opens the store"), &[])]);
+        add_file(&mut index, "./internal/store/get.go", vec![func("store.Get", Some("Reads a key"), &["store.Open"])]);
+
+        assert_eq!(package_of("./main.go"), ".");
+        assert_eq!(package_of("./internal/store/get.go"), "./internal/store");
+        assert_eq!(normalize_prefix("internal/store/"), "./internal/store");
+
+        let edges = package_edges(&index);
+        assert_eq!(edges.into_iter().collect::<Vec<_>>(), vec![((".", "./internal/store"), 2)]);
+
+        // Only the package with summarized functions gets a rollup request
+        let packages = members(&index);
+        assert!(rollup_request(&index, ".", &packages["."]).is_none());
+        let (req, hash) = rollup_request(&index, "./internal/store", &packages["./internal/store"]).unwrap();
+        assert_eq!(req.files, vec!["./internal/store/get.go", "./internal/store/open.go"]);
+
+        // A rollup is kept while its member summaries are unchanged, and dropped once one changes
+        let mut previous = Index::new();
+        let entry = PackageEntry { summary: "Key-value storage".to_string(), summary_hash: hash };
+        previous.packages.insert("./internal/store".to_string(), entry);
+        assert_eq!(rollup_packages(&Config::default(), &mut index, Some(&previous), false), 0);
+        assert_eq!(index.packages["./internal/store"].summary, "Key-value storage");
+
+        index.files.get_mut("./internal/store/get.go").unwrap().functions[0].summary = Some("Reads a value".to_string());
+        rollup_packages(&Config::default(), &mut index, Some(&previous), false);
+        assert!(index.packages.is_empty());
+    }
+}
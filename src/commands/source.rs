@@ -1,19 +1,36 @@
-use std::fs;
+use std::collections::HashMap;
+use std::path::Path;
 use std::process::ExitCode;
 
-use crate::index::{self, Index, TypeKind};
+use crate::index::{self, Index, IndexedSource, TypeKind};
 
 /// Print raw source code for a symbol range
-fn print_source(file_path: &str, line_start: u32, line_end: u32) {
-    if let Ok(content) = fs::read_to_string(file_path) {
-        let lines: Vec<&str> = content.lines().collect();
-        let start = (line_start as usize).saturating_sub(1);
-        let end = (line_end as usize).min(lines.len());
-        if start < lines.len() {
-            for line in &lines[start..end] {
-                println!("{line}");
-            }
+fn print_source(content: &str, line_start: u32, line_end: u32) {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = (line_start as usize).saturating_sub(1);
+    let end = (line_end as usize).min(lines.len());
+    if start < lines.len() {
+        for line in &lines[start..end] {
+            println!("{line}");
+        }
+    }
+}
+
+/// Read a matched file once, warning if it is missing or changed since indexing
+fn load_source(root: &Path, index: &Index, file_path: &str) -> Option<String> {
+    let state = match index::read_indexed_source(root, file_path, &index.files[file_path]) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return None;
         }
+    };
+    if let Some(warning) = state.warning(file_path) {
+        eprintln!("warning: {warning}");
+    }
+    match state {
+        IndexedSource::Fresh(content) | IndexedSource::Modified(content) => Some(content),
+        IndexedSource::Missing => None,
     }
 }
 
@@ -67,10 +84,10 @@ fn find_symbols(index: &Index, name: &str, kind: Option<&str>) -> Result<Vec<Sym
 
         if !matches!(filter, Some(KindFilter::Function) | Some(KindFilter::Variable)) {
             for t in &entry.types {
-                if let Some(KindFilter::Type(ref k)) = filter {
-                    if t.kind != *k {
-                        continue;
-                    }
+                if let Some(KindFilter::Type(ref k)) = filter
+                    && t.kind != *k
+                {
+                    continue;
                 }
                 if t.name == name || t.qualified_name == name || t.qualified_name.contains(name) {
                     matches.push(SymbolMatch {
@@ -97,6 +114,13 @@ fn find_symbols(index: &Index, name: &str, kind: Option<&str>) -> Result<Vec<Sym
         }
     }
 
+    matches.sort_by(|a, b| {
+        a.qualified_name
+            .cmp(&b.qualified_name)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.line_start.cmp(&b.line_start))
+    });
+
     Ok(matches)
 }
 
@@ -122,16 +146,47 @@ pub fn run(name: &str, kind: Option<&str>) -> ExitCode {
         return ExitCode::FAILURE;
     }
 
-    let multiple = matches.len() > 1;
+    let root = match index::repo_root() {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    for (i, m) in matches.iter().enumerate() {
+    let mut sources: HashMap<&str, Option<String>> = HashMap::new();
+    for m in &matches {
+        sources.entry(m.file_path.as_str()).or_insert_with(|| load_source(&root, &index, &m.file_path));
+    }
+
+    let readable: Vec<&SymbolMatch> = matches.iter().filter(|m| sources[m.file_path.as_str()].is_some()).collect();
+    let skipped = matches.len() - readable.len();
+    if skipped > 0 {
+        let files = sources.values().filter(|s| s.is_none()).count();
+        eprintln!(
+            "{} {} in {} unreadable {} skipped",
+            skipped,
+            if skipped == 1 { "symbol" } else { "symbols" },
+            files,
+            if files == 1 { "file" } else { "files" }
+        );
+    }
+    if readable.is_empty() {
+        return ExitCode::FAILURE;
+    }
+
+    let multiple = readable.len() > 1;
+
+    for (i, m) in readable.iter().enumerate() {
         if multiple {
             if i > 0 {
                 println!();
             }
             println!("--- {} ({}:{}-{}) ---", m.qualified_name, m.file_path, m.line_start, m.line_end);
         }
-        print_source(&m.file_path, m.line_start, m.line_end);
+        if let Some(content) = &sources[m.file_path.as_str()] {
+            print_source(content, m.line_start, m.line_end);
+        }
     }
 
     ExitCode::SUCCESS
@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::commands::validate;
+use crate::index::{self, Index, IndexFormat, Timings};
+use crate::parser::ParserRegistry;
+
+/// Options controlling what `aria stats` prints
+pub struct StatsOptions {
+    /// Number of most-called stale functions to list
+    pub top: usize,
+    /// Fail if the percentage of stale summaries exceeds this
+    pub fail_on_stale: Option<f64>,
+    /// Emit JSON instead of text
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Stats {
+    files: usize,
+    /// Indexed files by language, "other" for files no parser in this build handles
+    languages: BTreeMap<&'static str, usize>,
+    functions: usize,
+    /// Functions in test files, counted in `functions` too
+    test_functions: usize,
+    types: usize,
+    /// Files indexed despite syntax errors
+    parse_error_files: usize,
+    calls: usize,
+    resolved_calls: usize,
+    /// Symbols outside the index by kind (syscall, libc, macro, external)
+    externals: BTreeMap<String, ExternalCount>,
+    summaries: usize,
+    staleness: Staleness,
+    files_on_disk: Option<FileStaleness>,
+    /// The index file itself: format, size, and how long this run took to load it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storage: Option<Storage>,
+    /// Phase durations of the run that wrote the index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<Timings>,
+}
+
+/// Distinct external symbols of one kind and the calls to them
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct ExternalCount {
+    symbols: usize,
+    calls: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Storage {
+    pub format: &'static str,
+    /// Shard files of a sharded index, 0 for a single file
+    pub shards: usize,
+    bytes: u64,
+    load_ms: f64,
+}
+
+/// Indexed files compared with their current content, as checked by `aria validate`
+#[derive(Debug, Serialize)]
+struct FileStaleness {
+    /// Files modified or removed since they were indexed
+    stale_files: usize,
+    /// Inverted, overlapping, or out-of-file function ranges
+    range_problems: usize,
+}
+
+/// Summaries whose recorded `summary_hash` no longer matches the function's `ast_hash`
+#[derive(Debug, Default, Serialize)]
+struct Staleness {
+    stale_summaries: usize,
+    /// Summaries with no recorded hash, so freshness cannot be checked
+    unverified_summaries: usize,
+    stale_pct: f64,
+    /// Package (directory) -> counts, only packages with stale summaries
+    by_package: BTreeMap<String, PackageStaleness>,
+    most_called_stale: Vec<StaleFunction>,
+    /// Stale summaries that are locked against regeneration, so only a person can refresh them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locked_stale: Vec<StaleFunction>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct PackageStaleness {
+    summaries: usize,
+    stale: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct StaleFunction {
+    qualified_name: String,
+    file: String,
+    line: u32,
+    callers: usize,
+}
+
+pub fn run(opts: &StatsOptions) -> ExitCode {
+    let started = Instant::now();
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let load_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let mut stats = compute(&index, opts.top);
+    stats.storage = storage(index::index_path(), &index.shards, load_ms);
+    if let Ok(root) = index::repo_root() {
+        let report = validate::check_files(&index, &root);
+        stats.files_on_disk =
+            Some(FileStaleness { stale_files: report.stale.len(), range_problems: report.ranges.len() });
+    }
+
+    if opts.json {
+        match serde_json::to_string_pretty(&stats) {
+            Ok(out) => println!("{out}"),
+            Err(e) => {
+                eprintln!("error: failed to serialize: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        print_text(&stats);
+    }
+
+    if let Some(threshold) = opts.fail_on_stale
+        && stats.staleness.stale_pct > threshold
+    {
+        eprintln!(
+            "error: {:.1}% of summaries are stale (limit {:.1}%)",
+            stats.staleness.stale_pct, threshold
+        );
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn compute(index: &Index, top: usize) -> Stats {
+    let mut stats = Stats {
+        files: index.files.len(),
+        languages: BTreeMap::new(),
+        functions: 0,
+        test_functions: 0,
+        types: 0,
+        parse_error_files: 0,
+        calls: 0,
+        resolved_calls: 0,
+        externals: BTreeMap::new(),
+        summaries: 0,
+        staleness: Staleness::default(),
+        files_on_disk: None,
+        storage: None,
+        timings: index.timings.clone(),
+    };
+    let mut stale_funcs = Vec::new();
+    let registry = ParserRegistry::new();
+
+    for (file_path, entry) in &index.files {
+        let package = package_of(file_path);
+        let language = registry.language_of(Path::new(file_path)).unwrap_or("other");
+        *stats.languages.entry(language).or_default() += 1;
+        stats.types += entry.types.len();
+        if entry.parse_errors > 0 {
+            stats.parse_error_files += 1;
+        }
+        if entry.is_test {
+            stats.test_functions += entry.functions.len();
+        }
+
+        for func in &entry.functions {
+            stats.functions += 1;
+            stats.calls += func.calls.len();
+            stats.resolved_calls += func.calls.iter().filter(|c| !c.target.starts_with('[')).count();
+
+            if func.summary.is_none() {
+                continue;
+            }
+            stats.summaries += 1;
+
+            let Some(summary_hash) = &func.summary_hash else {
+                stats.staleness.unverified_summaries += 1;
+                continue;
+            };
+
+            let pkg = stats.staleness.by_package.entry(package.clone()).or_default();
+            pkg.summaries += 1;
+            if *summary_hash != func.ast_hash {
+                pkg.stale += 1;
+                stats.staleness.stale_summaries += 1;
+                let stale = StaleFunction {
+                    qualified_name: func.qualified_name.clone(),
+                    file: file_path.clone(),
+                    line: func.line_start,
+                    callers: func.called_by.len(),
+                };
+                if func.summary_locked {
+                    stats.staleness.locked_stale.push(stale.clone());
+                }
+                stale_funcs.push(stale);
+            }
+        }
+    }
+
+    for external in index.externals.values() {
+        let count = stats.externals.entry(external.kind.clone()).or_default();
+        count.symbols += 1;
+        count.calls += external.references as usize;
+    }
+
+    stats.staleness.by_package.retain(|_, pkg| pkg.stale > 0);
+    if stats.summaries > 0 {
+        stats.staleness.stale_pct = stats.staleness.stale_summaries as f64 * 100.0 / stats.summaries as f64;
+    }
+
+    stale_funcs.sort_by(|a, b| b.callers.cmp(&a.callers).then_with(|| a.qualified_name.cmp(&b.qualified_name)));
+    stale_funcs.truncate(top);
+    stats.staleness.locked_stale.sort_by(|a, b| a.qualified_name.cmp(&b.qualified_name));
+    stats.staleness.most_called_stale = stale_funcs;
+
+    stats
+}
+
+/// Format and size of the index file at `path`, or of its manifest and `shards`, with the
+/// format read back from the first bytes of the last of them
+pub fn storage(path: &Path, shards: &[String], load_ms: f64) -> Option<Storage> {
+    let files: Vec<PathBuf> = if shards.is_empty() {
+        vec![path.to_path_buf()]
+    } else {
+        let dir = index::shard_dir(path);
+        std::iter::once(index::shard_manifest(path)).chain(shards.iter().map(|s| dir.join(s))).collect()
+    };
+    let mut head = [0; 16];
+    let read = File::open(files.last()?).and_then(|mut f| f.read(&mut head)).ok()?;
+    let bytes = files.iter().filter_map(|f| fs::metadata(f).ok()).map(|m| m.len()).sum();
+    Some(Storage { format: IndexFormat::detect(&head[..read]).name(), shards: shards.len(), bytes, load_ms })
+}
+
+/// Directory of an index file key, e.g. "./internal/store/db.go" -> "./internal/store"
+fn package_of(file_path: &str) -> String {
+    match Path::new(file_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().into_owned(),
+        _ => ".".to_string(),
+    }
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+fn print_text(stats: &Stats) {
+    let languages: Vec<String> = stats.languages.iter().map(|(lang, count)| format!("{lang} {count}")).collect();
+    if languages.len() > 1 {
+        println!("Files:      {} ({})", stats.files, languages.join(", "));
+    } else {
+        println!("Files:      {}", stats.files);
+    }
+    if stats.test_functions > 0 {
+        let non_test = stats.functions - stats.test_functions;
+        println!("Functions:  {} ({} non-test, {} test)", stats.functions, non_test, stats.test_functions);
+    } else {
+        println!("Functions:  {}", stats.functions);
+    }
+    println!("Types:      {}", stats.types);
+    if stats.parse_error_files > 0 {
+        let files = if stats.parse_error_files == 1 { "file" } else { "files" };
+        println!("Errors:     {} {files} with parse errors (run `aria validate` for details)", stats.parse_error_files);
+    }
+    println!(
+        "Calls:      {} ({} resolved, {:.1}%)",
+        stats.calls, stats.resolved_calls, percent(stats.resolved_calls, stats.calls)
+    );
+    if !stats.externals.is_empty() {
+        let symbols: usize = stats.externals.values().map(|c| c.symbols).sum();
+        let calls: usize = stats.externals.values().map(|c| c.calls).sum();
+        let kinds: Vec<String> = stats.externals.iter().map(|(kind, c)| format!("{kind} {}", c.symbols)).collect();
+        println!("Externals:  {symbols} symbols, {calls} calls ({})", kinds.join(", "));
+    }
+    println!(
+        "Summaries:  {}/{} ({:.1}%)",
+        stats.summaries, stats.functions, percent(stats.summaries, stats.functions)
+    );
+
+    let staleness = &stats.staleness;
+    println!();
+    println!("Staleness:");
+    println!(
+        "  Summaries: {} stale, {} unverified of {} ({:.1}% stale)",
+        staleness.stale_summaries, staleness.unverified_summaries, stats.summaries, staleness.stale_pct
+    );
+    if let Some(files) = &stats.files_on_disk {
+        println!("  Files:     {} stale of {}", files.stale_files, stats.files);
+        if files.range_problems > 0 {
+            println!("  Ranges:    {} problems", files.range_problems);
+        }
+        if files.stale_files > 0 || files.range_problems > 0 {
+            println!("  (run `aria validate` for details)");
+        }
+    }
+
+    if !staleness.by_package.is_empty() {
+        println!();
+        println!("  By package:");
+        for (package, pkg) in &staleness.by_package {
+            println!("    {} ({} of {} stale)", package, pkg.stale, pkg.summaries);
+        }
+    }
+
+    if !staleness.most_called_stale.is_empty() {
+        println!();
+        println!("  Most-called stale functions:");
+        for func in &staleness.most_called_stale {
+            println!("    {} ({}:{}, {} callers)", func.qualified_name, func.file, func.line, func.callers);
+        }
+    }
+
+    if let Some(storage) = &stats.storage {
+        println!();
+        let shards = match storage.shards {
+            0 => String::new(),
+            1 => ", 1 shard".to_string(),
+            n => format!(", {n} shards"),
+        };
+        println!(
+            "Index file: {:.1} MB ({}{shards}), loaded in {:.0} ms",
+            storage.bytes as f64 / (1024.0 * 1024.0), storage.format, storage.load_ms
+        );
+    }
+
+    if let Some(timings) = &stats.timings {
+        let phases: Vec<String> = ["walk", "parse", "resolve", "summarize", "packages"]
+            .iter()
+            .filter_map(|phase| Some(format!("{phase} {} ms", timings.phases_ms.get(*phase)?)))
+            .collect();
+        println!();
+        println!("Last index run: {}", phases.join(", "));
+        if !timings.slowest_files.is_empty() {
+            println!("  Slowest files to parse:");
+            for file in &timings.slowest_files {
+                println!("    {:>9.2} ms  {}", file.parse_ms, file.file);
+            }
+        }
+    }
+
+    if !staleness.locked_stale.is_empty() {
+        println!();
+        println!("  Locked stale summaries (review by hand, `aria annotate` to update):");
+        for func in &staleness.locked_stale {
+            println!("    {} ({}:{})", func.qualified_name, func.file, func.line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{ExternalEntry, FileEntry, Function};
+
+    fn func(name: &str, ast_hash: &str, summary_hash: Option<&str>, callers: usize) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("pkg.{}", name),
+            ast_hash: ast_hash.to_string(),
+            line_start: 1,
+            line_end: 2,
+            summary: Some(format!("does {}", name)),
+            summary_hash: summary_hash.map(String::from),
+            called_by: (0..callers).map(|i| format!("pkg.caller{}", i)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_staleness_counts_and_ranking() {
+        let mut index = Index::new();
+        let mut unsummarized = func("plain", "h0", None, 0);
+        unsummarized.summary = None;
+        let mut locked = func("stale", "h2", Some("old"), 1);
+        locked.summary_locked = true;
+        index.files.insert(
+            "./pkg/a.go".to_string(),
+            FileEntry {
+                ast_hash: "a".to_string(),
+                functions: vec![
+                    func("fresh", "h1", Some("h1"), 5),
+                    locked,
+                    func("hot", "h3", Some("old"), 9),
+                    func("legacy", "h4", None, 0),
+                    unsummarized,
+                ],
+                ..Default::default()
+            },
+        );
+        let mut tests = index.files["./pkg/a.go"].clone();
+        tests.functions.truncate(2);
+        tests.is_test = true;
+        index.files.insert("./pkg/a_test.go".to_string(), tests);
+        let external = |kind: &str, references| ExternalEntry { kind: kind.to_string(), summary: None, references };
+        index.externals.insert("malloc".to_string(), external("libc", 3));
+        index.externals.insert("free".to_string(), external("libc", 2));
+        index.externals.insert("read".to_string(), external("syscall", 1));
+
+        let stats = compute(&index, 1);
+        assert_eq!((stats.functions, stats.test_functions), (7, 2));
+        index.files.remove("./pkg/a_test.go");
+        let stats = compute(&index, 1);
+        let language = if cfg!(feature = "lang-go") { "go" } else { "other" };
+        assert_eq!(stats.languages, BTreeMap::from([(language, 1)]));
+        assert_eq!(stats.externals["libc"], ExternalCount { symbols: 2, calls: 5 });
+        assert_eq!(stats.externals["syscall"], ExternalCount { symbols: 1, calls: 1 });
+        assert_eq!(stats.functions, 5);
+        assert_eq!(stats.summaries, 4);
+        assert_eq!(stats.staleness.stale_summaries, 2);
+        assert_eq!(stats.staleness.unverified_summaries, 1);
+        assert_eq!(stats.staleness.stale_pct, 50.0);
+
+        let pkg = &stats.staleness.by_package["./pkg"];
+        assert_eq!((pkg.summaries, pkg.stale), (3, 2));
+
+        assert_eq!(stats.staleness.most_called_stale.len(), 1);
+        assert_eq!(stats.staleness.most_called_stale[0].qualified_name, "pkg.hot");
+
+        // Locked summaries are listed whether or not they are among the most called
+        let locked: Vec<&str> = stats.staleness.locked_stale.iter().map(|f| f.qualified_name.as_str()).collect();
+        assert_eq!(locked, vec!["pkg.stale"]);
+    }
+
+    #[test]
+    fn test_package_of() {
+        assert_eq!(package_of("./internal/store/db.go"), "./internal/store");
+        assert_eq!(package_of("./main.go"), ".");
+    }
+}
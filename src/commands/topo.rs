@@ -61,7 +61,8 @@ pub fn run() -> ExitCode {
     ExitCode::SUCCESS
 }
 
-fn build_call_graph(idx: &Index) -> (HashSet<String>, HashMap<String, HashSet<String>>) {
+/// All indexed function names and the in-source callees of each
+pub fn build_call_graph(idx: &Index) -> (HashSet<String>, HashMap<String, HashSet<String>>) {
     let mut all_functions: HashSet<String> = HashSet::new();
     let mut calls_map: HashMap<String, HashSet<String>> = HashMap::new();
 
@@ -0,0 +1,231 @@
+use std::process::ExitCode;
+
+use crate::freshness;
+use crate::index::{self, Field, Function, ImplLink, Index, TypeDef};
+
+/// A function that mentions a type, with the lines where it constructs it
+struct TypeUser<'a> {
+    file: &'a str,
+    func: &'a Function,
+    construct_lines: Vec<u32>,
+}
+
+pub fn run(name: &str) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let matches = find_types(&index, name);
+    if matches.is_empty() {
+        eprintln!("error: no type found matching '{name}'");
+        return ExitCode::FAILURE;
+    }
+    freshness::check(&index, matches.iter().map(|(file, _)| *file));
+
+    for (i, (file, t)) in matches.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        for line in render(&index, file, t) {
+            println!("{line}");
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Lines describing `t`: fields, implementation links, then the functions constructing or mentioning it
+pub fn render(index: &Index, file: &str, t: &TypeDef) -> Vec<String> {
+    let mut out = vec![format!("{} ({}:{}-{})", t.qualified_name, file, t.line_start, t.line_end)];
+    if !t.fields.is_empty() {
+        out.push("  fields:".to_string());
+        out.extend(t.fields.iter().map(|f| format!("    {}", describe_field(f))));
+    }
+    if !t.variants.is_empty() {
+        out.push(format!("  variants: {}", t.variants.join(", ")));
+    }
+    push_links(&mut out, "implements", &t.implements);
+    push_links(&mut out, "implemented by", &t.implemented_by);
+    push_trait_methods(&mut out, index, t);
+
+    let users = type_users(index, t);
+    if users.is_empty() {
+        out.push("  (no users found)".to_string());
+        return out;
+    }
+
+    let (constructors, others): (Vec<_>, Vec<_>) = users.iter().partition(|u| !u.construct_lines.is_empty());
+    if !constructors.is_empty() {
+        out.push("  constructed in:".to_string());
+        for user in constructors {
+            let lines: Vec<String> = user.construct_lines.iter().map(|l| l.to_string()).collect();
+            out.push(format!("    {} ({}:{})", user.func.qualified_name, user.file, lines.join(", ")));
+        }
+    }
+    if !others.is_empty() {
+        out.push("  used by:".to_string());
+        for user in others {
+            out.push(format!("    {} ({}:{})", user.func.qualified_name, user.file, user.func.line_start));
+        }
+    }
+    out
+}
+
+fn push_links(out: &mut Vec<String>, label: &str, links: &[ImplLink]) {
+    if links.is_empty() {
+        return;
+    }
+    out.push(format!("  {label}:"));
+    for link in links {
+        out.push(format!("    {}", describe_link(link)));
+    }
+}
+
+/// The methods implementing each method a Rust trait declares, from `impl Trait for Type` blocks
+fn push_trait_methods(out: &mut Vec<String>, index: &Index, t: &TypeDef) {
+    let mut lines = Vec::new();
+    for method in &t.methods {
+        let query = format!("{}::{method}", t.name);
+        let mut impls: Vec<&str> = index
+            .files
+            .values()
+            .flat_map(|e| e.functions.iter())
+            .filter(|f| index::implements_trait_method(f, &query))
+            .map(|f| f.qualified_name.as_str())
+            .collect();
+        impls.sort();
+        if !impls.is_empty() {
+            lines.push(format!("    {method}: {}", impls.join(", ")));
+        }
+    }
+    if !lines.is_empty() {
+        out.push("  method implementations:".to_string());
+        out.extend(lines);
+    }
+}
+
+/// "Name string `json:\"name\"`", or "*Base (embedded)" for an embedded field
+fn describe_field(field: &Field) -> String {
+    let mut line = if field.embedded {
+        format!("{} (embedded)", field.type_name)
+    } else {
+        format!("{} {}", field.name, field.type_name)
+    };
+    if let Some(tag) = &field.tag {
+        line.push_str(&format!(" `{tag}`"));
+    }
+    line
+}
+
+/// "name (file:line)", flagged when the name is not an indexed type
+pub fn describe_link(link: &ImplLink) -> String {
+    let flag = if link.unresolved { " (unresolved)" } else { "" };
+    format!("{} ({}:{}){}", link.name, link.file, link.line, flag)
+}
+
+/// Types whose qualified or simple name is `name`, falling back to a contains match
+pub fn find_types<'a>(index: &'a Index, name: &str) -> Vec<(&'a str, &'a TypeDef)> {
+    let all = || index.files.iter().flat_map(|(file, e)| e.types.iter().map(move |t| (file.as_str(), t)));
+
+    let mut matches: Vec<_> = all().filter(|(_, t)| t.qualified_name == name || t.name == name).collect();
+    if matches.is_empty() {
+        matches = all().filter(|(_, t)| t.qualified_name.contains(name)).collect();
+    }
+    matches.sort_by(|a, b| a.1.qualified_name.cmp(&b.1.qualified_name).then_with(|| a.0.cmp(b.0)));
+    matches
+}
+
+/// Functions whose resolved `uses_types` include `t`, sorted by qualified name
+fn type_users<'a>(index: &'a Index, t: &TypeDef) -> Vec<TypeUser<'a>> {
+    let mut users: Vec<TypeUser> = index
+        .files
+        .iter()
+        .flat_map(|(file, e)| e.functions.iter().map(move |f| (file.as_str(), f)))
+        .filter(|(_, f)| f.uses_types.contains(&t.qualified_name))
+        .map(|(file, func)| {
+            let mut construct_lines: Vec<u32> = func
+                .type_refs
+                .iter()
+                .filter(|r| r.constructs && r.name.rsplit(['.', ':']).next() == Some(t.name.as_str()))
+                .map(|r| r.line)
+                .collect();
+            construct_lines.dedup();
+            TypeUser { file, func, construct_lines }
+        })
+        .collect();
+    users.sort_by(|a, b| a.func.qualified_name.cmp(&b.func.qualified_name).then_with(|| a.file.cmp(b.file)));
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{FileEntry, TypeKind, TypeRef};
+
+    fn func(name: &str, refs: &[(&str, bool, u32)], uses: &[&str]) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("app.{}", name),
+            line_start: 1,
+            line_end: 9,
+            type_refs: refs
+                .iter()
+                .map(|(name, constructs, line)| TypeRef { line: *line, name: name.to_string(), constructs: *constructs })
+                .collect(),
+            uses_types: uses.iter().map(|u| u.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_type_users_split_constructors() {
+        let mut entry_type = TypeDef {
+            name: "Entry".to_string(),
+            qualified_name: "store.Entry".to_string(),
+            kind: TypeKind::Struct,
+            line_start: 2,
+            line_end: 4,
+            summary: None,
+            methods: Vec::new(),
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: None,
+        };
+        entry_type.fields = vec![
+            Field { name: "Base".to_string(), type_name: "*Base".to_string(), tag: None, embedded: true },
+            Field { name: "Key".to_string(), type_name: "string".to_string(), tag: Some("json:\"key\"".to_string()), embedded: false },
+        ];
+        let mut index = Index::new();
+        index.files.insert(
+            "./app/app.go".to_string(),
+            FileEntry {
+                functions: vec![
+                    func("show", &[("store.Entry", false, 3)], &["store.Entry"]),
+                    func("make", &[("store.Entry", true, 5), ("store.Entry", true, 7)], &["store.Entry"]),
+                    func("other", &[("Entry", true, 2)], &[]),
+                ],
+                types: vec![entry_type],
+                ..Default::default()
+            },
+        );
+
+        let t = &index.files["./app/app.go"].types[0];
+        let users = type_users(&index, t);
+        let summary: Vec<(&str, Vec<u32>)> =
+            users.iter().map(|u| (u.func.qualified_name.as_str(), u.construct_lines.clone())).collect();
+        assert_eq!(summary, vec![("app.make", vec![5, 7]), ("app.show", vec![])]);
+
+        let lines = render(&index, "./app/app.go", t);
+        assert_eq!(lines[1..4], ["  fields:", "    *Base (embedded)", "    Key string `json:\"key\"`"]);
+
+        assert_eq!(find_types(&index, "Entry").len(), 1);
+        assert_eq!(find_types(&index, "store.En").len(), 1);
+        assert!(find_types(&index, "Missing").is_empty());
+    }
+}
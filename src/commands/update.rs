@@ -0,0 +1,252 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::commands::index::{
+    build_index, get_git_head, keep_unchanged_stamp, legacy_hash_warning, load_config, migrate_legacy_hashes,
+    preserve_summaries, restore_user_externals, run_summarization, write_index,
+};
+use crate::commands::package;
+use crate::index::{self, Function, Index};
+use crate::interrupt;
+use crate::parser::ParserRegistry;
+use crate::progress;
+
+/// Options controlling what `aria update` does after reindexing
+pub struct UpdateOptions {
+    /// Regenerate summaries for changed functions
+    pub summarize: bool,
+    /// Also regenerate summaries of direct callers of changed functions
+    pub cascade: bool,
+}
+
+/// Differences between the previous index and the updated one
+#[derive(Debug, Default)]
+struct ChangeSet {
+    /// Files added, modified, or removed
+    files: usize,
+    /// Qualified names of functions that are new or whose ast_hash changed, sorted
+    functions: Vec<String>,
+    /// Functions no longer in the index
+    removed: usize,
+}
+
+impl ChangeSet {
+    fn between(old: &Index, new: &Index) -> Self {
+        let old_hashes: HashMap<&str, &str> = all_functions(old)
+            .map(|f| (f.qualified_name.as_str(), f.ast_hash.as_str()))
+            .collect();
+        let new_names: HashSet<&str> = all_functions(new).map(|f| f.qualified_name.as_str()).collect();
+
+        let mut functions: Vec<String> = all_functions(new)
+            .filter(|f| old_hashes.get(f.qualified_name.as_str()) != Some(&f.ast_hash.as_str()))
+            .map(|f| f.qualified_name.clone())
+            .collect();
+        functions.sort();
+        functions.dedup();
+
+        let changed_files = new
+            .files
+            .iter()
+            .filter(|(path, entry)| old.files.get(*path).is_none_or(|o| o.ast_hash != entry.ast_hash))
+            .count();
+        let removed_files = old.files.keys().filter(|path| !new.files.contains_key(*path)).count();
+
+        Self {
+            files: changed_files + removed_files,
+            functions,
+            removed: old_hashes.keys().filter(|qn| !new_names.contains(*qn)).count(),
+        }
+    }
+}
+
+fn all_functions(index: &Index) -> impl Iterator<Item = &Function> {
+    index.files.values().flat_map(|e| e.functions.iter())
+}
+
+pub fn run(opts: &UpdateOptions) -> ExitCode {
+    let aria_dir = Path::new(".aria");
+
+    let mut old_index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    interrupt::install();
+
+    let config = load_config(aria_dir);
+    let mut registry = match ParserRegistry::configured(&config.languages, &config.index) {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(warning) = legacy_hash_warning(migrate_legacy_hashes(&mut old_index, Path::new("."), &mut registry)) {
+        progress::warn("legacy_hashes", &warning);
+    }
+    // An index built with `aria index --include-tests` keeps its test files
+    let include_tests = config.features.include_tests || old_index.files.values().any(|e| e.is_test);
+    let (mut new_index, sources) =
+        build_index(Path::new("."), opts.summarize, Some(&old_index), &config.index, include_tests, &mut registry);
+
+    if interrupt::is_interrupted() {
+        eprintln!("interrupted — existing index left unchanged");
+        return ExitCode::from(interrupt::EXIT_INTERRUPTED);
+    }
+    restore_user_externals(&mut new_index, aria_dir);
+
+    let changes = ChangeSet::between(&old_index, &new_index);
+    preserve_summaries(&mut new_index, Some(&old_index));
+
+    let mut regenerated = 0;
+    let mut cascaded = 0;
+    if opts.summarize {
+        let mut scope: HashSet<String> = changes.functions.iter().cloned().collect();
+        let callers = if opts.cascade { cascade_to_callers(&new_index, &mut scope) } else { HashSet::new() };
+        cascaded = callers.len();
+        regenerated = run_summarization(&config, &mut new_index, &sources, Some(&scope), &callers);
+    }
+
+    let package_summaries = opts.summarize && config.features.package_summaries;
+    let packages = package::rollup_packages(&config, &mut new_index, Some(&old_index), package_summaries);
+
+    new_index.commit = get_git_head().unwrap_or_default();
+    keep_unchanged_stamp(&mut new_index, &old_index);
+
+    let totals = match write_index(index::index_path(), &new_index) {
+        Ok(totals) => totals,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut report = format!(
+        "{} {}, {} {} changed",
+        changes.files,
+        if changes.files == 1 { "file" } else { "files" },
+        changes.functions.len(),
+        if changes.functions.len() == 1 { "function" } else { "functions" },
+    );
+    if changes.removed > 0 {
+        report.push_str(&format!(" ({} removed)", changes.removed));
+    }
+    if opts.summarize {
+        report.push_str(&format!(", {} summaries regenerated", regenerated));
+    }
+    if opts.cascade {
+        report.push_str(&format!(", {} caller summaries cascaded", cascaded));
+    }
+    println!("{report}");
+    progress::emit(&progress::Event::Result {
+        command: "update",
+        details: serde_json::json!({
+            "files": totals.files,
+            "functions": totals.functions,
+            "calls": totals.calls,
+            "resolved_pct": totals.resolved_pct,
+            "changed_files": changes.files,
+            "changed_functions": changes.functions.len(),
+            "removed_functions": changes.removed,
+            "regenerated_summaries": regenerated,
+            "cascaded_summaries": cascaded,
+            "package_summaries": packages,
+            "interrupted": interrupt::is_interrupted(),
+        }),
+    });
+
+    if interrupt::is_interrupted() {
+        eprintln!("interrupted — progress saved");
+        return ExitCode::from(interrupt::EXIT_INTERRUPTED);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Add direct callers of the functions in `scope` to it, since their callee context changed,
+/// and return them. Their summaries stay in place until regenerated ones replace them.
+fn cascade_to_callers(index: &Index, scope: &mut HashSet<String>) -> HashSet<String> {
+    // Locked summaries are never regenerated, so their functions are not cascaded to
+    let locked: HashSet<&str> =
+        all_functions(index).filter(|f| f.summary_locked).map(|f| f.qualified_name.as_str()).collect();
+    let callers: HashSet<String> = all_functions(index)
+        .filter(|f| scope.contains(&f.qualified_name))
+        .flat_map(|f| f.called_by.iter().cloned())
+        .filter(|caller| !scope.contains(caller) && !locked.contains(caller.as_str()))
+        .collect();
+
+    scope.extend(callers.iter().cloned());
+    callers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileEntry;
+
+    fn func(name: &str, ast_hash: &str, called_by: &[&str]) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("pkg.{}", name),
+            ast_hash: ast_hash.to_string(),
+            line_start: 1,
+            line_end: 2,
+            summary: Some(format!("does {}", name)),
+            summary_hash: Some(ast_hash.to_string()),
+            called_by: called_by.iter().map(|c| format!("pkg.{}", c)).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn index_of(files: Vec<(&str, &str, Vec<Function>)>) -> Index {
+        let mut index = Index::new();
+        for (path, hash, functions) in files {
+            index.files.insert(
+                path.to_string(),
+                FileEntry {
+                    ast_hash: hash.to_string(),
+                    functions,
+                    ..Default::default()
+                },
+            );
+        }
+        index
+    }
+
+    #[test]
+    fn test_change_set_between() {
+        let old = index_of(vec![
+            ("./a.go", "fa", vec![func("keep", "1", &[]), func("edit", "2", &[]), func("drop", "3", &[])]),
+            ("./gone.go", "fg", vec![func("gone", "4", &[])]),
+        ]);
+        let new = index_of(vec![
+            ("./a.go", "fa2", vec![func("keep", "1", &[]), func("edit", "2b", &[]), func("added", "5", &[])]),
+        ]);
+
+        let changes = ChangeSet::between(&old, &new);
+        assert_eq!(changes.files, 2);
+        assert_eq!(changes.functions, vec!["pkg.added", "pkg.edit"]);
+        assert_eq!(changes.removed, 2);
+    }
+
+    #[test]
+    fn test_cascade_to_callers() {
+        let index = index_of(vec![(
+            "./a.go",
+            "fa",
+            vec![func("leaf", "1", &["mid"]), func("mid", "2", &["top"]), func("top", "3", &[])],
+        )]);
+        let mut scope: HashSet<String> = HashSet::from(["pkg.leaf".to_string()]);
+
+        assert_eq!(cascade_to_callers(&index, &mut scope), HashSet::from(["pkg.mid".to_string()]));
+        assert!(scope.contains("pkg.mid"));
+        assert!(!scope.contains("pkg.top"));
+
+        // The caller keeps its summary until a regenerated one replaces it
+        assert_eq!(index.files["./a.go"].functions[1].summary.as_deref(), Some("does mid"));
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::process::ExitCode;
+
+use crate::commands::list::language_of;
+use crate::commands::types::{self, find_types};
+use crate::format::Formatter;
+use crate::freshness;
+use crate::index::{self, Function, Index, TypeDef};
+
+/// Which callers in test files a usages report lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestCallers {
+    Include,
+    Exclude,
+    Only,
+}
+
+impl TestCallers {
+    /// Parse a `--tests` value
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "include" => Ok(TestCallers::Include),
+            "exclude" => Ok(TestCallers::Exclude),
+            "only" => Ok(TestCallers::Only),
+            other => Err(format!("unknown --tests value '{other}' (expected include, exclude, or only)")),
+        }
+    }
+
+    /// Whether a caller in the file at index key `file` is listed
+    fn allows(self, file: Option<&str>) -> bool {
+        let is_test = file.is_some_and(index::is_test_file);
+        match self {
+            TestCallers::Include => true,
+            TestCallers::Exclude => !is_test,
+            TestCallers::Only => is_test,
+        }
+    }
+}
+
+/// What a usages query resolved to
+enum Target<'a> {
+    Functions(Vec<(&'a str, &'a Function)>),
+    Types(Vec<(&'a str, &'a TypeDef)>),
+}
+
+pub fn run(name: &str, kind: Option<&str>, full: bool, tests: TestCallers) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let matches = match select_target(&index, name, kind) {
+        Ok(Target::Functions(matches)) => matches,
+        Ok(Target::Types(types)) => {
+            freshness::check(&index, types.iter().map(|(file, _)| *file));
+            for (i, (file, t)) in types.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                for line in types::render(&index, file, t) {
+                    println!("{line}");
+                }
+            }
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    freshness::check(&index, matches.iter().map(|(file, _)| *file));
+
+    let func_map = index::build_function_map(&index);
+    let dim = std::io::stdout().is_terminal();
+    let fmt = Formatter::new(full);
+
+    for (i, (file_path, func)) in matches.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        for line in render(&func_map, file_path, func, dim, tests, &fmt) {
+            println!("{line}");
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Resolve a query to functions or types. Without `kind`, an exact type name is a type
+/// query unless a function has the same name, which is reported as ambiguous.
+fn select_target<'a>(index: &'a Index, name: &str, kind: Option<&str>) -> Result<Target<'a>, String> {
+    match kind {
+        Some("function") => return index::lookup_functions(index, name).map(Target::Functions),
+        Some("type") => {
+            let types = find_types(index, name);
+            if types.is_empty() {
+                return Err(format!("no type found matching '{name}'"));
+            }
+            return Ok(Target::Types(types));
+        }
+        Some(other) => return Err(format!("unknown kind '{other}' (expected: function, type)")),
+        None => {}
+    }
+
+    let exact_function = index
+        .files
+        .values()
+        .flat_map(|e| e.functions.iter())
+        .any(|f| f.qualified_name == name || f.name == name);
+    let exact_types: Vec<_> =
+        find_types(index, name).into_iter().filter(|(_, t)| t.qualified_name == name || t.name == name).collect();
+
+    if !exact_types.is_empty() {
+        if exact_function {
+            return Err(format!("'{name}' names both a function and a type; pass --kind function or --kind type"));
+        }
+        return Ok(Target::Types(exact_types));
+    }
+
+    match index::lookup_functions(index, name) {
+        Ok(matches) => Ok(Target::Functions(matches)),
+        Err(e) => {
+            let types = find_types(index, name);
+            if types.is_empty() { Err(e) } else { Ok(Target::Types(types)) }
+        }
+    }
+}
+
+/// Lines listing the callers of `func` that `tests` allows, ambiguous ones last (dimmed when `dim`)
+pub fn render(
+    func_map: &HashMap<&str, (&str, &Function)>,
+    file_path: &str,
+    func: &Function,
+    dim: bool,
+    tests: TestCallers,
+    fmt: &Formatter,
+) -> Vec<String> {
+    let mut out = vec![format!("{} ({}:{}-{})", fmt.name(&func.qualified_name), file_path, func.line_start, func.line_end)];
+
+    let allowed = |caller: &&String| tests.allows(func_map.get(caller.as_str()).map(|(file, _)| *file));
+    let called_by: Vec<&String> = func.called_by.iter().filter(allowed).collect();
+    let possibly_called_by: Vec<&String> = func.possibly_called_by.iter().filter(allowed).collect();
+    if called_by.is_empty() && possibly_called_by.is_empty() {
+        let note = match (func.exported_c, language_of(file_path)) {
+            (true, "rust") => "  (no Rust callers found; exported to C)",
+            (true, _) => "  (no Go callers found; exported to C)",
+            (false, _) => "  (no callers found)",
+        };
+        out.push(note.to_string());
+        return out;
+    }
+
+    for caller in called_by {
+        let line = format!("  {}", describe_caller(func_map, caller, &func.qualified_name, fmt));
+        if only_referenced(func_map, caller, &func.qualified_name) {
+            out.push(format!("{line} (referenced)"));
+        } else {
+            out.push(line);
+        }
+    }
+
+    // Ambiguous edges: the call matched this function and others, or went through an interface
+    for caller in possibly_called_by {
+        let label = if via_interface(func_map, caller, &func.qualified_name) { "via interface" } else { "ambiguous" };
+        let line = format!("  {} ({})", describe_caller(func_map, caller, &func.qualified_name, fmt), label);
+        if dim {
+            out.push(format!("\x1b[2m{line}\x1b[0m"));
+        } else {
+            out.push(line);
+        }
+    }
+
+    out
+}
+
+/// "pkg.main (./main.go:12, 15)" with the lines where `caller` calls `target`
+fn describe_caller(
+    func_map: &HashMap<&str, (&str, &Function)>,
+    caller: &str,
+    target: &str,
+    fmt: &Formatter,
+) -> String {
+    let name = fmt.name(caller);
+    let Some((file, func)) = func_map.get(caller) else {
+        return name.into_owned();
+    };
+
+    let lines = call_lines(func, target);
+    if lines.is_empty() {
+        return format!("{} ({}:{})", name, file, func.line_start);
+    }
+
+    let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    format!("{} ({}:{})", name, file, lines.join(", "))
+}
+
+/// Whether `caller` reaches `target` only through Go interface method calls
+fn via_interface(func_map: &HashMap<&str, (&str, &Function)>, caller: &str, target: &str) -> bool {
+    let Some((_, func)) = func_map.get(caller) else {
+        return false;
+    };
+    let mut calls = func.calls.iter().filter(|c| c.candidates.iter().any(|t| t == target)).peekable();
+    calls.peek().is_some() && calls.all(|c| c.target.starts_with("[iface:"))
+}
+
+/// Whether `caller` uses `target` as a value (e.g. passes it as a callback) but never calls it
+fn only_referenced(func_map: &HashMap<&str, (&str, &Function)>, caller: &str, target: &str) -> bool {
+    func_map.get(caller).is_some_and(|(_, func)| {
+        func.references.iter().any(|r| r.target == target) && !func.calls.iter().any(|c| c.target == target)
+    })
+}
+
+/// Lines in `func` that call or reference `target`, confirmed or as one of several candidates
+fn call_lines(func: &Function, target: &str) -> Vec<u32> {
+    let mut lines: Vec<u32> = func
+        .calls
+        .iter()
+        .chain(&func.references)
+        .filter(|c| c.target == target || c.candidates.iter().any(|t| t == target))
+        .map(|c| c.line)
+        .collect();
+    lines.sort();
+    lines.dedup();
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{CallSite, FileEntry, TypeKind};
+
+    fn call(target: &str, candidates: &[&str], line: u32) -> CallSite {
+        CallSite {
+            target: target.to_string(),
+            raw: "helper".to_string(),
+            callee_name: "helper".to_string(),
+            candidates: candidates.iter().map(|c| c.to_string()).collect(),
+            line,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_call_lines_include_ambiguous_candidates() {
+        let caller = Function {
+            name: "main".to_string(),
+            qualified_name: "app.main".to_string(),
+            line_start: 1,
+            line_end: 20,
+            calls: vec![
+                call("a.helper", &[], 3),
+                call("[ambiguous:helper]", &["a.helper", "b.helper"], 7),
+                call("a.helper", &[], 3),
+                call("c.other", &[], 9),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(call_lines(&caller, "a.helper"), vec![3, 7]);
+        assert_eq!(call_lines(&caller, "b.helper"), vec![7]);
+        assert!(call_lines(&caller, "d.none").is_empty());
+    }
+
+    #[test]
+    fn test_test_callers_filter() {
+        assert!(TestCallers::Include.allows(Some("./app_test.go")));
+        assert!(!TestCallers::Exclude.allows(Some("./app_test.go")));
+        assert!(TestCallers::Exclude.allows(Some("./app.go")));
+        assert!(TestCallers::Only.allows(Some("./app_test.go")));
+        assert!(!TestCallers::Only.allows(None));
+        assert!(TestCallers::parse("some").unwrap_err().contains("expected include, exclude, or only"));
+    }
+
+    #[test]
+    fn test_render_labels_interface_and_referencing_callers() {
+        let function = |name: &str, calls: Vec<CallSite>| Function {
+            name: name.to_string(),
+            qualified_name: format!("app.{name}"),
+            line_start: 1,
+            line_end: 9,
+            calls,
+            ..Default::default()
+        };
+        let run = function("Run", vec![call("[iface:Store.Save]", &["app.Save"], 4)]);
+        let main = function("main", vec![call("[ambiguous:Save]", &["app.Save", "b.Save"], 7)]);
+        let mut serve = function("serve", Vec::new());
+        serve.references = vec![call("app.Save", &[], 12)];
+        let mut save = function("Save", Vec::new());
+        save.called_by = vec!["app.serve".to_string()];
+        save.possibly_called_by = vec!["app.Run".to_string(), "app.main".to_string()];
+
+        let func_map = HashMap::from([
+            ("app.Run", ("./app.go", &run)),
+            ("app.main", ("./app.go", &main)),
+            ("app.serve", ("./app.go", &serve)),
+        ]);
+        let lines = render(&func_map, "./app.go", &save, false, TestCallers::Include, &Formatter::full());
+        assert_eq!(lines[1], "  app.serve (./app.go:12) (referenced)");
+        assert_eq!(lines[2], "  app.Run (./app.go:4) (via interface)");
+        assert_eq!(lines[3], "  app.main (./app.go:7) (ambiguous)");
+    }
+
+    #[test]
+    fn test_select_target_functions_types_and_ambiguity() {
+        let type_def = |name: &str| TypeDef {
+            name: name.to_string(),
+            qualified_name: format!("app.{name}"),
+            kind: TypeKind::Struct,
+            line_start: 1,
+            line_end: 3,
+            summary: None,
+            methods: Vec::new(),
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: None,
+        };
+        let function = |name: &str| Function {
+            name: name.to_string(),
+            qualified_name: format!("app.{name}"),
+            line_start: 5,
+            line_end: 9,
+            ..Default::default()
+        };
+
+        let mut index = Index::new();
+        index.files.insert(
+            "./app/app.go".to_string(),
+            FileEntry {
+                functions: vec![function("load"), function("Entry")],
+                types: vec![type_def("Config"), type_def("Entry")],
+                ..Default::default()
+            },
+        );
+
+        assert!(matches!(select_target(&index, "Config", None), Ok(Target::Types(t)) if t.len() == 1));
+        assert!(matches!(select_target(&index, "load", None), Ok(Target::Functions(f)) if f.len() == 1));
+        assert!(matches!(select_target(&index, "Conf", None), Ok(Target::Types(_))));
+        assert!(matches!(select_target(&index, "app/app.go:6", None), Ok(Target::Functions(_))));
+
+        let Err(e) = select_target(&index, "Entry", None) else { panic!("expected ambiguity") };
+        assert!(e.contains("--kind"));
+        assert!(matches!(select_target(&index, "Entry", Some("function")), Ok(Target::Functions(_))));
+        assert!(matches!(select_target(&index, "Entry", Some("type")), Ok(Target::Types(_))));
+        assert!(select_target(&index, "Entry", Some("struct")).is_err());
+        assert!(select_target(&index, "missing", None).is_err());
+    }
+}
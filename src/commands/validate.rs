@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::commands::update::{self, UpdateOptions};
+use crate::index::{self, Function, Index, IndexedSource};
+
+/// Problems found by comparing indexed files with their current content
+#[derive(Debug, Default)]
+pub struct FileReport {
+    /// Files modified or removed since they were indexed
+    pub stale: Vec<String>,
+    /// Inverted, overlapping, or out-of-file function ranges
+    pub ranges: Vec<String>,
+}
+
+pub fn run(fix_stale: bool) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let root = match index::repo_root() {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut problems = check_paths(&index, &root);
+    problems.extend(check_shards(&index, index::index_path()));
+    let files = check_files(&index, &root);
+
+    if fix_stale && !files.stale.is_empty() {
+        eprintln!("reindexing {} stale {}", files.stale.len(), if files.stale.len() == 1 { "file" } else { "files" });
+        let code = update::run(&UpdateOptions { summarize: false, cascade: false });
+        if code != ExitCode::SUCCESS {
+            return code;
+        }
+        return run(false);
+    }
+
+    // Only one of each duplicate gets callers and a summary, but the index is still usable
+    for warning in check_duplicates(&index) {
+        eprintln!("warning: {warning}");
+    }
+    // Files with syntax errors were indexed as far as the parser recovered
+    for warning in check_parse_errors(&index) {
+        eprintln!("warning: {warning}");
+    }
+
+    let stale = files.stale.len();
+    problems.extend(files.stale);
+    problems.extend(files.ranges);
+    if problems.is_empty() {
+        println!("Index OK ({} files)", index.files.len());
+        return ExitCode::SUCCESS;
+    }
+
+    for problem in &problems {
+        eprintln!("error: {problem}");
+    }
+    eprintln!("{} {} found", problems.len(), if problems.len() == 1 { "problem" } else { "problems" });
+    if stale > 0 {
+        eprintln!("run `aria validate --fix-stale` to reindex the {stale} stale {}", if stale == 1 { "file" } else { "files" });
+    }
+    ExitCode::FAILURE
+}
+
+/// Files in the directory of a sharded index that its manifest does not list, so their entries
+/// are never loaded
+fn check_shards(index: &Index, index_path: &Path) -> Vec<String> {
+    let dir = index::shard_dir(index_path);
+    if index.shards.is_empty() || !index::shard_manifest(index_path).exists() {
+        return Vec::new();
+    }
+    // Shard paths relative to `dir`, as the manifest lists them
+    let names_in = |subdir: &str| -> Vec<String> {
+        let Ok(entries) = fs::read_dir(dir.join(subdir)) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+            .filter_map(|e| e.file_name().into_string().ok())
+            .map(|name| if subdir.is_empty() { name } else { format!("{subdir}/{name}") })
+            .collect()
+    };
+    let mut unlisted: Vec<String> = names_in("")
+        .into_iter()
+        .chain(names_in(index::SHARDS_SUBDIR))
+        .filter(|name| name != index::SHARD_MANIFEST && !name.ends_with(".tmp"))
+        .filter(|name| !index.shards.contains(name))
+        .map(|name| format!("{}: shard is not listed in {} (run `aria index`)", dir.join(&name).display(), index::SHARD_MANIFEST))
+        .collect();
+    unlisted.sort();
+    unlisted
+}
+
+/// File keys that are absolute or resolve outside the repository root
+fn check_paths(index: &Index, root: &Path) -> Vec<String> {
+    index
+        .files
+        .keys()
+        .filter_map(|key| index::resolve_source_path(root, key).err())
+        .collect()
+}
+
+/// Qualified function names defined in more than one file, e.g. by Go files for different
+/// platforms that the build constraint detection missed. Overloads within a file are fine.
+fn check_duplicates(index: &Index) -> Vec<String> {
+    let mut files_by_name: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (key, entry) in &index.files {
+        for func in &entry.functions {
+            files_by_name.entry(&func.qualified_name).or_default().push(key);
+        }
+    }
+    files_by_name
+        .into_iter()
+        .filter_map(|(name, mut files)| {
+            files.sort();
+            files.dedup();
+            (files.len() > 1).then(|| format!("duplicate qualified name {name} in {}", files.join(", ")))
+        })
+        .collect()
+}
+
+/// Files indexed despite syntax errors, with the count and the line of the first
+fn check_parse_errors(index: &Index) -> Vec<String> {
+    index
+        .files
+        .iter()
+        .filter(|(_, entry)| entry.parse_errors > 0)
+        .map(|(key, entry)| {
+            let errors = if entry.parse_errors == 1 { "error" } else { "errors" };
+            let first = entry.first_error_line.map(|line| format!(", first at line {line}")).unwrap_or_default();
+            format!("{key}: {} parse {errors}{first}; functions after it may be missing", entry.parse_errors)
+        })
+        .collect()
+}
+
+/// Compare each indexed file with its content on disk: stale files, and function ranges
+/// that are inverted, partially overlap another, or run past the end of the file
+pub fn check_files(index: &Index, root: &Path) -> FileReport {
+    let mut report = FileReport::default();
+    for (key, entry) in &index.files {
+        // Keys that cannot be resolved are reported by check_paths
+        if index::resolve_source_path(root, key).is_err() {
+            continue;
+        }
+        let source = match index::read_indexed_source(root, key, entry) {
+            Ok(source) => source,
+            Err(e) => {
+                report.stale.push(e);
+                continue;
+            }
+        };
+        if let Some(warning) = source.warning(key) {
+            report.stale.push(warning);
+        }
+        let line_count = match &source {
+            IndexedSource::Fresh(content) | IndexedSource::Modified(content) => Some(content.lines().count()),
+            IndexedSource::Missing => None,
+        };
+        report.ranges.extend(check_ranges(key, &entry.functions, line_count));
+    }
+    report
+}
+
+/// Range problems among one file's functions. Nested ranges are fine; ranges that cross are not.
+fn check_ranges(key: &str, functions: &[Function], line_count: Option<usize>) -> Vec<String> {
+    let describe = |f: &Function| format!("{} ({}-{})", f.qualified_name, f.line_start, f.line_end);
+    let mut problems = Vec::new();
+
+    let mut sorted: Vec<&Function> = Vec::new();
+    for func in functions {
+        if func.line_start > func.line_end {
+            problems.push(format!("{key}: {} has an inverted range", describe(func)));
+            continue;
+        }
+        if let Some(count) = line_count
+            && func.line_end as usize > count
+        {
+            problems.push(format!("{key}: {} extends past the end of the file ({count} lines)", describe(func)));
+        }
+        sorted.push(func);
+    }
+
+    // Outermost first, so each range only needs checking against the ranges still open around it
+    sorted.sort_by(|a, b| a.line_start.cmp(&b.line_start).then_with(|| b.line_end.cmp(&a.line_end)));
+    let mut open: Vec<&Function> = Vec::new();
+    for func in sorted {
+        while open.last().is_some_and(|o| o.line_end < func.line_start) {
+            open.pop();
+        }
+        if let Some(outer) = open.last()
+            && func.line_end > outer.line_end
+        {
+            problems.push(format!("{key}: {} overlaps {}", describe(outer), describe(func)));
+            continue;
+        }
+        open.push(func);
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileEntry;
+
+    fn func(name: &str, line_start: u32, line_end: u32) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("pkg.{}", name),
+            line_start,
+            line_end,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_paths_flags_escaping_keys() {
+        let mut index = Index::new();
+        for key in ["./src/main.rs", "../../etc/passwd", "/etc/passwd"] {
+            index.files.insert(
+                key.to_string(),
+                FileEntry {
+                    ..Default::default()
+                },
+            );
+        }
+
+        let problems = check_paths(&index, Path::new("/nonexistent/repo"));
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().any(|p| p.contains("../../etc/passwd")));
+        assert!(problems.iter().any(|p| p.contains("'/etc/passwd' is an absolute path")));
+    }
+
+    #[test]
+    fn test_check_duplicates_across_files() {
+        let mut index = Index::new();
+        for (key, functions) in [
+            ("./pkg/a.go", vec![func("open", 1, 2), func("open", 3, 4)]),
+            ("./pkg/b_other.go", vec![func("open", 1, 2), func("close", 3, 4)]),
+            ("./pkg/c.go", vec![func("open", 1, 2), func("read", 3, 4)]),
+        ] {
+            index.files.insert(
+                key.to_string(),
+                FileEntry {
+                    functions,
+                    ..Default::default()
+                },
+            );
+        }
+
+        assert_eq!(check_duplicates(&index), vec!["duplicate qualified name pkg.open in ./pkg/a.go, ./pkg/b_other.go, ./pkg/c.go"]);
+    }
+
+    #[test]
+    fn test_check_ranges_flags_crossing_inverted_and_out_of_file() {
+        let functions = vec![
+            func("outer", 1, 20),
+            func("nested", 5, 10),
+            func("crossing", 15, 25),
+            func("inverted", 30, 28),
+            func("last", 40, 45),
+        ];
+        let problems = check_ranges("./pkg/a.go", &functions, Some(42));
+        assert_eq!(
+            problems,
+            vec![
+                "./pkg/a.go: pkg.inverted (30-28) has an inverted range",
+                "./pkg/a.go: pkg.last (40-45) extends past the end of the file (42 lines)",
+                "./pkg/a.go: pkg.outer (1-20) overlaps pkg.crossing (15-25)",
+            ]
+        );
+
+        assert!(check_ranges("./pkg/a.go", &functions[..2], None).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use std::process::ExitCode;
+
+use crate::index::{self, Function, Index, Variable};
+
+pub fn run(name: &str) -> ExitCode {
+    let index = match index::load_index() {
+        Ok(idx) => idx,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let matches = find_globals(&index, name);
+    if matches.is_empty() {
+        eprintln!("error: no package-level variable or static found matching '{name}'");
+        return ExitCode::FAILURE;
+    }
+
+    for (i, (file, var)) in matches.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{} ({}:{})", var.qualified_name, file, var.line_start);
+        let writers = writers_of(&index, var);
+        if writers.is_empty() {
+            println!("  (no writers found)");
+        }
+        for (file, func, lines) in writers {
+            let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            println!("  {} ({}:{})", func.qualified_name, file, lines.join(", "));
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Variables whose qualified or simple name is `name`, falling back to a contains match
+pub fn find_globals<'a>(index: &'a Index, name: &str) -> Vec<(&'a str, &'a Variable)> {
+    let all = || index.files.iter().flat_map(|(file, e)| e.variables.iter().map(move |v| (file.as_str(), v)));
+
+    let mut matches: Vec<_> = all().filter(|(_, v)| v.qualified_name == name || v.name == name).collect();
+    if matches.is_empty() {
+        matches = all().filter(|(_, v)| v.qualified_name.contains(name)).collect();
+    }
+    matches.sort_by(|a, b| a.1.qualified_name.cmp(&b.1.qualified_name).then_with(|| a.0.cmp(b.0)));
+    matches
+}
+
+/// Functions whose resolved `writes_globals` include `var`, with the lines of their writes to it,
+/// sorted by qualified name
+fn writers_of<'a>(index: &'a Index, var: &Variable) -> Vec<(&'a str, &'a Function, Vec<u32>)> {
+    let mut writers: Vec<_> = index
+        .files
+        .iter()
+        .flat_map(|(file, e)| e.functions.iter().map(move |f| (file.as_str(), f)))
+        .filter(|(_, f)| f.writes_globals.contains(&var.qualified_name))
+        .map(|(file, func)| {
+            let lines = func
+                .global_refs
+                .iter()
+                .filter(|r| r.write && r.name.split(['.', ':']).any(|part| part == var.name))
+                .map(|r| r.line)
+                .collect();
+            (file, func, lines)
+        })
+        .collect();
+    writers.sort_by(|a, b| a.1.qualified_name.cmp(&b.1.qualified_name).then_with(|| a.0.cmp(b.0)));
+    writers
+}
@@ -1,6 +1,12 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::index::IndexFormat;
+use crate::parser;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub debug: bool,
@@ -8,16 +14,14 @@ pub struct Config {
     pub llm: LlmConfig,
     #[serde(default)]
     pub features: FeaturesConfig,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            debug: false,
-            llm: LlmConfig::default(),
-            features: FeaturesConfig::default(),
-        }
-    }
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub index: IndexConfig,
+    /// Extensions per language, replacing the built-in ones (e.g. go = [".go", ".gno"]); an empty
+    /// list disables the language
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub languages: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +36,18 @@ pub struct LlmConfig {
     pub batch_size: usize,
     #[serde(default = "default_parallel")]
     pub parallel: usize,
+    /// Most callees without a summary listed by name in a prompt; 0 leaves them out
+    #[serde(default = "default_max_raw_callees")]
+    pub max_raw_callees: usize,
+    /// Unix socket of the LLM gateway, for provider = "gateway"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<PathBuf>,
+    /// Seconds to wait for a gateway response before failing the request
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Most single-function prompts per run for members of batches that failed twice
+    #[serde(default = "default_max_fallback_requests")]
+    pub max_fallback_requests: usize,
 }
 
 impl Default for LlmConfig {
@@ -42,6 +58,10 @@ impl Default for LlmConfig {
             model: default_model(),
             batch_size: default_batch_size(),
             parallel: default_parallel(),
+            max_raw_callees: default_max_raw_callees(),
+            socket_path: None,
+            timeout_secs: default_timeout_secs(),
+            max_fallback_requests: default_max_fallback_requests(),
         }
     }
 }
@@ -62,16 +82,106 @@ fn default_parallel() -> usize {
     4
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_max_raw_callees() -> usize {
+    10
+}
+
+fn default_timeout_secs() -> u64 {
+    300
+}
+
+fn default_max_fallback_requests() -> usize {
+    50
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FeaturesConfig {
     #[serde(default)]
     pub summaries: bool,
+    /// Roll function summaries up into one summary per package (needs `summaries`)
+    #[serde(default)]
+    pub package_summaries: bool,
+    /// Index Go test files (`_test.go`) too, so tests show up as callers
+    #[serde(default)]
+    pub include_tests: bool,
+}
+
+/// Truncation limits for terminal output; unset limits follow the terminal width
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// Longest qualified name shown before middle-truncating it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_name: Option<usize>,
+    /// Longest summary shown before clipping it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_summary: Option<usize>,
+    /// Warn before query output when the index is behind HEAD or the files it shows changed
+    #[serde(default = "default_freshness_check")]
+    pub freshness_check: bool,
 }
 
-impl Default for FeaturesConfig {
+impl Default for OutputConfig {
     fn default() -> Self {
-        Self {
-            summaries: false,
-        }
+        Self { max_name: None, max_summary: None, freshness_check: default_freshness_check() }
+    }
+}
+
+fn default_freshness_check() -> bool {
+    true
+}
+
+/// What `aria index` walks and how deeply it indexes it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexConfig {
+    /// Directories parsed for symbols only, so calls into them resolve (e.g. "vendor", "third_party").
+    /// A bare name matches a directory of that name anywhere; a path with "/" matches from the root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reference_dirs: Vec<String>,
+    /// Languages to index (e.g. ["go"]); empty indexes every language this build supports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Rust closures at least this many lines long are indexed as functions of their own
+    /// (`module::run::{closure@L42}`); unset leaves closures part of the enclosing function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closure_min_lines: Option<usize>,
+    /// Files larger than this many bytes are skipped when indexing [default: 4 MiB]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    /// How the index file is written: "json" or "bin" (MessagePack); either is read back
+    #[serde(default, skip_serializing_if = "is_json")]
+    pub format: IndexFormat,
+    /// Write the index as one file per top-level directory under .aria/index/, plus a manifest,
+    /// instead of a single index file
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sharded: bool,
+}
+
+fn is_json(format: &IndexFormat) -> bool {
+    *format == IndexFormat::Json
+}
+
+impl IndexConfig {
+    /// Largest source file, in bytes, that is read and parsed
+    pub fn max_file_size(&self) -> u64 {
+        self.max_file_size.unwrap_or(crate::index::DEFAULT_MAX_FILE_SIZE)
+    }
+
+    /// Whether files of `language` should be indexed
+    pub fn includes_language(&self, language: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|l| l == language)
+    }
+
+    /// One warning per `languages` entry that is unknown or not compiled into this build
+    pub fn language_warnings(&self) -> Vec<String> {
+        self.languages
+            .iter()
+            .filter_map(|name| match parser::grammar(name) {
+                None => Some(format!("index.languages lists unknown language '{name}'")),
+                Some(g) if !g.compiled => {
+                    Some(format!("index.languages lists '{name}' but this build lacks {}", g.feature))
+                }
+                Some(_) => None,
+            })
+            .collect()
     }
 }
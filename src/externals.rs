@@ -1,8 +1,16 @@
 //! External symbol detection and categorization.
 //!
 //! Categorizes unresolved calls into: syscalls, libc, macros, or unknown external.
+//! Descriptions users give with `aria annotate-external` live in `.aria/externals.toml`
+//! and are applied again whenever the index is rebuilt.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::Index;
 
 /// Categories for external (unresolved) symbols
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,6 +32,33 @@ impl ExternalKind {
     }
 }
 
+/// Language of the calling file, which decides the tables and heuristics that apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    C,
+    Go,
+    Rust,
+    Python,
+    TypeScript,
+    Java,
+    Cpp,
+}
+
+impl Language {
+    /// Language of an indexed file by extension; anything else is treated as C
+    pub fn of_file(path: &str) -> Self {
+        match path.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("go") => Language::Go,
+            Some("rs") => Language::Rust,
+            Some("py") => Language::Python,
+            Some("ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs") => Language::TypeScript,
+            Some("java") => Language::Java,
+            Some("cpp" | "cc" | "cxx" | "c++" | "hpp" | "hh" | "hxx") => Language::Cpp,
+            _ => Language::C,
+        }
+    }
+}
+
 /// Database of known external symbols
 pub struct ExternalDb {
     syscalls: HashMap<&'static str, &'static str>,
@@ -38,14 +73,19 @@ impl ExternalDb {
         }
     }
 
-    /// Categorize an unresolved symbol
-    pub fn categorize(&self, name: &str) -> (ExternalKind, Option<&'static str>) {
+    /// Categorize an unresolved symbol called from a file in `lang`. The syscall and libc tables
+    /// and the naming-based macro heuristics only apply to C and C++; Rust macros are recognized by the
+    /// `!` sigil, and the other languages have no macros.
+    pub fn categorize(&self, name: &str, lang: Language) -> (ExternalKind, Option<&'static str>) {
+        match lang {
+            Language::C | Language::Cpp => {}
+            Language::Go | Language::Python | Language::TypeScript | Language::Java => return (ExternalKind::External, None),
+            Language::Rust if name.ends_with('!') => return (ExternalKind::Macro, None),
+            Language::Rust => return (ExternalKind::External, None),
+        }
+
         // Check sys_* prefix for syscalls
-        let syscall_name = if name.starts_with("sys_") {
-            &name[4..]
-        } else {
-            name
-        };
+        let syscall_name = name.strip_prefix("sys_").unwrap_or(name);
 
         if let Some(summary) = self.syscalls.get(syscall_name) {
             return (ExternalKind::Syscall, Some(summary));
@@ -65,6 +105,18 @@ impl ExternalDb {
         (ExternalKind::External, None)
     }
 
+    /// Built-in description of a symbol already categorized as `kind` (e.g. "syscall", "libc")
+    pub fn describe(&self, kind: &str, name: &str) -> Option<&'static str> {
+        let summary = match kind {
+            "syscall" => {
+                let syscall_name = name.strip_prefix("sys_").unwrap_or(name);
+                self.syscalls.get(syscall_name).or_else(|| self.syscalls.get(name))
+            }
+            "libc" => self.libc.get(name),
+            _ => None,
+        };
+        summary.copied()
+    }
 }
 
 impl Default for ExternalDb {
@@ -73,6 +125,75 @@ impl Default for ExternalDb {
     }
 }
 
+/// File in the .aria directory holding user descriptions of externals, keyed by name as called
+pub const USER_EXTERNALS_FILE: &str = "externals.toml";
+
+/// Kinds an external can be given by hand
+pub const KINDS: [&str; 4] = ["syscall", "libc", "macro", "external"];
+
+/// A user's description of an external
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserExternal {
+    pub kind: String,
+    pub summary: String,
+}
+
+/// User descriptions from `aria_dir`; none when the file does not exist
+pub fn load_user_externals(aria_dir: &Path) -> Result<BTreeMap<String, UserExternal>, String> {
+    let path = aria_dir.join(USER_EXTERNALS_FILE);
+    match fs::read_to_string(&path) {
+        Ok(content) => toml::from_str(&content).map_err(|e| format!("failed to parse {}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(format!("failed to read {}: {e}", path.display())),
+    }
+}
+
+pub fn save_user_externals(aria_dir: &Path, externals: &BTreeMap<String, UserExternal>) -> Result<(), String> {
+    let path = aria_dir.join(USER_EXTERNALS_FILE);
+    let body = toml::to_string(externals).map_err(|e| format!("failed to serialize externals: {e}"))?;
+    let content = format!("# Written by `aria annotate-external`; applied again on every reindex\n\n{body}");
+    fs::write(&path, content).map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+/// Give the externals in `user` their description and kind, both in the externals
+/// table and in the "[kind:name]" targets of the calls to them
+pub fn apply_user_externals(index: &mut Index, user: &BTreeMap<String, UserExternal>) {
+    if user.is_empty() {
+        return;
+    }
+    for func in index.files.values_mut().flat_map(|e| e.functions.iter_mut()) {
+        for call in &mut func.calls {
+            if is_external_target(&call.target)
+                && let Some(ext) = user.get(external_name(&call.target))
+            {
+                call.target = format!("[{}:{}]", ext.kind, external_name(&call.target));
+            }
+        }
+    }
+    for (name, ext) in user {
+        if let Some(entry) = index.externals.get_mut(name) {
+            entry.kind = ext.kind.clone();
+            entry.summary = Some(ext.summary.clone());
+        }
+    }
+}
+
+/// Whether a call target is "[kind:name]" for an external rather than an ambiguous
+/// or interface call
+pub fn is_external_target(target: &str) -> bool {
+    target.starts_with('[') && !target.starts_with("[ambiguous:") && !target.starts_with("[iface:")
+}
+
+/// "compute_hmac" for "[external:compute_hmac]" or "compute_hmac"; the name is as called,
+/// except that a cgo call like `C.malloc` is named after the C function
+pub fn external_name(target: &str) -> &str {
+    target
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .and_then(|t| t.split_once(':'))
+        .map_or(target, |(_, name)| name)
+}
+
 /// Heuristic detection of likely macros
 fn is_likely_macro(name: &str) -> bool {
     // ALL_CAPS (with underscores) is usually a macro
@@ -544,11 +665,11 @@ mod tests {
     fn test_categorize_syscall() {
         let db = ExternalDb::new();
 
-        let (kind, summary) = db.categorize("sys_openat");
+        let (kind, summary) = db.categorize("sys_openat", Language::C);
         assert_eq!(kind, ExternalKind::Syscall);
         assert!(summary.is_some());
 
-        let (kind, _) = db.categorize("sys_close");
+        let (kind, _) = db.categorize("sys_close", Language::C);
         assert_eq!(kind, ExternalKind::Syscall);
     }
 
@@ -556,11 +677,11 @@ mod tests {
     fn test_categorize_libc() {
         let db = ExternalDb::new();
 
-        let (kind, summary) = db.categorize("printf");
+        let (kind, summary) = db.categorize("printf", Language::C);
         assert_eq!(kind, ExternalKind::Libc);
         assert!(summary.is_some());
 
-        let (kind, _) = db.categorize("malloc");
+        let (kind, _) = db.categorize("malloc", Language::C);
         assert_eq!(kind, ExternalKind::Libc);
     }
 
@@ -568,16 +689,16 @@ mod tests {
     fn test_categorize_macro() {
         let db = ExternalDb::new();
 
-        let (kind, _) = db.categorize("BUG_ON");
+        let (kind, _) = db.categorize("BUG_ON", Language::C);
         assert_eq!(kind, ExternalKind::Macro);
 
-        let (kind, _) = db.categorize("pr_err");
+        let (kind, _) = db.categorize("pr_err", Language::C);
         assert_eq!(kind, ExternalKind::Macro);
 
-        let (kind, _) = db.categorize("ARRAY_SIZE");
+        let (kind, _) = db.categorize("ARRAY_SIZE", Language::C);
         assert_eq!(kind, ExternalKind::Macro);
 
-        let (kind, _) = db.categorize("list_for_each_entry");
+        let (kind, _) = db.categorize("list_for_each_entry", Language::C);
         assert_eq!(kind, ExternalKind::Macro);
     }
 
@@ -585,8 +706,31 @@ mod tests {
     fn test_categorize_external() {
         let db = ExternalDb::new();
 
-        let (kind, _) = db.categorize("some_unknown_function");
+        let (kind, _) = db.categorize("some_unknown_function", Language::C);
         assert_eq!(kind, ExternalKind::External);
     }
 
+    #[test]
+    fn test_categorize_depends_on_caller_language() {
+        let db = ExternalDb::new();
+
+        assert_eq!(db.categorize("ARRAY_SIZE", Language::C).0, ExternalKind::Macro);
+        assert_eq!(db.categorize("ARRAY_SIZE", Language::Go).0, ExternalKind::External);
+
+        assert_eq!(db.categorize("pr_err", Language::C).0, ExternalKind::Macro);
+        assert_eq!(db.categorize("pr_err", Language::Go).0, ExternalKind::External);
+        assert_eq!(db.categorize("pr_err", Language::Rust).0, ExternalKind::External);
+
+        // Go's builtin close is not the close syscall, and Rust macros need the `!`
+        assert_eq!(db.categorize("close", Language::Go), (ExternalKind::External, None));
+        assert_eq!(db.categorize("__private_helper", Language::Rust).0, ExternalKind::External);
+        assert_eq!(db.categorize("println!", Language::Rust).0, ExternalKind::Macro);
+
+        assert_eq!(db.describe("syscall", "sys_close"), db.categorize("close", Language::C).1);
+        assert_eq!(db.describe("external", "close"), None);
+        assert_eq!(Language::of_file("./cmd/main.go"), Language::Go);
+        assert_eq!(Language::of_file("./src/lib.rs"), Language::Rust);
+        assert_eq!(Language::of_file("./src/io.h"), Language::C);
+    }
+
 }
@@ -0,0 +1,141 @@
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::commands::index::load_config;
+
+/// Width assumed when stdout is not a terminal
+const DEFAULT_WIDTH: usize = 120;
+
+/// Limits are never set below these, however narrow the terminal
+const MIN_NAME: usize = 30;
+const MIN_SUMMARY: usize = 40;
+
+/// Shortens qualified names and summaries for terminal output.
+/// Shared by every command that prints names so they truncate identically.
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    max_name: usize,
+    max_summary: usize,
+}
+
+impl Formatter {
+    /// Limits from `.aria/config.toml` `[output]`, else derived from the terminal width;
+    /// `full` disables truncation
+    pub fn new(full: bool) -> Self {
+        if full {
+            return Self::full();
+        }
+
+        let output = load_config(Path::new(".aria")).output;
+        let width = terminal_width();
+        Self {
+            max_name: output.max_name.unwrap_or(width / 2).max(MIN_NAME),
+            max_summary: output.max_summary.unwrap_or(width.saturating_sub(8)).max(MIN_SUMMARY),
+        }
+    }
+
+    /// No truncation, as for `--full` and JSON output
+    pub fn full() -> Self {
+        Self { max_name: usize::MAX, max_summary: usize::MAX }
+    }
+
+    pub fn name<'a>(&self, qualified_name: &'a str) -> Cow<'a, str> {
+        truncate_middle(qualified_name, self.max_name)
+    }
+
+    pub fn summary<'a>(&self, summary: &'a str) -> Cow<'a, str> {
+        clip(summary, self.max_summary)
+    }
+}
+
+fn terminal_width() -> usize {
+    if !std::io::stdout().is_terminal() {
+        return DEFAULT_WIDTH;
+    }
+    terminal_size::terminal_size().map_or(DEFAULT_WIDTH, |(w, _)| w.0 as usize)
+}
+
+/// Shorten a qualified name to at most `max` characters by replacing the middle with "…".
+/// The tail (final package or module segment through the simple name) is always kept,
+/// even if that alone exceeds `max`.
+pub fn truncate_middle(qualified_name: &str, max: usize) -> Cow<'_, str> {
+    let len = qualified_name.chars().count();
+    if len <= max {
+        return Cow::Borrowed(qualified_name);
+    }
+
+    let tail = protected_tail(qualified_name);
+    let tail_len = tail.chars().count();
+    let head_len = max.saturating_sub(tail_len + 1).min(len - tail_len);
+    let head: String = qualified_name.chars().take(head_len).collect();
+    Cow::Owned(format!("{head}…{tail}"))
+}
+
+/// Part of a qualified name that truncation must keep:
+/// Go/C "a/b/pkg.Type.Method" -> "pkg.Type.Method",
+/// Rust "a::module::Type::method" -> "module::Type::method", "a::module::func" -> "module::func"
+fn protected_tail(qualified_name: &str) -> &str {
+    if qualified_name.contains("::") {
+        let segments: Vec<&str> = qualified_name.split("::").collect();
+        let n = segments.len();
+        let has_type = n >= 3 && segments[n - 2].starts_with(char::is_uppercase);
+        let keep = if has_type { 3 } else { 2 }.min(n);
+        let skip: usize = segments[..n - keep].iter().map(|s| s.len() + 2).sum();
+        return &qualified_name[skip..];
+    }
+
+    match qualified_name.rfind('/') {
+        Some(pos) => &qualified_name[pos + 1..],
+        None => qualified_name,
+    }
+}
+
+/// Clip text to at most `max` characters, ending with "…" when shortened
+pub fn clip(text: &str, max: usize) -> Cow<'_, str> {
+    if text.chars().count() <= max {
+        return Cow::Borrowed(text);
+    }
+    let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+    Cow::Owned(format!("{}…", kept.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_middle_keeps_package_and_name() {
+        let go = "services/payments/internal/reconciliation/batchprocessor.Processor.ReconcileOutstandingSettlements";
+        let short = truncate_middle(go, 70);
+        assert_eq!(short.chars().count(), 70);
+        assert!(short.starts_with("services/"));
+        assert!(short.ends_with("…batchprocessor.Processor.ReconcileOutstandingSettlements"));
+
+        // The tail is kept even when it alone is over the limit
+        assert_eq!(truncate_middle(go, 10), "…batchprocessor.Processor.ReconcileOutstandingSettlements");
+
+        assert_eq!(truncate_middle("pkg.Short", 70), "pkg.Short");
+    }
+
+    #[test]
+    fn test_truncate_middle_rust_paths() {
+        assert_eq!(truncate_middle("commands::index::ReadyQueue::complete", 30), "co…index::ReadyQueue::complete");
+        assert_eq!(truncate_middle("commands::index::helpers::build_index", 25), "comm…helpers::build_index");
+    }
+
+    #[test]
+    fn test_clip() {
+        assert_eq!(clip("Loads the config.", 40), "Loads the config.");
+        assert_eq!(clip("Loads the config from disk", 12), "Loads the c…");
+        assert_eq!(clip("Loads the config", 11), "Loads the…");
+    }
+
+    #[test]
+    fn test_full_never_truncates() {
+        let f = Formatter::full();
+        let long = "x".repeat(500);
+        assert_eq!(f.name(&long), long);
+        assert_eq!(f.summary(&long), long);
+    }
+}
@@ -0,0 +1,154 @@
+//! Warns when query output may come from a stale index.
+//!
+//! The check is kept cheap enough to run before every query: one `git rev-list` for the
+//! commits made since indexing, and one stat per file the output points into, compared
+//! with the modification time recorded when the file was indexed. Only a file whose time
+//! differs is read and hashed, since a fresh clone gives every file a new time.
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::commands::index::load_config;
+use crate::index::{self, Index};
+use crate::progress;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Skip the check for this run (`--no-freshness-check`)
+pub fn disable() {
+    DISABLED.store(true, Ordering::SeqCst);
+}
+
+/// Warn once if the index is behind HEAD or any of `files` changed on disk since it was indexed.
+/// Never fails the command.
+pub fn check<'a>(index: &Index, files: impl IntoIterator<Item = &'a str>) {
+    if DISABLED.load(Ordering::SeqCst) || !load_config(Path::new(".aria")).output.freshness_check {
+        return;
+    }
+    let Ok(root) = index::repo_root() else {
+        return;
+    };
+    if let Some(warning) = freshness_warning(index, &root, files) {
+        progress::warn("stale_index", &warning);
+    }
+}
+
+/// The warning `check` prints for `files` under `root`, if any
+fn freshness_warning<'a>(index: &Index, root: &Path, files: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut files: Vec<&str> = files.into_iter().collect();
+    files.sort_unstable();
+    files.dedup();
+    let changed = files.into_iter().filter(|file| file_changed(index, root, file)).count();
+    stale_warning(commits_behind(&index.commit), changed)
+}
+
+/// Commits on HEAD that the index commit does not have; None outside git or for an unknown commit
+fn commits_behind(commit: &str) -> Option<usize> {
+    if commit.is_empty() {
+        return None;
+    }
+    let output = Command::new("git").args(["rev-list", "--count", &format!("{commit}..HEAD")]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()?.trim().parse().ok()
+}
+
+/// Whether an indexed file was modified or removed since indexing. A file whose modification
+/// time differs from the recorded one is changed only if its content hash differs too. Files
+/// indexed before modification times were recorded are taken as unchanged.
+fn file_changed(index: &Index, root: &Path, file: &str) -> bool {
+    let Some(entry) = index.files.get(file).filter(|e| e.mtime_ms != 0) else {
+        return false;
+    };
+    match index::resolve_source_path(root, file) {
+        Ok(path) if index::mtime_ms(&path) == entry.mtime_ms => false,
+        Ok(_) => !matches!(index::read_indexed_source(root, file, entry), Ok(index::IndexedSource::Fresh(_))),
+        Err(_) => false,
+    }
+}
+
+fn stale_warning(commits: Option<usize>, files: usize) -> Option<String> {
+    let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+    let behind = match (commits.unwrap_or(0), files) {
+        (0, 0) => return None,
+        (c, 0) => plural(c, "commit"),
+        (0, f) => plural(f, "file"),
+        (c, f) => format!("{} / {}", plural(c, "commit"), plural(f, "file")),
+    };
+    Some(format!("index is {behind} behind — line numbers may be wrong; run `aria update`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::FileEntry;
+    use std::fs;
+
+    #[test]
+    fn test_stale_warning() {
+        assert_eq!(stale_warning(Some(0), 0), None);
+        assert_eq!(stale_warning(None, 0), None);
+        assert_eq!(
+            stale_warning(Some(3), 0).unwrap(),
+            "index is 3 commits behind — line numbers may be wrong; run `aria update`"
+        );
+        assert_eq!(
+            stale_warning(Some(1), 2).unwrap(),
+            "index is 1 commit / 2 files behind — line numbers may be wrong; run `aria update`"
+        );
+        assert!(stale_warning(None, 1).unwrap().starts_with("index is 1 file behind"));
+    }
+
+    #[test]
+    fn test_file_changed_compares_recorded_mtime() {
+        let root = std::env::temp_dir().join(format!("aria-freshness-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.go"), "package a\n").unwrap();
+
+        let mut index = Index::new();
+        let entry = |mtime_ms| FileEntry {
+            mtime_ms,
+            ..Default::default()
+        };
+        index.files.insert("./a.go".to_string(), entry(index::mtime_ms(&root.join("a.go"))));
+        index.files.insert("./gone.go".to_string(), entry(1));
+        index.files.insert("./old.go".to_string(), entry(0));
+
+        assert!(!file_changed(&index, &root, "./a.go"));
+        assert!(file_changed(&index, &root, "./gone.go"));
+        // Indexed before mtimes were recorded: no way to tell, so not reported
+        assert!(!file_changed(&index, &root, "./old.go"));
+        index.files.get_mut("./a.go").unwrap().mtime_ms -= 1000;
+        assert!(file_changed(&index, &root, "./a.go"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_new_mtime_with_same_content_is_not_stale() {
+        // A fresh clone: every file has a new mtime but the content the index was built from
+        let root = std::env::temp_dir().join(format!("aria-freshness-clone-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let source = "package a\n";
+        fs::write(root.join("a.go"), source).unwrap();
+
+        let mut index = Index::new();
+        let entry = FileEntry {
+            ast_hash: crate::parser::content_hash(source.as_bytes()),
+            mtime_ms: index::mtime_ms(&root.join("a.go")) - 60_000,
+            ..Default::default()
+        };
+        index.files.insert("./a.go".to_string(), entry);
+
+        assert!(!file_changed(&index, &root, "./a.go"));
+        assert_eq!(freshness_warning(&index, &root, ["./a.go"]), None);
+
+        fs::write(root.join("a.go"), "package a\n\nfunc New() {}\n").unwrap();
+        let warning = freshness_warning(&index, &root, ["./a.go"]).unwrap();
+        assert!(warning.starts_with("index is 1 file behind"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+//! Client for an LLM gateway reached over a Unix domain socket.
+//!
+//! The protocol is newline-delimited JSON: the client writes one request per line and
+//! reads one response line back, any number of times over one connection.
+//!
+//!   request:  {"model": "claude-3-haiku", "prompt": "..."}
+//!   response: {"text": "..."}
+//!         or: {"error": "..."} / {"error": {"message": "...", ...}}
+//!
+//! Strings are JSON-escaped, so prompts and completions never break a line. An error
+//! response fails that request only; the connection stays open for the next one.
+
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::interrupt;
+use crate::summarizer::SummarizerError;
+
+/// How often a blocked read wakes up to check for Ctrl-C
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Serialize)]
+struct Request<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Text { text: String },
+    Error { error: serde_json::Value },
+}
+
+/// A gateway connection, opened on first use and kept for later requests
+pub struct Gateway {
+    socket_path: PathBuf,
+    model: String,
+    timeout: Duration,
+    conn: Option<BufReader<UnixStream>>,
+}
+
+impl Gateway {
+    pub fn new(socket_path: PathBuf, model: String, timeout: Duration) -> Self {
+        Self { socket_path, model, timeout, conn: None }
+    }
+
+    /// Send one prompt and wait up to the timeout for its completion. A request that fails on a
+    /// reused connection, which the gateway may have closed since, is retried once on a new one.
+    pub fn complete(&mut self, prompt: &str) -> Result<String, SummarizerError> {
+        let mut line = serde_json::to_string(&Request { model: &self.model, prompt })
+            .map_err(|e| SummarizerError::Gateway(format!("failed to encode request: {e}")))?;
+        line.push('\n');
+
+        let reused = self.conn.is_some();
+        let response = match self.exchange(&line) {
+            Err(e) if reused && is_disconnect(&e) => {
+                self.conn = None;
+                self.exchange(&line)
+            }
+            other => other,
+        };
+
+        match response {
+            Ok(Response::Text { text }) => Ok(text.trim().to_string()),
+            Ok(Response::Error { error }) => Err(SummarizerError::Gateway(error_message(&error))),
+            Err(e) => {
+                // A connection in an unknown state is not reused
+                self.conn = None;
+                if e.kind() == ErrorKind::Interrupted && interrupt::is_interrupted() {
+                    return Err(SummarizerError::Interrupted);
+                }
+                Err(SummarizerError::Gateway(format!("{}: {e}", self.socket_path.display())))
+            }
+        }
+    }
+
+    fn exchange(&mut self, line: &str) -> io::Result<Response> {
+        if self.conn.is_none() {
+            let stream = UnixStream::connect(&self.socket_path)?;
+            stream.set_read_timeout(Some(POLL_INTERVAL))?;
+            self.conn = Some(BufReader::new(stream));
+        }
+        let conn = self.conn.as_mut().expect("connected above");
+        conn.get_mut().write_all(line.as_bytes())?;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut response = Vec::new();
+        loop {
+            match conn.read_until(b'\n', &mut response) {
+                Ok(0) => return Err(io::Error::new(ErrorKind::UnexpectedEof, "gateway closed the connection")),
+                Ok(_) if response.ends_with(b"\n") => break,
+                Ok(_) => {}
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    if interrupt::is_interrupted() {
+                        return Err(io::Error::new(ErrorKind::Interrupted, "interrupted"));
+                    }
+                    if Instant::now() >= deadline {
+                        let secs = self.timeout.as_secs();
+                        return Err(io::Error::new(ErrorKind::TimedOut, format!("no response within {secs}s")));
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        serde_json::from_slice(&response)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, format!("malformed response: {e}")))
+    }
+}
+
+/// Errors that mean the other end went away rather than that the request failed
+fn is_disconnect(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::BrokenPipe | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Text of an error response: the string itself, its "message" field, or the raw JSON
+fn error_message(error: &serde_json::Value) -> String {
+    match error {
+        serde_json::Value::String(message) => message.clone(),
+        _ => match error.get("message").and_then(|m| m.as_str()) {
+            Some(message) => message.to_string(),
+            None => error.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    #[test]
+    fn test_gateway_round_trips_over_one_connection() {
+        let socket_path = std::env::temp_dir().join(format!("aria-gateway-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        // Accepts a single connection, so every request below must reuse it
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut requests = Vec::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let reply = match request["prompt"].as_str().unwrap() {
+                    "quota" => r#"{"error": {"message": "quota exceeded", "code": 429}}"#.to_string(),
+                    prompt => serde_json::json!({ "text": format!("Summary of {prompt}.\n") }).to_string(),
+                };
+                writeln!(writer, "{reply}").unwrap();
+                requests.push(request);
+                line.clear();
+            }
+            requests
+        });
+
+        let mut gateway = Gateway::new(socket_path.clone(), "haiku".to_string(), Duration::from_secs(5));
+        assert_eq!(gateway.complete("parse\nheaders").unwrap(), "Summary of parse\nheaders.");
+        let err = gateway.complete("quota").unwrap_err();
+        assert_eq!(err.to_string(), "gateway error: quota exceeded");
+        assert_eq!(gateway.complete("load").unwrap(), "Summary of load.");
+        drop(gateway);
+
+        let requests = server.join().unwrap();
+        let _ = std::fs::remove_file(&socket_path);
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0], serde_json::json!({ "model": "haiku", "prompt": "parse\nheaders" }));
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(error_message(&serde_json::json!("overloaded")), "overloaded");
+        assert_eq!(error_message(&serde_json::json!({ "code": 500 })), r#"{"code":500}"#);
+    }
+}
@@ -1,29 +1,94 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// How `ast_hash` and summary hashes are computed: 0 (missing) hashes source text with std's
+/// `DefaultHasher`, 1 with XXH64, and 2 hashes Go and Rust functions by syntax tree
+pub const HASH_VERSION: u32 = 2;
+
+/// Layout of the index this build reads and writes. Indexes of an older schema are upgraded
+/// on load by `MIGRATIONS`; a newer one is refused rather than misread and overwritten.
+pub const SCHEMA_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Index {
+    /// `SCHEMA_VERSION` of the index; 0 for indexes written before schemas were versioned
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Version of aria that wrote the index
     pub version: String,
+    /// `HASH_VERSION` of the hashes in this index
+    #[serde(default)]
+    pub hash_version: u32,
     pub commit: String,
     pub indexed_at: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_sorted")]
     pub files: HashMap<String, FileEntry>,
     /// External symbols (syscalls, libc, macros) referenced but not defined in codebase
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "serialize_sorted")]
     pub externals: HashMap<String, ExternalEntry>,
+    /// Package rollup summaries keyed by directory (e.g. "./internal/store")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub packages: BTreeMap<String, PackageEntry>,
+    /// How long the `aria index` or `aria update` run that wrote this index took
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+    /// Shard files holding `files`, in the manifest of a sharded index (index.sharded)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shards: Vec<String>,
+}
+
+/// Durations of one indexing run, for spotting slowdowns between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timings {
+    /// Milliseconds per phase: walk, parse, resolve, and summarize and packages when they ran
+    pub phases_ms: BTreeMap<String, u64>,
+    /// Files that took longest to parse in this run, slowest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slowest_files: Vec<FileTiming>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileTiming {
+    pub file: String,
+    pub parse_ms: f64,
+}
+
+impl Timings {
+    /// Record a phase's duration on the index, starting its timings if this run has none yet
+    pub fn record(index: &mut Index, phase: &str, elapsed_ms: u64) {
+        index.timings.get_or_insert_with(Timings::default).phases_ms.insert(phase.to_string(), elapsed_ms);
+    }
+}
+
+/// Serialize a map with keys in sorted order so index.json is stable across runs
+fn serialize_sorted<S, V>(map: &HashMap<String, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    let sorted: BTreeMap<&String, &V> = map.iter().collect();
+    sorted.serialize(serializer)
 }
 
 impl Index {
     pub fn new() -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
             version: env!("CARGO_PKG_VERSION").to_string(),
+            hash_version: HASH_VERSION,
             commit: String::new(),
             indexed_at: Utc::now(),
             files: HashMap::new(),
             externals: HashMap::new(),
+            packages: BTreeMap::new(),
+            timings: None,
+            shards: Vec::new(),
         }
     }
 }
@@ -34,16 +99,61 @@ impl Default for Index {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FileEntry {
     pub ast_hash: String,
     pub functions: Vec<Function>,
     pub types: Vec<TypeDef>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub variables: Vec<Variable>,
+    /// Rust `impl Trait for Type` blocks, linked to indexed types during resolution
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub impls: Vec<TraitImpl>,
+    /// Parsed for symbols only (a `reference_dirs` entry such as vendor/): no calls, no summaries
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reference: bool,
+    /// Fraction of the file's bytes inside tree-sitter ERROR nodes; symbols there are lost
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub parse_error_ratio: f64,
+    /// Syntax errors tree-sitter recovered from; functions after the first may be missing or cut short
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub parse_errors: u32,
+    /// Line of the first syntax error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_error_line: Option<u32>,
+    /// Modification time of the file when it was indexed, in milliseconds since the epoch
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub mtime_ms: u64,
+    /// Go imports and Rust `use` items, under the name the file refers to each by
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<Import>,
+    /// A test file (`_test.go`), indexed only with `features.include_tests`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_test: bool,
+    /// Module documentation from the Rust `//!` comments at the top of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Whether the file at index key `key` holds tests, which are only indexed on request
+pub fn is_test_file(key: &str) -> bool {
+    key.ends_with("_test.go")
+}
+
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Modification time of `path` in milliseconds since the epoch, or 0 if it cannot be read
+pub fn mtime_ms(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Function {
     pub name: String,
     pub qualified_name: String,
@@ -51,24 +161,160 @@ pub struct Function {
     pub ast_hash: String,
     pub line_start: u32,
     pub line_end: u32,
+    /// Byte range of the declaration in the file; 0..0 in indexes written before it was recorded
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub byte_start: u32,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub byte_end: u32,
+    /// First line of the outer attributes above the item (Rust), when there are any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attr_line_start: Option<u32>,
     pub signature: String,
+    /// Outer attributes as written without `#[` and `]`, e.g. "tokio::main" (Rust)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    /// Doc comment directly above the declaration, without comment markers (Go)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    /// ast_hash the summary was generated from; differs from ast_hash when the summary is stale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_hash: Option<String>,
+    /// Function whose byte-identical body produced this summary, when one prompt served both
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_shared_from: Option<String>,
+    /// Summary was written or reviewed by hand and is never regenerated, even when stale
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub summary_locked: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub receiver: Option<String>,
+    /// Trait the method implements, as written without generic arguments (Rust `impl Trait for Type`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trait_impl: Option<String>,
+    /// Rust impl function taking `self` in some form, called as `x.f()`; one without is an
+    /// associated function, called as `Type::f()`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub takes_self: bool,
     pub scope: Scope,
+    /// Program entry point: `main` in a Go main package, a Rust binary root, or a C file
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_entry: bool,
+    /// Callable from C without callers in the index: a Go function with a cgo `//export`
+    /// directive, or a Rust `extern "C"` or `#[no_mangle]` function
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exported_c: bool,
+    /// Rust `async fn`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_async: bool,
+    /// Parameter and receiver names mapped to their declared type text (e.g., "s" -> "*Server")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub param_types: BTreeMap<String, String>,
+    /// Go locals bound once with `:=`, and Rust locals bound once with `let`, mapped to the type
+    /// text of the literal they hold or declare (e.g., "c" -> "*Client"), or to "f()" when bound
+    /// to the result of calling f
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub local_types: BTreeMap<String, String>,
     pub calls: Vec<CallSite>,
     pub called_by: Vec<String>,
+    /// Callers whose call matched this function and others, so the edge is unconfirmed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub possibly_called_by: Vec<String>,
+    /// Go functions used as values rather than called (callback arguments, struct fields, map
+    /// values); the target is "[unresolved]" unless the name resolves to an indexed function
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<CallSite>,
+    /// Type names in the signature or constructed in the body, as written in source
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub type_refs: Vec<TypeRef>,
+    /// Qualified names of indexed types in `type_refs` (name-based, heuristic), sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uses_types: Vec<String>,
+    /// Names in the body that may be package-level variables or statics, as written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub global_refs: Vec<GlobalRef>,
+    /// Qualified names of indexed globals the function reads (name-based, heuristic), sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reads_globals: Vec<String>,
+    /// Qualified names of indexed globals the function assigns or mutates (name-based, heuristic), sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub writes_globals: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Function {
+    /// The declaration's text in `source`, the file it was parsed from; None when the index
+    /// has no byte range for it or the range does not fit `source`
+    pub fn source_text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        if self.byte_end <= self.byte_start {
+            return None;
+        }
+        source.get(self.byte_start as usize..self.byte_end as usize)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CallSite {
     /// Resolved qualified name of the called function, or "[unresolved]" if resolution fails
     pub target: String,
     /// Original call expression as written in source (e.g., "pkg.Foo", "obj.Method()")
     pub raw: String,
+    /// Name of the called function or method (e.g., "Get" in "s.store.Get")
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub callee_name: String,
+    /// Expression the method is called on, if any (e.g., "s.store" in "s.store.Get")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receiver_expr: Option<String>,
+    /// Possible targets when the call matched several indexed functions (target is "[ambiguous:raw]")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+    /// How the target was inferred when name lookup alone could not find it
+    /// ("local_type": from the type of the local variable the method is called on)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
     /// 1-indexed line number of the call site
     pub line: u32,
+    /// 1-indexed column of the call site, in bytes; 0 in indexes written before columns were recorded
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub column: u32,
+    /// Byte range of the call expression in the file
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub byte_start: u32,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub byte_end: u32,
+}
+
+impl CallSite {
+    /// "line:column", or the line alone in indexes without columns
+    pub fn position(&self) -> String {
+        match self.column {
+            0 => self.line.to_string(),
+            column => format!("{}:{column}", self.line),
+        }
+    }
+}
+
+/// A name in a function body that may refer to a global
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GlobalRef {
+    /// 1-indexed line of the first such use
+    pub line: u32,
+    /// Name as written (e.g., "cache", "config.Default", "crate::CACHE")
+    pub name: String,
+    /// Assigned, incremented, or passed to a mutating method rather than only read
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub write: bool,
+}
+
+/// A type mentioned by a function
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TypeRef {
+    /// 1-indexed line of the mention
+    pub line: u32,
+    /// Type name as written, without generics or pointers (e.g., "FileEntry", "store.FileEntry")
+    pub name: String,
+    /// Constructed here: composite literal, struct expression, or `Type::new`-style call
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub constructs: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,7 +326,71 @@ pub struct TypeDef {
     pub line_end: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    /// Doc comment directly above the declaration, without comment markers (Go)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
     pub methods: Vec<String>,
+    /// Traits or interfaces this type implements, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub implements: Vec<ImplLink>,
+    /// Types implementing this trait or interface, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub implemented_by: Vec<ImplLink>,
+    /// Go struct fields in declaration order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<Field>,
+    /// Rust enum variant names in declaration order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+}
+
+/// A Go struct field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+    /// Field name; for an embedded field, the embedded type's name (e.g., "Mutex" for "sync.Mutex")
+    pub name: String,
+    /// Type as written in source (e.g., "map[string]*Conn", "*Base")
+    pub type_name: String,
+    /// Struct tag without its quotes (e.g., `json:"name,omitempty"`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub embedded: bool,
+}
+
+/// A Rust `impl Trait for Type` block, with both names as written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraitImpl {
+    /// Trait path as written, without generic arguments (e.g., "Handler", "fmt::Display")
+    pub trait_name: String,
+    /// Implementing type as written (e.g., "Server", "Vec<T>", "T")
+    pub type_name: String,
+    /// 1-indexed line of the impl block
+    pub line: u32,
+}
+
+/// A Go import or Rust `use` item as the file refers to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Import {
+    /// Name in calls: the alias if one is given, else implied by the path; "." for a Go dot
+    /// import, "*" for a Rust glob import
+    pub alias: String,
+    /// Import path, e.g. "github.com/acme/app/internal/utils", or "crate::store::Store" for a
+    /// Rust item of this crate (`self::` and `super::` rewritten against the file's module)
+    pub path: String,
+}
+
+/// One end of an implementation relationship between a type and a trait or interface
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ImplLink {
+    /// Qualified name of the indexed type, or the text as written when unresolved
+    pub name: String,
+    /// File and line of the impl block (Rust), or of the linked type's definition (Go)
+    pub file: String,
+    pub line: u32,
+    /// Not an indexed type: a foreign type or trait, or a blanket impl over a type parameter
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unresolved: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,16 +402,38 @@ pub struct Variable {
     pub line_start: u32,
     pub line_end: u32,
     pub scope: Scope,
+    /// A Rust `const` rather than a `static`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_const: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Scope {
+    #[default]
     Public,
+    /// Rust `pub(crate)`, `pub(super)`, or `pub(in path)`: visible beyond its module but not
+    /// outside the crate
+    Crate,
     Static,
     Internal,
 }
 
+impl Scope {
+    /// Every scope, in the order `aria list --scope` names them
+    pub const ALL: [Scope; 4] = [Scope::Public, Scope::Crate, Scope::Static, Scope::Internal];
+
+    /// Name as serialized and printed: "public", "crate", "static", or "internal"
+    pub fn name(self) -> &'static str {
+        match self {
+            Scope::Public => "public",
+            Scope::Crate => "crate",
+            Scope::Static => "static",
+            Scope::Internal => "internal",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TypeKind {
@@ -111,6 +443,14 @@ pub enum TypeKind {
     Enum,
 }
 
+/// Summary of one package (the files in one directory), rolled up from its function summaries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageEntry {
+    pub summary: String,
+    /// Hash of the member function summaries the rollup was generated from
+    pub summary_hash: String,
+}
+
 /// Entry for an external symbol (syscall, libc function, macro)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExternalEntry {
@@ -124,37 +464,242 @@ pub struct ExternalEntry {
     pub references: u32,
 }
 
-/// Load index from .aria/index.json
+/// Where the index is read and written unless `--index-path` says otherwise
+pub const DEFAULT_INDEX_PATH: &str = ".aria/index.json";
+
+static INDEX_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `path` instead of `DEFAULT_INDEX_PATH` for the rest of the run
+pub fn set_index_path(path: PathBuf) {
+    let _ = INDEX_PATH.set(path);
+}
+
+/// Index file used by this run
+pub fn index_path() -> &'static Path {
+    INDEX_PATH.get().map(PathBuf::as_path).unwrap_or(Path::new(DEFAULT_INDEX_PATH))
+}
+
+/// Load the index from `index_path()`, or from the shards next to it
 pub fn load_index() -> Result<Index, String> {
-    let index_path = Path::new(".aria/index.json");
-    if !index_path.exists() {
-        return Err("index not found (run `aria index` first)".to_string());
+    let index_path = index_path();
+    if !index_path.exists() && !shard_manifest(index_path).exists() {
+        if index_path == Path::new(DEFAULT_INDEX_PATH) {
+            return Err("index not found (run `aria index` first)".to_string());
+        }
+        return Err(format!("index not found at {}", index_path.display()));
+    }
+    load_index_from(index_path)
+}
+
+/// How the index is stored on disk (index.format)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexFormat {
+    /// Pretty-printed JSON
+    #[default]
+    Json,
+    /// MessagePack: smaller, and much faster to read and write on large repositories
+    Bin,
+}
+
+impl IndexFormat {
+    /// Format of an index file's content: JSON starts with `{`, MessagePack with a map marker
+    pub fn detect(content: &[u8]) -> Self {
+        match content.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => IndexFormat::Json,
+            _ => IndexFormat::Bin,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IndexFormat::Json => "json",
+            IndexFormat::Bin => "bin",
+        }
+    }
+}
+
+/// Serialize an index, or a shard of one, in `format`
+pub fn encode_index<T: Serialize>(value: &T, format: IndexFormat) -> Result<Vec<u8>, String> {
+    match format {
+        IndexFormat::Json => serde_json::to_vec_pretty(value).map_err(|e| e.to_string()),
+        // Named fields, so serde defaults and skipped fields work as they do in JSON
+        IndexFormat::Bin => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
     }
+}
+
+/// Deserialize an index, or a shard of one, in whichever format `content` is in
+pub fn decode_index<T: DeserializeOwned>(content: &[u8]) -> Result<T, String> {
+    match IndexFormat::detect(content) {
+        IndexFormat::Json => serde_json::from_slice(content).map_err(|e| e.to_string()),
+        IndexFormat::Bin => rmp_serde::from_slice(content).map_err(|e| e.to_string()),
+    }
+}
+
+fn read_decoded<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = fs::read(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    decode_index(&content).map_err(|e| format!("failed to parse {}: {e}", path.display()))
+}
 
-    let content = fs::read_to_string(index_path)
-        .map_err(|e| format!("failed to read index: {e}"))?;
+/// Just the schema of a stored index, which reads even when the rest of the layout does not
+#[derive(Deserialize)]
+struct SchemaOnly {
+    #[serde(default)]
+    schema_version: u32,
+}
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("failed to parse index: {e}"))
+/// Schema of the index stored at `index_path`, or of its shard manifest
+pub fn stored_schema(index_path: &Path) -> Option<u32> {
+    let path = if index_path.exists() { index_path.to_path_buf() } else { shard_manifest(index_path) };
+    read_decoded::<SchemaOnly>(&path).ok().map(|s| s.schema_version)
 }
 
-/// Find functions matching a name (exact qualified, exact simple, then contains)
+fn newer_schema_error(path: &Path, schema: u32) -> String {
+    format!(
+        "{} was written by a newer aria (index schema {schema}, this build reads up to {SCHEMA_VERSION}); \
+         upgrade aria, or remove it and run `aria index`",
+        path.display()
+    )
+}
+
+/// Upgrades between schemas: `MIGRATIONS[n]` takes an index of schema n to n + 1, describing
+/// what it changed, if anything
+const MIGRATIONS: &[fn(&mut Index) -> Option<String>] = &[migrate_externals_table];
+
+/// Schema 0 indexes may predate the externals table; rebuild it from the calls that leave the index
+fn migrate_externals_table(index: &mut Index) -> Option<String> {
+    if !index.externals.is_empty() {
+        return None;
+    }
+    crate::resolver::rebuild_externals(index, &crate::externals::ExternalDb::new());
+    (!index.externals.is_empty()).then(|| format!("rebuilt the externals table ({} symbols)", index.externals.len()))
+}
+
+/// Refuse an index of a newer schema, and run the migrations an older one needs
+fn upgrade(mut index: Index, path: &Path) -> Result<Index, String> {
+    let schema = index.schema_version;
+    if schema > SCHEMA_VERSION {
+        return Err(newer_schema_error(path, schema));
+    }
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(schema as usize) {
+        if let Some(change) = migrate(&mut index) {
+            eprintln!("migrated {} from schema {from} to {}: {change}", path.display(), from + 1);
+        }
+    }
+    index.schema_version = SCHEMA_VERSION;
+    Ok(index)
+}
+
+/// Load an index file at an explicit path, in either format, or the sharded index next to it,
+/// upgraded to `SCHEMA_VERSION`
+pub fn load_index_from(index_path: &Path) -> Result<Index, String> {
+    if !index_path.exists() && shard_manifest(index_path).exists() {
+        return load_shards(index_path, None);
+    }
+    match read_decoded(index_path) {
+        Ok(index) => upgrade(index, index_path),
+        // A newer layout may not parse at all
+        Err(e) => match stored_schema(index_path) {
+            Some(schema) if schema > SCHEMA_VERSION => Err(newer_schema_error(index_path, schema)),
+            _ => Err(e),
+        },
+    }
+}
+
+/// Load only the files under `key`'s top-level directory when the index is sharded, or the
+/// whole index otherwise; `files` then holds that shard alone
+pub fn load_index_for(key: &str) -> Result<Index, String> {
+    let index_path = index_path();
+    if !index_path.exists() && shard_manifest(index_path).exists() {
+        return load_shards(index_path, Some(&shard_of(key)));
+    }
+    load_index()
+}
+
+/// Directory of a sharded index, named after the single file it replaces: ".aria/index/"
+pub fn shard_dir(index_path: &Path) -> PathBuf {
+    index_path.with_extension("")
+}
+
+/// File name of a sharded index's manifest: the index without its files, listing the shards
+/// that hold them
+pub const SHARD_MANIFEST: &str = "manifest.json";
+
+pub fn shard_manifest(index_path: &Path) -> PathBuf {
+    shard_dir(index_path).join(SHARD_MANIFEST)
+}
+
+/// Subdirectory of the shard directory with one shard per top-level directory, so no directory
+/// name can clash with the manifest or the shard of root-level files
+pub const SHARDS_SUBDIR: &str = "shards";
+
+/// Shard a file key belongs to, relative to the shard directory and without extension:
+/// "shards/<top-level dir>", or "root" for files at the root
+pub fn shard_of(key: &str) -> String {
+    let path = key.strip_prefix("./").unwrap_or(key);
+    match path.split_once('/') {
+        Some((dir, _)) => format!("{SHARDS_SUBDIR}/{dir}"),
+        None => "root".to_string(),
+    }
+}
+
+/// Reassemble a sharded index from its manifest and shards, or only the shard named `only`
+fn load_shards(index_path: &Path, only: Option<&str>) -> Result<Index, String> {
+    let dir = shard_dir(index_path);
+    let manifest_path = shard_manifest(index_path);
+    let manifest = read_decoded(&manifest_path).map_err(|e| match stored_schema(index_path) {
+        Some(schema) if schema > SCHEMA_VERSION => newer_schema_error(&manifest_path, schema),
+        _ => e,
+    })?;
+    // Sharding came with schema 1, so there are no older shards to upgrade
+    let mut index = upgrade(manifest, &manifest_path)?;
+    for shard in &index.shards {
+        if only.is_some_and(|name| Path::new(shard).with_extension("") != Path::new(name)) {
+            continue;
+        }
+        let files: HashMap<String, FileEntry> = read_decoded(&dir.join(shard))?;
+        index.files.extend(files);
+    }
+    Ok(index)
+}
+
+/// Find functions matching a name (exact qualified, exact simple, then contains), or
+/// every implementation of a trait method named "Trait::method", sorted by qualified name then file path
 pub fn find_functions<'a>(index: &'a Index, name: &str) -> Vec<(&'a str, &'a Function)> {
     let mut matches = Vec::new();
 
     for (file_path, entry) in &index.files {
         for func in &entry.functions {
-            if func.qualified_name == name || func.name == name || func.qualified_name.contains(name) {
+            if func.qualified_name == name
+                || func.name == name
+                || func.qualified_name.contains(name)
+                || implements_trait_method(func, name)
+            {
                 matches.push((file_path.as_str(), func));
             }
         }
     }
 
+    matches.sort_by(|a, b| {
+        a.1.qualified_name
+            .cmp(&b.1.qualified_name)
+            .then_with(|| a.0.cmp(b.0))
+            .then_with(|| a.1.line_start.cmp(&b.1.line_start))
+    });
     matches
 }
 
+/// Whether `func` implements the trait method `name`, given as "Handler::handle" or with
+/// the trait's path as written in the impl
+pub fn implements_trait_method(func: &Function, name: &str) -> bool {
+    let (Some(trait_name), Some((trait_part, method))) = (&func.trait_impl, name.rsplit_once("::")) else {
+        return false;
+    };
+    method == func.name && (trait_part == trait_name || trait_name.rsplit("::").next() == Some(trait_part))
+}
+
 /// Build a lookup table: qualified_name -> (file_path, &Function)
-pub fn build_function_map<'a>(index: &'a Index) -> HashMap<&'a str, (&'a str, &'a Function)> {
+pub fn build_function_map(index: &Index) -> HashMap<&str, (&str, &Function)> {
     let mut map = HashMap::new();
     for (file_path, entry) in &index.files {
         for func in &entry.functions {
@@ -163,3 +708,332 @@ pub fn build_function_map<'a>(index: &'a Index) -> HashMap<&'a str, (&'a str, &'
     }
     map
 }
+
+/// Map a user-supplied file path to its index key ("./<relative path>").
+/// Accepts "src/a.rs", "./src/a.rs", and absolute paths under the current directory.
+pub fn resolve_file_key<'a>(index: &'a Index, input: &str) -> Option<&'a str> {
+    let mut path = Path::new(input).to_path_buf();
+
+    if path.is_absolute() {
+        let cwd = std::env::current_dir().ok()?;
+        let cwd = fs::canonicalize(&cwd).unwrap_or(cwd);
+        let abs = fs::canonicalize(&path).unwrap_or(path);
+        path = abs.strip_prefix(&cwd).ok()?.to_path_buf();
+    }
+
+    // Drop "." components so "./a", ".//a", and "a/./b" all normalize
+    let parts: Vec<String> = path
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    let key = format!("./{}", parts.join("/"));
+    index.files.get_key_value(&key).map(|(k, _)| k.as_str())
+}
+
+/// Canonical path of the repository root (the current directory) that index keys are relative to
+pub fn repo_root() -> Result<PathBuf, String> {
+    fs::canonicalize(".").map_err(|e| format!("failed to resolve repository root: {e}"))
+}
+
+/// Resolve an index key to a path inside `root` (as returned by `repo_root`) for reading source.
+/// Keys that are absolute, contain "..", or lead through a symlink out of the root are refused.
+pub fn resolve_source_path(root: &Path, key: &str) -> Result<PathBuf, String> {
+    let path = Path::new(key);
+    if path.is_absolute() {
+        return Err(format!("index key '{key}' is an absolute path"));
+    }
+    if path.components().any(|c| !matches!(c, Component::CurDir | Component::Normal(_))) {
+        return Err(format!("index key '{key}' escapes the repository root"));
+    }
+
+    let joined = root.join(path);
+    match fs::canonicalize(&joined) {
+        Ok(canonical) if !canonical.starts_with(root) => {
+            Err(format!("index key '{key}' resolves outside the repository root"))
+        }
+        Ok(canonical) => Ok(canonical),
+        // Missing files are reported by whoever reads them
+        Err(_) => Ok(joined),
+    }
+}
+
+/// An indexed file as found on disk
+pub enum IndexedSource {
+    /// Content matches the hash recorded in the index
+    Fresh(String),
+    /// File changed since it was indexed, so recorded line ranges may be off
+    Modified(String),
+    /// File no longer exists
+    Missing,
+}
+
+impl IndexedSource {
+    /// Warning to show before using this file's recorded ranges, if any
+    pub fn warning(&self, key: &str) -> Option<String> {
+        match self {
+            IndexedSource::Fresh(_) => None,
+            IndexedSource::Modified(_) => {
+                Some(format!("{key} changed since it was indexed — line ranges may be off (run `aria update`)"))
+            }
+            IndexedSource::Missing => {
+                Some(format!("{key} no longer exists — index may be stale (run `aria update`)"))
+            }
+        }
+    }
+}
+
+/// Source files larger than this are skipped unless index.max_file_size says otherwise
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Read a source file as text, replacing invalid UTF-8 with U+FFFD so one stray byte does not
+/// hide the whole file; None when it is larger than `max_size` bytes
+pub fn read_source(path: &Path, max_size: u64) -> std::io::Result<Option<String>> {
+    if fs::metadata(path)?.len() > max_size {
+        return Ok(None);
+    }
+    let bytes = fs::read(path)?;
+    Ok(Some(match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+    }))
+}
+
+/// Read an indexed file and check it against the hash recorded in `entry`
+pub fn read_indexed_source(root: &Path, key: &str, entry: &FileEntry) -> Result<IndexedSource, String> {
+    let path = resolve_source_path(root, key)?;
+    match read_source(&path, u64::MAX).map(Option::unwrap_or_default) {
+        Ok(content) if crate::parser::content_hash(content.as_bytes()) == entry.ast_hash => {
+            Ok(IndexedSource::Fresh(content))
+        }
+        Ok(content) => Ok(IndexedSource::Modified(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(IndexedSource::Missing),
+        Err(e) => Err(format!("failed to read {key}: {e}")),
+    }
+}
+
+/// Split a "path:line" location, e.g. "src/resolver.rs:123"
+pub fn parse_location(query: &str) -> Option<(&str, u32)> {
+    let (path, line) = query.rsplit_once(':')?;
+    if path.is_empty() || path.ends_with(':') {
+        return None;
+    }
+    line.parse().ok().map(|line| (path, line))
+}
+
+/// Find the functions a query names: either a "path:line" location (the innermost
+/// function containing that line) or a name as in `find_functions`
+pub fn lookup_functions<'a>(index: &'a Index, query: &str) -> Result<Vec<(&'a str, &'a Function)>, String> {
+    let Some((path, line)) = parse_location(query) else {
+        let matches = find_functions(index, query);
+        if matches.is_empty() {
+            return Err(format!("no function found matching '{query}'"));
+        }
+        return Ok(matches);
+    };
+
+    let key = resolve_file_key(index, path).ok_or_else(|| format!("file '{path}' is not indexed"))?;
+    let entry = &index.files[key];
+
+    entry
+        .functions
+        .iter()
+        .filter(|f| f.line_start <= line && line <= f.line_end)
+        .min_by_key(|f| f.line_end - f.line_start)
+        .map(|f| vec![(key, f)])
+        .ok_or_else(|| format!("no function in {key} contains line {line}"))
+}
+
+/// Narrow name matches to program entry points: a query for a bare name such as "main"
+/// that matches several functions keeps only the entry-tagged ones. Several entry
+/// points are an error listing them, since each starts a different program.
+pub fn prefer_entry_points<'a>(
+    matches: Vec<(&'a str, &'a Function)>,
+    query: &str,
+) -> Result<Vec<(&'a str, &'a Function)>, String> {
+    if matches.len() < 2 {
+        return Ok(matches);
+    }
+    let entries: Vec<_> = matches.iter().filter(|(_, f)| f.is_entry && f.name == query).copied().collect();
+    match entries.len() {
+        0 => Ok(matches),
+        1 => Ok(entries),
+        _ => {
+            let listed: Vec<String> = entries
+                .iter()
+                .map(|(file, f)| format!("  {} ({}:{})", f.qualified_name, file, f.line_start))
+                .collect();
+            Err(format!(
+                "'{query}' matches {} entry points; pass a qualified name:\n{}",
+                entries.len(),
+                listed.join("\n")
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn func(name: &str, line_start: u32, line_end: u32) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: format!("pkg.{}", name),
+            line_start,
+            line_end,
+            ..Default::default()
+        }
+    }
+
+    fn sample_index() -> Index {
+        let mut index = Index::new();
+        index.files.insert(
+            "./src/resolver.rs".to_string(),
+            FileEntry {
+                functions: vec![func("outer", 10, 40), func("inner", 20, 25)],
+                ..Default::default()
+            },
+        );
+        index
+    }
+
+    #[test]
+    fn test_lookup_by_location() {
+        let index = sample_index();
+
+        for query in ["src/resolver.rs:12", "./src/resolver.rs:12", ".//src/resolver.rs:12"] {
+            let found = lookup_functions(&index, query).unwrap();
+            assert_eq!(found[0].1.name, "outer", "query {query}");
+        }
+
+        // Innermost function wins
+        let found = lookup_functions(&index, "src/resolver.rs:22").unwrap();
+        assert_eq!(found[0].1.name, "inner");
+
+        let err = lookup_functions(&index, "src/resolver.rs:5").unwrap_err();
+        assert!(err.contains("no function"));
+        let err = lookup_functions(&index, "src/missing.rs:5").unwrap_err();
+        assert!(err.contains("not indexed"));
+    }
+
+    #[test]
+    fn test_lookup_by_name_is_not_a_location() {
+        let index = sample_index();
+        assert_eq!(parse_location("resolver::Resolver::resolve"), None);
+        assert_eq!(parse_location("src/a.rs:7"), Some(("src/a.rs", 7)));
+        assert_eq!(lookup_functions(&index, "inner").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_prefer_entry_points() {
+        let entry = |qualified: &str| Function {
+            qualified_name: qualified.to_string(),
+            is_entry: true,
+            exported_c: false,
+            is_async: false,
+            ..func("main", 1, 3)
+        };
+        let (go_main, rust_main, bin_main, helper) =
+            (entry("main.main"), entry("crate::main"), entry("bin::tool::main"), func("main", 5, 9));
+        let names = |m: Vec<(&str, &Function)>| m.iter().map(|(_, f)| f.qualified_name.clone()).collect::<Vec<_>>();
+
+        let one = prefer_entry_points(vec![("./cmd/main.go", &go_main), ("./util.go", &helper)], "main").unwrap();
+        assert_eq!(names(one), vec!["main.main"]);
+
+        // Only a bare-name query narrows
+        let both = vec![("./cmd/main.go", &go_main), ("./util.go", &helper)];
+        assert_eq!(prefer_entry_points(both, "pkg").unwrap().len(), 2);
+
+        let err = prefer_entry_points(
+            vec![("./cmd/main.go", &go_main), ("./src/main.rs", &rust_main), ("./src/bin/tool.rs", &bin_main)],
+            "main",
+        )
+        .unwrap_err();
+        assert!(err.starts_with("'main' matches 3 entry points"));
+        assert!(err.contains("  crate::main (./src/main.rs:1)"));
+        assert!(err.contains("  bin::tool::main (./src/bin/tool.rs:1)"));
+    }
+
+    #[test]
+    fn test_resolve_source_path_rejects_escapes() {
+        let root = std::env::temp_dir().join(format!("aria-paths-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+
+        assert_eq!(resolve_source_path(&root, "./src/main.rs").unwrap(), root.join("src/main.rs"));
+        assert_eq!(resolve_source_path(&root, "./src/missing.rs").unwrap(), root.join("./src/missing.rs"));
+
+        for key in ["../../etc/passwd", "./src/../../outside.rs", "/etc/passwd", "./a/../../b"] {
+            assert!(resolve_source_path(&root, key).is_err(), "{key} should be refused");
+        }
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", root.join("src/link")).unwrap();
+            assert!(resolve_source_path(&root, "./src/link/passwd").is_err());
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_read_indexed_source_detects_stale_files() {
+        let root = std::env::temp_dir().join(format!("aria-stale-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.go"), "package a\n").unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+
+        let mut entry = FileEntry {
+            ast_hash: crate::parser::content_hash(b"package a\n"),
+            ..Default::default()
+        };
+        let state = read_indexed_source(&root, "./a.go", &entry).unwrap();
+        assert!(matches!(state, IndexedSource::Fresh(ref c) if c == "package a\n"));
+        assert!(state.warning("./a.go").is_none());
+
+        entry.ast_hash = "0".to_string();
+        let state = read_indexed_source(&root, "./a.go", &entry).unwrap();
+        assert!(matches!(state, IndexedSource::Modified(_)));
+        assert!(state.warning("./a.go").unwrap().contains("changed since it was indexed"));
+
+        let state = read_indexed_source(&root, "./gone.go", &entry).unwrap();
+        assert!(matches!(state, IndexedSource::Missing));
+        assert!(state.warning("./gone.go").unwrap().contains("no longer exists"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_index_is_upgraded_on_load() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/index-schema0.json");
+        let index = load_index_from(&fixture).unwrap();
+        assert_eq!(index.schema_version, SCHEMA_VERSION);
+        let load = &index.files["./src/io.c"].functions[0];
+        assert_eq!(load.summary.as_deref(), Some("Allocates a buffer and reads the file into it"));
+        assert_eq!(load.calls[0].target, "[libc:malloc]");
+        assert_eq!((index.externals["malloc"].kind.as_str(), index.externals["malloc"].references), ("libc", 2));
+        assert_eq!(index.externals.len(), 2);
+
+        // Written back, it loads as the current schema with nothing left to migrate
+        let encoded = encode_index(&index, IndexFormat::Json).unwrap();
+        let root = std::env::temp_dir().join(format!("aria-schema-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("index.json");
+        fs::write(&path, &encoded).unwrap();
+        let reloaded = load_index_from(&path).unwrap();
+        assert_eq!(serde_json::to_value(&reloaded).unwrap(), serde_json::to_value(&index).unwrap());
+        assert!(!String::from_utf8(encoded).unwrap().contains("embedding"));
+
+        // A newer schema is refused, even when its layout no longer parses
+        fs::write(&path, r#"{"schema_version": 99, "files": []}"#).unwrap();
+        let err = load_index_from(&path).unwrap_err();
+        assert!(err.contains("written by a newer aria (index schema 99"), "{err}");
+        assert_eq!(stored_schema(&path), Some(99));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Exit code used when a command stops early on Ctrl-C (128 + SIGINT)
+pub const EXIT_INTERRUPTED: u8 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the SIGINT handler for long-running commands.
+///
+/// The first Ctrl-C sets a flag that loops check between files, batches, and
+/// levels so they can stop and save progress. A second Ctrl-C exits immediately.
+pub fn install() {
+    let result = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(EXIT_INTERRUPTED as i32);
+        }
+        eprintln!("\ninterrupting... finishing current work (Ctrl-C again to force quit)");
+    });
+
+    if let Err(e) = result {
+        eprintln!("warning: failed to install Ctrl-C handler: {e}");
+    }
+}
+
+/// Whether Ctrl-C has been pressed
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -1,18 +1,55 @@
+mod card;
 mod commands;
 mod config;
 mod externals;
+mod format;
+mod freshness;
+#[cfg(unix)]
+mod gateway;
 mod index;
+mod interrupt;
 mod parser;
+mod progress;
 mod resolver;
+mod sanitize;
 mod summarizer;
 mod topo;
 
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{CommandFactory, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "aria")]
 #[command(about = "Git-native codebase indexer for LLMs")]
 struct Cli {
+    /// Index file to read and write [default: .aria/index.json]
+    #[arg(long, global = true, value_name = "FILE")]
+    index_path: Option<PathBuf>,
+
+    /// Report progress of index and update as JSON lines on stderr (or set ARIA_PROGRESS=json)
+    ///
+    /// Each line is an object whose "event" field is one of:
+    ///   phase_start  {phase, total?}             a phase began: parse, resolve, summarize
+    ///   progress     {phase, current, total}     items of the phase done so far
+    ///   phase_end    {phase, count, elapsed_ms}  a phase finished after `count` items
+    ///   warning      {code, message}             read_failed, parse_failed, parse_errors, summarize_failed,
+    ///                                            missing_grammar, legacy_hashes, stale_index,
+    ///                                            file_too_large
+    ///   result       {command, ...}              final counts, as in the printed summary
+    #[arg(long, global = true, verbatim_doc_comment)]
+    progress_json: bool,
+
+    /// Print how long each phase of index and update took as it finishes (walk, parse,
+    /// resolve, summarize, packages, write); the index keeps them under "timings"
+    #[arg(long, global = true)]
+    verbose: bool,
+
+    /// Skip the check that warns when the index is behind HEAD or the files a query shows
+    /// changed since indexing (or set output.freshness_check = false)
+    #[arg(long, global = true)]
+    no_freshness_check: bool,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -20,7 +57,52 @@ struct Cli {
 #[derive(Subcommand)]
 enum Command {
     /// Build the index
-    Index,
+    Index {
+        /// Write the index to this file instead of the index path
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Index Go test files too, as with features.include_tests
+        #[arg(long)]
+        include_tests: bool,
+    },
+
+    /// Reindex changed files and report what changed
+    Update {
+        /// Regenerate summaries for changed functions
+        #[arg(long)]
+        summarize: bool,
+        /// Also regenerate summaries of direct callers of changed functions
+        #[arg(long, requires = "summarize")]
+        cascade: bool,
+    },
+
+    /// Set or lock a function's summary in the index
+    #[command(group(clap::ArgGroup::new("change").required(true).multiple(true)))]
+    Annotate {
+        /// Exact qualified name of the function
+        qualified_name: String,
+        /// New summary text
+        #[arg(long, group = "change")]
+        summary: Option<String>,
+        /// Never regenerate or overwrite this summary, even when the function changes
+        #[arg(long, group = "change", conflicts_with = "unlock")]
+        lock: bool,
+        /// Allow summarization to regenerate this summary again
+        #[arg(long, group = "change")]
+        unlock: bool,
+    },
+
+    /// Describe an external call target; the description is kept across reindexing
+    AnnotateExternal {
+        /// External as shown in traces (e.g., "[external:compute_hmac]") or its bare name
+        target: String,
+        /// Description shown wherever the external is called
+        #[arg(long)]
+        summary: String,
+        /// Recategorize the external: syscall, libc, macro, or external
+        #[arg(long)]
+        kind: Option<String>,
+    },
 
     /// Print raw source code for any symbol
     Source {
@@ -31,9 +113,30 @@ enum Command {
         kind: Option<String>,
     },
 
+    /// Show a function's location, signature, and summary
+    Function {
+        /// Function name (exact, then contains match) or path:line location
+        name: String,
+        /// Also show the signature and summary of everything it calls
+        #[arg(long)]
+        callees: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Do not truncate long names or summaries
+        #[arg(long)]
+        full: bool,
+        /// Show summaries exactly as stored, without removing instruction-like text
+        #[arg(long)]
+        raw: bool,
+        /// How much to show: oneline, standard, or full
+        #[arg(long, default_value = "standard")]
+        card: String,
+    },
+
     /// Trace call graph for a function
     Trace {
-        /// Function name (exact, then contains match)
+        /// Function name (exact, then contains match) or path:line location
         name: String,
         /// Show only forward trace (what this function calls)
         #[arg(long, short = 'f')]
@@ -41,24 +144,371 @@ enum Command {
         /// Show only backward trace (what calls this function)
         #[arg(long, short = 'b')]
         backward: bool,
-        /// Depth limit (default: 2, 0 = unlimited)
-        #[arg(long, short = 'd', default_value = "2")]
+        /// Depth limit (default: 2, or unlimited with --fit-tokens; 0 = unlimited)
+        #[arg(long, short = 'd')]
+        depth: Option<usize>,
+        /// Aggregate the forward trace by file instead of by function
+        #[arg(long, conflicts_with = "backward")]
+        files: bool,
+        /// Output as JSON, with the call-site line of every edge
+        #[arg(long)]
+        json: bool,
+        /// Do not truncate long names or summaries
+        #[arg(long)]
+        full: bool,
+        /// Only show forward branches that reach this function
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["backward", "files", "json"])]
+        to: Option<String>,
+        /// Show where in the caller each call happens, e.g. [called at ./main.go:12]
+        #[arg(long, conflicts_with = "files")]
+        call_sites: bool,
+        /// Expand the forward trace breadth-first until it reaches about N tokens
+        #[arg(long, value_name = "N", conflicts_with_all = ["backward", "files", "json", "to"])]
+        fit_tokens: Option<usize>,
+    },
+
+    /// List the functions that construct or mention a type (name-based, heuristic)
+    Type {
+        /// Type name (exact, then contains match)
+        name: String,
+    },
+
+    /// List the types implementing a Rust trait or Go interface
+    Implementations {
+        /// Trait or interface name (exact, then contains match)
+        name: String,
+    },
+
+    /// Show a package's summary, files, public API, and the packages it calls into or is called from
+    Package {
+        /// Package directory, or a prefix matching several (e.g. internal/store)
+        prefix: String,
+    },
+
+    /// List the functions that assign or mutate a package-level variable or static (name-based, heuristic)
+    Writers {
+        /// Variable name (exact, then contains match)
+        name: String,
+    },
+
+    /// List the call edges between two files, in both directions
+    BetweenFiles {
+        /// First file (index key, relative or absolute path)
+        a: String,
+        /// Second file
+        b: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Also list ambiguous calls with a candidate in the other file
+        #[arg(long)]
+        ambiguous: bool,
+    },
+
+    /// List the callers of a function, or the functions and types using a type
+    Usages {
+        /// Function or type name (exact, then contains match) or path:line location
+        name: String,
+        /// What the name refers to when a function and a type share it: function, type
+        #[arg(long)]
+        kind: Option<String>,
+        /// Do not truncate long names or summaries
+        #[arg(long)]
+        full: bool,
+        /// Callers in indexed test files: include, exclude, or only
+        #[arg(long, value_name = "WHICH", default_value = "include")]
+        tests: String,
+    },
+
+    /// List indexed functions, as a tree with --tree, or files with --paths
+    List {
+        /// List indexed files with per-file counts, optionally filtered by glob (e.g. 'internal/**')
+        #[arg(long, value_name = "GLOB", num_args = 0..=1)]
+        paths: Option<Option<String>>,
+        /// Filter by language: go, rust, c, cpp, python, typescript, java
+        #[arg(long)]
+        lang: Option<String>,
+        /// Only functions whose signature mentions or body constructs this type
+        #[arg(long, value_name = "TYPE", conflicts_with = "paths")]
+        uses_type: Option<String>,
+        /// File order with --paths: functions, size, path
+        #[arg(long, default_value = "path", requires = "paths")]
+        sort: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Do not truncate long names or summaries
+        #[arg(long)]
+        full: bool,
+        /// Also list reference-only files and their functions (see index.reference_dirs)
+        #[arg(long)]
+        include_reference: bool,
+        /// Only functions marked public (exported in Go, `pub` in Rust, non-static in C)
+        #[arg(long, conflicts_with = "paths")]
+        public_only: bool,
+        /// Only functions of this scope: public, crate (Rust `pub(crate)` and the like), static, or internal
+        #[arg(long, value_name = "SCOPE", conflicts_with_all = ["paths", "public_only"])]
+        scope: Option<String>,
+        /// Only async functions (Rust `async fn`)
+        #[arg(long = "async", conflicts_with = "paths")]
+        is_async: bool,
+        /// Only functions callable from C: cgo `//export`, Rust `extern "C"`, `#[no_mangle]`, or `#[export_name]`
+        #[arg(long, conflicts_with = "paths")]
+        ffi: bool,
+        /// Show modules, files, types, and functions as a tree, with counts per node
+        #[arg(long, conflicts_with = "paths")]
+        tree: bool,
+        /// Levels of the tree to show (0 = unlimited)
+        #[arg(long, default_value = "0", requires = "tree")]
         depth: usize,
     },
 
     /// Rank functions by dependency depth
     Rank,
+
+    /// Show index coverage and summary staleness
+    Stats {
+        /// Number of most-called stale functions to list
+        #[arg(long, default_value = "10")]
+        top: usize,
+        /// Exit with failure if more than this percentage of summaries are stale
+        #[arg(long, value_name = "PCT")]
+        fail_on_stale: Option<f64>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check the index for problems: file keys outside the repository, files changed since
+    /// indexing, and overlapping or out-of-file function ranges
+    Validate {
+        /// Reindex files that changed since they were indexed, then check again
+        #[arg(long)]
+        fix_stale: bool,
+    },
+
+    /// Bundle context for reviewing a change: changed functions with their previous summary,
+    /// current source, and direct callers and callees
+    Context {
+        /// Collect every function changed in the working tree
+        #[arg(long, required = true)]
+        for_diff: bool,
+        /// Revision to compare the working tree with
+        #[arg(long, value_name = "REV", default_value = "HEAD")]
+        since: String,
+        /// Approximate token limit for the bundle
+        #[arg(long, value_name = "TOKENS", default_value = "8000")]
+        budget: usize,
+        /// Output as JSON instead of markdown
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compare two index files: functions added, removed, or modified, and coverage changes
+    DiffIndex {
+        /// Index before the change, e.g. built on the base commit
+        before: PathBuf,
+        /// Index after the change
+        after: PathBuf,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Time index loading and representative queries on the current index
+    Bench {
+        /// Number of times each operation is run
+        #[arg(long, default_value = "10")]
+        iterations: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Inspect and manage cached data under .aria/cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+
+    /// Stream the call graph as CSV or JSON lines for graph tools
+    Export {
+        #[command(subcommand)]
+        what: ExportCommand,
+    },
+
+    /// Print a shell completion script
+    Completions {
+        shell: clap_complete::Shell,
+    },
+
+    /// List function names starting with a prefix (used by completion scripts)
+    #[command(name = "__complete-functions", hide = true)]
+    CompleteFunctions {
+        #[arg(default_value = "")]
+        prefix: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// One record per resolved call or function reference: caller, callee, caller file, line, edge kind
+    Edges {
+        /// Record format: csv, jsonl
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// Also export calls to external symbols, tagged "[external:name]"
+        #[arg(long)]
+        include_external: bool,
+        /// Only calls made from this package directory or below it (e.g. internal/store)
+        #[arg(long, value_name = "PREFIX")]
+        package: Option<String>,
+    },
+
+    /// One record per function: file, lines, scope, language, fan-in/out, and rank if computed
+    Nodes {
+        /// Record format: csv, jsonl
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// Only functions in this package directory or below it
+        #[arg(long, value_name = "PREFIX")]
+        package: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Show size and entry count of each cache category, and where it lives
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Remove cached entries
+    Clear {
+        /// Category to clear: summaries, all
+        #[arg(long, default_value = "all")]
+        category: String,
+    },
+
+    /// Evict least recently used entries until the cache fits a size bound
+    Gc {
+        /// Largest total cache size to keep, in megabytes
+        #[arg(long, value_name = "MB")]
+        max_size: u64,
+    },
 }
 
 fn main() -> std::process::ExitCode {
     let cli = Cli::parse();
+    if let Some(path) = cli.index_path {
+        index::set_index_path(path);
+    }
+    if cli.progress_json || std::env::var(progress::ENV_VAR).is_ok_and(|v| v == "json") {
+        progress::enable_json();
+    }
+    if cli.verbose {
+        progress::enable_verbose();
+    }
+    if cli.no_freshness_check {
+        freshness::disable();
+    }
 
     match cli.command {
-        Command::Index => commands::index::run(),
+        Command::Index { output, include_tests } => commands::index::run(output.as_deref(), include_tests),
+        Command::Update { summarize, cascade } => {
+            let opts = commands::update::UpdateOptions { summarize, cascade };
+            commands::update::run(&opts)
+        }
+        Command::Annotate { qualified_name, summary, lock, unlock } => {
+            let opts = commands::annotate::AnnotateOptions { summary, lock, unlock };
+            commands::annotate::run(&qualified_name, &opts)
+        }
+        Command::AnnotateExternal { target, summary, kind } => {
+            let opts = commands::annotate_external::AnnotateExternalOptions { summary, kind };
+            commands::annotate_external::run(&target, &opts)
+        }
         Command::Source { name, kind } => commands::source::run(&name, kind.as_deref()),
-        Command::Trace { name, forward, backward, depth } => {
-            commands::callstack::run(&name, forward, backward, depth)
+        Command::Function { name, callees, json, full, raw, card } => {
+            let card = match card::Verbosity::parse(&card) {
+                Ok(card) => card,
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    return std::process::ExitCode::FAILURE;
+                }
+            };
+            let opts = commands::function::FunctionOptions { callees, json, full, raw, card };
+            commands::function::run(&name, &opts)
+        }
+        Command::Trace { name, forward, backward, depth, files, json, full, to, call_sites, fit_tokens } => {
+            let depth = depth.unwrap_or(if fit_tokens.is_some() { 0 } else { 2 });
+            let opts =
+                commands::callstack::TraceOptions { forward, backward, depth, files, json, full, to, call_sites, fit_tokens };
+            commands::callstack::run(&name, &opts)
+        }
+        Command::Type { name } => commands::types::run(&name),
+        Command::Implementations { name } => commands::implementations::run(&name),
+        Command::Package { prefix } => commands::package::run(&prefix),
+        Command::Writers { name } => commands::writers::run(&name),
+        Command::BetweenFiles { a, b, json, ambiguous } => commands::between_files::run(&a, &b, json, ambiguous),
+        Command::Usages { name, kind, full, tests } => {
+            let tests = match commands::usages::TestCallers::parse(&tests) {
+                Ok(tests) => tests,
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    return std::process::ExitCode::FAILURE;
+                }
+            };
+            commands::usages::run(&name, kind.as_deref(), full, tests)
+        }
+        Command::List { paths, lang, uses_type, sort, json, full, include_reference, public_only, scope, is_async, ffi, tree, depth } => {
+            let opts = commands::list::ListOptions {
+                paths,
+                lang,
+                uses_type,
+                sort,
+                json,
+                full,
+                include_reference,
+                public_only,
+                scope,
+                is_async,
+                ffi,
+                tree,
+                depth,
+            };
+            commands::list::run(&opts)
         }
         Command::Rank => commands::topo::run(),
+        Command::Stats { top, fail_on_stale, json } => {
+            let opts = commands::stats::StatsOptions { top, fail_on_stale, json };
+            commands::stats::run(&opts)
+        }
+        Command::Validate { fix_stale } => commands::validate::run(fix_stale),
+        Command::Context { for_diff: _, since, budget, json } => {
+            let opts = commands::context::ContextOptions { since, budget, json };
+            commands::context::run(&opts)
+        }
+        Command::DiffIndex { before, after, json } => commands::diff_index::run(&before, &after, json),
+        Command::Bench { iterations, json } => {
+            let opts = commands::bench::BenchOptions { iterations, json };
+            commands::bench::run(&opts)
+        }
+        Command::Cache { action } => match action {
+            CacheCommand::Stats { json } => commands::cache::stats(json),
+            CacheCommand::Clear { category } => commands::cache::clear(&category),
+            CacheCommand::Gc { max_size } => commands::cache::gc(max_size),
+        },
+        Command::Export { what } => match what {
+            ExportCommand::Edges { format, include_external, package } => {
+                commands::export::edges(&commands::export::ExportOptions { format, package, include_external })
+            }
+            ExportCommand::Nodes { format, package } => {
+                commands::export::nodes(&commands::export::ExportOptions { format, package, include_external: false })
+            }
+        },
+        Command::Completions { shell } => commands::completions::run(shell, Cli::command()),
+        Command::CompleteFunctions { prefix } => commands::completions::complete_functions(&prefix),
     }
 }
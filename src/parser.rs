@@ -1,6 +1,26 @@
+// Helpers shared by several languages go unused when only some grammars are compiled in
+#![cfg_attr(
+    not(all(
+        feature = "lang-go",
+        feature = "lang-rust",
+        feature = "lang-c",
+        feature = "lang-python",
+        feature = "lang-typescript",
+        feature = "lang-java",
+        feature = "lang-cpp"
+    )),
+    allow(dead_code, unused_imports)
+)]
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
 use tree_sitter::Parser;
 
-use crate::index::{CallSite, FileEntry, Function, Scope, TypeDef, TypeKind, Variable};
+use crate::config::IndexConfig;
+use crate::index::{
+    CallSite, Field, FileEntry, Function, GlobalRef, Import, Scope, TraitImpl, TypeDef, TypeKind, TypeRef, Variable,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
@@ -8,12 +28,191 @@ pub enum Language {
     Go,
     Rust,
     C,
+    Python,
+    TypeScript,
+    Java,
+    Cpp,
+}
+
+/// Parses source files of one language into index entries
+pub trait LanguageParser {
+    /// Language name, as in `--lang` and `index.languages`
+    fn language(&self) -> &'static str;
+
+    /// File extensions this parser handles, without the dot; the grammar table's by default
+    fn extensions(&self) -> &[&str] {
+        grammar(self.language()).map_or(&[], |g| g.extensions)
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry>;
+
+    /// Apply the `[index]` settings that change what gets extracted; most parsers have none
+    fn configure(&mut self, _config: &IndexConfig) {}
+
+    /// Learn the layout of the repository at `root` before its files are parsed; Rust
+    /// reads the Cargo manifests to name each crate's modules
+    fn scan_root(&mut self, _root: &Path) {}
+}
+
+/// Parser instances by the file extensions they handle
+#[derive(Default)]
+pub struct ParserRegistry {
+    parsers: Vec<Box<dyn LanguageParser>>,
+    by_extension: HashMap<String, usize>,
+}
+
+impl ParserRegistry {
+    /// A registry with a parser for every grammar compiled into this build
+    pub fn new() -> Self {
+        let mut registry = Self::default();
+        for parser in GRAMMARS.iter().filter_map(Grammar::parser) {
+            registry.register(parser);
+        }
+        registry
+    }
+
+    /// A registry for this build with the `[languages]` config applied: each listed language
+    /// handles exactly the listed extensions, and an empty list disables it. Languages this build
+    /// lacks are left for the missing-grammar warnings. Every parser gets the `[index]` settings.
+    pub fn configured(languages: &BTreeMap<String, Vec<String>>, index: &IndexConfig) -> Result<Self, String> {
+        if let Some(name) = languages.keys().find(|name| grammar(name).is_none()) {
+            let known: Vec<&str> = GRAMMARS.iter().map(|g| g.name).collect();
+            return Err(format!("[languages] lists unknown language '{name}' (expected {})", known.join(", ")));
+        }
+        let mut registry = Self::new();
+        for (name, extensions) in languages {
+            let Some(i) = registry.parsers.iter().position(|p| p.language() == name) else {
+                continue;
+            };
+            registry.by_extension.retain(|_, parser| *parser != i);
+            for ext in extensions {
+                registry.by_extension.insert(ext.trim_start_matches('.').to_string(), i);
+            }
+        }
+        for parser in &mut registry.parsers {
+            parser.configure(index);
+        }
+        Ok(registry)
+    }
+
+    /// Let every parser learn the layout of the repository at `root` (see LanguageParser::scan_root)
+    pub fn scan_root(&mut self, root: &Path) {
+        for parser in &mut self.parsers {
+            parser.scan_root(root);
+        }
+    }
+
+    /// Add a parser for its extensions, taking over any extension another parser had
+    pub fn register(&mut self, parser: Box<dyn LanguageParser>) {
+        for ext in parser.extensions() {
+            self.by_extension.insert(ext.to_string(), self.parsers.len());
+        }
+        self.parsers.push(parser);
+    }
+
+    fn index_of(&self, path: &Path) -> Option<usize> {
+        let ext = path.extension()?.to_str()?;
+        self.by_extension.get(ext).copied()
+    }
+
+    /// Language of the parser registered for a file's extension
+    pub fn language_of(&self, path: &Path) -> Option<&'static str> {
+        self.index_of(path).map(|i| self.parsers[i].language())
+    }
+
+    /// Parse a file with the parser registered for its extension
+    pub fn parse_file(&mut self, path: &Path, source: &str, key: &str) -> Option<FileEntry> {
+        let i = self.index_of(path)?;
+        self.parsers[i].parse_file(source, key)
+    }
+}
+
+/// A language aria can index and the cargo feature that compiles its grammar in
+pub struct Grammar {
+    pub name: &'static str,
+    pub feature: &'static str,
+    pub extensions: &'static [&'static str],
+    /// Whether this build includes the grammar
+    pub compiled: bool,
+}
+
+/// Every language aria knows, whether or not this build includes it
+pub const GRAMMARS: &[Grammar] = &[
+    Grammar { name: "go", feature: "lang-go", extensions: &["go"], compiled: cfg!(feature = "lang-go") },
+    Grammar { name: "rust", feature: "lang-rust", extensions: &["rs"], compiled: cfg!(feature = "lang-rust") },
+    Grammar { name: "c", feature: "lang-c", extensions: &["c", "h"], compiled: cfg!(feature = "lang-c") },
+    Grammar { name: "python", feature: "lang-python", extensions: &["py"], compiled: cfg!(feature = "lang-python") },
+    Grammar {
+        name: "typescript",
+        feature: "lang-typescript",
+        extensions: TS_EXTENSIONS,
+        compiled: cfg!(feature = "lang-typescript"),
+    },
+    Grammar { name: "java", feature: "lang-java", extensions: &["java"], compiled: cfg!(feature = "lang-java") },
+    Grammar { name: "cpp", feature: "lang-cpp", extensions: CPP_EXTENSIONS, compiled: cfg!(feature = "lang-cpp") },
+];
+
+/// C++ sources and headers; `.h` stays with the C grammar
+pub const CPP_EXTENSIONS: &[&str] = &["cpp", "cc", "cxx", "c++", "hpp", "hh", "hxx"];
+
+/// TypeScript and JavaScript sources, all parsed with the TypeScript grammars
+const TS_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+impl Grammar {
+    /// A parser for this language, or None when the grammar is not compiled in
+    pub fn parser(&self) -> Option<Box<dyn LanguageParser>> {
+        match self.name {
+            #[cfg(feature = "lang-go")]
+            "go" => Some(Box::new(GoParser::new())),
+            #[cfg(feature = "lang-rust")]
+            "rust" => Some(Box::new(RustParser::new())),
+            #[cfg(feature = "lang-c")]
+            "c" => Some(Box::new(CParser::new())),
+            #[cfg(feature = "lang-python")]
+            "python" => Some(Box::new(PythonParser::new())),
+            #[cfg(feature = "lang-typescript")]
+            "typescript" => Some(Box::new(TypeScriptParser::new())),
+            #[cfg(feature = "lang-java")]
+            "java" => Some(Box::new(JavaParser::new())),
+            #[cfg(feature = "lang-cpp")]
+            "cpp" => Some(Box::new(CppParser::new())),
+            _ => None,
+        }
+    }
+}
+
+/// Grammar by name ("go", "rust", "c", "python", "typescript", "java", "cpp")
+pub fn grammar(name: &str) -> Option<&'static Grammar> {
+    GRAMMARS.iter().find(|g| g.name == name)
+}
+
+/// Grammar for a source file by its extension, whether or not it is compiled in
+pub fn grammar_of(path: &Path) -> Option<&'static Grammar> {
+    let ext = path.extension()?.to_str()?;
+    GRAMMARS.iter().find(|g| g.extensions.contains(&ext))
 }
 
+// ============================================================================
+// Go Parser
+// ============================================================================
+
+#[cfg(feature = "lang-go")]
 pub struct GoParser {
     parser: Parser,
 }
 
+#[cfg(feature = "lang-go")]
+impl LanguageParser for GoParser {
+    fn language(&self) -> &'static str {
+        "go"
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        GoParser::parse_file(self, source, path)
+    }
+}
+
+#[cfg(feature = "lang-go")]
 impl GoParser {
     pub fn new() -> Self {
         let mut parser = Parser::new();
@@ -29,6 +228,7 @@ impl GoParser {
 
         let mut functions = Vec::new();
         let mut types = Vec::new();
+        let mut variables = Vec::new();
 
         // Extract package name for qualified names
         let package_name = self.extract_package_name(&root, source.as_bytes());
@@ -58,20 +258,72 @@ impl GoParser {
                 "type_declaration" => {
                     self.extract_types(&child, source.as_bytes(), &package_name, &path_prefix, &mut types);
                 }
+                "var_declaration" => {
+                    self.extract_variables(&child, source.as_bytes(), &package_name, &path_prefix, &mut variables);
+                    self.extract_var_functions(&child, source.as_bytes(), &package_name, &path_prefix, &mut functions);
+                }
                 _ => {}
             }
         }
 
-        let ast_hash = format!("{:016x}", hash_bytes(source.as_bytes()));
+        // Each platform's variant of a function gets its own qualified name (e.g., "pkg.readEvents@linux")
+        if let Some(constraint) = go_build_constraint(&root, source.as_bytes(), path) {
+            for func in functions.iter_mut().filter(|f| !f.qualified_name.contains('@')) {
+                func.qualified_name = format!("{}@{}", func.qualified_name, constraint);
+            }
+        }
+
+        let ast_hash = content_hash(source.as_bytes());
 
         Some(FileEntry {
             ast_hash,
             functions,
             types,
-            variables: Vec::new(),
+            variables,
+            impls: Vec::new(),
+            reference: false,
+            parse_error_ratio: error_ratio(&root, source.len()),
+            parse_errors: error_count(&root),
+            first_error_line: first_error_line(&root),
+            mtime_ms: 0,
+            imports: self.extract_imports(&root, source.as_bytes()),
+            is_test: false,
+            doc: None,
         })
     }
 
+    /// Imports of the file by the name calls use; blank imports are left out
+    fn extract_imports(&self, root: &tree_sitter::Node, source: &[u8]) -> Vec<Import> {
+        let mut specs = Vec::new();
+        let mut cursor = root.walk();
+        for decl in root.children(&mut cursor).filter(|c| c.kind() == "import_declaration") {
+            let mut decl_cursor = decl.walk();
+            for child in decl.named_children(&mut decl_cursor) {
+                match child.kind() {
+                    "import_spec" => specs.push(child),
+                    "import_spec_list" => {
+                        let mut list_cursor = child.walk();
+                        specs.extend(child.named_children(&mut list_cursor).filter(|c| c.kind() == "import_spec"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        specs
+            .iter()
+            .filter_map(|spec| {
+                let path = node_text(&spec.child_by_field_name("path")?, source).trim_matches(['"', '`']);
+                let alias = match spec.child_by_field_name("name") {
+                    Some(name) if name.kind() == "blank_identifier" => return None,
+                    Some(name) => node_text(&name, source),
+                    None => go_package_name(path),
+                };
+                Some(Import { alias: alias.to_string(), path: path.to_string() })
+            })
+            .collect()
+    }
+
     fn extract_package_name(&self, root: &tree_sitter::Node, source: &[u8]) -> String {
         let mut cursor = root.walk();
         for child in root.children(&mut cursor) {
@@ -135,16 +387,26 @@ impl GoParser {
             Scope::Internal
         };
 
-        // Extract call sites from function body
-        let calls = if let Some(body) = node.child_by_field_name("body") {
-            extract_calls(&body, source)
+        // Record declared types of the receiver and parameters
+        let mut param_types = BTreeMap::new();
+        for field in ["receiver", "parameters"] {
+            if let Some(params) = node.child_by_field_name(field) {
+                collect_go_param_types(&params, source, &mut param_types);
+            }
+        }
+
+        // Extract call sites and local variable types from function body
+        let (calls, local_types) = if let Some(body) = node.child_by_field_name("body") {
+            (extract_calls(&body, source), collect_go_local_types(&body, source, &param_types))
         } else {
-            Vec::new()
+            (Vec::new(), BTreeMap::new())
         };
 
-        // Compute AST hash from the function's source bytes
-        let func_source = &source[node.start_byte()..node.end_byte()];
-        let ast_hash = format!("{:016x}", hash_bytes(func_source));
+        let references = extract_go_references(node, source, &param_types);
+        let type_refs = extract_type_refs(node, source, &["parameters", "result"], None);
+        let is_entry = package == "main" && name == "main" && receiver.is_none();
+
+        let ast_hash = syntax_hash(node, source);
 
         Some(Function {
             name,
@@ -152,12 +414,34 @@ impl GoParser {
             ast_hash,
             line_start,
             line_end,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+            attr_line_start: None,
             signature,
+            attributes: Vec::new(),
+            doc: go_doc_comment(node, source),
             summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
             receiver,
+            trait_impl: None,
+            takes_self: false,
             scope,
+            is_entry,
+            exported_c: go_exports_to_c(node, source),
+            is_async: false,
+            param_types,
+            local_types,
             calls,
             called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references,
+            type_refs,
+            uses_types: Vec::new(),
+            global_refs: extract_global_refs(node, source, GlobalSyntax::Go),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
         })
     }
 
@@ -198,6 +482,11 @@ impl GoParser {
                 }
             }
             "type_identifier" => node_text(type_node, source).to_string(),
+            // Cache[K, V] -> Cache
+            "generic_type" => match type_node.child_by_field_name("type") {
+                Some(inner) => self.extract_type_name(&inner, source),
+                None => node_text(type_node, source).to_string(),
+            },
             _ => node_text(type_node, source).to_string(),
         }
     }
@@ -208,6 +497,11 @@ impl GoParser {
         source: &[u8],
         name: &str,
     ) -> String {
+        let type_params = node
+            .child_by_field_name("type_parameters")
+            .map(|n| node_text(&n, source))
+            .unwrap_or("");
+
         let params = node
             .child_by_field_name("parameters")
             .map(|n| node_text(&n, source))
@@ -218,7 +512,7 @@ impl GoParser {
             .map(|n| format!(" {}", node_text(&n, source)))
             .unwrap_or_default();
 
-        format!("func {}{}{}", name, params, result)
+        format!("func {}{}{}{}", name, type_params, params, result)
     }
 
     fn extract_types(
@@ -232,12 +526,167 @@ impl GoParser {
         // type_declaration contains type_spec children
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if child.kind() == "type_spec" {
-                if let Some(type_def) = self.extract_type_spec(&child, source, package, path_prefix) {
-                    types.push(type_def);
+            if child.kind() == "type_spec"
+                && let Some(type_def) = self.extract_type_spec(&child, source, package, path_prefix)
+            {
+                types.push(type_def);
+            }
+        }
+    }
+
+    /// Package-level `var` declarations, one Variable per declared name
+    fn extract_variables(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        package: &str,
+        path_prefix: &str,
+        variables: &mut Vec<Variable>,
+    ) {
+        let base = if !path_prefix.is_empty() { path_prefix } else { package };
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "var_spec_list" => self.extract_variables(&child, source, package, path_prefix, variables),
+                "var_spec" => {
+                    let type_name =
+                        child.child_by_field_name("type").map(|t| node_text(&t, source).to_string()).unwrap_or_default();
+                    let mut names = child.walk();
+                    for name_node in child.children_by_field_name("name", &mut names) {
+                        let name = node_text(&name_node, source).to_string();
+                        if name == "_" {
+                            continue;
+                        }
+                        let scope = if name.starts_with(|c: char| c.is_uppercase()) { Scope::Public } else { Scope::Internal };
+                        variables.push(Variable {
+                            qualified_name: if base.is_empty() { name.clone() } else { format!("{}.{}", base, name) },
+                            name,
+                            type_name: type_name.clone(),
+                            line_start: child.start_position().row as u32 + 1,
+                            line_end: child.end_position().row as u32 + 1,
+                            scope,
+                            is_const: false,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Package-level `var`s holding function literals or tables of functions, such as
+    /// `var handler = func(...) {...}` or `var routes = map[string]func(){...}`, as functions
+    /// named after the variable, so the calls and function references in them are indexed
+    fn extract_var_functions(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        package: &str,
+        path_prefix: &str,
+        functions: &mut Vec<Function>,
+    ) {
+        let base = if !path_prefix.is_empty() { path_prefix } else { package };
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "var_spec_list" {
+                self.extract_var_functions(&child, source, package, path_prefix, functions);
+                continue;
+            }
+            let Some(values) = child.child_by_field_name("value").filter(|_| child.kind() == "var_spec") else {
+                continue;
+            };
+            let mut names = child.walk();
+            let mut value_cursor = values.walk();
+            for (name_node, value) in
+                child.children_by_field_name("name", &mut names).zip(values.named_children(&mut value_cursor))
+            {
+                let name = node_text(&name_node, source).to_string();
+                let is_table = value.kind() == "composite_literal"
+                    && value
+                        .child_by_field_name("type")
+                        .is_some_and(|t| node_text(&t, source).to_lowercase().contains("func"));
+                if name == "_" || !is_table && first_descendant_of_kind(&value, &["func_literal"]).is_none() {
+                    continue;
                 }
+                if let Some(func) = self.extract_var_function(&child, &value, name, source, base, is_table) {
+                    functions.push(func);
+                }
+            }
+        }
+    }
+
+    fn extract_var_function(
+        &self,
+        spec: &tree_sitter::Node,
+        value: &tree_sitter::Node,
+        name: String,
+        source: &[u8],
+        base: &str,
+        is_table: bool,
+    ) -> Option<Function> {
+        let qualified_name = if base.is_empty() { name.clone() } else { format!("{}.{}", base, name) };
+        let scope = if name.starts_with(|c: char| c.is_uppercase()) { Scope::Public } else { Scope::Internal };
+
+        // The value up to the first body: "var handler = func(w http.ResponseWriter, r *http.Request)"
+        let body_start =
+            first_descendant_of_kind(value, &["block", "literal_value"]).map_or(value.end_byte(), |b| b.start_byte());
+        let head = std::str::from_utf8(&source[value.start_byte()..body_start]).ok()?;
+        let signature = format!("var {} = {}", name, head.split_whitespace().collect::<Vec<_>>().join(" "));
+
+        let mut param_types = BTreeMap::new();
+        let mut local_types = BTreeMap::new();
+        let mut type_refs = Vec::new();
+        if value.kind() == "func_literal" {
+            if let Some(params) = value.child_by_field_name("parameters") {
+                collect_go_param_types(&params, source, &mut param_types);
+            }
+            if let Some(body) = value.child_by_field_name("body") {
+                local_types = collect_go_local_types(&body, source, &param_types);
             }
+            type_refs = extract_type_refs(value, source, &["parameters", "result"], None);
+        }
+
+        let mut calls = extract_calls(value, source);
+        if is_table && let Some(body) = value.child_by_field_name("body") {
+            collect_func_table_refs(&body, source, &mut calls);
+            calls.sort_by_key(|c| c.line);
         }
+
+        Some(Function {
+            name,
+            qualified_name,
+            ast_hash: syntax_hash(spec, source),
+            line_start: spec.start_position().row as u32 + 1,
+            line_end: spec.end_position().row as u32 + 1,
+            byte_start: spec.start_byte() as u32,
+            byte_end: spec.end_byte() as u32,
+            attr_line_start: None,
+            signature,
+            attributes: Vec::new(),
+            doc: None,
+            summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
+            receiver: None,
+            trait_impl: None,
+            takes_self: false,
+            scope,
+            is_entry: false,
+            exported_c: false,
+            is_async: false,
+            param_types,
+            local_types,
+            calls,
+            called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: Vec::new(),
+            type_refs,
+            uses_types: Vec::new(),
+            global_refs: extract_global_refs(value, source, GlobalSyntax::Go),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
+        })
     }
 
     fn extract_type_spec(
@@ -269,6 +718,20 @@ impl GoParser {
         let line_start = node.start_position().row as u32 + 1;
         let line_end = node.end_position().row as u32 + 1;
 
+        // Interfaces declare their method set; struct methods are separate declarations
+        let methods = if kind == TypeKind::Interface {
+            go_interface_methods(&type_node, source)
+        } else {
+            Vec::new()
+        };
+        let fields = if kind == TypeKind::Struct { go_struct_fields(&type_node, source) } else { Vec::new() };
+
+        // `type X ...` is documented above the declaration, a spec in a `type (...)` group above itself
+        let doc = match node.parent().filter(|decl| decl.start_position().row == node.start_position().row) {
+            Some(decl) => go_doc_comment(&decl, source),
+            None => go_doc_comment(node, source),
+        };
+
         Some(TypeDef {
             name,
             qualified_name,
@@ -276,7 +739,12 @@ impl GoParser {
             line_start,
             line_end,
             summary: None,
-            methods: Vec::new(), // TODO: populate from method declarations
+            methods,
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields,
+            variants: Vec::new(),
+            doc,
         })
     }
 
@@ -286,109 +754,1114 @@ fn node_text<'a>(node: &tree_sitter::Node, source: &'a [u8]) -> &'a str {
     node.utf8_text(source).unwrap_or("")
 }
 
-/// Extract all call sites from an AST node (shared across all parsers)
+/// Source text of an expression with Rust `.await` suffixes dropped, so
+/// `client.get(url).send().await.json` reads as `client.get(url).send().json`
+fn text_without_await(node: &tree_sitter::Node, source: &[u8]) -> String {
+    let mut text = String::new();
+    let mut pos = node.start_byte();
+    push_without_await(node, source, &mut pos, &mut text);
+    text.push_str(&String::from_utf8_lossy(&source[pos..node.end_byte()]));
+    text
+}
+
+/// Append the source up to the end of each `.await` operand under `node`, skipping the suffix
+fn push_without_await(node: &tree_sitter::Node, source: &[u8], pos: &mut usize, text: &mut String) {
+    if node.kind() == "await_expression"
+        && let Some(operand) = node.named_child(0)
+        && operand.end_byte() < node.end_byte()
+    {
+        push_without_await(&operand, source, pos, text);
+        text.push_str(&String::from_utf8_lossy(&source[*pos..operand.end_byte()]));
+        *pos = node.end_byte();
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        push_without_await(&child, source, pos, text);
+    }
+}
+
+/// Extract all call sites from an AST node (shared across all parsers), in line order
 fn extract_calls(node: &tree_sitter::Node, source: &[u8]) -> Vec<CallSite> {
+    extract_calls_except(node, source, &[])
+}
+
+/// Call sites under `node`, leaving out those inside the `skip` subtrees (nested functions)
+fn extract_calls_except(node: &tree_sitter::Node, source: &[u8], skip: &[tree_sitter::Node]) -> Vec<CallSite> {
     let mut calls = Vec::new();
-    collect_calls(node, source, &mut calls);
+    collect_calls(node, source, skip, &mut calls);
+    calls.sort_by_key(|c| c.line);
     calls
 }
 
 /// Recursively collect call_expression nodes
-fn collect_calls(node: &tree_sitter::Node, source: &[u8], calls: &mut Vec<CallSite>) {
+fn collect_calls(node: &tree_sitter::Node, source: &[u8], skip: &[tree_sitter::Node], calls: &mut Vec<CallSite>) {
+    if skip.contains(node) {
+        return;
+    }
     if node.kind() == "call_expression" {
-        if let Some(func_node) = node.child_by_field_name("function") {
-            let raw = node_text(&func_node, source).to_string();
-            let line = node.start_position().row as u32 + 1;
-            calls.push(CallSite {
-                target: "[unresolved]".to_string(),
-                raw,
-                line,
-            });
-        }
+        calls.extend(call_site(node, source));
+    }
+    // Go: a generic call with one argument, Keys[string](m), parses as a conversion to a generic
+    // type; the two cannot be told apart, and calls are the common case
+    if node.kind() == "type_conversion_expression"
+        && let Some(generic) = node.child_by_field_name("type").filter(|t| t.kind() == "generic_type")
+        && let Some(func_node) = generic.child_by_field_name("type")
+    {
+        calls.push(call_site_of(node, &func_node, source));
     }
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_calls(&child, source, calls);
+        collect_calls(&child, source, skip, calls);
     }
 }
 
-/// Convert a file path to a prefix for qualified names.
-/// e.g., "./cmd/foo/main.go" -> "cmd/foo"
-/// e.g., "internal/bar/main.go" -> "internal/bar"
-fn path_to_prefix(path: &str) -> String {
-    // Remove leading "./"
-    let path = path.strip_prefix("./").unwrap_or(path);
+/// An unresolved call site for a call node with a `function` field
+fn call_site(node: &tree_sitter::Node, source: &[u8]) -> Option<CallSite> {
+    let func_node = node.child_by_field_name("function")?;
+    Some(call_site_of(node, &func_node, source))
+}
 
-    // Remove the filename, keep directory
-    if let Some(dir) = std::path::Path::new(path).parent() {
-        let dir_str = dir.to_string_lossy();
-        if dir_str.is_empty() || dir_str == "." {
-            // File is in root, use filename without extension
-            std::path::Path::new(path)
-                .file_stem()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_default()
-        } else {
-            dir_str.to_string()
+/// An unresolved call site for `node`, calling the function expression `func_node`
+fn call_site_of(node: &tree_sitter::Node, func_node: &tree_sitter::Node, source: &[u8]) -> CallSite {
+    let (callee_name, receiver_expr) = split_callee(func_node, source);
+    CallSite {
+        target: "[unresolved]".to_string(),
+        raw: text_without_await(func_node, source),
+        callee_name,
+        receiver_expr,
+        candidates: Vec::new(),
+        resolved_by: None,
+        line: node.start_position().row as u32 + 1,
+        column: node.start_position().column as u32 + 1,
+        byte_start: node.start_byte() as u32,
+        byte_end: node.end_byte() as u32,
+    }
+}
+
+/// Split a call's function expression into (callee name, receiver expression).
+/// e.g., "s.store.Get" -> ("Get", Some("s.store")), "Foo::new" -> ("new", None)
+fn split_callee(func_node: &tree_sitter::Node, source: &[u8]) -> (String, Option<String>) {
+    match func_node.kind() {
+        // Go: operand.field
+        "selector_expression" => {
+            let callee = func_node.child_by_field_name("field").map(|n| node_text(&n, source));
+            let receiver = func_node.child_by_field_name("operand").map(|n| node_text(&n, source));
+            if let Some(callee) = callee {
+                return (callee.to_string(), receiver.map(String::from));
+            }
         }
-    } else {
-        String::new()
+        // Rust: value.field, C: argument.field / argument->field
+        "field_expression" => {
+            let callee = func_node.child_by_field_name("field").map(|n| node_text(&n, source));
+            let receiver = func_node
+                .child_by_field_name("value")
+                .or_else(|| func_node.child_by_field_name("argument"))
+                .map(|n| text_without_await(&n, source));
+            if let Some(callee) = callee {
+                return (callee.to_string(), receiver);
+            }
+        }
+        // TypeScript: object.property, including this.method
+        "member_expression" => {
+            let callee = func_node.child_by_field_name("property").map(|n| node_text(&n, source));
+            let receiver = func_node.child_by_field_name("object").map(|n| node_text(&n, source));
+            if let Some(callee) = callee {
+                return (callee.to_string(), receiver.map(String::from));
+            }
+        }
+        // Python: object.attribute
+        "attribute" => {
+            let callee = func_node.child_by_field_name("attribute").map(|n| node_text(&n, source));
+            let receiver = func_node.child_by_field_name("object").map(|n| node_text(&n, source));
+            if let Some(callee) = callee {
+                return (callee.to_string(), receiver.map(String::from));
+            }
+        }
+        // Rust: path::name is a path, not a receiver
+        "scoped_identifier" => {
+            if let Some(name) = func_node.child_by_field_name("name") {
+                return (node_text(&name, source).to_string(), None);
+            }
+        }
+        // C++: ns::name, where name may itself be qualified or templated
+        "qualified_identifier" => {
+            if let Some(name) = func_node.child_by_field_name("name") {
+                return split_callee(&name, source);
+            }
+        }
+        // C++: max<int>()
+        "template_function" => {
+            if let Some(name) = func_node.child_by_field_name("name") {
+                return split_callee(&name, source);
+            }
+        }
+        // Rust: foo::<T>()
+        "generic_function" => {
+            if let Some(inner) = func_node.child_by_field_name("function") {
+                return split_callee(&inner, source);
+            }
+        }
+        _ => {}
     }
+
+    let raw = node_text(func_node, source);
+    let callee = raw.rsplit(['.', ':']).next().unwrap_or(raw);
+    (callee.to_string(), None)
 }
 
-/// Convert a file path to a suffix for init function disambiguation.
-/// e.g., "./internal/foo/bar.go" -> "bar"
-fn path_to_file_suffix(path: &str) -> String {
-    std::path::Path::new(path)
-        .file_stem()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_default()
+/// Go predeclared and Rust prelude types, never indexed
+const BUILTIN_TYPES: &[&str] = &[
+    "bool", "byte", "complex64", "complex128", "error", "float32", "float64", "int", "int8", "int16",
+    "int32", "int64", "rune", "string", "uint", "uint8", "uint16", "uint32", "uint64", "uintptr", "any",
+    "Box", "Option", "Result", "String", "Vec",
+];
+
+/// Type names in the given signature fields plus types constructed in the body, in line order.
+/// `self_type` replaces Rust's `Self`.
+fn extract_type_refs(
+    node: &tree_sitter::Node,
+    source: &[u8],
+    signature_fields: &[&str],
+    self_type: Option<&str>,
+) -> Vec<TypeRef> {
+    let mut refs = Vec::new();
+    for field in signature_fields {
+        if let Some(child) = node.child_by_field_name(field) {
+            collect_type_names(&child, source, self_type, false, &mut refs);
+        }
+    }
+    if let Some(body) = node.child_by_field_name("body") {
+        collect_constructions(&body, source, self_type, &mut refs);
+    }
+    refs.sort();
+    refs.dedup();
+    refs
 }
 
-fn hash_bytes(input: &[u8]) -> u64 {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    let mut hasher = DefaultHasher::new();
-    input.hash(&mut hasher);
-    hasher.finish()
+/// Collect named types inside a type expression, e.g. "map[string]*store.Entry" -> store.Entry
+fn collect_type_names(
+    node: &tree_sitter::Node,
+    source: &[u8],
+    self_type: Option<&str>,
+    constructs: bool,
+    refs: &mut Vec<TypeRef>,
+) {
+    match node.kind() {
+        // Go pkg.Type, Rust path::Type
+        "type_identifier" | "qualified_type" | "scoped_type_identifier" => {
+            let mut name = node_text(node, source);
+            if name == "Self" {
+                match self_type {
+                    Some(t) => name = t,
+                    None => return,
+                }
+            }
+            if !BUILTIN_TYPES.contains(&name) {
+                refs.push(TypeRef {
+                    line: node.start_position().row as u32 + 1,
+                    name: name.to_string(),
+                    constructs,
+                });
+            }
+        }
+        // Parameter names and patterns are not types
+        "identifier" | "field_identifier" => {}
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                collect_type_names(&child, source, self_type, constructs, refs);
+            }
+        }
+    }
 }
 
-// ============================================================================
-// Rust Parser
-// ============================================================================
+/// Collect types constructed in a function body: Go composite literals, Rust struct
+/// expressions, and Rust `Type::new`-style associated function calls
+fn collect_constructions(node: &tree_sitter::Node, source: &[u8], self_type: Option<&str>, refs: &mut Vec<TypeRef>) {
+    match node.kind() {
+        "composite_literal" => {
+            if let Some(type_node) = node.child_by_field_name("type") {
+                collect_type_names(&type_node, source, self_type, true, refs);
+            }
+        }
+        "struct_expression" => {
+            if let Some(name_node) = node.child_by_field_name("name") {
+                collect_type_names(&name_node, source, self_type, true, refs);
+            }
+        }
+        "call_expression" => {
+            if let Some(func_node) = node.child_by_field_name("function")
+                && func_node.kind() == "scoped_identifier"
+                && let (Some(path), Some(name)) =
+                    (func_node.child_by_field_name("path"), func_node.child_by_field_name("name"))
+                && is_constructor_name(node_text(&name, source))
+            {
+                let path_text = node_text(&path, source);
+                let type_name = if path_text == "Self" { self_type } else { Some(path_text) };
+                let last = path_text.rsplit("::").next().unwrap_or(path_text);
+                if let Some(type_name) = type_name
+                    && last.starts_with(char::is_uppercase)
+                    && !BUILTIN_TYPES.contains(&last)
+                {
+                    refs.push(TypeRef {
+                        line: node.start_position().row as u32 + 1,
+                        name: type_name.to_string(),
+                        constructs: true,
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
 
-pub struct RustParser {
-    parser: Parser,
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_constructions(&child, source, self_type, refs);
+    }
 }
 
-impl RustParser {
-    pub fn new() -> Self {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_rust::LANGUAGE.into())
-            .expect("failed to load Rust grammar");
-        Self { parser }
-    }
+/// Associated functions that conventionally return a new value of their type
+fn is_constructor_name(name: &str) -> bool {
+    name == "new" || name == "default" || name.starts_with("new_") || name.starts_with("with_") || name.starts_with("from")
+}
 
-    pub fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
-        let tree = self.parser.parse(source, None)?;
-        let root = tree.root_node();
+/// Which names in a function body are taken as possible globals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GlobalSyntax {
+    /// Identifiers not bound by the function's parameters or declarations
+    Go,
+    C,
+    /// SCREAMING_CASE names, the naming convention for statics
+    Rust,
+}
 
-        let mut functions = Vec::new();
-        let mut types = Vec::new();
+/// Nodes that bind local names, with the field holding the names (Go)
+const GO_BINDINGS: &[(&str, &str)] = &[
+    ("parameter_declaration", "name"),
+    ("variadic_parameter_declaration", "name"),
+    ("short_var_declaration", "left"),
+    ("var_spec", "name"),
+    ("const_spec", "name"),
+    ("range_clause", "left"),
+    ("type_switch_statement", "alias"),
+    ("receive_statement", "left"),
+];
+
+/// Go functions a body uses as values, in line order: names and `pkg.Func` or `recv.Method`
+/// selectors passed as call arguments or held in composite literal elements. Names bound in
+/// the function are skipped; the resolver keeps only references to indexed functions.
+fn extract_go_references(
+    node: &tree_sitter::Node,
+    source: &[u8],
+    param_types: &BTreeMap<String, String>,
+) -> Vec<CallSite> {
+    let Some(body) = node.child_by_field_name("body") else {
+        return Vec::new();
+    };
+    let mut locals = HashSet::new();
+    collect_bindings(node, source, GO_BINDINGS, &mut locals);
+
+    let mut refs = Vec::new();
+    collect_go_references(&body, source, &locals, param_types, &mut refs);
+    refs.sort_by_key(|c| c.line);
+    refs
+}
 
-        // Use module path from file location for qualified names
-        // e.g., "src/parser.rs" -> "parser", "src/commands/index.rs" -> "commands::index"
-        let module_path = rust_path_to_module(path);
+fn collect_go_references(
+    node: &tree_sitter::Node,
+    source: &[u8],
+    locals: &HashSet<&str>,
+    param_types: &BTreeMap<String, String>,
+    refs: &mut Vec<CallSite>,
+) {
+    let mut cursor = node.walk();
+    let values: Vec<tree_sitter::Node> = match node.kind() {
+        "argument_list" => node.named_children(&mut cursor).collect(),
+        // Struct fields and map values: `Handler: handleX`, `"x": handleX`
+        "keyed_element" => node.child_by_field_name("value").and_then(|v| v.named_child(0)).into_iter().collect(),
+        // Slice and array elements: `[]func(){first, second}`
+        "literal_value" => node
+            .named_children(&mut cursor)
+            .filter(|c| c.kind() == "literal_element")
+            .filter_map(|c| c.named_child(0))
+            .collect(),
+        _ => Vec::new(),
+    };
+    for value in values {
+        let is_func_value = match value.kind() {
+            "identifier" => {
+                let name = node_text(&value, source);
+                !locals.contains(name) && !matches!(name, "nil" | "true" | "false" | "iota")
+            }
+            // A package's function, or a method value on a typed parameter or receiver
+            "selector_expression" => value.child_by_field_name("operand").is_some_and(|operand| {
+                operand.kind() == "identifier" && {
+                    let operand = node_text(&operand, source);
+                    !locals.contains(operand) || param_types.contains_key(operand)
+                }
+            }),
+            _ => false,
+        };
+        if is_func_value {
+            refs.push(call_site_of(&value, &value, source));
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_go_references(&child, source, locals, param_types, refs);
+    }
+}
+
+/// Nodes that bind local names, with the field holding the names (C)
+const C_BINDINGS: &[(&str, &str)] = &[("parameter_declaration", "declarator"), ("declaration", "declarator")];
+
+/// Expressions whose first operand stays the variable being accessed, e.g. `cfg.Port`, `cache[k]`, `*p`
+const ACCESS_WRAPPERS: &[&str] = &[
+    "selector_expression",
+    "field_expression",
+    "index_expression",
+    "subscript_expression",
+    "pointer_expression",
+    "parenthesized_expression",
+    "unary_expression",
+    "call_expression",
+    "try_expression",
+];
+
+/// Assignments; the root variable of their `left` field is written
+const ASSIGNMENTS: &[&str] = &["assignment_statement", "assignment_expression", "compound_assignment_expr"];
+
+/// Increments and decrements; the root variable of their operand is written
+const UPDATES: &[&str] = &["inc_statement", "dec_statement", "update_expression"];
+
+/// Methods (lowercased) taken to mutate their receiver, e.g. sync.Map.Store, Vec::push, AtomicU64::fetch_add
+const MUTATING_METHODS: &[&str] = &[
+    "add", "borrow_mut", "clear", "compare_exchange", "delete", "extend", "fetch_add", "fetch_sub", "get_mut", "insert",
+    "loadorstore", "push", "remove", "replace", "set", "store", "swap", "take",
+];
+
+/// Names in a function body that may be globals, first use per name and access kind, in line order.
+/// Name-based: resolution later keeps only names matching indexed package-level variables and statics.
+fn extract_global_refs(node: &tree_sitter::Node, source: &[u8], syntax: GlobalSyntax) -> Vec<GlobalRef> {
+    let Some(body) = node.child_by_field_name("body") else {
+        return Vec::new();
+    };
+
+    let bindings = match syntax {
+        GlobalSyntax::Go => GO_BINDINGS,
+        GlobalSyntax::C => C_BINDINGS,
+        GlobalSyntax::Rust => &[],
+    };
+    let mut locals = HashSet::new();
+    collect_bindings(node, source, bindings, &mut locals);
+
+    let mut refs = Vec::new();
+    collect_global_refs(&body, source, syntax, &locals, &mut refs);
+    refs.sort_by(|a, b| (&a.name, a.write, a.line).cmp(&(&b.name, b.write, b.line)));
+    refs.dedup_by(|a, b| a.name == b.name && a.write == b.write);
+    refs.sort();
+    refs
+}
+
+/// Names bound anywhere in the function, without regard to block scope
+fn collect_bindings<'a>(
+    node: &tree_sitter::Node,
+    source: &'a [u8],
+    bindings: &[(&str, &str)],
+    locals: &mut HashSet<&'a str>,
+) {
+    if let Some((_, field)) = bindings.iter().find(|(kind, _)| *kind == node.kind()) {
+        let mut cursor = node.walk();
+        for child in node.children_by_field_name(field, &mut cursor) {
+            collect_bound_names(&child, source, locals);
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_bindings(&child, source, bindings, locals);
+    }
+}
+
+/// Identifiers a binding introduces; C declarators are followed to their name, skipping
+/// initializers and array sizes
+fn collect_bound_names<'a>(node: &tree_sitter::Node, source: &'a [u8], locals: &mut HashSet<&'a str>) {
+    if node.kind() == "identifier" {
+        locals.insert(node_text(node, source));
+    } else if let Some(declarator) = node.child_by_field_name("declarator") {
+        collect_bound_names(&declarator, source, locals);
+    } else {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            collect_bound_names(&child, source, locals);
+        }
+    }
+}
+
+fn collect_global_refs(
+    node: &tree_sitter::Node,
+    source: &[u8],
+    syntax: GlobalSyntax,
+    locals: &HashSet<&str>,
+    refs: &mut Vec<GlobalRef>,
+) {
+    match node.kind() {
+        "identifier" => refs.extend(global_ref(node, source, syntax, locals)),
+        // A Rust path names one item; the segments before it are modules
+        "scoped_identifier" if syntax == GlobalSyntax::Rust => refs.extend(global_ref(node, source, syntax, locals)),
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                collect_global_refs(&child, source, syntax, locals, refs);
+            }
+        }
+    }
+}
+
+/// A possible global use at `node`, or None for locals, called functions, and (in Rust)
+/// names that are not SCREAMING_CASE
+fn global_ref(node: &tree_sitter::Node, source: &[u8], syntax: GlobalSyntax, locals: &HashSet<&str>) -> Option<GlobalRef> {
+    let text = node_text(node, source);
+    let simple = text.rsplit("::").next().unwrap_or(text);
+    let candidate = match syntax {
+        GlobalSyntax::Rust => {
+            simple.chars().any(|c| c.is_ascii_uppercase())
+                && simple.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        }
+        GlobalSyntax::Go | GlobalSyntax::C => !locals.contains(text),
+    };
+    if !candidate || is_callee(node) {
+        return None;
+    }
+
+    // Go `pkg.Name` may be another package's variable; resolution tries it before the root
+    let mut name = text.to_string();
+    if syntax == GlobalSyntax::Go
+        && let Some(parent) = node.parent()
+        && parent.kind() == "selector_expression"
+        && parent.child_by_field_name("operand") == Some(*node)
+        && !is_callee(&parent)
+        && let Some(field) = parent.child_by_field_name("field")
+    {
+        name = format!("{}.{}", text, node_text(&field, source));
+    }
+
+    // Walk out to the whole access (`cache[k].hits`), noting mutating method calls on the way
+    let mut access = *node;
+    let mut write = false;
+    while let Some(parent) = access.parent() {
+        if !ACCESS_WRAPPERS.contains(&parent.kind()) || parent.named_child(0) != Some(access) {
+            break;
+        }
+        if parent.kind() == "call_expression"
+            && let Some(method) = access.child_by_field_name("field")
+        {
+            write |= MUTATING_METHODS.contains(&node_text(&method, source).to_lowercase().as_str());
+        }
+        access = parent;
+    }
+
+    // Assignment targets may sit in an expression_list (Go `a, b = ...`)
+    if let Some(parent) = access.parent() {
+        let (statement, target) = match parent.kind() {
+            "expression_list" => (parent.parent(), parent),
+            _ => (Some(parent), access),
+        };
+        if let Some(statement) = statement {
+            let assigned = ASSIGNMENTS.contains(&statement.kind()) && statement.child_by_field_name("left") == Some(target);
+            write |= assigned || UPDATES.contains(&statement.kind());
+        }
+    }
+
+    Some(GlobalRef { line: node.start_position().row as u32 + 1, name, write })
+}
+
+/// Whether `node` is the function a call expression invokes
+fn is_callee(node: &tree_sitter::Node) -> bool {
+    node.parent()
+        .is_some_and(|p| p.kind() == "call_expression" && p.child_by_field_name("function") == Some(*node))
+}
+
+/// Collect parameter names and their declared types from a Go parameter_list
+fn collect_go_param_types(
+    params: &tree_sitter::Node,
+    source: &[u8],
+    param_types: &mut BTreeMap<String, String>,
+) {
+    let mut cursor = params.walk();
+    for param in params.children(&mut cursor) {
+        if !matches!(param.kind(), "parameter_declaration" | "variadic_parameter_declaration") {
+            continue;
+        }
+        let Some(type_node) = param.child_by_field_name("type") else {
+            continue;
+        };
+        let type_text = node_text(&type_node, source);
+
+        let mut name_cursor = param.walk();
+        for name in param.children_by_field_name("name", &mut name_cursor) {
+            param_types.insert(node_text(&name, source).to_string(), type_text.to_string());
+        }
+    }
+}
+
+/// Go locals bound exactly once in `body` with `name := Type{...}`, `name := &Type{...}` or
+/// `name := f(...)`, mapped to the literal's type text or to "f()" for a call, whose return
+/// type the resolver looks up. Names bound or assigned more than once, or shadowing a
+/// parameter, are left out.
+fn collect_go_local_types(
+    body: &tree_sitter::Node,
+    source: &[u8],
+    param_types: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut bindings: BTreeMap<&str, Option<String>> = BTreeMap::new();
+    collect_go_bindings(body, source, &mut bindings);
+    bindings
+        .into_iter()
+        .filter(|(name, _)| !param_types.contains_key(*name))
+        .filter_map(|(name, type_text)| Some((name.to_string(), type_text?)))
+        .collect()
+}
+
+fn collect_go_bindings<'a>(
+    node: &tree_sitter::Node,
+    source: &'a [u8],
+    bindings: &mut BTreeMap<&'a str, Option<String>>,
+) {
+    match node.kind() {
+        "short_var_declaration" | "assignment_statement" | "range_clause" => {
+            let names: Vec<tree_sitter::Node> = node
+                .child_by_field_name("left")
+                .map(|left| {
+                    let mut cursor = left.walk();
+                    left.named_children(&mut cursor).filter(|n| n.kind() == "identifier").collect()
+                })
+                .unwrap_or_default();
+            let values: Vec<tree_sitter::Node> = node
+                .child_by_field_name("right")
+                .map(|right| {
+                    let mut cursor = right.walk();
+                    right.named_children(&mut cursor).collect()
+                })
+                .unwrap_or_default();
+            let single = node.kind() == "short_var_declaration" && names.len() == 1 && values.len() == 1;
+            for name in names {
+                let type_text = if single { go_value_type(&values[0], source) } else { None };
+                bind(bindings, node_text(&name, source), type_text);
+            }
+        }
+        "var_spec" => {
+            let mut cursor = node.walk();
+            for name in node.children_by_field_name("name", &mut cursor) {
+                bind(bindings, node_text(&name, source), None);
+            }
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_go_bindings(&child, source, bindings);
+    }
+}
+
+/// Rust locals bound once with `let` mapped to their declared type, the type of the struct
+/// literal they hold, or "f()" when bound to the result of calling the path f (e.g. "Store::new()")
+#[cfg(feature = "lang-rust")]
+fn collect_rust_local_types(
+    body: &tree_sitter::Node,
+    source: &[u8],
+    param_types: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut bindings: BTreeMap<&str, Option<String>> = BTreeMap::new();
+    collect_rust_bindings(body, source, &mut bindings);
+    bindings
+        .into_iter()
+        .filter(|(name, _)| !param_types.contains_key(*name))
+        .filter_map(|(name, type_text)| Some((name.to_string(), type_text?)))
+        .collect()
+}
+
+#[cfg(feature = "lang-rust")]
+fn collect_rust_bindings<'a>(
+    node: &tree_sitter::Node,
+    source: &'a [u8],
+    bindings: &mut BTreeMap<&'a str, Option<String>>,
+) {
+    if node.kind() == "let_declaration"
+        && let Some(pattern) = node.child_by_field_name("pattern").filter(|p| p.kind() == "identifier")
+    {
+        let type_text = match node.child_by_field_name("type") {
+            Some(declared) => Some(node_text(&declared, source).to_string()),
+            None => node.child_by_field_name("value").and_then(|value| rust_value_type(&value, source)),
+        };
+        bind(bindings, node_text(&pattern, source), type_text);
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_rust_bindings(&child, source, bindings);
+    }
+}
+
+/// Type of a struct literal or a reference to one, or "f()" for a call of the path `f`
+#[cfg(feature = "lang-rust")]
+fn rust_value_type(value: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    match value.kind() {
+        "struct_expression" => value.child_by_field_name("name").map(|name| node_text(&name, source).to_string()),
+        "reference_expression" => rust_value_type(&value.child_by_field_name("value")?, source),
+        "call_expression" => {
+            let function = value.child_by_field_name("function")?;
+            (function.kind() == "scoped_identifier").then(|| format!("{}()", node_text(&function, source)))
+        }
+        _ => None,
+    }
+}
+
+/// Record a binding; a second binding of the same name makes its type unknown
+fn bind<'a>(bindings: &mut BTreeMap<&'a str, Option<String>>, name: &'a str, type_text: Option<String>) {
+    if name == "_" {
+        return;
+    }
+    bindings.entry(name).and_modify(|t| *t = None).or_insert(type_text);
+}
+
+/// Type text of a composite literal or its address, or "f()" for a call of `f`
+fn go_value_type(value: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let literal_type = |literal: &tree_sitter::Node| {
+        literal
+            .child_by_field_name("type")
+            .filter(|t| matches!(t.kind(), "type_identifier" | "qualified_type" | "generic_type"))
+            .map(|t| node_text(&t, source).to_string())
+    };
+    match value.kind() {
+        "composite_literal" => literal_type(value),
+        "unary_expression" => {
+            let operand = value.child_by_field_name("operand")?;
+            let is_address = value.child_by_field_name("operator").is_some_and(|op| node_text(&op, source) == "&");
+            (is_address && operand.kind() == "composite_literal")
+                .then(|| literal_type(&operand).map(|t| format!("*{t}")))?
+        }
+        "call_expression" => {
+            let function = value.child_by_field_name("function")?;
+            matches!(function.kind(), "identifier" | "selector_expression")
+                .then(|| format!("{}()", node_text(&function, source)))
+        }
+        _ => None,
+    }
+}
+
+/// The comments directly above a Go declaration, top to bottom
+fn go_comments_above<'a>(node: &tree_sitter::Node, source: &'a [u8]) -> Vec<&'a str> {
+    let mut comments = Vec::new();
+    let mut next_row = node.start_position().row;
+    let mut sibling = node.prev_sibling();
+    while let Some(comment) = sibling.filter(|s| s.kind() == "comment" && s.end_position().row + 1 == next_row) {
+        comments.push(node_text(&comment, source));
+        next_row = comment.start_position().row;
+        sibling = comment.prev_sibling();
+    }
+    comments.reverse();
+    comments
+}
+
+/// Text of the comment lines directly above a Go declaration, without comment markers.
+/// `//go:generate`-style and `//export` directives are left out, as in go doc.
+fn go_doc_comment(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let mut lines = Vec::new();
+    for comment in go_comments_above(node, source) {
+        if let Some(line) = comment.strip_prefix("//") {
+            let is_directive = ["line ", "extern ", "export "].iter().any(|d| line.starts_with(d))
+                || line.split_once(':').is_some_and(|(word, _)| {
+                    !word.is_empty() && word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+                });
+            if !is_directive {
+                lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end());
+            }
+        } else {
+            let block = comment.trim_start_matches("/*").trim_end_matches("*/");
+            lines.extend(block.lines().map(str::trim).filter(|l| !l.is_empty()));
+        }
+    }
+    let doc = lines.join("\n");
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+/// Whether a Go function has a cgo `//export Name` directive, making it callable from C
+fn go_exports_to_c(node: &tree_sitter::Node, source: &[u8]) -> bool {
+    go_comments_above(node, source).iter().any(|c| c.starts_with("//export "))
+}
+
+/// Names of the methods a Go interface_type declares directly (embedded interfaces are not expanded)
+fn go_interface_methods(node: &tree_sitter::Node, source: &[u8]) -> Vec<String> {
+    let mut methods = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "method_elem"
+            && let Some(name) = child.child_by_field_name("name")
+        {
+            methods.push(node_text(&name, source).to_string());
+        }
+    }
+    methods
+}
+
+/// Fields of a Go struct_type in declaration order, one per name ("X, Y int" is two fields)
+fn go_struct_fields(node: &tree_sitter::Node, source: &[u8]) -> Vec<Field> {
+    let Some(list) = first_descendant_of_kind(node, &["field_declaration_list"]) else {
+        return Vec::new();
+    };
+    let mut fields = Vec::new();
+    let mut cursor = list.walk();
+    for decl in list.children(&mut cursor).filter(|c| c.kind() == "field_declaration") {
+        let Some(type_node) = decl.child_by_field_name("type") else {
+            continue;
+        };
+        let tag = decl
+            .child_by_field_name("tag")
+            .map(|t| node_text(&t, source))
+            .map(|t| t.get(1..t.len().saturating_sub(1)).unwrap_or(t).to_string());
+
+        let mut names_cursor = decl.walk();
+        let names: Vec<&str> =
+            decl.children_by_field_name("name", &mut names_cursor).map(|n| node_text(&n, source)).collect();
+        if names.is_empty() {
+            // Embedded: named after the type, pointer and package dropped ("*sync.Mutex" -> "Mutex")
+            let type_text = node_text(&type_node, source);
+            let pointer = decl.child(0).is_some_and(|c| c.kind() == "*");
+            let base = type_text.split('[').next().unwrap_or(type_text);
+            fields.push(Field {
+                name: base.rsplit('.').next().unwrap_or(base).to_string(),
+                type_name: if pointer { format!("*{type_text}") } else { type_text.to_string() },
+                tag,
+                embedded: true,
+            });
+            continue;
+        }
+        for name in names {
+            fields.push(Field {
+                name: name.to_string(),
+                type_name: node_text(&type_node, source).to_string(),
+                tag: tag.clone(),
+                embedded: false,
+            });
+        }
+    }
+    fields
+}
+
+/// Convert a file path to a prefix for qualified names.
+/// e.g., "./cmd/foo/main.go" -> "cmd/foo"
+/// e.g., "internal/bar/main.go" -> "internal/bar"
+fn path_to_prefix(path: &str) -> String {
+    // Remove leading "./"
+    let path = path.strip_prefix("./").unwrap_or(path);
+
+    // Remove the filename, keep directory
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        let dir_str = dir.to_string_lossy();
+        if dir_str.is_empty() || dir_str == "." {
+            // File is in root, use filename without extension
+            std::path::Path::new(path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        } else {
+            dir_str.to_string()
+        }
+    } else {
+        String::new()
+    }
+}
+
+/// Convert a file path to a suffix for init function disambiguation.
+/// e.g., "./internal/foo/bar.go" -> "bar"
+fn path_to_file_suffix(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+const GOOS: &[&str] = &[
+    "aix", "android", "darwin", "dragonfly", "freebsd", "hurd", "illumos", "ios", "js", "linux", "nacl", "netbsd",
+    "openbsd", "plan9", "solaris", "wasip1", "windows", "zos",
+];
+
+const GOARCH: &[&str] = &[
+    "386", "amd64", "arm", "arm64", "loong64", "mips", "mips64", "mips64le", "mipsle", "ppc64", "ppc64le", "riscv64",
+    "s390x", "wasm",
+];
+
+/// The build constraint of a Go file, if any: its `_GOOS`, `_GOARCH`, or `_GOOS_GOARCH`
+/// filename suffix (e.g., "linux", "linux_amd64"), else its `//go:build` expression or
+/// first `// +build` line without spaces (e.g., "linux&&!cgo", "linux||darwin")
+fn go_build_constraint(root: &tree_sitter::Node, source: &[u8], path: &str) -> Option<String> {
+    let stem = path_to_file_suffix(path);
+    let stem = stem.strip_suffix("_test").unwrap_or(&stem);
+    let parts: Vec<&str> = stem.split('_').collect();
+    match parts.as_slice() {
+        [_, .., os, arch] if GOOS.contains(os) && GOARCH.contains(arch) => return Some(format!("{os}_{arch}")),
+        [_, .., last] if GOOS.contains(last) || GOARCH.contains(last) => return Some(last.to_string()),
+        _ => {}
+    }
+
+    // Constraints must precede the package clause
+    let mut cursor = root.walk();
+    let header: Vec<&str> = root
+        .children(&mut cursor)
+        .take_while(|child| child.kind() == "comment")
+        .map(|comment| node_text(&comment, source))
+        .collect();
+    if let Some(expr) = header.iter().find_map(|c| c.strip_prefix("//go:build ")) {
+        return Some(expr.split_whitespace().collect());
+    }
+    let plus_build = header.iter().find_map(|c| c.strip_prefix("// +build "))?;
+    Some(plus_build.split_whitespace().collect::<Vec<_>>().join("||").replace(',', "&&"))
+}
+
+/// Hex hash of source bytes, as stored in `ast_hash` fields: XXH64 with seed 0, which is
+/// specified independently of the Rust toolchain, so hashes stay comparable across builds
+pub fn content_hash(input: &[u8]) -> String {
+    format!("{:016x}", xxhash_rust::xxh64::xxh64(input, 0))
+}
+
+/// Hash of a syntax tree rather than its text: node kinds and the text of named leaves, without
+/// comments, whitespace, or trailing commas, so reformatting a function or editing its comments
+/// keeps the hash
+pub fn syntax_hash(node: &tree_sitter::Node, source: &[u8]) -> String {
+    fn push(node: &tree_sitter::Node, source: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(node.kind().as_bytes());
+        // String and character literals hold their text outside child nodes in some grammars
+        let literal = ["string", "char", "rune"].iter().any(|k| node.kind().contains(k));
+        if node.child_count() == 0 || literal {
+            if node.is_named() {
+                out.push(b' ');
+                out.extend_from_slice(&source[node.byte_range()]);
+            }
+            out.push(0);
+            return;
+        }
+        out.push(b'(');
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.children(&mut cursor).filter(|c| !(c.is_extra() && c.kind().contains("comment"))).collect();
+        for (i, child) in children.iter().enumerate() {
+            // A formatter adds a comma after the last item of a list split over lines
+            let closes = children.get(i + 1).is_none_or(|next| matches!(next.kind(), ")" | "]" | "}" | ">"));
+            if child.kind() == "," && closes {
+                continue;
+            }
+            push(child, source, out);
+        }
+        out.push(b')');
+    }
+
+    let mut out = Vec::new();
+    push(node, source, &mut out);
+    content_hash(&out)
+}
+
+/// The hash indexes of `hash_version` 0 stored, from std's `DefaultHasher`.
+/// Its output may change with any Rust release; only used to carry those indexes over.
+pub fn legacy_content_hash(input: &[u8]) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Fraction of the source's bytes covered by ERROR nodes, e.g. syntax newer than the grammar
+fn error_ratio(root: &tree_sitter::Node, source_len: usize) -> f64 {
+    fn error_bytes(node: &tree_sitter::Node) -> usize {
+        if node.is_error() {
+            return node.byte_range().len();
+        }
+        if !node.has_error() {
+            return 0;
+        }
+        let mut cursor = node.walk();
+        node.children(&mut cursor).map(|child| error_bytes(&child)).sum()
+    }
+
+    if source_len == 0 {
+        return 0.0;
+    }
+    // Rounded so the index JSON stays short and stable
+    (error_bytes(root) as f64 / source_len as f64 * 10_000.0).round() / 10_000.0
+}
+
+/// Number of syntax errors: ERROR nodes and nodes the parser inserted as missing, not counting
+/// errors nested inside another
+fn error_count(root: &tree_sitter::Node) -> u32 {
+    if root.is_error() || root.is_missing() {
+        return 1;
+    }
+    if !root.has_error() {
+        return 0;
+    }
+    let mut cursor = root.walk();
+    root.children(&mut cursor).map(|child| error_count(&child)).sum()
+}
+
+/// Line of the first syntax error, if any
+fn first_error_line(root: &tree_sitter::Node) -> Option<u32> {
+    if root.is_error() || root.is_missing() {
+        return Some(root.start_position().row as u32 + 1);
+    }
+    if !root.has_error() {
+        return None;
+    }
+    let mut cursor = root.walk();
+    root.children(&mut cursor).find_map(|child| first_error_line(&child))
+}
+
+/// Functions named in a table of functions (`{"x": doX, "y": pkg.DoY}`), recorded as call sites
+/// since the table's users call them
+fn collect_func_table_refs(literal: &tree_sitter::Node, source: &[u8], calls: &mut Vec<CallSite>) {
+    let mut cursor = literal.walk();
+    for child in literal.named_children(&mut cursor) {
+        let element = match child.kind() {
+            "keyed_element" => child.child_by_field_name("value"),
+            "literal_element" => Some(child),
+            _ => None,
+        };
+        let Some(expr) = element.and_then(|e| e.named_child(0)) else {
+            continue;
+        };
+        match expr.kind() {
+            "identifier" | "selector_expression" => calls.push(call_site_of(&expr, &expr, source)),
+            "literal_value" => collect_func_table_refs(&expr, source, calls),
+            _ => {}
+        }
+    }
+}
+
+/// Package name implied by a Go import path: its last element, skipping a major version
+/// suffix ("github.com/go-chi/chi/v5" -> "chi", "gopkg.in/yaml.v3" -> "yaml")
+fn go_package_name(import_path: &str) -> &str {
+    let is_major = |s: &str| s.strip_prefix('v').is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+    let mut elements = import_path.rsplit('/');
+    let last = elements.next().unwrap_or(import_path);
+    let last = if is_major(last) { elements.next().unwrap_or(last) } else { last };
+    match last.rsplit_once('.') {
+        Some((name, version)) if is_major(version) => name,
+        _ => last,
+    }
+}
+
+// ============================================================================
+// Rust Parser
+// ============================================================================
+
+#[cfg(feature = "lang-rust")]
+pub struct RustParser {
+    parser: Parser,
+    /// Closures at least this many lines long are indexed as functions (index.closure_min_lines)
+    closure_min_lines: Option<usize>,
+    /// Crates found by scan_root; files outside all of them belong to an unnamed root crate
+    crates: Vec<RustCrate>,
+}
+
+/// A Cargo package in the repository being indexed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustCrate {
+    /// Directory of its Cargo.toml relative to the repository root ("" at the root)
+    pub dir: String,
+    /// Root segment of its module paths: the package name with `-` as `_`, or "" for the
+    /// package at the repository root so single-crate repositories keep short names
+    pub name: String,
+    /// The package name with `-` as `_`, which its binaries, tests, examples, and benches
+    /// use to reach its lib
+    pub package: String,
+}
+
+impl RustCrate {
+    /// The crate described by a Cargo.toml in `dir`, or None for a virtual workspace manifest
+    pub fn from_manifest(dir: &str, manifest: &str) -> Option<Self> {
+        let manifest: toml::Table = toml::from_str(manifest).ok()?;
+        let name = manifest.get("package")?.get("name")?.as_str()?;
+        let dir = dir.trim_start_matches("./").trim_start_matches('.').trim_matches('/').to_string();
+        let package = name.replace('-', "_");
+        let name = if dir.is_empty() { String::new() } else { package.clone() };
+        Some(RustCrate { dir, name, package })
+    }
+}
+
+#[cfg(feature = "lang-rust")]
+impl LanguageParser for RustParser {
+    fn language(&self) -> &'static str {
+        "rust"
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        RustParser::parse_file(self, source, path)
+    }
+
+    fn configure(&mut self, config: &IndexConfig) {
+        self.closure_min_lines = config.closure_min_lines;
+    }
+
+    fn scan_root(&mut self, root: &Path) {
+        let skipped = |e: &walkdir::DirEntry| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() > 0 && (name.starts_with('.') || matches!(name.as_ref(), "target" | "vendor" | "node_modules"))
+        };
+        self.crates = walkdir::WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| !skipped(e))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name() == "Cargo.toml")
+            .filter_map(|e| {
+                let dir = e.path().parent()?.strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+                RustCrate::from_manifest(&dir, &std::fs::read_to_string(e.path()).ok()?)
+            })
+            .collect();
+    }
+}
+
+#[cfg(feature = "lang-rust")]
+impl RustParser {
+    pub fn new() -> Self {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_rust::LANGUAGE.into())
+            .expect("failed to load Rust grammar");
+        Self { parser, closure_min_lines: None, crates: Vec::new() }
+    }
+
+    pub fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        let tree = self.parser.parse(source, None)?;
+        let root = tree.root_node();
+
+        let mut functions = Vec::new();
+        let mut types = Vec::new();
+        let mut variables = Vec::new();
+
+        // Use module path from file location for qualified names
+        // e.g., "src/parser.rs" -> "parser", "crates/core/src/parser.rs" -> "core::parser"
+        let module_path = rust_path_to_module(path, &self.crates);
+        let crate_root = rust_crate_root(path, &self.crates);
 
         // Walk top-level declarations
         let mut cursor = root.walk();
         for child in root.children(&mut cursor) {
             match child.kind() {
                 "function_item" => {
-                    if let Some(func) = self.extract_function(&child, source.as_bytes(), &module_path, None) {
+                    if let Some(mut func) = self.extract_function(&child, source.as_bytes(), &module_path, None) {
+                        // A binary's main is named for its target so it cannot be mistaken for a library fn
+                        if func.name == "main" && is_rust_binary_root(path, &self.crates) {
+                            func.is_entry = true;
+                            if module_path.is_empty() {
+                                func.qualified_name = "crate::main".to_string();
+                            }
+                        }
+                        let parent = func.qualified_name.clone();
                         functions.push(func);
+                        self.extract_nested_functions(&child, source.as_bytes(), &parent, &mut functions);
                     }
                 }
                 "impl_item" => {
@@ -408,22 +1881,62 @@ impl RustParser {
                     if let Some(t) = self.extract_trait(&child, source.as_bytes(), &module_path) {
                         types.push(t);
                     }
+                    self.extract_trait_functions(&child, source.as_bytes(), &module_path, &mut functions);
+                }
+                "static_item" | "const_item" => {
+                    variables.extend(self.extract_static(&child, source.as_bytes(), &module_path));
                 }
                 "mod_item" => {
                     // Handle inline modules: mod foo { ... }
-                    self.extract_mod_contents(&child, source.as_bytes(), &module_path, &mut functions, &mut types);
+                    let source = source.as_bytes();
+                    self.extract_mod_contents(&child, source, &module_path, &mut functions, &mut types, &mut variables);
                 }
                 _ => {}
             }
         }
 
-        let ast_hash = format!("{:016x}", hash_bytes(source.as_bytes()));
+        let mut impls = Vec::new();
+        collect_trait_impls(&root, source.as_bytes(), &mut impls);
+
+        let mut imports = Vec::new();
+        let mut cursor = root.walk();
+        for child in root.children(&mut cursor) {
+            if child.kind() == "use_declaration"
+                && let Some(argument) = child.child_by_field_name("argument")
+            {
+                collect_rust_uses(&argument, source.as_bytes(), "", &module_path, &crate_root, &mut imports);
+            }
+        }
+        // `crate::` in a member crate or a target of its own names that crate's root module
+        if !crate_root.is_empty() {
+            imports.push(Import { alias: "crate".to_string(), path: crate_root });
+        }
+        // Other targets of a package name its lib by the package name
+        if let Some((package, lib_root)) = rust_lib_of_target(path, &self.crates) {
+            for import in &mut imports {
+                if let Some(rest) = import.path.strip_prefix(package).and_then(|rest| rest.strip_prefix("::")) {
+                    import.path = rust_join(lib_root, rest);
+                }
+            }
+            imports.push(Import { alias: package.to_string(), path: lib_root.to_string() });
+        }
+
+        let ast_hash = content_hash(source.as_bytes());
 
         Some(FileEntry {
             ast_hash,
             functions,
             types,
-            variables: Vec::new(),
+            variables,
+            impls,
+            reference: false,
+            parse_error_ratio: error_ratio(&root, source.len()),
+            parse_errors: error_count(&root),
+            first_error_line: first_error_line(&root),
+            mtime_ms: 0,
+            imports,
+            is_test: false,
+            doc: rust_module_doc(&root, source.as_bytes()),
         })
     }
 
@@ -458,22 +1971,42 @@ impl RustParser {
         let line_start = node.start_position().row as u32 + 1;
         let line_end = node.end_position().row as u32 + 1;
 
+        // Attributes are siblings before the item, not part of it
+        let (attributes, attr_line_start) = rust_outer_attributes(node, source);
+
         // Build signature
-        let signature = self.build_rust_signature(node, source, &name);
+        let signature = self.build_rust_signature(node, source, &name, &attributes);
+        let exported_c = rust_exports_to_c(node, source, &attributes);
+        let is_async = rust_function_modifiers(node, source).is_some_and(|m| m.split_whitespace().any(|w| w == "async"));
 
         // Determine visibility
-        let scope = self.extract_visibility(node);
-
-        // Extract call sites from function body
-        let calls = if let Some(body) = node.child_by_field_name("body") {
-            extract_calls(&body, source)
+        let scope = self.extract_visibility(node, source);
+
+        // Record declared types of self and the parameters
+        let params = node.child_by_field_name("parameters");
+        let param_types = params.map(|params| rust_param_types(&params, source, impl_type)).unwrap_or_default();
+        let takes_self = params.is_some_and(|params| {
+            let mut cursor = params.walk();
+            params.children(&mut cursor).any(|p| {
+                p.kind() == "self_parameter"
+                    || p.child_by_field_name("pattern")
+                        .is_some_and(|pat| node_text(&pat, source).trim_start_matches("mut ").trim() == "self")
+            })
+        });
+
+        // Extract call sites and local variable types from function body; calls in nested
+        // functions and indexed closures belong to those, and the closures are referenced
+        let mut nested = Vec::new();
+        let (calls, local_types) = if let Some(body) = node.child_by_field_name("body") {
+            collect_rust_nested_items(&body, self.closure_min_lines, &mut nested);
+            (extract_calls_except(&body, source, &nested), collect_rust_local_types(&body, source, &param_types))
         } else {
-            Vec::new()
+            (Vec::new(), BTreeMap::new())
         };
 
-        // Compute AST hash
-        let func_source = &source[node.start_byte()..node.end_byte()];
-        let ast_hash = format!("{:016x}", hash_bytes(func_source));
+        let type_refs = extract_type_refs(node, source, &["parameters", "return_type"], impl_type);
+
+        let ast_hash = syntax_hash(node, source);
 
         Some(Function {
             name,
@@ -481,12 +2014,34 @@ impl RustParser {
             ast_hash,
             line_start,
             line_end,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+            attr_line_start,
             signature,
+            attributes,
+            doc: rust_doc_comment(node, source),
             summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
             receiver: impl_type.map(String::from),
+            trait_impl: None,
+            takes_self,
             scope,
+            is_entry: false,
+            exported_c,
+            is_async,
+            param_types,
+            local_types,
             calls,
             called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: rust_closure_references(&nested),
+            type_refs,
+            uses_types: Vec::new(),
+            global_refs: extract_global_refs(node, source, GlobalSyntax::Rust),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
         })
     }
 
@@ -503,12 +2058,15 @@ impl RustParser {
             .map(|n| node_text(&n, source).to_string())
             .unwrap_or_default();
 
-        // Strip pointer/reference from type if present (e.g., "&mut Foo" -> "Foo")
-        let impl_type = impl_type
-            .trim_start_matches('&')
-            .trim_start_matches("mut ")
-            .trim()
-            .to_string();
+        // Strip pointer/reference and generic arguments from type (e.g., "&mut Foo<T>" -> "Foo")
+        let impl_type = impl_type.trim_start_matches('&').trim_start_matches("mut ").trim();
+        let impl_type = impl_type.split('<').next().unwrap_or(impl_type).trim().to_string();
+
+        // The trait of `impl Trait for Type`, without generic arguments as in collect_trait_impls
+        let trait_impl = node.child_by_field_name("trait").map(|n| {
+            let text = node_text(&n, source);
+            text.split('<').next().unwrap_or(text).trim().to_string()
+        });
 
         // Find the body (declaration_list)
         let Some(body) = node.child_by_field_name("body") else {
@@ -517,14 +2075,125 @@ impl RustParser {
 
         let mut cursor = body.walk();
         for child in body.children(&mut cursor) {
-            if child.kind() == "function_item" {
-                if let Some(func) = self.extract_function(&child, source, module_path, Some(&impl_type)) {
-                    functions.push(func);
-                }
+            if child.kind() == "function_item"
+                && let Some(mut func) = self.extract_function(&child, source, module_path, Some(&impl_type))
+            {
+                func.trait_impl = trait_impl.clone();
+                let parent = func.qualified_name.clone();
+                functions.push(func);
+                self.extract_nested_functions(&child, source, &parent, functions);
+            }
+        }
+    }
+
+    /// Index trait methods with a default body under the trait, as `module::Trait::method`
+    fn extract_trait_functions(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        module_path: &str,
+        functions: &mut Vec<Function>,
+    ) {
+        let (Some(name_node), Some(body)) = (node.child_by_field_name("name"), node.child_by_field_name("body")) else {
+            return;
+        };
+        let trait_name = node_text(&name_node, source);
+
+        // Trait items carry no visibility of their own; they are as visible as the trait
+        let scope = self.extract_visibility(node, source);
+
+        let mut cursor = body.walk();
+        for child in body.children(&mut cursor) {
+            if child.kind() == "function_item"
+                && let Some(mut func) = self.extract_function(&child, source, module_path, Some(trait_name))
+            {
+                func.scope = scope;
+                let parent = func.qualified_name.clone();
+                functions.push(func);
+                self.extract_nested_functions(&child, source, &parent, functions);
+            }
+        }
+    }
+
+    /// Index the functions declared in `node`'s body, and its closures of at least
+    /// `closure_min_lines` lines, under `parent` (`module::run::helper`, `module::run::{closure@L42}`)
+    fn extract_nested_functions(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        parent: &str,
+        functions: &mut Vec<Function>,
+    ) {
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let mut items = Vec::new();
+        collect_rust_nested_items(&body, self.closure_min_lines, &mut items);
+        for item in items {
+            let func = match item.kind() {
+                "function_item" => self.extract_function(&item, source, parent, None),
+                _ => self.extract_closure(&item, source, parent),
+            };
+            if let Some(func) = func {
+                let qualified_name = func.qualified_name.clone();
+                functions.push(func);
+                self.extract_nested_functions(&item, source, &qualified_name, functions);
             }
         }
     }
 
+    /// A closure indexed as a function named for its line, e.g. `{closure@L42}`
+    fn extract_closure(&self, node: &tree_sitter::Node, source: &[u8], parent: &str) -> Option<Function> {
+        let body = node.child_by_field_name("body")?;
+        let line_start = node.start_position().row as u32 + 1;
+        let name = rust_closure_name(line_start);
+
+        let params = node.child_by_field_name("parameters").map_or("||", |p| node_text(&p, source));
+        let return_type = node
+            .child_by_field_name("return_type")
+            .map(|n| format!(" -> {}", node_text(&n, source)))
+            .unwrap_or_default();
+
+        let mut nested = Vec::new();
+        collect_rust_nested_items(&body, self.closure_min_lines, &mut nested);
+
+        Some(Function {
+            qualified_name: format!("{parent}::{name}"),
+            name,
+            ast_hash: syntax_hash(node, source),
+            line_start,
+            line_end: node.end_position().row as u32 + 1,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+            attr_line_start: None,
+            signature: format!("{params}{return_type}"),
+            attributes: Vec::new(),
+            doc: None,
+            summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
+            receiver: None,
+            trait_impl: None,
+            takes_self: false,
+            scope: Scope::Internal,
+            is_entry: false,
+            exported_c: false,
+            is_async: false,
+            param_types: BTreeMap::new(),
+            local_types: collect_rust_local_types(&body, source, &BTreeMap::new()),
+            calls: extract_calls_except(&body, source, &nested),
+            called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: rust_closure_references(&nested),
+            type_refs: extract_type_refs(node, source, &["parameters", "return_type"], None),
+            uses_types: Vec::new(),
+            global_refs: extract_global_refs(node, source, GlobalSyntax::Rust),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
+        })
+    }
+
     fn extract_struct(
         &self,
         node: &tree_sitter::Node,
@@ -551,6 +2220,11 @@ impl RustParser {
             line_end,
             summary: None,
             methods: Vec::new(),
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: rust_doc_comment(node, source),
         })
     }
 
@@ -572,6 +2246,18 @@ impl RustParser {
         let line_start = node.start_position().row as u32 + 1;
         let line_end = node.end_position().row as u32 + 1;
 
+        let mut variants = Vec::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for item in body.children(&mut cursor) {
+                if item.kind() == "enum_variant"
+                    && let Some(variant) = item.child_by_field_name("name")
+                {
+                    variants.push(node_text(&variant, source).to_string());
+                }
+            }
+        }
+
         Some(TypeDef {
             name,
             qualified_name,
@@ -580,6 +2266,11 @@ impl RustParser {
             line_end,
             summary: None,
             methods: Vec::new(),
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants,
+            doc: rust_doc_comment(node, source),
         })
     }
 
@@ -601,6 +2292,19 @@ impl RustParser {
         let line_start = node.start_position().row as u32 + 1;
         let line_end = node.end_position().row as u32 + 1;
 
+        // Methods the trait declares, with or without a default body
+        let mut methods = Vec::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for item in body.children(&mut cursor) {
+                if matches!(item.kind(), "function_signature_item" | "function_item")
+                    && let Some(method) = item.child_by_field_name("name")
+                {
+                    methods.push(node_text(&method, source).to_string());
+                }
+            }
+        }
+
         Some(TypeDef {
             name,
             qualified_name,
@@ -608,7 +2312,12 @@ impl RustParser {
             line_start,
             line_end,
             summary: None,
-            methods: Vec::new(),
+            methods,
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: rust_doc_comment(node, source),
         })
     }
 
@@ -619,6 +2328,7 @@ impl RustParser {
         parent_module: &str,
         functions: &mut Vec<Function>,
         types: &mut Vec<TypeDef>,
+        variables: &mut Vec<Variable>,
     ) {
         // Get module name
         let Some(name_node) = node.child_by_field_name("name") else {
@@ -643,7 +2353,9 @@ impl RustParser {
             match child.kind() {
                 "function_item" => {
                     if let Some(func) = self.extract_function(&child, source, &nested_path, None) {
+                        let parent = func.qualified_name.clone();
                         functions.push(func);
+                        self.extract_nested_functions(&child, source, &parent, functions);
                     }
                 }
                 "impl_item" => {
@@ -663,20 +2375,40 @@ impl RustParser {
                     if let Some(t) = self.extract_trait(&child, source, &nested_path) {
                         types.push(t);
                     }
+                    self.extract_trait_functions(&child, source, &nested_path, functions);
+                }
+                "static_item" | "const_item" => {
+                    variables.extend(self.extract_static(&child, source, &nested_path));
                 }
                 "mod_item" => {
-                    self.extract_mod_contents(&child, source, &nested_path, functions, types);
+                    self.extract_mod_contents(&child, source, &nested_path, functions, types, variables);
                 }
                 _ => {}
             }
         }
     }
 
+    /// A `static` or `const` item as a variable
+    fn extract_static(&self, node: &tree_sitter::Node, source: &[u8], module_path: &str) -> Option<Variable> {
+        let name = node_text(&node.child_by_field_name("name")?, source).to_string();
+        let type_name = node.child_by_field_name("type").map(|t| node_text(&t, source).to_string()).unwrap_or_default();
+        Some(Variable {
+            qualified_name: if module_path.is_empty() { name.clone() } else { format!("{}::{}", module_path, name) },
+            name,
+            type_name,
+            line_start: node.start_position().row as u32 + 1,
+            line_end: node.end_position().row as u32 + 1,
+            scope: self.extract_visibility(node, source),
+            is_const: node.kind() == "const_item",
+        })
+    }
+
     fn build_rust_signature(
         &self,
         node: &tree_sitter::Node,
         source: &[u8],
         name: &str,
+        attributes: &[String],
     ) -> String {
         let params = node
             .child_by_field_name("parameters")
@@ -688,55 +2420,506 @@ impl RustParser {
             .map(|n| format!(" -> {}", node_text(&n, source)))
             .unwrap_or_default();
 
-        format!("fn {}{}{}", name, params, return_type)
+        let mut signature = String::new();
+        for attr in attributes.iter().filter(|a| is_notable_attribute(a)) {
+            signature.push_str(&format!("#[{}] ", attr));
+        }
+        if let Some(modifiers) = rust_function_modifiers(node, source) {
+            signature.push_str(&modifiers);
+            signature.push(' ');
+        }
+        signature.push_str(&format!("fn {}{}{}", name, params, return_type));
+        signature
     }
 
-    fn extract_visibility(&self, node: &tree_sitter::Node) -> Scope {
-        // Check for visibility modifier (pub, pub(crate), etc.)
+    fn extract_visibility(&self, node: &tree_sitter::Node, source: &[u8]) -> Scope {
+        // `pub` is public; `pub(crate)`, `pub(super)`, and `pub(in path)` stay inside the crate
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "visibility_modifier" {
-                return Scope::Public;
+                let text: String = node_text(&child, source).split_whitespace().collect();
+                return match text.as_str() {
+                    "pub" => Scope::Public,
+                    "pub(self)" => Scope::Internal,
+                    _ => Scope::Crate,
+                };
             }
         }
         Scope::Internal
     }
 }
 
-/// Convert Rust file path to module path
+/// Functions declared under `node`, and closures of at least `closure_min_lines` lines, without
+/// looking inside them; their contents belong to them rather than the enclosing function
+fn collect_rust_nested_items<'t>(
+    node: &tree_sitter::Node<'t>,
+    closure_min_lines: Option<usize>,
+    items: &mut Vec<tree_sitter::Node<'t>>,
+) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        let lines = child.end_position().row - child.start_position().row + 1;
+        let significant_closure = child.kind() == "closure_expression" && closure_min_lines.is_some_and(|min| lines >= min);
+        if child.kind() == "function_item" || significant_closure {
+            items.push(child);
+        } else {
+            collect_rust_nested_items(&child, closure_min_lines, items);
+        }
+    }
+}
+
+/// Name of a closure indexed as a function, for the line it starts on
+fn rust_closure_name(line: u32) -> String {
+    format!("{{closure@L{line}}}")
+}
+
+/// References from a function to the closures it defines, which run as part of it
+fn rust_closure_references(nested: &[tree_sitter::Node]) -> Vec<CallSite> {
+    nested
+        .iter()
+        .filter(|item| item.kind() == "closure_expression")
+        .map(|closure| {
+            let line = closure.start_position().row as u32 + 1;
+            let name = rust_closure_name(line);
+            let column = closure.start_position().column as u32 + 1;
+            CallSite {
+                target: "[unresolved]".to_string(),
+                raw: name.clone(),
+                callee_name: name,
+                receiver_expr: None,
+                candidates: Vec::new(),
+                resolved_by: None,
+                line,
+                column,
+                byte_start: closure.start_byte() as u32,
+                byte_end: closure.end_byte() as u32,
+            }
+        })
+        .collect()
+}
+
+/// Qualifiers before `fn` (`const`, `async`, `unsafe`, `extern "C"`), with whitespace collapsed
+fn rust_function_modifiers(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let mut cursor = node.walk();
+    let modifiers = node.children(&mut cursor).find(|c| c.kind() == "function_modifiers")?;
+    Some(node_text(&modifiers, source).split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Whether a Rust function is callable from C: declared with a non-Rust ABI (`extern "C" fn`),
+/// or kept under a fixed symbol name by `#[no_mangle]` or `#[export_name = "..."]`
+fn rust_exports_to_c(node: &tree_sitter::Node, source: &[u8], attributes: &[String]) -> bool {
+    let mut cursor = node.walk();
+    let foreign_abi = node
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "function_modifiers")
+        .flat_map(|m| {
+            let mut cursor = m.walk();
+            m.children(&mut cursor).filter(|c| c.kind() == "extern_modifier").collect::<Vec<_>>()
+        })
+        .any(|m| node_text(&m, source).split_whitespace().nth(1) != Some("\"Rust\""));
+    let exported_symbol = attributes.iter().any(|attr| {
+        // Edition 2024 spells these `#[unsafe(no_mangle)]`
+        let attr = attr.strip_prefix("unsafe(").unwrap_or(attr);
+        let path = attr.split(['(', '=', ')']).next().unwrap_or(attr).trim();
+        path == "no_mangle" || path == "export_name"
+    });
+    foreign_abi || exported_symbol
+}
+
+/// Attributes that only affect lints, docs, or codegen hints and are left out of signatures
+const QUIET_ATTRIBUTES: &[&str] = &["allow", "cold", "deny", "doc", "expect", "forbid", "inline", "must_use", "warn"];
+
+/// Outer attributes directly above an item, with whitespace collapsed, and the line of the first.
+/// Comments between the attributes and the item are skipped.
+fn rust_outer_attributes(node: &tree_sitter::Node, source: &[u8]) -> (Vec<String>, Option<u32>) {
+    let mut attributes = Vec::new();
+    let mut first_line = None;
+    let mut sibling = node.prev_sibling();
+    while let Some(prev) = sibling {
+        match prev.kind() {
+            "attribute_item" => {
+                let text = node_text(&prev, source);
+                let inner = text.trim_start_matches("#[").trim_end_matches(']');
+                let flat = inner.split_whitespace().collect::<Vec<_>>().join(" ");
+                attributes.push(flat.replace("( ", "(").replace(" )", ")"));
+                first_line = Some(prev.start_position().row as u32 + 1);
+            }
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = prev.prev_sibling();
+    }
+    attributes.reverse();
+    (attributes, first_line)
+}
+
+/// Text of the `///` or `/** */` doc comments above an item, above or among its attributes
+fn rust_doc_comment(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let mut comments = Vec::new();
+    let mut sibling = node.prev_sibling();
+    while let Some(prev) = sibling {
+        match prev.kind() {
+            "attribute_item" => {}
+            "line_comment" | "block_comment" => {
+                let text = node_text(&prev, source);
+                let outer_doc = (text.starts_with("///") && !text.starts_with("////"))
+                    || (text.starts_with("/**") && !text.starts_with("/***") && text != "/**/");
+                if outer_doc {
+                    comments.push(text);
+                }
+            }
+            _ => break,
+        }
+        sibling = prev.prev_sibling();
+    }
+    comments.reverse();
+    rust_doc_text(&comments)
+}
+
+/// Text of the `//!` or `/*! */` comments at the top of a file, documenting its module
+fn rust_module_doc(root: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let mut comments = Vec::new();
+    let mut cursor = root.walk();
+    for child in root.children(&mut cursor) {
+        match child.kind() {
+            "inner_attribute_item" | "attribute_item" => {}
+            "line_comment" | "block_comment" => {
+                let text = node_text(&child, source);
+                if text.starts_with("//!") || text.starts_with("/*!") {
+                    comments.push(text);
+                }
+            }
+            _ => break,
+        }
+    }
+    rust_doc_text(&comments)
+}
+
+/// Doc comments with their markers and leading ` * ` stripped, one line per source line
+fn rust_doc_text(comments: &[&str]) -> Option<String> {
+    let mut lines = Vec::new();
+    for comment in comments {
+        if let Some(line) = comment.strip_prefix("///").or_else(|| comment.strip_prefix("//!")) {
+            lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end());
+        } else {
+            let block = comment[3..].trim_end_matches("*/");
+            lines.extend(block.lines().map(|l| l.trim().trim_start_matches('*').trim()).filter(|l| !l.is_empty()));
+        }
+    }
+    let doc = lines.join("\n");
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+/// Whether an attribute says something about behavior (`tokio::main`, `test`, `cfg(...)`);
+/// `cfg_attr` counts when any attribute it applies does
+fn is_notable_attribute(attr: &str) -> bool {
+    let path = attr.split(['(', '=']).next().unwrap_or(attr).trim();
+    if path == "cfg_attr" {
+        let Some(args) = attr.strip_prefix("cfg_attr").map(str::trim).and_then(|a| a.strip_prefix('(')) else {
+            return false;
+        };
+        let args = args.strip_suffix(')').unwrap_or(args);
+        return split_top_level(args).iter().skip(1).any(|a| is_notable_attribute(a.trim()));
+    }
+    !QUIET_ATTRIBUTES.contains(&path) && !path.starts_with("clippy::") && !path.starts_with("rustfmt::")
+}
+
+/// Split on commas that are not nested inside brackets or string literals
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' | '[' | '{' if !in_string => depth += 1,
+            ')' | ']' | '}' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Map Rust parameter names to their declared types; `self` maps to the impl type
+fn rust_param_types(
+    params: &tree_sitter::Node,
+    source: &[u8],
+    impl_type: Option<&str>,
+) -> BTreeMap<String, String> {
+    let mut param_types = BTreeMap::new();
+    let mut cursor = params.walk();
+    for param in params.children(&mut cursor) {
+        match param.kind() {
+            "self_parameter" => {
+                if let Some(t) = impl_type {
+                    param_types.insert("self".to_string(), t.to_string());
+                }
+            }
+            "parameter" => {
+                let (Some(pattern), Some(type_node)) =
+                    (param.child_by_field_name("pattern"), param.child_by_field_name("type"))
+                else {
+                    continue;
+                };
+                let name = node_text(&pattern, source).trim_start_matches("mut ").trim();
+                if name == "self" {
+                    // `self: &Self` and friends name the impl type the same way `&self` does
+                    if let Some(t) = impl_type {
+                        param_types.insert("self".to_string(), t.to_string());
+                    }
+                } else if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    param_types.insert(name.to_string(), node_text(&type_node, source).to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    param_types
+}
+
+/// Collect `impl Trait for Type` blocks at the top level and in inline modules
+fn collect_trait_impls(node: &tree_sitter::Node, source: &[u8], impls: &mut Vec<TraitImpl>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "impl_item" => {
+                let (Some(trait_node), Some(type_node)) =
+                    (child.child_by_field_name("trait"), child.child_by_field_name("type"))
+                else {
+                    continue;
+                };
+                let trait_text = node_text(&trait_node, source);
+                impls.push(TraitImpl {
+                    trait_name: trait_text.split('<').next().unwrap_or(trait_text).trim().to_string(),
+                    type_name: node_text(&type_node, source).to_string(),
+                    line: child.start_position().row as u32 + 1,
+                });
+            }
+            "mod_item" => {
+                if let Some(body) = child.child_by_field_name("body") {
+                    collect_trait_impls(&body, source, impls);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Flatten a `use` tree under `prefix` into imports: `a::b::{C, d as e, f::*}` gives
+/// C, e, and a glob of `a::b::f`
+#[cfg(feature = "lang-rust")]
+fn collect_rust_uses(
+    node: &tree_sitter::Node,
+    source: &[u8],
+    prefix: &str,
+    module: &str,
+    crate_root: &str,
+    imports: &mut Vec<Import>,
+) {
+    let join = |path: &str| if prefix.is_empty() { path.to_string() } else { format!("{prefix}::{path}") };
+    let mut push = |alias: &str, path: String| {
+        imports.push(Import { alias: alias.to_string(), path: rust_use_path(&path, module, crate_root) });
+    };
+    match node.kind() {
+        "identifier" | "scoped_identifier" | "self" | "super" | "crate" => {
+            let path = join(node_text(node, source));
+            // `use a::b::{self}` imports the module `a::b`
+            let path = path.strip_suffix("::self").map_or(path.clone(), String::from);
+            let alias = path.rsplit("::").next().unwrap_or(&path).to_string();
+            push(&alias, path);
+        }
+        "use_as_clause" => {
+            if let (Some(path), Some(alias)) = (node.child_by_field_name("path"), node.child_by_field_name("alias")) {
+                push(node_text(&alias, source), join(node_text(&path, source)));
+            }
+        }
+        "use_wildcard" => {
+            let path = node.named_child(0).map_or(prefix.to_string(), |path| join(node_text(&path, source)));
+            push("*", path);
+        }
+        "scoped_use_list" => {
+            let prefix = node.child_by_field_name("path").map_or(prefix.to_string(), |path| join(node_text(&path, source)));
+            if let Some(list) = node.child_by_field_name("list") {
+                collect_rust_uses(&list, source, &prefix, module, crate_root, imports);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                collect_rust_uses(&child, source, prefix, module, crate_root, imports);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A `use` path with leading `self::` and `super::` rewritten against `module` into a
+/// `crate::` path, and `crate::` spelled out as `crate_root` when the crate has one;
+/// other paths are kept as written
+#[cfg(feature = "lang-rust")]
+fn rust_use_path(path: &str, module: &str, crate_root: &str) -> String {
+    if let Some(rest) = path.strip_prefix("crate::").filter(|_| !crate_root.is_empty()) {
+        return format!("{crate_root}::{rest}");
+    }
+    if !path.starts_with("self::") && !path.starts_with("super::") {
+        return path.to_string();
+    }
+    let mut base: Vec<&str> = module.split("::").filter(|s| !s.is_empty()).collect();
+    let mut rest = path;
+    loop {
+        if let Some(r) = rest.strip_prefix("super::") {
+            base.pop();
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("self::") {
+            rest = r;
+        } else {
+            break;
+        }
+    }
+    base.push(rest);
+    // `module` already starts with the crate root when there is one
+    match crate_root {
+        "" => format!("crate::{}", base.join("::")),
+        _ => base.join("::"),
+    }
+}
+
+/// Cargo target directories whose files each build a crate of their own
+const RUST_TARGET_DIRS: &[&str] = &["tests", "examples", "benches"];
+
+/// The crate holding a Rust file and the file's path inside it, e.g. ("core", "src/parser.rs")
+/// for "./crates/core/src/parser.rs". Files outside every crate are in the root crate ("").
+fn rust_crate_of<'a>(path: &'a str, crates: &'a [RustCrate]) -> (&'a str, &'a str) {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    rust_package_of(path, crates).map_or(("", path), |(c, rest)| (c.name.as_str(), rest))
+}
+
+/// The package holding a Rust file and the file's path inside it
+fn rust_package_of<'a, 'p>(path: &'p str, crates: &'a [RustCrate]) -> Option<(&'a RustCrate, &'p str)> {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    crates
+        .iter()
+        .filter_map(|c| match c.dir.as_str() {
+            "" => Some((c, path)),
+            dir => path.strip_prefix(dir).and_then(|rest| rest.strip_prefix('/')).map(|rest| (c, rest)),
+        })
+        .max_by_key(|(c, _)| c.dir.len())
+}
+
+/// For a file of a binary, test, example, or bench target, the package name its `use` items
+/// and paths reach the package's lib by, and the lib's root module ("" at the repository root)
+fn rust_lib_of_target<'a>(path: &str, crates: &'a [RustCrate]) -> Option<(&'a str, &'a str)> {
+    let (package, rest) = rust_package_of(path, crates)?;
+    let first = rest.split('/').next().unwrap_or(rest);
+    let is_target = rest == "src/main.rs" || rest.starts_with("src/bin/") || RUST_TARGET_DIRS.contains(&first);
+    is_target.then_some((package.package.as_str(), package.name.as_str()))
+}
+
+/// Join module path segments, skipping empty ones
+fn rust_join(prefix: &str, path: &str) -> String {
+    match (prefix, path) {
+        ("", path) => path.to_string(),
+        (prefix, "") => prefix.to_string(),
+        (prefix, path) => format!("{prefix}::{path}"),
+    }
+}
+
+/// Convert Rust file path to module path, prefixed by the name of a crate not at the root
 /// "src/parser.rs" -> "parser"
 /// "src/commands/index.rs" -> "commands::index"
 /// "src/lib.rs" -> ""
 /// "src/main.rs" -> ""
-fn rust_path_to_module(path: &str) -> String {
-    let path = path.strip_prefix("./").unwrap_or(path);
-    let path = path.strip_prefix("src/").unwrap_or(path);
+/// "src/bin/tool/main.rs" -> "bin::tool"
+/// "tests/cli.rs" -> "tests::cli"
+/// "crates/core/src/parser.rs" -> "core::parser" (crates/core/Cargo.toml names "core")
+fn rust_path_to_module(path: &str, crates: &[RustCrate]) -> String {
+    let (crate_name, path) = rust_crate_of(path, crates);
 
     // Remove .rs extension
     let path = path.strip_suffix(".rs").unwrap_or(path);
 
+    // Integration tests, examples, and benches are crates named for their file or directory
+    if let Some((kind, rest)) = path.split_once('/').filter(|(kind, _)| RUST_TARGET_DIRS.contains(kind)) {
+        let rest = rest.strip_suffix("/main").or_else(|| rest.strip_suffix("/mod")).unwrap_or(rest);
+        return rust_join(crate_name, &format!("{kind}::{}", rest.replace('/', "::")));
+    }
+
+    let path = path.strip_prefix("src/").unwrap_or(path);
+
     // lib.rs and main.rs are crate roots
     if path == "lib" || path == "main" {
-        return String::new();
+        return crate_name.to_string();
+    }
+
+    // A binary target in its own directory is rooted at its main.rs
+    if let Some(target) = path.strip_prefix("bin/").and_then(|p| p.strip_suffix("/main"))
+        && !target.contains('/')
+    {
+        return rust_join(crate_name, &format!("bin::{target}"));
     }
 
     // mod.rs files use parent directory name
-    if path.ends_with("/mod") {
-        let parent = &path[..path.len() - 4];
-        return parent.replace('/', "::");
+    if let Some(parent) = path.strip_suffix("/mod") {
+        return rust_join(crate_name, &parent.replace('/', "::"));
+    }
+
+    rust_join(crate_name, &path.replace('/', "::"))
+}
+
+/// Module path that `crate::` names in a Rust file: its crate's name, or for a binary,
+/// test, example, or bench target, that target's root module ("bin::tool", "tests::cli")
+fn rust_crate_root(path: &str, crates: &[RustCrate]) -> String {
+    let (crate_name, path) = rust_crate_of(path, crates);
+    let path = path.strip_suffix(".rs").unwrap_or(path);
+    let path = path.strip_prefix("src/").filter(|p| p.starts_with("bin/")).unwrap_or(path);
+    let mut segments = path.split('/');
+    match (segments.next(), segments.next()) {
+        (Some(kind), Some(target)) if kind == "bin" || RUST_TARGET_DIRS.contains(&kind) => {
+            rust_join(crate_name, &format!("{kind}::{target}"))
+        }
+        _ => crate_name.to_string(),
     }
+}
 
-    path.replace('/', "::")
+/// Whether a Rust file is a binary crate root: src/main.rs, src/bin/<name>.rs, or
+/// src/bin/<name>/main.rs in its crate
+fn is_rust_binary_root(path: &str, crates: &[RustCrate]) -> bool {
+    let (_, path) = rust_crate_of(path, crates);
+    let path = path.strip_prefix("src/").unwrap_or(path);
+    match path.strip_prefix("bin/") {
+        Some(target) => !target.trim_end_matches("/main.rs").contains('/'),
+        None => path == "main.rs",
+    }
 }
 
 // ============================================================================
 // C Parser
 // ============================================================================
 
+#[cfg(feature = "lang-c")]
 pub struct CParser {
     parser: Parser,
 }
 
+#[cfg(feature = "lang-c")]
+impl LanguageParser for CParser {
+    fn language(&self) -> &'static str {
+        "c"
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        CParser::parse_file(self, source, path)
+    }
+}
+
+#[cfg(feature = "lang-c")]
 impl CParser {
     pub fn new() -> Self {
         let mut parser = Parser::new();
@@ -761,13 +2944,22 @@ impl CParser {
         // Walk declarations (recursively enters preprocessor blocks)
         self.extract_declarations(&root, source.as_bytes(), &path_prefix, &mut functions, &mut types, &mut variables);
 
-        let ast_hash = format!("{:016x}", hash_bytes(source.as_bytes()));
+        let ast_hash = content_hash(source.as_bytes());
 
         Some(FileEntry {
             ast_hash,
             functions,
             types,
             variables,
+            impls: Vec::new(),
+            reference: false,
+            parse_error_ratio: error_ratio(&root, source.len()),
+            parse_errors: error_count(&root),
+            first_error_line: first_error_line(&root),
+            mtime_ms: 0,
+            imports: Vec::new(),
+            is_test: false,
+            doc: None,
         })
     }
 
@@ -853,10 +3045,11 @@ impl CParser {
         } else {
             Vec::new()
         };
+        let is_entry = name == "main" && scope == Scope::Public;
 
         // Compute AST hash
         let func_source = &source[node.start_byte()..node.end_byte()];
-        let ast_hash = format!("{:016x}", hash_bytes(func_source));
+        let ast_hash = content_hash(func_source);
 
         Some(Function {
             name,
@@ -864,12 +3057,34 @@ impl CParser {
             ast_hash,
             line_start,
             line_end,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+            attr_line_start: None,
             signature,
+            attributes: Vec::new(),
+            doc: None,
             summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
             receiver: None,
+            trait_impl: None,
+            takes_self: false,
             scope,
+            is_entry,
+            exported_c: false,
+            is_async: false,
+            param_types: BTreeMap::new(),
+            local_types: BTreeMap::new(),
             calls,
             called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: Vec::new(),
+            type_refs: Vec::new(),
+            uses_types: Vec::new(),
+            global_refs: extract_global_refs(node, source, GlobalSyntax::C),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
         })
     }
 
@@ -979,6 +3194,11 @@ impl CParser {
             line_end,
             summary: None,
             methods: Vec::new(),
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: None,
         })
     }
 
@@ -1025,6 +3245,11 @@ impl CParser {
             line_end,
             summary: None,
             methods: Vec::new(),
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: None,
         })
     }
 
@@ -1058,6 +3283,11 @@ impl CParser {
             line_end,
             summary: None,
             methods: Vec::new(),
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: None,
         })
     }
 
@@ -1127,17 +3357,13 @@ impl CParser {
                     // init_declarator contains the variable name (possibly nested in array_declarator, pointer_declarator)
                     var_name = self.extract_variable_name_from_declarator(&child, source);
                 }
-                "identifier" => {
-                    // Simple declaration without initializer: "int x;"
-                    if var_name.is_none() {
-                        var_name = Some(node_text(&child, source).to_string());
-                    }
+                // Simple declaration without initializer: "int x;"
+                "identifier" if var_name.is_none() => {
+                    var_name = Some(node_text(&child, source).to_string());
                 }
-                "array_declarator" | "pointer_declarator" => {
-                    // Declaration without init: "int arr[10];" or "int *ptr;"
-                    if var_name.is_none() {
-                        var_name = self.extract_variable_name_from_declarator(&child, source);
-                    }
+                // Declaration without init: "int arr[10];" or "int *ptr;"
+                "array_declarator" | "pointer_declarator" if var_name.is_none() => {
+                    var_name = self.extract_variable_name_from_declarator(&child, source);
                 }
                 _ => {}
             }
@@ -1175,6 +3401,7 @@ impl CParser {
             line_start,
             line_end,
             scope,
+            is_const: false,
         })
     }
 
@@ -1212,33 +3439,1637 @@ fn c_path_to_prefix(path: &str) -> String {
     // Remove .c or .h extension
     let path = path.strip_suffix(".c").or_else(|| path.strip_suffix(".h")).unwrap_or(path);
 
-    path.to_string()
-}
+    path.to_string()
+}
+
+// ============================================================================
+// Python Parser
+// ============================================================================
+
+#[cfg(feature = "lang-python")]
+pub struct PythonParser {
+    parser: Parser,
+}
+
+#[cfg(feature = "lang-python")]
+impl LanguageParser for PythonParser {
+    fn language(&self) -> &'static str {
+        "python"
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        PythonParser::parse_file(self, source, path)
+    }
+}
+
+#[cfg(feature = "lang-python")]
+impl PythonParser {
+    pub fn new() -> Self {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_python::LANGUAGE.into())
+            .expect("failed to load Python grammar");
+        Self { parser }
+    }
+
+    pub fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        let tree = self.parser.parse(source, None)?;
+        let root = tree.root_node();
+
+        let mut functions = Vec::new();
+        let mut types = Vec::new();
+
+        // e.g., "pkg/store.py" -> "pkg/store", so functions are "pkg/store.load"
+        let module = python_path_to_module(path);
+        self.extract_definitions(&root, source.as_bytes(), &module, None, &mut functions, &mut types);
+
+        Some(FileEntry {
+            ast_hash: content_hash(source.as_bytes()),
+            functions,
+            types,
+            variables: Vec::new(),
+            impls: Vec::new(),
+            reference: false,
+            parse_error_ratio: error_ratio(&root, source.len()),
+            parse_errors: error_count(&root),
+            first_error_line: first_error_line(&root),
+            mtime_ms: 0,
+            imports: Vec::new(),
+            is_test: false,
+            doc: None,
+        })
+    }
+
+    /// Functions and classes under `node`, qualified with `prefix`. `class` is set when
+    /// `node` is a class body, whose functions are that class's methods. Definitions
+    /// nested in control flow (`if TYPE_CHECKING:`, `try:`) are found too.
+    fn extract_definitions(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        prefix: &str,
+        class: Option<&str>,
+        functions: &mut Vec<Function>,
+        types: &mut Vec<TypeDef>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let definition = match child.kind() {
+                "decorated_definition" => match child.child_by_field_name("definition") {
+                    Some(definition) => definition,
+                    None => continue,
+                },
+                _ => child,
+            };
+            match definition.kind() {
+                "function_definition" => {
+                    let Some(func) = self.extract_function(&child, &definition, source, prefix, class) else {
+                        continue;
+                    };
+                    // Nested functions are qualified by their enclosing function
+                    let nested_prefix = func.qualified_name.clone();
+                    functions.push(func);
+                    if let Some(body) = definition.child_by_field_name("body") {
+                        self.extract_definitions(&body, source, &nested_prefix, None, functions, types);
+                    }
+                }
+                "class_definition" => {
+                    let Some(t) = self.extract_class(&definition, source, prefix) else {
+                        continue;
+                    };
+                    let class_prefix = t.qualified_name.clone();
+                    let class_name = t.name.clone();
+                    types.push(t);
+                    if let Some(body) = definition.child_by_field_name("body") {
+                        self.extract_definitions(&body, source, &class_prefix, Some(&class_name), functions, types);
+                    }
+                }
+                _ => self.extract_definitions(&child, source, prefix, class, functions, types),
+            }
+        }
+    }
+
+    /// `outer` is the decorated_definition when the function has decorators, else `node` itself
+    fn extract_function(
+        &self,
+        outer: &tree_sitter::Node,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        prefix: &str,
+        class: Option<&str>,
+    ) -> Option<Function> {
+        let name = node_text(&node.child_by_field_name("name")?, source).to_string();
+        let qualified_name = if prefix.is_empty() { name.clone() } else { format!("{prefix}.{name}") };
+
+        let decorators = python_decorators(outer, source);
+        let attr_line_start = (outer.id() != node.id()).then(|| outer.start_position().row as u32 + 1);
+        let signature = self.build_python_signature(node, source, &name, &decorators);
+
+        // self/cls name the class; other parameters are recorded when annotated
+        let mut param_types = BTreeMap::new();
+        if let Some(params) = node.child_by_field_name("parameters") {
+            let is_static = decorators.iter().any(|d| d == "staticmethod");
+            let mut cursor = params.walk();
+            for (i, param) in params.named_children(&mut cursor).enumerate() {
+                match param.kind() {
+                    "identifier" if i == 0 && !is_static => {
+                        if let Some(class) = class {
+                            param_types.insert(node_text(&param, source).to_string(), class.to_string());
+                        }
+                    }
+                    "typed_parameter" | "typed_default_parameter" => {
+                        let name = param.child_by_field_name("name").or_else(|| param.named_child(0));
+                        if let (Some(name), Some(ty)) = (name, param.child_by_field_name("type"))
+                            && name.kind() == "identifier"
+                        {
+                            param_types.insert(node_text(&name, source).to_string(), node_text(&ty, source).to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let calls = match node.child_by_field_name("body") {
+            Some(body) => {
+                let mut calls = Vec::new();
+                collect_python_calls(&body, source, &mut calls);
+                calls.sort_by_key(|c| c.line);
+                calls
+            }
+            None => Vec::new(),
+        };
+
+        Some(Function {
+            scope: python_scope(&name),
+            name,
+            qualified_name,
+            ast_hash: content_hash(&source[node.start_byte()..node.end_byte()]),
+            line_start: node.start_position().row as u32 + 1,
+            line_end: node.end_position().row as u32 + 1,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+            attr_line_start,
+            signature,
+            attributes: decorators,
+            doc: None,
+            summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
+            receiver: class.map(String::from),
+            trait_impl: None,
+            takes_self: false,
+            is_entry: false,
+            exported_c: false,
+            is_async: false,
+            param_types,
+            local_types: BTreeMap::new(),
+            calls,
+            called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: Vec::new(),
+            type_refs: Vec::new(),
+            uses_types: Vec::new(),
+            global_refs: Vec::new(),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
+        })
+    }
+
+    fn extract_class(&self, node: &tree_sitter::Node, source: &[u8], prefix: &str) -> Option<TypeDef> {
+        let name = node_text(&node.child_by_field_name("name")?, source).to_string();
+        let qualified_name = if prefix.is_empty() { name.clone() } else { format!("{prefix}.{name}") };
+
+        // Methods defined directly in the class body
+        let mut methods = Vec::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for child in body.named_children(&mut cursor) {
+                let definition = match child.kind() {
+                    "decorated_definition" => child.child_by_field_name("definition"),
+                    _ => Some(child),
+                };
+                if let Some(definition) = definition
+                    && definition.kind() == "function_definition"
+                    && let Some(method) = definition.child_by_field_name("name")
+                {
+                    methods.push(node_text(&method, source).to_string());
+                }
+            }
+        }
+
+        Some(TypeDef {
+            name,
+            qualified_name,
+            kind: TypeKind::Struct,
+            line_start: node.start_position().row as u32 + 1,
+            line_end: node.end_position().row as u32 + 1,
+            summary: None,
+            methods,
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: None,
+        })
+    }
+
+    /// "@staticmethod def parse(text: str) -> Config", with "async" kept
+    fn build_python_signature(&self, node: &tree_sitter::Node, source: &[u8], name: &str, decorators: &[String]) -> String {
+        let params = node
+            .child_by_field_name("parameters")
+            .map(|n| node_text(&n, source))
+            .unwrap_or("()");
+
+        let return_type = node
+            .child_by_field_name("return_type")
+            .map(|n| format!(" -> {}", node_text(&n, source)))
+            .unwrap_or_default();
+
+        let mut signature = String::new();
+        for decorator in decorators {
+            signature.push_str(&format!("@{} ", decorator));
+        }
+        if node_text(node, source).starts_with("async") {
+            signature.push_str("async ");
+        }
+        signature.push_str(&format!("def {}{}{}", name, params, return_type));
+        signature
+    }
+}
+
+/// Decorators of a decorated_definition without the `@`, with whitespace collapsed
+fn python_decorators(node: &tree_sitter::Node, source: &[u8]) -> Vec<String> {
+    let mut decorators = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() == "decorator" {
+            let text = node_text(&child, source).trim_start_matches('@');
+            let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            decorators.push(flat.replace("( ", "(").replace(" )", ")"));
+        }
+    }
+    decorators
+}
+
+/// Calls in a Python function body, leaving out those in nested functions and classes,
+/// which are indexed on their own
+fn collect_python_calls(node: &tree_sitter::Node, source: &[u8], calls: &mut Vec<CallSite>) {
+    match node.kind() {
+        "function_definition" | "class_definition" | "decorated_definition" => return,
+        "call" => calls.extend(call_site(node, source)),
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_python_calls(&child, source, calls);
+    }
+}
+
+/// Leading-underscore names are private by convention; dunder methods are not
+fn python_scope(name: &str) -> Scope {
+    let dunder = name.len() > 4 && name.starts_with("__") && name.ends_with("__");
+    if name.starts_with('_') && !dunder { Scope::Internal } else { Scope::Public }
+}
+
+/// Convert a Python file path to its module prefix for qualified names.
+/// e.g., "./pkg/store.py" -> "pkg/store", "pkg/__init__.py" -> "pkg"
+fn python_path_to_module(path: &str) -> String {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let path = path.strip_suffix(".py").unwrap_or(path);
+    match path.strip_suffix("/__init__") {
+        Some(package) => package.to_string(),
+        None => path.to_string(),
+    }
+}
+
+// ============================================================================
+// TypeScript Parser
+// ============================================================================
+
+#[cfg(feature = "lang-typescript")]
+pub struct TypeScriptParser {
+    typescript: Parser,
+    /// .tsx and JavaScript files, which may contain JSX
+    tsx: Parser,
+}
+
+#[cfg(feature = "lang-typescript")]
+impl LanguageParser for TypeScriptParser {
+    fn language(&self) -> &'static str {
+        "typescript"
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        TypeScriptParser::parse_file(self, source, path)
+    }
+}
+
+#[cfg(feature = "lang-typescript")]
+impl TypeScriptParser {
+    pub fn new() -> Self {
+        let mut typescript = Parser::new();
+        typescript
+            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+            .expect("failed to load TypeScript grammar");
+        let mut tsx = Parser::new();
+        tsx.set_language(&tree_sitter_typescript::LANGUAGE_TSX.into()).expect("failed to load TSX grammar");
+        Self { typescript, tsx }
+    }
+
+    pub fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        let parser = match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("ts" | "mts" | "cts") => &mut self.typescript,
+            _ => &mut self.tsx,
+        };
+        let tree = parser.parse(source, None)?;
+        let root = tree.root_node();
+
+        let mut functions = Vec::new();
+        let mut types = Vec::new();
+
+        // e.g., "src/api/users.ts" -> "api/users", so functions are "api/users.getUser"
+        let module = ts_path_to_module(path);
+        let mut cursor = root.walk();
+        for child in root.named_children(&mut cursor) {
+            // `export function f`, `export default class C`, `export const g = () => ...`
+            let (declaration, exported) = match child.kind() {
+                "export_statement" => match child.child_by_field_name("declaration") {
+                    Some(declaration) => (declaration, true),
+                    None => continue,
+                },
+                _ => (child, false),
+            };
+            self.extract_declaration(&declaration, source.as_bytes(), &module, exported, &mut functions, &mut types);
+        }
+
+        Some(FileEntry {
+            ast_hash: content_hash(source.as_bytes()),
+            functions,
+            types,
+            variables: Vec::new(),
+            impls: Vec::new(),
+            reference: false,
+            parse_error_ratio: error_ratio(&root, source.len()),
+            parse_errors: error_count(&root),
+            first_error_line: first_error_line(&root),
+            mtime_ms: 0,
+            imports: Vec::new(),
+            is_test: false,
+            doc: None,
+        })
+    }
+
+    fn extract_declaration(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        module: &str,
+        exported: bool,
+        functions: &mut Vec<Function>,
+        types: &mut Vec<TypeDef>,
+    ) {
+        let scope = if exported { Scope::Public } else { Scope::Internal };
+        match node.kind() {
+            "function_declaration" | "generator_function_declaration" => {
+                functions.extend(self.extract_function(node, node, source, module, None, scope, ""));
+            }
+            // const handler = (req) => ..., const load = function () { ... }
+            "lexical_declaration" | "variable_declaration" => {
+                let mut cursor = node.walk();
+                for declarator in node.named_children(&mut cursor) {
+                    let Some(value) = declarator.child_by_field_name("value") else {
+                        continue;
+                    };
+                    if matches!(value.kind(), "arrow_function" | "function_expression")
+                        && let Some(name) = declarator.child_by_field_name("name")
+                        && name.kind() == "identifier"
+                    {
+                        let head = format!("const {} = ", node_text(&name, source));
+                        functions.extend(self.extract_function(&declarator, &value, source, module, None, scope, &head));
+                    }
+                }
+            }
+            "class_declaration" | "abstract_class_declaration" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let class = node_text(&name, source);
+                let prefix = format!("{module}.{class}");
+                let mut methods = Vec::new();
+                if let Some(body) = node.child_by_field_name("body") {
+                    let mut cursor = body.walk();
+                    for member in body.named_children(&mut cursor) {
+                        let method = match member.kind() {
+                            "method_definition" => self.extract_function(&member, &member, source, &prefix, Some(class), scope, ""),
+                            // handle = (e) => { ... }
+                            "public_field_definition" => match member.child_by_field_name("value") {
+                                Some(value) if value.kind() == "arrow_function" => {
+                                    let head = member
+                                        .child_by_field_name("name")
+                                        .map(|n| format!("{} = ", node_text(&n, source)))
+                                        .unwrap_or_default();
+                                    self.extract_function(&member, &value, source, &prefix, Some(class), scope, &head)
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        };
+                        if let Some(mut method) = method {
+                            if ts_is_private_member(&member, source) {
+                                method.scope = Scope::Internal;
+                            }
+                            methods.push(method.name.clone());
+                            functions.push(method);
+                        }
+                    }
+                }
+                types.push(ts_type_def(node, class, &prefix, TypeKind::Struct, methods));
+            }
+            "interface_declaration" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let name = node_text(&name, source);
+                let mut methods = Vec::new();
+                if let Some(body) = node.child_by_field_name("body") {
+                    let mut cursor = body.walk();
+                    for member in body.named_children(&mut cursor) {
+                        if member.kind() == "method_signature"
+                            && let Some(method) = member.child_by_field_name("name")
+                        {
+                            methods.push(node_text(&method, source).to_string());
+                        }
+                    }
+                }
+                types.push(ts_type_def(node, name, &format!("{module}.{name}"), TypeKind::Interface, methods));
+            }
+            "type_alias_declaration" | "enum_declaration" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let name = node_text(&name, source);
+                let kind = if node.kind() == "enum_declaration" { TypeKind::Enum } else { TypeKind::Typedef };
+                types.push(ts_type_def(node, name, &format!("{module}.{name}"), kind, Vec::new()));
+            }
+            _ => {}
+        }
+    }
+
+    /// A function from `node` (a declaration, method, or function value). `outer` spans
+    /// what the function's lines cover: the declarator or class field for function values.
+    /// `head` precedes the signature, e.g. "const handler = ".
+    #[allow(clippy::too_many_arguments)]
+    fn extract_function(
+        &self,
+        outer: &tree_sitter::Node,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        prefix: &str,
+        class: Option<&str>,
+        scope: Scope,
+        head: &str,
+    ) -> Option<Function> {
+        let name_node = outer.child_by_field_name("name")?;
+        let name = node_text(&name_node, source).to_string();
+        let qualified_name = format!("{prefix}.{name}");
+
+        // this names the class; other parameters are recorded when annotated
+        let mut param_types = BTreeMap::new();
+        if let Some(class) = class {
+            param_types.insert("this".to_string(), class.to_string());
+        }
+        if let Some(params) = node.child_by_field_name("parameters") {
+            let mut cursor = params.walk();
+            for param in params.named_children(&mut cursor) {
+                let pattern = param.child_by_field_name("pattern").or_else(|| param.child_by_field_name("name"));
+                if let (Some(pattern), Some(ty)) = (pattern, param.child_by_field_name("type"))
+                    && pattern.kind() == "identifier"
+                {
+                    let ty = node_text(&ty, source).trim_start_matches(':').trim();
+                    param_types.insert(node_text(&pattern, source).to_string(), ty.to_string());
+                }
+            }
+        }
+
+        let calls = match node.child_by_field_name("body") {
+            Some(body) => extract_calls(&body, source),
+            None => Vec::new(),
+        };
+
+        Some(Function {
+            name,
+            qualified_name,
+            ast_hash: content_hash(&source[outer.start_byte()..outer.end_byte()]),
+            line_start: outer.start_position().row as u32 + 1,
+            line_end: outer.end_position().row as u32 + 1,
+            byte_start: outer.start_byte() as u32,
+            byte_end: outer.end_byte() as u32,
+            attr_line_start: None,
+            signature: self.build_ts_signature(outer, node, source, head),
+            attributes: Vec::new(),
+            doc: None,
+            summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
+            receiver: class.map(String::from),
+            trait_impl: None,
+            takes_self: false,
+            scope,
+            is_entry: false,
+            exported_c: false,
+            is_async: false,
+            param_types,
+            local_types: BTreeMap::new(),
+            calls,
+            called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: Vec::new(),
+            type_refs: Vec::new(),
+            uses_types: Vec::new(),
+            global_refs: Vec::new(),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
+        })
+    }
+
+    /// "async function getUser(id: string): Promise<User>", "static parse(text: string): Config",
+    /// or "const handler = async (req: Request): void =>"
+    fn build_ts_signature(&self, outer: &tree_sitter::Node, node: &tree_sitter::Node, source: &[u8], head: &str) -> String {
+        let params = node
+            .child_by_field_name("parameters")
+            .or_else(|| node.child_by_field_name("parameter"));
+        let return_type = node.child_by_field_name("return_type").map(|n| node_text(&n, source)).unwrap_or_default();
+
+        // Keywords and the name as written, up to the parameters
+        let start = if head.is_empty() { outer.start_byte() } else { node.start_byte() };
+        let end = params.map_or(start, |p| p.start_byte());
+        let keywords = std::str::from_utf8(&source[start..end.max(start)]).unwrap_or("");
+        let mut keywords = keywords.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !head.is_empty() && !keywords.is_empty() {
+            keywords.push(' ');
+        }
+        let params = params.map(|p| node_text(&p, source)).unwrap_or("()");
+        let arrow = if node.kind() == "arrow_function" { " =>" } else { "" };
+        format!("{head}{keywords}{params}{return_type}{arrow}")
+    }
+}
+
+/// `private` and `#private` class members
+fn ts_is_private_member(node: &tree_sitter::Node, source: &[u8]) -> bool {
+    let mut cursor = node.walk();
+    let private_modifier = node
+        .children(&mut cursor)
+        .any(|c| c.kind() == "accessibility_modifier" && node_text(&c, source) == "private");
+    let private_name = node.child_by_field_name("name").is_some_and(|n| n.kind() == "private_property_identifier");
+    private_modifier || private_name
+}
+
+fn ts_type_def(node: &tree_sitter::Node, name: &str, qualified_name: &str, kind: TypeKind, methods: Vec<String>) -> TypeDef {
+    TypeDef {
+        name: name.to_string(),
+        qualified_name: qualified_name.to_string(),
+        kind,
+        line_start: node.start_position().row as u32 + 1,
+        line_end: node.end_position().row as u32 + 1,
+        summary: None,
+        methods,
+        implements: Vec::new(),
+        implemented_by: Vec::new(),
+        fields: Vec::new(),
+        variants: Vec::new(),
+        doc: None,
+    }
+}
+
+/// Convert a TypeScript or JavaScript file path to its module for qualified names.
+/// Like Rust modules, `src/` is dropped and an index file names its directory; dots
+/// left in the file name become underscores so they cannot be read as separators.
+/// e.g., "src/api/users.ts" -> "api/users", "src/api/index.ts" -> "api",
+/// "lib/users.test.ts" -> "lib/users_test"
+fn ts_path_to_module(path: &str) -> String {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let path = path.strip_prefix("src/").unwrap_or(path);
+    let path = path.strip_suffix(".d.ts").unwrap_or(path);
+    let path = match path.rsplit_once('.') {
+        Some((stem, ext)) if TS_EXTENSIONS.contains(&ext) => stem,
+        _ => path,
+    };
+    let path = path.strip_suffix("/index").unwrap_or(path);
+    path.replace('.', "_")
+}
+
+// ============================================================================
+// Java Parser
+// ============================================================================
+
+#[cfg(feature = "lang-java")]
+pub struct JavaParser {
+    parser: Parser,
+}
+
+#[cfg(feature = "lang-java")]
+impl LanguageParser for JavaParser {
+    fn language(&self) -> &'static str {
+        "java"
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        JavaParser::parse_file(self, source, path)
+    }
+}
+
+#[cfg(feature = "lang-java")]
+impl JavaParser {
+    pub fn new() -> Self {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_java::LANGUAGE.into())
+            .expect("failed to load Java grammar");
+        Self { parser }
+    }
+
+    pub fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        let tree = self.parser.parse(source, None)?;
+        let root = tree.root_node();
+        let source_bytes = source.as_bytes();
+
+        // "package com.example.service;" -> "com/example/service"; the directory without one
+        let package = named_child_of_kind(&root, &["package_declaration"])
+            .and_then(|decl| named_child_of_kind(&decl, &["scoped_identifier", "identifier"]))
+            .map(|name| node_text(&name, source_bytes).replace('.', "/"))
+            .unwrap_or_else(|| path_to_prefix(path));
+
+        let mut functions = Vec::new();
+        let mut types = Vec::new();
+        self.extract_types(&root, source_bytes, &package, &mut functions, &mut types);
+
+        Some(FileEntry {
+            ast_hash: content_hash(source_bytes),
+            functions,
+            types,
+            variables: Vec::new(),
+            impls: Vec::new(),
+            reference: false,
+            parse_error_ratio: error_ratio(&root, source.len()),
+            parse_errors: error_count(&root),
+            first_error_line: first_error_line(&root),
+            mtime_ms: 0,
+            imports: Vec::new(),
+            is_test: false,
+            doc: None,
+        })
+    }
+
+    /// Classes, interfaces, enums, and records declared directly in `node`, with their
+    /// methods. Nested types are qualified by their enclosing type.
+    fn extract_types(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        prefix: &str,
+        functions: &mut Vec<Function>,
+        types: &mut Vec<TypeDef>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let kind = match child.kind() {
+                "class_declaration" | "record_declaration" => TypeKind::Struct,
+                "interface_declaration" => TypeKind::Interface,
+                "enum_declaration" => TypeKind::Enum,
+                _ => continue,
+            };
+            let Some(name) = child.child_by_field_name("name") else {
+                continue;
+            };
+            let name = node_text(&name, source);
+            let qualified_name = format!("{prefix}.{name}");
+
+            // An enum's methods follow its constants
+            let mut body = child.child_by_field_name("body");
+            if kind == TypeKind::Enum {
+                body = body.and_then(|b| named_child_of_kind(&b, &["enum_body_declarations"]));
+            }
+
+            let mut methods = Vec::new();
+            if let Some(body) = body {
+                let mut cursor = body.walk();
+                for member in body.named_children(&mut cursor) {
+                    if !matches!(member.kind(), "method_declaration" | "constructor_declaration") {
+                        continue;
+                    }
+                    let interface = kind == TypeKind::Interface;
+                    if let Some(method) = self.extract_method(&member, source, &qualified_name, name, interface) {
+                        methods.push(method.name.clone());
+                        functions.push(method);
+                    }
+                }
+                self.extract_types(&body, source, &qualified_name, functions, types);
+            }
+
+            types.push(TypeDef {
+                name: name.to_string(),
+                qualified_name,
+                kind,
+                line_start: child.start_position().row as u32 + 1,
+                line_end: child.end_position().row as u32 + 1,
+                summary: None,
+                methods,
+                implements: Vec::new(),
+                implemented_by: Vec::new(),
+                fields: Vec::new(),
+                variants: Vec::new(),
+                doc: None,
+            });
+        }
+    }
+
+    /// A method or constructor of `class`. Interface methods are public unless marked private.
+    fn extract_method(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        prefix: &str,
+        class: &str,
+        interface: bool,
+    ) -> Option<Function> {
+        let name_node = node.child_by_field_name("name")?;
+        let name = node_text(&name_node, source).to_string();
+        let qualified_name = format!("{prefix}.{name}");
+
+        // Keywords and annotations from the modifiers node; like Rust attributes, annotations
+        // start at attr_line_start and line_start stays on the declaration itself
+        let mut keywords = Vec::new();
+        let mut annotations = Vec::new();
+        let mut attr_line_start = None;
+        let mut line_start = node.child_by_field_name("type").unwrap_or(name_node).start_position().row as u32 + 1;
+        let mut cursor = node.walk();
+        if let Some(modifiers) = node.children(&mut cursor).find(|c| c.kind() == "modifiers") {
+            let mut cursor = modifiers.walk();
+            for modifier in modifiers.children(&mut cursor) {
+                let text = node_text(&modifier, source);
+                if modifier.kind().ends_with("annotation") {
+                    attr_line_start.get_or_insert(modifier.start_position().row as u32 + 1);
+                    annotations.push(text.trim_start_matches('@').split_whitespace().collect::<Vec<_>>().join(" "));
+                } else {
+                    line_start = line_start.min(modifier.start_position().row as u32 + 1);
+                    keywords.push(text);
+                }
+            }
+        }
+        let scope = if keywords.contains(&"private") {
+            Scope::Internal
+        } else if interface || keywords.contains(&"public") || keywords.contains(&"protected") {
+            Scope::Public
+        } else {
+            Scope::Internal
+        };
+
+        let params = node.child_by_field_name("parameters");
+        let mut signature = keywords.join(" ");
+        if let Some(return_type) = node.child_by_field_name("type") {
+            signature = format!("{signature} {}", node_text(&return_type, source));
+        }
+        let params_text = params.map(|p| node_text(&p, source)).unwrap_or("()");
+        let signature = format!("{signature} {name}{params_text}").trim_start().to_string();
+
+        // this names the class; parameters carry their declared types
+        let mut param_types = BTreeMap::from([("this".to_string(), class.to_string())]);
+        if let Some(params) = params {
+            let mut cursor = params.walk();
+            for param in params.named_children(&mut cursor) {
+                if let (Some(name), Some(ty)) = (param.child_by_field_name("name"), param.child_by_field_name("type")) {
+                    param_types.insert(node_text(&name, source).to_string(), node_text(&ty, source).to_string());
+                }
+            }
+        }
+
+        let mut calls = Vec::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            collect_java_calls(&body, source, &mut calls);
+            calls.sort_by_key(|c| c.line);
+        }
+
+        Some(Function {
+            name,
+            qualified_name,
+            ast_hash: content_hash(&source[node.start_byte()..node.end_byte()]),
+            line_start,
+            line_end: node.end_position().row as u32 + 1,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+            attr_line_start,
+            signature,
+            attributes: annotations,
+            doc: None,
+            summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
+            receiver: Some(class.to_string()),
+            trait_impl: None,
+            takes_self: false,
+            scope,
+            is_entry: false,
+            exported_c: false,
+            is_async: false,
+            param_types,
+            local_types: BTreeMap::new(),
+            calls,
+            called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: Vec::new(),
+            type_refs: Vec::new(),
+            uses_types: Vec::new(),
+            global_refs: Vec::new(),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
+        })
+    }
+}
+
+// ============================================================================
+// C++ Parser
+// ============================================================================
+
+#[cfg(feature = "lang-cpp")]
+pub struct CppParser {
+    parser: Parser,
+}
+
+#[cfg(feature = "lang-cpp")]
+impl LanguageParser for CppParser {
+    fn language(&self) -> &'static str {
+        "cpp"
+    }
+
+    fn parse_file(&mut self, source: &str, path: &str) -> Option<FileEntry> {
+        CppParser::parse_file(self, source, path)
+    }
+}
+
+/// Where a C++ declaration sits: its enclosing namespaces and classes
+#[derive(Clone, Copy)]
+struct CppScope<'a> {
+    /// Qualified name of the namespace or class, "" at file level (e.g., "net::Server")
+    path: &'a str,
+    /// The enclosing class, whose functions are its methods
+    class: Option<&'a str>,
+    /// `template <typename T>` of the declaration being walked, if any
+    template: Option<&'a str>,
+}
+
+#[cfg(feature = "lang-cpp")]
+impl CppParser {
+    pub fn new() -> Self {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_cpp::LANGUAGE.into())
+            .expect("failed to load C++ grammar");
+        Self { parser }
+    }
+
+    pub fn parse_file(&mut self, source: &str, _path: &str) -> Option<FileEntry> {
+        let tree = self.parser.parse(source, None)?;
+        let root = tree.root_node();
+        let source_bytes = source.as_bytes();
+
+        // Out-of-line definitions name their class or namespace the same way (`a::b()`);
+        // the namespaces opened in this file tell the two apart
+        let mut namespaces = HashSet::new();
+        collect_cpp_namespaces(&root, source_bytes, &mut namespaces);
+
+        let mut functions = Vec::new();
+        let mut types = Vec::new();
+        let scope = CppScope {
+            path: "",
+            class: None,
+            template: None,
+        };
+        self.extract_declarations(
+            &root,
+            source_bytes,
+            scope,
+            &namespaces,
+            &mut functions,
+            &mut types,
+        );
+
+        Some(FileEntry {
+            ast_hash: content_hash(source_bytes),
+            functions,
+            types,
+            variables: Vec::new(),
+            impls: Vec::new(),
+            reference: false,
+            parse_error_ratio: error_ratio(&root, source.len()),
+            parse_errors: error_count(&root),
+            first_error_line: first_error_line(&root),
+            mtime_ms: 0,
+            imports: Vec::new(),
+            is_test: false,
+            doc: None,
+        })
+    }
+
+    fn extract_declarations(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        scope: CppScope,
+        namespaces: &HashSet<&str>,
+        functions: &mut Vec<Function>,
+        types: &mut Vec<TypeDef>,
+    ) {
+        // Class members are private until an access specifier says otherwise; struct members public
+        let mut access = if node.parent().is_some_and(|p| p.kind() == "class_specifier") {
+            Scope::Internal
+        } else {
+            Scope::Public
+        };
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "function_definition" => {
+                    functions
+                        .extend(self.extract_function(&child, source, scope, access, namespaces));
+                }
+                "template_declaration" => {
+                    let template = child
+                        .child_by_field_name("parameters")
+                        .map(|p| node_text(&p, source));
+                    let inner = CppScope { template, ..scope };
+                    self.extract_declarations(&child, source, inner, namespaces, functions, types);
+                }
+                "namespace_definition" => {
+                    let Some(body) = child.child_by_field_name("body") else {
+                        continue;
+                    };
+                    // Anonymous namespaces add nothing to the name
+                    let path = match child.child_by_field_name("name") {
+                        Some(name) => cpp_join(scope.path, node_text(&name, source)),
+                        None => scope.path.to_string(),
+                    };
+                    let inner = CppScope {
+                        path: &path,
+                        class: None,
+                        template: None,
+                    };
+                    self.extract_declarations(&body, source, inner, namespaces, functions, types);
+                }
+                "class_specifier" | "struct_specifier" | "enum_specifier" => {
+                    self.extract_type(&child, source, scope, namespaces, functions, types);
+                }
+                // `struct Foo { ... } foo;`, `typedef struct { ... } Foo;`
+                "declaration" | "type_definition" | "field_declaration" => {
+                    if let Some(ty) = child.child_by_field_name("type")
+                        && matches!(
+                            ty.kind(),
+                            "class_specifier" | "struct_specifier" | "enum_specifier"
+                        )
+                    {
+                        self.extract_type(&ty, source, scope, namespaces, functions, types);
+                    }
+                }
+                "access_specifier" => {
+                    access = if node_text(&child, source).starts_with("public") {
+                        Scope::Public
+                    } else {
+                        Scope::Internal
+                    };
+                }
+                "declaration_list"
+                | "linkage_specification"
+                | "preproc_if"
+                | "preproc_ifdef"
+                | "preproc_else"
+                | "preproc_elif" => {
+                    let inner = CppScope {
+                        template: None,
+                        ..scope
+                    };
+                    self.extract_declarations(&child, source, inner, namespaces, functions, types);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// A class, struct, or enum with a body, then the members defined inside a class body
+    fn extract_type(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        scope: CppScope,
+        namespaces: &HashSet<&str>,
+        functions: &mut Vec<Function>,
+        types: &mut Vec<TypeDef>,
+    ) {
+        let (Some(name), Some(body)) = (
+            node.child_by_field_name("name"),
+            node.child_by_field_name("body"),
+        ) else {
+            return;
+        };
+        let segments = cpp_segments(node_text(&name, source));
+        let Some(name) = segments.last().cloned() else {
+            return;
+        };
+        let qualified_name = cpp_join(scope.path, &segments.join("::"));
+
+        let kind = if node.kind() == "enum_specifier" {
+            TypeKind::Enum
+        } else {
+            TypeKind::Struct
+        };
+        let mut methods = Vec::new();
+        if kind == TypeKind::Struct {
+            cpp_member_functions(&body, source, &mut methods);
+        }
+        types.push(TypeDef {
+            name: name.clone(),
+            qualified_name: qualified_name.clone(),
+            kind,
+            line_start: node.start_position().row as u32 + 1,
+            line_end: node.end_position().row as u32 + 1,
+            summary: None,
+            methods,
+            implements: Vec::new(),
+            implemented_by: Vec::new(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+            doc: None,
+        });
+
+        if kind == TypeKind::Struct {
+            let inner = CppScope {
+                path: &qualified_name,
+                class: Some(&name),
+                template: None,
+            };
+            self.extract_declarations(&body, source, inner, namespaces, functions, types);
+        }
+    }
+
+    fn extract_function(
+        &self,
+        node: &tree_sitter::Node,
+        source: &[u8],
+        scope: CppScope,
+        access: Scope,
+        namespaces: &HashSet<&str>,
+    ) -> Option<Function> {
+        let declarator = cpp_function_declarator(&node.child_by_field_name("declarator")?)?;
+        let segments = cpp_segments(node_text(
+            &declarator.child_by_field_name("declarator")?,
+            source,
+        ));
+        let (name, qualifier) = segments.split_last()?;
+        let name = name.clone();
+
+        // `void Server::start()` defined outside its class belongs to Server, unless the
+        // qualifier is a namespace opened in this file
+        let receiver = match (scope.class, qualifier.last()) {
+            (Some(class), _) if qualifier.is_empty() => Some(class.to_string()),
+            (_, Some(last)) if !namespaces.contains(last.as_str()) => Some(last.clone()),
+            _ => None,
+        };
+        let qualified_name = cpp_join(scope.path, &segments.join("::"));
+
+        let mut cursor = node.walk();
+        let is_static = node
+            .children(&mut cursor)
+            .any(|c| c.kind() == "storage_class_specifier" && node_text(&c, source) == "static");
+        let func_scope = match scope.class {
+            Some(_) => access,
+            None if is_static && receiver.is_none() => Scope::Static,
+            None => Scope::Public,
+        };
+
+        // Everything up to the body or the constructor's initializer list
+        let mut end = node
+            .child_by_field_name("body")
+            .map_or(node.end_byte(), |b| b.start_byte());
+        let mut cursor = node.walk();
+        if let Some(init) = node
+            .children(&mut cursor)
+            .find(|c| c.kind() == "field_initializer_list")
+        {
+            end = end.min(init.start_byte());
+        }
+        let head = std::str::from_utf8(&source[node.start_byte()..end]).unwrap_or("");
+        let head = head.split_whitespace().collect::<Vec<_>>().join(" ");
+        let signature = match scope.template {
+            Some(template) => format!("template {template} {head}"),
+            None => head,
+        };
+
+        // this names the class; parameters carry their declared type names
+        let mut param_types = BTreeMap::new();
+        if let Some(receiver) = &receiver {
+            param_types.insert("this".to_string(), receiver.clone());
+        }
+        if let Some(params) = declarator.child_by_field_name("parameters") {
+            let mut cursor = params.walk();
+            for param in params.named_children(&mut cursor) {
+                if param.kind() != "parameter_declaration" {
+                    continue;
+                }
+                let name = param
+                    .child_by_field_name("declarator")
+                    .and_then(|d| cpp_declared_name(&d, source));
+                if let (Some(name), Some(ty)) = (name, param.child_by_field_name("type"))
+                    && let Some(ty) = cpp_segments(node_text(&ty, source)).last()
+                {
+                    param_types.insert(name.to_string(), ty.clone());
+                }
+            }
+        }
+
+        let calls = node
+            .child_by_field_name("body")
+            .map(|b| extract_calls(&b, source))
+            .unwrap_or_default();
+
+        Some(Function {
+            is_entry: name == "main" && qualified_name == "main",
+            exported_c: false,
+            is_async: false,
+            name,
+            qualified_name,
+            ast_hash: content_hash(&source[node.start_byte()..node.end_byte()]),
+            line_start: node.start_position().row as u32 + 1,
+            line_end: node.end_position().row as u32 + 1,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+            attr_line_start: None,
+            signature,
+            attributes: Vec::new(),
+            doc: None,
+            summary: None,
+            summary_hash: None,
+            summary_shared_from: None,
+            summary_locked: false,
+            receiver,
+            trait_impl: None,
+            takes_self: false,
+            scope: func_scope,
+            param_types,
+            local_types: BTreeMap::new(),
+            calls,
+            called_by: Vec::new(),
+            possibly_called_by: Vec::new(),
+            references: Vec::new(),
+            type_refs: Vec::new(),
+            uses_types: Vec::new(),
+            global_refs: extract_global_refs(node, source, GlobalSyntax::C),
+            reads_globals: Vec::new(),
+            writes_globals: Vec::new(),
+        })
+    }
+}
+
+/// Names of every namespace opened in the file, nested ones by segment
+fn collect_cpp_namespaces<'a>(
+    node: &tree_sitter::Node,
+    source: &'a [u8],
+    namespaces: &mut HashSet<&'a str>,
+) {
+    if node.kind() == "namespace_definition"
+        && let Some(name) = node.child_by_field_name("name")
+    {
+        namespaces.extend(node_text(&name, source).split("::").map(str::trim));
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_cpp_namespaces(&child, source, namespaces);
+    }
+}
+
+/// Names of the member functions declared or defined in a class body
+fn cpp_member_functions(body: &tree_sitter::Node, source: &[u8], methods: &mut Vec<String>) {
+    let mut cursor = body.walk();
+    for member in body.named_children(&mut cursor) {
+        match member.kind() {
+            "function_definition" | "field_declaration" | "declaration" => {
+                if let Some(declarator) = member
+                    .child_by_field_name("declarator")
+                    .and_then(|d| cpp_function_declarator(&d))
+                    && let Some(name) = declarator.child_by_field_name("declarator")
+                    && let Some(name) = cpp_segments(node_text(&name, source)).pop()
+                {
+                    methods.push(name);
+                }
+            }
+            "template_declaration" => cpp_member_functions(&member, source, methods),
+            _ => {}
+        }
+    }
+}
+
+/// The function_declarator under pointer and reference declarators (`Foo* make()`, `Foo& get()`)
+fn cpp_function_declarator<'t>(node: &tree_sitter::Node<'t>) -> Option<tree_sitter::Node<'t>> {
+    match node.kind() {
+        "function_declarator" => Some(*node),
+        "pointer_declarator" | "reference_declarator" => {
+            let inner = node
+                .child_by_field_name("declarator")
+                .or_else(|| node.named_child(node.named_child_count().checked_sub(1)?))?;
+            cpp_function_declarator(&inner)
+        }
+        _ => None,
+    }
+}
+
+/// The identifier a parameter declarator names, under pointers and references
+fn cpp_declared_name<'a>(node: &tree_sitter::Node, source: &'a [u8]) -> Option<&'a str> {
+    match node.kind() {
+        "identifier" => Some(node_text(node, source)),
+        _ => {
+            let inner = node
+                .child_by_field_name("declarator")
+                .or_else(|| node.named_child(node.named_child_count().checked_sub(1)?))?;
+            cpp_declared_name(&inner, source)
+        }
+    }
+}
+
+/// "net::Server<T>::start" -> ["net", "Server", "start"]; operator names are kept whole
+fn cpp_segments(text: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if current.starts_with("operator") {
+            current.push(c);
+            continue;
+        }
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ':' if depth == 0 && chars.peek() == Some(&':') => {
+                chars.next();
+                segments.push(std::mem::take(&mut current));
+            }
+            c if depth == 0 && !c.is_whitespace() => current.push(c),
+            _ => {}
+        }
+    }
+    segments.push(current);
+    segments.retain(|s| !s.is_empty());
+    segments
+}
+
+/// "net" + "Server::start" -> "net::Server::start"
+fn cpp_join(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{scope}::{name}")
+    }
+}
+
+/// The first named child of `node` whose kind is one of `kinds`
+fn named_child_of_kind<'t>(node: &tree_sitter::Node<'t>, kinds: &[&str]) -> Option<tree_sitter::Node<'t>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).find(|c| kinds.contains(&c.kind()))
+}
+
+/// The first node under `node` (or `node` itself) in source order whose kind is one of `kinds`
+fn first_descendant_of_kind<'t>(node: &tree_sitter::Node<'t>, kinds: &[&str]) -> Option<tree_sitter::Node<'t>> {
+    if kinds.contains(&node.kind()) {
+        return Some(*node);
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).find_map(|c| first_descendant_of_kind(&c, kinds))
+}
+
+/// method_invocation nodes under `node`, as "object.name" with the object kept as the receiver
+fn collect_java_calls(node: &tree_sitter::Node, source: &[u8], calls: &mut Vec<CallSite>) {
+    if node.kind() == "method_invocation"
+        && let Some(name) = node.child_by_field_name("name")
+    {
+        let callee_name = node_text(&name, source).to_string();
+        let receiver_expr = node.child_by_field_name("object").map(|o| node_text(&o, source).to_string());
+        let raw = match &receiver_expr {
+            Some(object) => format!("{object}.{callee_name}"),
+            None => callee_name.clone(),
+        };
+        calls.push(CallSite {
+            target: "[unresolved]".to_string(),
+            raw,
+            callee_name,
+            receiver_expr,
+            candidates: Vec::new(),
+            resolved_by: None,
+            line: node.start_position().row as u32 + 1,
+            column: node.start_position().column as u32 + 1,
+            byte_start: node.start_byte() as u32,
+            byte_end: node.end_byte() as u32,
+        });
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_java_calls(&child, source, calls);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_parse_simple_function() {
+        let source = r#"
+package main
+
+func Hello(name string) string {
+    return "Hello, " + name
+}
+"#;
+        let mut parser = GoParser::new();
+        let entry = parser.parse_file(source, "main.go").unwrap();
+
+        assert_eq!(entry.functions.len(), 1);
+        let f = &entry.functions[0];
+        assert_eq!(f.name, "Hello");
+        assert_eq!(f.qualified_name, "main.Hello");
+        assert_eq!(f.scope, Scope::Public);
+        assert!(f.signature.contains("func Hello(name string) string"));
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_generics() {
+        let source = r#"
+package coll
+
+func Map[T any, U any](s []T, f func(T) U) []U {
+    return nil
+}
+
+type Cache[K comparable, V any] struct{}
+
+func (c *Cache[K, V]) Get(k K) V {
+    c.load(k)
+    return Map[int, string](nil, nil)
+}
+
+func (c Cache[_, V]) load(k any) {
+    Keys[string](c)
+}
+"#;
+        let entry = GoParser::new().parse_file(source, "pkg/coll/coll.go").unwrap();
+        let map_fn = &entry.functions[0];
+        assert_eq!(map_fn.signature, "func Map[T any, U any](s []T, f func(T) U) []U");
+
+        let get = &entry.functions[1];
+        assert_eq!((get.qualified_name.as_str(), get.receiver.as_deref()), ("pkg/coll.Cache.Get", Some("Cache")));
+        assert_eq!(get.signature, "func Get(k K) V");
+        let raws: Vec<&str> = get.calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["c.load", "Map"]);
+        assert_eq!((get.calls[1].callee_name.as_str(), get.calls[1].receiver_expr.as_deref()), ("Map", None));
+
+        let load = &entry.functions[2];
+        assert_eq!(load.receiver.as_deref(), Some("Cache"));
+        assert_eq!(load.calls[0].raw, "Keys");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_var_func_literals() {
+        let source = r#"
+package web
+
+var handler = func(w http.ResponseWriter, r *http.Request) {
+    doWork(r)
+}
+
+var (
+    Setup = http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) { prepare() })
+    limit = 10
+)
+
+var routes = map[string]func(){
+    "x": doX,
+    "y": func() { doY() },
+    "z": admin.Reset,
+}
+"#;
+        let entry = GoParser::new().parse_file(source, "web/routes.go").unwrap();
+        let names: Vec<&str> = entry.functions.iter().map(|f| f.qualified_name.as_str()).collect();
+        assert_eq!(names, vec!["web.handler", "web.Setup", "web.routes"]);
+        assert_eq!(entry.variables.len(), 4);
+
+        let handler = &entry.functions[0];
+        assert_eq!(handler.signature, "var handler = func(w http.ResponseWriter, r *http.Request)");
+        assert_eq!((handler.line_start, handler.line_end, handler.scope), (4, 6, Scope::Internal));
+        assert_eq!(handler.calls[0].raw, "doWork");
+        assert_eq!(handler.param_types.get("r").map(String::as_str), Some("*http.Request"));
+
+        let setup = &entry.functions[1];
+        assert_eq!(setup.scope, Scope::Public);
+        let raws: Vec<&str> = setup.calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["http.HandlerFunc", "prepare"]);
+
+        // Functions named in the table count as calls too
+        let routes = &entry.functions[2];
+        assert_eq!(routes.signature, "var routes = map[string]func()");
+        let raws: Vec<&str> = routes.calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["doX", "doY", "admin.Reset"]);
+        assert_eq!(routes.calls[2].callee_name, "Reset");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_imports() {
+        let source = "package main\n\nimport \"fmt\"\n\nimport (\n\tu \"example.com/app/internal/utils\"\n\t. \"example.com/app/dsl\"\n\t_ \"example.com/app/driver\"\n\t\"github.com/go-chi/chi/v5\"\n\t\"gopkg.in/yaml.v3\"\n)\n";
+        let entry = GoParser::new().parse_file(source, "cmd/app/main.go").unwrap();
+        let imports: Vec<(&str, &str)> = entry.imports.iter().map(|i| (i.alias.as_str(), i.path.as_str())).collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("fmt", "fmt"),
+                ("u", "example.com/app/internal/utils"),
+                (".", "example.com/app/dsl"),
+                ("chi", "github.com/go-chi/chi/v5"),
+                ("yaml", "gopkg.in/yaml.v3"),
+            ]
+        );
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_struct_fields() {
+        let source = "package store\n\ntype Conn struct {\n\tsync.Mutex\n\t*Base\n\tX, Y int\n\tName string `json:\"name,omitempty\"`\n\tpeers map[string]*Conn\n}\n\ntype ID int\n";
+        let entry = GoParser::new().parse_file(source, "store/conn.go").unwrap();
+        let fields: Vec<(&str, &str, Option<&str>, bool)> = entry.types[0]
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.type_name.as_str(), f.tag.as_deref(), f.embedded))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("Mutex", "sync.Mutex", None, true),
+                ("Base", "*Base", None, true),
+                ("X", "int", None, false),
+                ("Y", "int", None, false),
+                ("Name", "string", Some("json:\"name,omitempty\""), false),
+                ("peers", "map[string]*Conn", None, false),
+            ]
+        );
+        assert!(entry.types[1].fields.is_empty());
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_build_constrained_names() {
+        let mut parser = GoParser::new();
+        let qualified = |parser: &mut GoParser, source: &str, path: &str| -> Vec<String> {
+            parser.parse_file(source, path).unwrap().functions.into_iter().map(|f| f.qualified_name).collect()
+        };
+        let source = "package events\n\nfunc init() {}\n\nfunc readEvents() {}\n\nfunc (w *Watcher) Close() {}\n";
+
+        assert_eq!(
+            qualified(&mut parser, source, "events/poll_linux.go"),
+            vec!["events.init@poll_linux", "events.readEvents@linux", "events.Watcher.Close@linux"]
+        );
+        assert_eq!(qualified(&mut parser, source, "events/poll_darwin_arm64_test.go")[1], "events.readEvents@darwin_arm64");
+        assert_eq!(qualified(&mut parser, source, "events/linux.go")[1], "events.readEvents");
+        assert_eq!(qualified(&mut parser, source, "events/poll.go")[1], "events.readEvents");
+
+        let tagged = format!("//go:build linux && !cgo\n// +build linux,!cgo\n\n{source}");
+        assert_eq!(qualified(&mut parser, &tagged, "events/poll.go")[1], "events.readEvents@linux&&!cgo");
+        let legacy = format!("// +build linux darwin\n\n{source}");
+        assert_eq!(qualified(&mut parser, &legacy, "events/poll.go")[1], "events.readEvents@linux||darwin");
+        // Only comments before the package clause are constraints
+        let late = source.replace("package events\n", "package events\n//go:build linux\n");
+        assert_eq!(qualified(&mut parser, &late, "events/poll.go")[1], "events.readEvents");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_doc_comments() {
+        let source = "package store\n\n// Store keeps entries.\n//\n// It is safe for concurrent use.\ntype Store struct{}\n\ntype (\n\t// ID names an entry.\n\tID string\n\tKey string\n)\n\n\
+                      // Open opens the store.\n//go:noinline\nfunc Open() {}\n\n// Unrelated.\n\nfunc Close() {}\n\n/*\n Get returns an entry.\n*/\nfunc (s *Store) Get() {}\n";
+        let entry = GoParser::new().parse_file(source, "store/store.go").unwrap();
+
+        let types: Vec<Option<&str>> = entry.types.iter().map(|t| t.doc.as_deref()).collect();
+        assert_eq!(types, vec![Some("Store keeps entries.\n\nIt is safe for concurrent use."), Some("ID names an entry."), None]);
+        let functions: Vec<Option<&str>> = entry.functions.iter().map(|f| f.doc.as_deref()).collect();
+        assert_eq!(functions, vec![Some("Open opens the store."), None, Some("Get returns an entry.")]);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_cgo_exports() {
+        let source = "package native\n\nimport \"C\"\n\n// Add sums two ints.\n//export Add\nfunc Add(a, b C.int) C.int { return a + b }\n\nfunc helper() {}\n";
+        let entry = GoParser::new().parse_file(source, "native/add.go").unwrap();
+
+        let exported: Vec<bool> = entry.functions.iter().map(|f| f.exported_c).collect();
+        assert_eq!(exported, vec![true, false]);
+        assert_eq!(entry.functions[0].doc.as_deref(), Some("Add sums two ints."));
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_call_columns_and_byte_ranges() {
+        let source = "package a\n\nfunc run() {\n\ta(b(), c())\n}\n";
+        let entry = GoParser::new().parse_file(source, "a/a.go").unwrap();
+        let calls: Vec<(&str, u32, u32, &str)> = entry.functions[0]
+            .calls
+            .iter()
+            .map(|c| (c.raw.as_str(), c.line, c.column, &source[c.byte_start as usize..c.byte_end as usize]))
+            .collect();
+        assert_eq!(calls, vec![("a", 4, 2, "a(b(), c())"), ("b", 4, 4, "b()"), ("c", 4, 9, "c()")]);
+        assert_eq!(entry.functions[0].calls[2].position(), "4:9");
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_hash_ignores_comments_and_formatting() {
+        let hash = |source: &str| GoParser::new().parse_file(source, "a/a.go").unwrap().functions[0].ast_hash.clone();
+        let original = hash("package a\n\nfunc Open(path string) error {\n\tf := os.Open(path)\n\treturn use(f, \"a b\")\n}\n");
+        let reformatted = hash(
+            "package a\n\nfunc Open( path string ) error {\n  // Open the file\n  f := os.Open(path) /* then use it */\n\n\n  return use(f, \"a b\")\n}\n",
+        );
+        assert_eq!(original, reformatted);
+
+        let renamed = hash("package a\n\nfunc Open(path string) error {\n\tg := os.Open(path)\n\treturn use(g, \"a b\")\n}\n");
+        let new_text = hash("package a\n\nfunc Open(path string) error {\n\tf := os.Open(path)\n\treturn use(f, \"a  b\")\n}\n");
+        assert_ne!(original, renamed);
+        assert_ne!(original, new_text);
+    }
 
+    #[cfg(feature = "lang-rust")]
     #[test]
-    fn test_parse_simple_function() {
-        let source = r#"
-package main
+    fn test_rust_hash_ignores_comments_and_formatting() {
+        let hash = |source: &str| RustParser::new().parse_file(source, "src/lib.rs").unwrap().functions[0].ast_hash.clone();
+        let original = hash("fn open(path: &str) -> File {\n    let f = File::open(path); // no retry\n    check(&f, 'x', \"a b\");\n    f\n}\n");
+        let reformatted = hash(
+            "fn open(\n    path: &str,\n) -> File {\n    /* open it */\n    let f =\n        File::open(path);\n    check(\n        &f,\n        'x',\n        \"a b\",\n    );\n    f\n}\n",
+        );
+        assert_eq!(original, reformatted);
+
+        let new_text = hash("fn open(path: &str) -> File {\n    let f = File::open(path);\n    check(&f, 'y', \"a b\");\n    f\n}\n");
+        assert_ne!(original, new_text);
+    }
 
-func Hello(name string) string {
-    return "Hello, " + name
-}
-"#;
+    #[test]
+    fn test_content_hash_is_xxh64() {
+        // Fixed by the XXH64 specification, so indexes stay valid across Rust releases
+        assert_eq!(content_hash(b""), "ef46db3751d8e999");
+        assert_eq!(content_hash(b"abc"), "44bc2cf5ad770999");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_parse_error_ratio() {
         let mut parser = GoParser::new();
-        let entry = parser.parse_file(source, "main.go").unwrap();
+        let clean = parser.parse_file("package main\n\nfunc A() {}\n", "main.go").unwrap();
+        assert_eq!(clean.parse_error_ratio, 0.0);
+        assert_eq!((clean.parse_errors, clean.first_error_line), (0, None));
+
+        let broken = parser.parse_file("package main\n\nfunc A() {}\n\n@@@ ### $$$ %%% ^^^\n", "main.go").unwrap();
+        assert!(broken.parse_error_ratio > 0.2 && broken.parse_error_ratio < 1.0, "{}", broken.parse_error_ratio);
+        assert_eq!(broken.functions.len(), 1);
+        assert!(broken.parse_errors > 0);
+        assert_eq!(broken.first_error_line, Some(5));
+    }
 
-        assert_eq!(entry.functions.len(), 1);
-        let f = &entry.functions[0];
-        assert_eq!(f.name, "Hello");
-        assert_eq!(f.qualified_name, "main.Hello");
-        assert_eq!(f.scope, Scope::Public);
-        assert!(f.signature.contains("func Hello(name string) string"));
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_partial_file_keeps_functions_before_error() {
+        let source = "package main\n\nfunc A() { b() }\n\nfunc B() {\n\tx := \n}\n\nfunc C( {\n";
+        let entry = GoParser::new().parse_file(source, "main.go").unwrap();
+        assert!(entry.parse_errors > 0);
+        assert_eq!(entry.first_error_line, Some(6));
+        let a = entry.functions.iter().find(|f| f.name == "A").unwrap();
+        assert_eq!((a.line_start, a.line_end), (3, 3));
+        assert_eq!(a.calls[0].raw, "b");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_partial_file_keeps_functions_before_error() {
+        let source = "fn a() { b(); }\n\nfn c() {\n    let x = ;\n}\n\nfn d(\n";
+        let entry = RustParser::new().parse_file(source, "src/lib.rs").unwrap();
+        assert!(entry.parse_errors > 0);
+        assert_eq!(entry.first_error_line, Some(4));
+        let a = entry.functions.iter().find(|f| f.name == "a").unwrap();
+        assert_eq!((a.line_start, a.line_end), (1, 1));
+        assert_eq!(a.calls[0].raw, "b");
     }
 
+    #[cfg(feature = "lang-go")]
     #[test]
     fn test_parse_method() {
         let source = r#"
@@ -1267,6 +5098,7 @@ func (s *Server) Start() error {
         assert_eq!(t.kind, TypeKind::Struct);
     }
 
+    #[cfg(feature = "lang-go")]
     #[test]
     fn test_extract_calls() {
         let source = r#"
@@ -1301,6 +5133,7 @@ func main() {
         assert_eq!(main_fn.calls[1].raw, "fmt.Printf");
     }
 
+    #[cfg(feature = "lang-go")]
     #[test]
     fn test_extract_method_calls() {
         let source = r#"
@@ -1325,12 +5158,82 @@ func (s *Server) init() {}
         assert_eq!(start.calls.len(), 2);
         assert_eq!(start.calls[0].raw, "s.logger.Info");
         assert_eq!(start.calls[1].raw, "s.init");
+        assert_eq!(start.calls[0].callee_name, "Info");
+        assert_eq!(start.calls[0].receiver_expr.as_deref(), Some("s.logger"));
+        assert_eq!(start.calls[1].callee_name, "init");
+        assert_eq!(start.calls[1].receiver_expr.as_deref(), Some("s"));
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_param_types() {
+        let source = r#"
+package server
+
+func (s *Server) Handle(ctx context.Context, a, b int, st *store.Store, opts ...Option) {
+    st.Get(a)
+    helper()
+}
+"#;
+        let mut parser = GoParser::new();
+        let entry = parser.parse_file(source, "server.go").unwrap();
+
+        let handle = &entry.functions[0];
+        assert_eq!(handle.param_types.get("s").map(String::as_str), Some("*Server"));
+        assert_eq!(handle.param_types.get("ctx").map(String::as_str), Some("context.Context"));
+        assert_eq!(handle.param_types.get("a").map(String::as_str), Some("int"));
+        assert_eq!(handle.param_types.get("b").map(String::as_str), Some("int"));
+        assert_eq!(handle.param_types.get("st").map(String::as_str), Some("*store.Store"));
+        assert!(handle.param_types.contains_key("opts"));
+
+        assert_eq!(handle.calls[0].receiver_expr.as_deref(), Some("st"));
+        assert_eq!(handle.calls[1].callee_name, "helper");
+        assert_eq!(handle.calls[1].receiver_expr, None);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_local_types() {
+        let source = r#"
+package app
+
+func run(opts Options) {
+    c := client.New(opts.Addr)
+    s := &Server{addr: ":80"}
+    cfg := Config{}
+    r := NewRetry()
+    r = NewRetry()
+    w := NewWriter()
+    if true {
+        w := other()
+        w.Flush()
+    }
+    opts := Options{}
+    a, b := pair()
+    n := len(s.addr)
+    for i, item := range items {
+        item.Use(i)
+    }
+    c.Do()
+}
+"#;
+        let mut parser = GoParser::new();
+        let entry = parser.parse_file(source, "app.go").unwrap();
+
+        let run = &entry.functions[0];
+        let locals: Vec<(&str, &str)> = run.local_types.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(locals, vec![("c", "client.New()"), ("cfg", "Config"), ("n", "len()"), ("s", "*Server")]);
+        // Reassigned, shadowed, multi-valued, and parameter names are left out
+        for name in ["r", "w", "opts", "a", "b", "item"] {
+            assert!(!run.local_types.contains_key(name), "{name}");
+        }
     }
 
     // ========================================================================
     // Rust Parser Tests
     // ========================================================================
 
+    #[cfg(feature = "lang-rust")]
     #[test]
     fn test_rust_parse_simple_function() {
         let source = r#"
@@ -1349,6 +5252,118 @@ pub fn hello(name: &str) -> String {
         assert!(f.signature.contains("fn hello"));
     }
 
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_attributes() {
+        let source = r#"
+/// Entry point
+#[tokio::main(flavor = "current_thread")]
+#[allow(dead_code)]
+async fn main() {}
+
+#[inline]
+#[must_use]
+fn quiet() -> u32 { 1 }
+
+struct Server;
+
+impl Server {
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(skip(self, req), fields(id = "a,b"))
+    )]
+    // handles one request
+    #[cfg_attr(test, allow(unused))]
+    pub fn handle(&self, req: u32) {}
+}
+"#;
+        let mut parser = RustParser::new();
+        let entry = parser.parse_file(source, "src/main.rs").unwrap();
+        let get = |name: &str| entry.functions.iter().find(|f| f.name == name).unwrap();
+
+        let main_fn = get("main");
+        assert_eq!(main_fn.attributes, vec!["tokio::main(flavor = \"current_thread\")", "allow(dead_code)"]);
+        assert_eq!((main_fn.attr_line_start, main_fn.line_start), (Some(3), 5));
+        assert_eq!(main_fn.signature, "#[tokio::main(flavor = \"current_thread\")] async fn main()");
+        assert!(main_fn.is_async);
+
+        let quiet = get("quiet");
+        assert_eq!(quiet.attributes.len(), 2);
+        assert_eq!(quiet.signature, "fn quiet() -> u32");
+
+        let handle = get("handle");
+        assert_eq!((handle.attr_line_start, handle.line_start), (Some(14), 20));
+        assert_eq!(
+            handle.attributes[0],
+            "cfg_attr(feature = \"trace\", tracing::instrument(skip(self, req), fields(id = \"a,b\")))"
+        );
+        assert!(handle.signature.starts_with("#[cfg_attr(feature = \"trace\", tracing::instrument("));
+        assert!(!handle.signature.contains("allow(unused)"));
+
+        let plain = RustParser::new().parse_file("fn f() {}\n", "src/lib.rs").unwrap();
+        assert!(plain.functions[0].attributes.is_empty());
+        assert_eq!(plain.functions[0].attr_line_start, None);
+    }
+
+    #[cfg(all(feature = "lang-go", feature = "lang-rust"))]
+    #[test]
+    fn test_global_refs() {
+        let go = r#"package config
+
+var (
+	registry = map[string]int{}
+	hits, _  int
+)
+
+func Register(name string, n int) {
+	registry[name] = n
+	hits++
+	cache.Store(name, n)
+	config.Default.Port = n
+	fmt.Println(len(registry))
+}
+
+func Local() int {
+	registry := 3
+	return registry
+}
+"#;
+        let entry = GoParser::new().parse_file(go, "config/config.go").unwrap();
+        let names: Vec<&str> = entry.variables.iter().map(|v| v.qualified_name.as_str()).collect();
+        assert_eq!(names, vec!["config.registry", "config.hits"]);
+
+        let refs = |f: &Function| f.global_refs.iter().map(|r| (r.name.clone(), r.line, r.write)).collect::<Vec<_>>();
+        let expected = [
+            ("registry", 9, true),
+            ("hits", 10, true),
+            ("cache", 11, true),
+            ("config.Default", 12, true),
+            ("fmt", 13, false),
+            ("registry", 13, false),
+        ];
+        let register = refs(&entry.functions[0]);
+        assert_eq!(register, expected.iter().map(|(n, l, w)| (n.to_string(), *l, *w)).collect::<Vec<_>>());
+        assert!(entry.functions[1].global_refs.is_empty());
+
+        let rust = "static COUNTER: AtomicU64 = AtomicU64::new(0);\nmod state {\n    pub static mut LAST: u32 = 0;\n}\n\nfn bump(step: u64) -> u64 {\n    unsafe { state::LAST = 3; }\n    COUNTER.fetch_add(step, Ordering::SeqCst) + COUNTER.load(Ordering::SeqCst)\n}\n";
+        let entry = RustParser::new().parse_file(rust, "src/lib.rs").unwrap();
+        let names: Vec<&str> = entry.variables.iter().map(|v| v.qualified_name.as_str()).collect();
+        assert_eq!(names, vec!["COUNTER", "state::LAST"]);
+        let bump = refs(&entry.functions[0]);
+        assert_eq!(
+            bump,
+            vec![("state::LAST".to_string(), 7, true), ("COUNTER".to_string(), 8, false), ("COUNTER".to_string(), 8, true)]
+        );
+
+        #[cfg(feature = "lang-c")]
+        {
+            let c = "int total;\nvoid add(int n) {\n    int local = n;\n    total += local;\n    printf(\"%d\", total);\n}\n";
+            let entry = CParser::new().parse_file(c, "g.c").unwrap();
+            assert_eq!(refs(&entry.functions[0]), vec![("total".to_string(), 4, true), ("total".to_string(), 5, false)]);
+        }
+    }
+
+    #[cfg(feature = "lang-rust")]
     #[test]
     fn test_rust_parse_impl_methods() {
         let source = r#"
@@ -1390,6 +5405,39 @@ impl Server {
         assert_eq!(server_type.kind, TypeKind::Struct);
     }
 
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_param_types_and_call_parts() {
+        let source = r#"
+impl Server {
+    fn handle(&mut self, mut store: &Store, count: usize) {
+        self.log("x");
+        store.get(count);
+        Config::load();
+        parse::<u32>();
+    }
+}
+"#;
+        let mut parser = RustParser::new();
+        let entry = parser.parse_file(source, "src/server.rs").unwrap();
+
+        let handle = &entry.functions[0];
+        assert_eq!(handle.param_types.get("self").map(String::as_str), Some("Server"));
+        assert_eq!(handle.param_types.get("store").map(String::as_str), Some("&Store"));
+        assert_eq!(handle.param_types.get("count").map(String::as_str), Some("usize"));
+
+        let parts: Vec<(&str, Option<&str>)> = handle
+            .calls
+            .iter()
+            .map(|c| (c.callee_name.as_str(), c.receiver_expr.as_deref()))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![("log", Some("self")), ("get", Some("store")), ("load", None), ("parse", None)]
+        );
+    }
+
+    #[cfg(feature = "lang-rust")]
     #[test]
     fn test_rust_extract_calls() {
         let source = r#"
@@ -1417,16 +5465,19 @@ fn main() {
         assert_eq!(main_fn.calls[0].raw, "greet");
     }
 
+    #[cfg(feature = "lang-rust")]
     #[test]
     fn test_rust_parse_enum_and_trait() {
         let source = r#"
 pub enum Status {
     Active,
-    Inactive,
+    Inactive(u32),
+    Failed { code: i32 },
 }
 
 pub trait Handler {
     fn handle(&self);
+    fn name(&self) -> &str { default_name() }
 }
 "#;
         let mut parser = RustParser::new();
@@ -1437,26 +5488,327 @@ pub trait Handler {
         let status = entry.types.iter().find(|t| t.name == "Status").unwrap();
         assert_eq!(status.kind, TypeKind::Enum);
         assert_eq!(status.qualified_name, "types::Status");
+        assert_eq!(status.variants, vec!["Active", "Inactive", "Failed"]);
 
         let handler = entry.types.iter().find(|t| t.name == "Handler").unwrap();
         assert_eq!(handler.kind, TypeKind::Interface);
         assert_eq!(handler.qualified_name, "types::Handler");
+        assert_eq!(handler.methods, vec!["handle", "name"]);
+
+        // Only the default-bodied method is indexed, under the trait and as visible as it
+        assert_eq!(entry.functions.len(), 1);
+        let name = &entry.functions[0];
+        assert_eq!(name.qualified_name, "types::Handler::name");
+        assert_eq!(name.receiver.as_deref(), Some("Handler"));
+        assert_eq!(name.scope, Scope::Public);
+        assert_eq!(name.calls[0].raw, "default_name");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_c_exports() {
+        let source = "pub extern \"C\" fn add(a: i32, b: i32) -> i32 { a + b }\n\
+                      #[no_mangle]\npub fn plain_symbol() {}\n\
+                      #[unsafe(no_mangle)]\npub unsafe extern \"C\" fn init() {}\n\
+                      #[export_name = \"lib_version\"]\npub fn version() {}\n\
+                      extern \"Rust\" fn rust_abi() {}\n\
+                      fn helper() {}\n";
+        let entry = RustParser::new().parse_file(source, "src/lib.rs").unwrap();
+        let exported: Vec<(&str, bool)> = entry.functions.iter().map(|f| (f.name.as_str(), f.exported_c)).collect();
+        assert_eq!(
+            exported,
+            vec![
+                ("add", true),
+                ("plain_symbol", true),
+                ("init", true),
+                ("version", true),
+                ("rust_abi", false),
+                ("helper", false),
+            ]
+        );
+        assert_eq!(entry.functions[0].signature, "extern \"C\" fn add(a: i32, b: i32) -> i32");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_consts_and_statics() {
+        let source = "pub const EMBEDDING_DIM: usize = 768;
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+                      mod limits {
+    pub(crate) const MAX: u32 = 8;
+}
+";
+        let entry = RustParser::new().parse_file(source, "src/embed.rs").unwrap();
+        let vars: Vec<(&str, &str, &str, u32, Scope, bool)> = entry
+            .variables
+            .iter()
+            .map(|v| (v.name.as_str(), v.qualified_name.as_str(), v.type_name.as_str(), v.line_start, v.scope, v.is_const))
+            .collect();
+        assert_eq!(
+            vars,
+            vec![
+                ("EMBEDDING_DIM", "embed::EMBEDDING_DIM", "usize", 1, Scope::Public, true),
+                ("COUNTER", "embed::COUNTER", "AtomicU64", 2, Scope::Internal, false),
+                ("MAX", "embed::limits::MAX", "u32", 4, Scope::Crate, true),
+            ]
+        );
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_async_functions_and_await_calls() {
+        let source = "pub async fn fetch(client: &Client, url: &str) -> Result<Body> {\n    let body = client.get(url).send().await?.json().await?;\n    Ok(body)\n}\n\
+                      pub const unsafe fn raw() {}\n\
+                      extern \"C\" fn callback() {}\n";
+        let entry = RustParser::new().parse_file(source, "src/net.rs").unwrap();
+
+        let signatures: Vec<(&str, bool)> = entry.functions.iter().map(|f| (f.signature.as_str(), f.is_async)).collect();
+        assert_eq!(
+            signatures,
+            vec![
+                ("async fn fetch(client: &Client, url: &str) -> Result<Body>", true),
+                ("const unsafe fn raw()", false),
+                ("extern \"C\" fn callback()", false),
+            ]
+        );
+
+        // `.await` is left out of the recorded call chain
+        let calls: Vec<(&str, &str, Option<&str>)> = entry.functions[0]
+            .calls
+            .iter()
+            .map(|c| (c.raw.as_str(), c.callee_name.as_str(), c.receiver_expr.as_deref()))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("client.get(url).send()?.json", "json", Some("client.get(url).send()?")),
+                ("client.get(url).send", "send", Some("client.get(url)")),
+                ("client.get", "get", Some("client")),
+                ("Ok", "Ok", None),
+            ]
+        );
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_visibility() {
+        let source = "pub fn api() {}\npub(crate) fn shared() {}\npub(super) fn parent() {}\npub(in crate::store) fn scoped() {}\n\
+                      pub(self) fn own() {}\nfn private() {}\npub(crate) static LIMIT: u32 = 3;\n";
+        let entry = RustParser::new().parse_file(source, "src/store/disk.rs").unwrap();
+
+        let scopes: Vec<(&str, Scope)> = entry.functions.iter().map(|f| (f.name.as_str(), f.scope)).collect();
+        assert_eq!(
+            scopes,
+            vec![
+                ("api", Scope::Public),
+                ("shared", Scope::Crate),
+                ("parent", Scope::Crate),
+                ("scoped", Scope::Crate),
+                ("own", Scope::Internal),
+                ("private", Scope::Internal),
+            ]
+        );
+        assert_eq!(entry.variables[0].scope, Scope::Crate);
+        assert_eq!(serde_json::to_string(&Scope::Crate).unwrap(), "\"crate\"");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_doc_comments() {
+        let source = r#"//! Loading and saving settings.
+//!
+//! Missing keys keep their defaults.
+#![allow(dead_code)]
+
+use std::fs;
+
+/// Settings read from disk.
+#[derive(Debug)]
+pub struct Config {}
+
+/** Where settings come from. */
+pub enum Source { File }
+
+// Not documentation
+pub fn plain() {}
+
+/// Load reads the file at `path`.
+// a note between the doc and the item
+#[inline]
+pub fn load(path: &str) -> Config { Config {} }
+
+impl Config {
+    /// Save writes the settings back.
+    pub fn save(&self) {}
+}
+"#;
+        let entry = RustParser::new().parse_file(source, "src/config.rs").unwrap();
+
+        assert_eq!(entry.doc.as_deref(), Some("Loading and saving settings.\n\nMissing keys keep their defaults."));
+        let types: Vec<Option<&str>> = entry.types.iter().map(|t| t.doc.as_deref()).collect();
+        assert_eq!(types, vec![Some("Settings read from disk."), Some("Where settings come from.")]);
+        let functions: Vec<Option<&str>> = entry.functions.iter().map(|f| f.doc.as_deref()).collect();
+        assert_eq!(functions, vec![None, Some("Load reads the file at `path`."), Some("Save writes the settings back.")]);
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_nested_functions_and_closures() {
+        let source = r#"pub fn run(items: &[u32]) -> u32 {
+    fn helper(x: u32) -> u32 {
+        double(x)
+    }
+    let total = items.iter().map(|x| {
+        let y = helper(*x);
+        y + offset()
+    }).sum();
+    let short = |x: u32| x + 1;
+    log(short(total))
+}
+"#;
+        let mut parser = RustParser::new();
+        parser.configure(&IndexConfig { closure_min_lines: Some(3), ..Default::default() });
+        let entry = parser.parse_file(source, "src/ops.rs").unwrap();
+
+        let calls_of = |qn: &str| -> Vec<String> {
+            let func = entry.functions.iter().find(|f| f.qualified_name == qn).unwrap();
+            func.calls.iter().map(|c| c.callee_name.clone()).collect()
+        };
+        let names: Vec<&str> = entry.functions.iter().map(|f| f.qualified_name.as_str()).collect();
+        assert_eq!(names, vec!["ops::run", "ops::run::helper", "ops::run::{closure@L5}"]);
+
+        // Each call belongs to the innermost indexed function; the one-line closure stays inline
+        assert_eq!(calls_of("ops::run"), vec!["sum", "map", "iter", "log", "short"]);
+        assert_eq!(calls_of("ops::run::helper"), vec!["double"]);
+        assert_eq!(calls_of("ops::run::{closure@L5}"), vec!["helper", "offset"]);
+        assert_eq!(entry.functions[0].references[0].raw, "{closure@L5}");
+        assert_eq!(entry.functions[2].line_end, 8);
+
+        // Without the setting, closures are not indexed
+        let entry = RustParser::new().parse_file(source, "src/ops.rs").unwrap();
+        assert_eq!(entry.functions.len(), 2);
+        assert_eq!(entry.functions[0].calls.len(), 7);
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_generic_impls_and_associated_functions() {
+        let source = "impl<T: Clone> Cache<T> {\n    pub fn new() -> Self { todo!() }\n    pub fn get(&self, k: usize) -> T { todo!() }\n}\n\
+                      impl Cache<String> {\n    fn describe(self: &Self) -> String { todo!() }\n    fn into_inner(mut self) {}\n}\n";
+        let entry = RustParser::new().parse_file(source, "src/cache.rs").unwrap();
+
+        let functions: Vec<(&str, Option<&str>, bool)> =
+            entry.functions.iter().map(|f| (f.qualified_name.as_str(), f.receiver.as_deref(), f.takes_self)).collect();
+        assert_eq!(
+            functions,
+            vec![
+                ("cache::Cache::new", Some("Cache"), false),
+                ("cache::Cache::get", Some("Cache"), true),
+                ("cache::Cache::describe", Some("Cache"), true),
+                ("cache::Cache::into_inner", Some("Cache"), true),
+            ]
+        );
+        assert_eq!(entry.functions[1].param_types["self"], "Cache");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_rust_impl_methods_record_trait() {
+        let source = "impl Server {\n    fn new() -> Self { Server }\n}\n\
+                      impl Handler for Server {\n    fn handle(&self) {}\n}\n\
+                      impl<T> crate::handler::Handler for &mut Wrapper<T> {\n    fn handle(&self) {}\n}\n";
+        let entry = RustParser::new().parse_file(source, "src/server.rs").unwrap();
+
+        let methods: Vec<(&str, Option<&str>, Option<&str>)> = entry
+            .functions
+            .iter()
+            .map(|f| (f.qualified_name.as_str(), f.receiver.as_deref(), f.trait_impl.as_deref()))
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                ("server::Server::new", Some("Server"), None),
+                ("server::Server::handle", Some("Server"), Some("Handler")),
+                ("server::Wrapper::handle", Some("Wrapper"), Some("crate::handler::Handler")),
+            ]
+        );
     }
 
     #[test]
     fn test_rust_module_path() {
-        assert_eq!(rust_path_to_module("src/lib.rs"), "");
-        assert_eq!(rust_path_to_module("src/main.rs"), "");
-        assert_eq!(rust_path_to_module("src/parser.rs"), "parser");
-        assert_eq!(rust_path_to_module("src/commands/index.rs"), "commands::index");
-        assert_eq!(rust_path_to_module("./src/foo/bar.rs"), "foo::bar");
-        assert_eq!(rust_path_to_module("src/utils/mod.rs"), "utils");
+        let module = |path| rust_path_to_module(path, &[]);
+        assert_eq!(module("src/lib.rs"), "");
+        assert_eq!(module("src/main.rs"), "");
+        assert_eq!(module("src/parser.rs"), "parser");
+        assert_eq!(module("src/commands/index.rs"), "commands::index");
+        assert_eq!(module("./src/foo/bar.rs"), "foo::bar");
+        assert_eq!(module("src/utils/mod.rs"), "utils");
+        assert_eq!(module("src/bin/tool.rs"), "bin::tool");
+        assert_eq!(module("src/bin/tool/main.rs"), "bin::tool");
+        assert_eq!(module("src/bin/tool/args.rs"), "bin::tool::args");
+        assert_eq!(module("tests/cli.rs"), "tests::cli");
+        assert_eq!(module("tests/common/mod.rs"), "tests::common");
+        assert_eq!(module("examples/demo/main.rs"), "examples::demo");
+        assert_eq!(module("benches/parse.rs"), "benches::parse");
+    }
+
+    #[test]
+    fn test_rust_module_path_in_workspace() {
+        let crates = [
+            RustCrate::from_manifest("", "[package]\nname = \"app\"\n\n[workspace]\nmembers = [\"crates/*\"]\n").unwrap(),
+            RustCrate::from_manifest("crates/core", "[package]\nname = \"my-core\"\nversion = \"0.1.0\"\n").unwrap(),
+            RustCrate::from_manifest("crates/cli", "[package]\nname = \"cli\"\n").unwrap(),
+        ];
+        assert_eq!(RustCrate::from_manifest("", "[workspace]\nmembers = []\n"), None);
+
+        let module = |path| rust_path_to_module(path, &crates);
+        let root = |path| rust_crate_root(path, &crates);
+        assert_eq!(module("./src/parser.rs"), "parser");
+        assert_eq!(module("./crates/core/src/lib.rs"), "my_core");
+        assert_eq!(module("./crates/core/src/parser.rs"), "my_core::parser");
+        assert_eq!(module("./crates/core/src/parser/mod.rs"), "my_core::parser");
+        assert_eq!(module("./crates/core/tests/parse.rs"), "my_core::tests::parse");
+        assert_eq!(module("./crates/cli/src/bin/lint.rs"), "cli::bin::lint");
+        assert_eq!(module("./crates/cli/src/main.rs"), "cli");
+
+        // `crate::` names the crate, or the target for files that build a crate of their own
+        assert_eq!(root("./src/parser.rs"), "");
+        assert_eq!(root("./src/bin/tool/args.rs"), "bin::tool");
+        assert_eq!(root("./crates/core/src/parser.rs"), "my_core");
+        assert_eq!(root("./crates/core/tests/parse.rs"), "my_core::tests::parse");
+        assert_eq!(root("./crates/cli/src/bin/lint.rs"), "cli::bin::lint");
+
+        assert!(is_rust_binary_root("./crates/cli/src/main.rs", &crates));
+        assert!(is_rust_binary_root("./crates/cli/src/bin/lint.rs", &crates));
+        assert!(!is_rust_binary_root("./crates/core/src/parser.rs", &crates));
+    }
+
+    #[cfg(all(feature = "lang-go", feature = "lang-rust"))]
+    #[test]
+    fn test_entry_points() {
+        let go = GoParser::new().parse_file("package main\n\nfunc main() {}\n\nfunc run() {}\n", "cmd/main.go").unwrap();
+        let go_lib = GoParser::new().parse_file("package util\n\nfunc main() {}\n", "util/util.go").unwrap();
+        let rust_main = RustParser::new().parse_file("fn main() { run(); }\n\nfn run() {}\n", "src/main.rs").unwrap();
+        let rust_bin = RustParser::new().parse_file("fn main() {}\n", "src/bin/tool.rs").unwrap();
+        let rust_lib = RustParser::new().parse_file("pub fn main() {}\n", "src/lib.rs").unwrap();
+        let rust_mod = RustParser::new().parse_file("fn main() {}\n", "src/bin/tool/args.rs").unwrap();
+
+        let entry = |e: &FileEntry| (e.functions[0].qualified_name.clone(), e.functions[0].is_entry);
+        assert_eq!(entry(&go), ("cmd.main".to_string(), true));
+        assert!(!go.functions[1].is_entry);
+        assert_eq!(entry(&go_lib), ("util.main".to_string(), false));
+        assert_eq!(entry(&rust_main), ("crate::main".to_string(), true));
+        assert!(!rust_main.functions[1].is_entry);
+        assert_eq!(entry(&rust_bin), ("bin::tool::main".to_string(), true));
+        assert_eq!(entry(&rust_lib), ("main".to_string(), false));
+        assert_eq!(entry(&rust_mod), ("bin::tool::args::main".to_string(), false));
     }
 
     // ========================================================================
     // C Parser Tests
     // ========================================================================
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_parse_simple_function() {
         let source = r#"
@@ -1476,6 +5828,7 @@ int add(int a, int b) {
         assert!(f.signature.contains("add"));
     }
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_parse_static_function() {
         let source = r#"
@@ -1499,6 +5852,7 @@ void public_func(void) {
         assert_eq!(public_fn.scope, Scope::Public);
     }
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_parse_struct() {
         let source = r#"
@@ -1517,6 +5871,7 @@ struct Point {
         assert_eq!(t.kind, TypeKind::Struct);
     }
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_parse_enum() {
         let source = r#"
@@ -1535,6 +5890,7 @@ enum Status {
         assert_eq!(t.kind, TypeKind::Enum);
     }
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_parse_typedef() {
         let source = r#"
@@ -1549,6 +5905,7 @@ typedef unsigned int uint32;
         assert_eq!(t.kind, TypeKind::Typedef);
     }
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_extract_calls() {
         let source = r#"
@@ -1583,6 +5940,7 @@ int main(void) {
         assert_eq!(c_path_to_prefix("src/auth/login.c"), "src/auth/login");
     }
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_parse_struct_with_include_guards() {
         let source = r#"
@@ -1608,6 +5966,7 @@ struct cr_fd_desc_tmpl {
         assert_eq!(t.line_end, 9);
     }
 
+    #[cfg(feature = "lang-c")]
     #[test]
     fn test_c_parse_global_variables() {
         let source = r#"
@@ -1646,4 +6005,496 @@ static char *secret_key = "abc";
         let secret = entry.variables.iter().find(|v| v.name == "secret_key").unwrap();
         assert_eq!(secret.scope, Scope::Static);
     }
+
+    // ========================================================================
+    // Python Parser Tests
+    // ========================================================================
+
+    #[cfg(feature = "lang-python")]
+    #[test]
+    fn test_python_parse_functions_and_methods() {
+        let source = r#"
+import os
+
+def load(path: str) -> "Config":
+    return Config.parse(os.read(path))
+
+def _helper():
+    pass
+
+class Config:
+    def __init__(self, data):
+        self.data = data
+
+    async def save(self, store: Store):
+        self._write(store)
+        store.put(self.data)
+
+    def _write(self, store):
+        pass
+"#;
+        let mut parser = PythonParser::new();
+        let entry = parser.parse_file(source, "./pkg/config.py").unwrap();
+        let get = |name: &str| entry.functions.iter().find(|f| f.name == name).unwrap();
+
+        let load = get("load");
+        assert_eq!(load.qualified_name, "pkg/config.load");
+        assert_eq!(load.signature, "def load(path: str) -> \"Config\"");
+        assert_eq!(load.scope, Scope::Public);
+        assert_eq!(load.receiver, None);
+        let raws: Vec<&str> = load.calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["Config.parse", "os.read"]);
+        assert_eq!(load.calls[0].callee_name, "parse");
+        assert_eq!(load.calls[0].receiver_expr.as_deref(), Some("Config"));
+
+        assert_eq!(get("_helper").scope, Scope::Internal);
+        assert_eq!(get("__init__").scope, Scope::Public);
+
+        let save = get("save");
+        assert_eq!(save.qualified_name, "pkg/config.Config.save");
+        assert_eq!(save.receiver.as_deref(), Some("Config"));
+        assert!(save.signature.starts_with("async def save(self, store: Store)"));
+        assert_eq!(save.param_types.get("self").map(String::as_str), Some("Config"));
+        assert_eq!(save.param_types.get("store").map(String::as_str), Some("Store"));
+        assert_eq!(save.calls.len(), 2);
+        assert_eq!(save.calls[0].raw, "self._write");
+
+        assert_eq!(entry.types.len(), 1);
+        let config = &entry.types[0];
+        assert_eq!((config.qualified_name.as_str(), config.kind), ("pkg/config.Config", TypeKind::Struct));
+        assert_eq!(config.methods, vec!["__init__", "save", "_write"]);
+    }
+
+    #[cfg(feature = "lang-python")]
+    #[test]
+    fn test_python_nested_functions_and_decorators() {
+        let source = r#"
+@app.route(
+    "/items",
+)
+def items():
+    def render(item):
+        return fmt(item)
+    return [render(i) for i in fetch()]
+
+class Cache:
+    @staticmethod
+    def key(name):
+        return name.lower()
+
+    @property
+    def size(self):
+        return len(self.entries)
+
+    class Entry:
+        def touch(self):
+            pass
+"#;
+        let mut parser = PythonParser::new();
+        let entry = parser.parse_file(source, "app/__init__.py").unwrap();
+        let get = |qualified: &str| entry.functions.iter().find(|f| f.qualified_name == qualified).unwrap();
+
+        let items = get("app.items");
+        assert_eq!(items.attributes, vec!["app.route(\"/items\",)"]);
+        assert_eq!((items.attr_line_start, items.line_start), (Some(2), 5));
+        assert_eq!(items.signature, "@app.route(\"/items\",) def items()");
+        // The nested function's call to fmt is its own
+        let raws: Vec<&str> = items.calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["render", "fetch"]);
+
+        let render = get("app.items.render");
+        assert_eq!(render.receiver, None);
+        assert_eq!(render.calls[0].raw, "fmt");
+
+        let key = get("app.Cache.key");
+        assert_eq!(key.attributes, vec!["staticmethod"]);
+        assert!(key.param_types.is_empty());
+        assert_eq!(get("app.Cache.size").signature, "@property def size(self)");
+
+        let touch = get("app.Cache.Entry.touch");
+        assert_eq!(touch.receiver.as_deref(), Some("Entry"));
+        let types: Vec<&str> = entry.types.iter().map(|t| t.qualified_name.as_str()).collect();
+        assert_eq!(types, vec!["app.Cache", "app.Cache.Entry"]);
+    }
+
+    #[test]
+    fn test_python_module_path() {
+        assert_eq!(python_path_to_module("./pkg/store.py"), "pkg/store");
+        assert_eq!(python_path_to_module("pkg/__init__.py"), "pkg");
+        assert_eq!(python_path_to_module("main.py"), "main");
+        assert_eq!(python_scope("_private"), Scope::Internal);
+        assert_eq!(python_scope("__eq__"), Scope::Public);
+        assert_eq!(python_scope("__"), Scope::Internal);
+    }
+
+    // ========================================================================
+    // TypeScript Parser Tests
+    // ========================================================================
+
+    #[cfg(feature = "lang-typescript")]
+    #[test]
+    fn test_ts_parse_functions_classes_and_types() {
+        let source = r#"
+import { db } from "./db";
+
+export async function getUser(id: string): Promise<User> {
+    return normalize(db.find(id));
+}
+
+function normalize(u) {
+    return u;
+}
+
+export const handler = async (req: Request): Promise<void> => {
+    await getUser(req.id);
+};
+
+export class UserService {
+    constructor(private store: Store) {}
+
+    get(id: string): User {
+        return this.load(id);
+    }
+
+    private load(id: string) {
+        return this.store.fetch(id);
+    }
+
+    onChange = (e: Event) => {
+        this.get(e.id);
+    };
+}
+
+export interface User {
+    id: string;
+    greet(): string;
+}
+
+type Id = string;
+enum Color { Red, Green }
+"#;
+        let mut parser = TypeScriptParser::new();
+        let entry = parser.parse_file(source, "./src/api/users.ts").unwrap();
+        let get = |qualified: &str| entry.functions.iter().find(|f| f.qualified_name == qualified).unwrap();
+
+        let get_user = get("api/users.getUser");
+        assert_eq!(get_user.scope, Scope::Public);
+        assert_eq!(get_user.signature, "async function getUser(id: string): Promise<User>");
+        let raws: Vec<&str> = get_user.calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["normalize", "db.find"]);
+        assert_eq!(get_user.calls[1].receiver_expr.as_deref(), Some("db"));
+
+        assert_eq!(get("api/users.normalize").scope, Scope::Internal);
+
+        let handler = get("api/users.handler");
+        assert_eq!(handler.signature, "const handler = async (req: Request): Promise<void> =>");
+        assert_eq!(handler.param_types.get("req").map(String::as_str), Some("Request"));
+        assert_eq!(handler.calls[0].raw, "getUser");
+
+        let method = get("api/users.UserService.get");
+        assert_eq!(method.receiver.as_deref(), Some("UserService"));
+        assert_eq!(method.signature, "get(id: string): User");
+        assert_eq!(method.calls[0].raw, "this.load");
+        assert_eq!(method.calls[0].receiver_expr.as_deref(), Some("this"));
+        assert_eq!(method.param_types.get("this").map(String::as_str), Some("UserService"));
+        assert_eq!(get("api/users.UserService.load").scope, Scope::Internal);
+        assert_eq!(get("api/users.UserService.onChange").signature, "onChange = (e: Event) =>");
+
+        let kinds: Vec<(&str, TypeKind)> = entry.types.iter().map(|t| (t.qualified_name.as_str(), t.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("api/users.UserService", TypeKind::Struct),
+                ("api/users.User", TypeKind::Interface),
+                ("api/users.Id", TypeKind::Typedef),
+                ("api/users.Color", TypeKind::Enum),
+            ]
+        );
+        assert_eq!(entry.types[0].methods, vec!["constructor", "get", "load", "onChange"]);
+        assert_eq!(entry.types[1].methods, vec!["greet"]);
+    }
+
+    #[cfg(feature = "lang-typescript")]
+    #[test]
+    fn test_ts_parses_jsx() {
+        let source = "export function App() {\n  return <div onClick={() => track()}>{render()}</div>;\n}\n";
+        let entry = TypeScriptParser::new().parse_file(source, "web/App.jsx").unwrap();
+        assert_eq!(entry.parse_error_ratio, 0.0);
+        let raws: Vec<&str> = entry.functions[0].calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["track", "render"]);
+    }
+
+    #[test]
+    fn test_ts_module_path() {
+        assert_eq!(ts_path_to_module("src/api/users.ts"), "api/users");
+        assert_eq!(ts_path_to_module("./src/api/index.tsx"), "api");
+        assert_eq!(ts_path_to_module("lib/users.test.ts"), "lib/users_test");
+        assert_eq!(ts_path_to_module("types/global.d.ts"), "types/global");
+        assert_eq!(ts_path_to_module("index.js"), "index");
+    }
+
+    // ========================================================================
+    // Java Parser Tests
+    // ========================================================================
+
+    #[cfg(feature = "lang-java")]
+    #[test]
+    fn test_java_parse_classes_and_methods() {
+        let source = r#"
+package com.example.service;
+
+import java.util.List;
+
+public class UserService {
+    private final UserRepo repo;
+
+    public UserService(UserRepo repo) {
+        this.repo = repo;
+    }
+
+    @Override
+    @Transactional(readOnly = false)
+    public User create(String name) {
+        validate(name);
+        return repo.save(new User(name));
+    }
+
+    public User create(String name, int age) {
+        return this.create(name);
+    }
+
+    void validate(String name) {
+        Objects.requireNonNull(name).trim();
+    }
+
+    private static class Cache {
+        User get(String key) { return null; }
+    }
+}
+
+interface Repo {
+    void save(User user);
+}
+
+enum Color {
+    RED, GREEN;
+    public String lower() { return name().toLowerCase(); }
+}
+"#;
+        let mut parser = JavaParser::new();
+        let entry = parser.parse_file(source, "src/main/java/com/example/service/UserService.java").unwrap();
+        let named = |qualified: &str| entry.functions.iter().filter(|f| f.qualified_name == qualified).collect::<Vec<_>>();
+
+        let creates = named("com/example/service.UserService.create");
+        assert_eq!(creates.len(), 2, "overloads are indexed separately");
+        assert_eq!(creates[0].signature, "public User create(String name)");
+        assert_eq!(creates[0].attributes, vec!["Override", "Transactional(readOnly = false)"]);
+        assert_eq!((creates[0].attr_line_start, creates[0].line_start), (Some(13), 15));
+        assert_eq!(creates[1].signature, "public User create(String name, int age)");
+        assert_eq!(creates[0].scope, Scope::Public);
+        assert_eq!(creates[0].receiver.as_deref(), Some("UserService"));
+        assert_eq!(creates[0].param_types.get("name").map(String::as_str), Some("String"));
+
+        let raws: Vec<&str> = creates[0].calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["validate", "repo.save"]);
+        assert_eq!(creates[0].calls[1].receiver_expr.as_deref(), Some("repo"));
+        assert_eq!(creates[1].calls[0].raw, "this.create");
+
+        let validate = &named("com/example/service.UserService.validate")[0];
+        assert_eq!(validate.scope, Scope::Internal);
+        let raws: Vec<&str> = validate.calls.iter().map(|c| c.raw.as_str()).collect();
+        assert_eq!(raws, vec!["Objects.requireNonNull(name).trim", "Objects.requireNonNull"]);
+
+        assert_eq!(named("com/example/service.UserService.UserService")[0].signature, "public UserService(UserRepo repo)");
+        let get = &named("com/example/service.UserService.Cache.get")[0];
+        assert_eq!((get.receiver.as_deref(), get.scope), (Some("Cache"), Scope::Internal));
+        assert_eq!(named("com/example/service.Repo.save")[0].scope, Scope::Public);
+        assert_eq!(named("com/example/service.Color.lower")[0].calls.len(), 2);
+
+        let kinds: Vec<(&str, TypeKind)> = entry.types.iter().map(|t| (t.qualified_name.as_str(), t.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("com/example/service.UserService.Cache", TypeKind::Struct),
+                ("com/example/service.UserService", TypeKind::Struct),
+                ("com/example/service.Repo", TypeKind::Interface),
+                ("com/example/service.Color", TypeKind::Enum),
+            ]
+        );
+        assert_eq!(entry.types[1].methods, vec!["UserService", "create", "create", "validate"]);
+        assert_eq!(entry.types[3].methods, vec!["lower"]);
+    }
+
+    #[cfg(feature = "lang-java")]
+    #[test]
+    fn test_java_default_package() {
+        let entry = JavaParser::new().parse_file("class Main { void run() {} }\n", "tools/Main.java").unwrap();
+        assert_eq!(entry.functions[0].qualified_name, "tools.Main.run");
+    }
+
+    // ========================================================================
+    // C++ Parser Tests
+    // ========================================================================
+
+    #[cfg(feature = "lang-cpp")]
+    #[test]
+    fn test_cpp_namespaces_and_methods() {
+        let source = r#"
+namespace net {
+
+class Server {
+public:
+    Server(int port) : port_(port) {}
+    void start();
+    int port() const { return port_; }
+private:
+    void listen(Socket* sock) { sock->bind(port_); }
+    int port_;
+};
+
+void Server::start() {
+    listen(nullptr);
+    helper();
+}
+
+static void helper() { printf("x"); }
+
+namespace detail {
+template <typename T>
+T clamp(T v, T lo) { return std::max<T>(v, lo); }
+}
+
+}  // namespace net
+
+void net::Server::stop() {}
+
+int main() {
+    net::Server s(80);
+    s.start();
+}
+"#;
+        let mut parser = CppParser::new();
+        let entry = parser.parse_file(source, "src/net/server.cpp").unwrap();
+        let get = |qualified: &str| {
+            entry
+                .functions
+                .iter()
+                .find(|f| f.qualified_name == qualified)
+                .unwrap()
+        };
+
+        let ctor = get("net::Server::Server");
+        assert_eq!(ctor.signature, "Server(int port)");
+        assert_eq!(
+            (ctor.receiver.as_deref(), ctor.scope),
+            (Some("Server"), Scope::Public)
+        );
+
+        let port = get("net::Server::port");
+        assert_eq!(port.signature, "int port() const");
+        let listen = get("net::Server::listen");
+        assert_eq!(listen.scope, Scope::Internal);
+        assert_eq!(
+            listen.param_types.get("sock").map(String::as_str),
+            Some("Socket")
+        );
+        assert_eq!(listen.calls[0].raw, "sock->bind");
+        assert_eq!(listen.calls[0].receiver_expr.as_deref(), Some("sock"));
+
+        // Out-of-line definitions attach to their class
+        let start = get("net::Server::start");
+        assert_eq!(start.receiver.as_deref(), Some("Server"));
+        assert_eq!(
+            start.param_types.get("this").map(String::as_str),
+            Some("Server")
+        );
+        assert_eq!(start.signature, "void Server::start()");
+        assert_eq!(get("net::Server::stop").receiver.as_deref(), Some("Server"));
+
+        let helper = get("net::helper");
+        assert_eq!(
+            (helper.receiver.as_deref(), helper.scope),
+            (None, Scope::Static)
+        );
+
+        let clamp = get("net::detail::clamp");
+        assert_eq!(clamp.signature, "template <typename T> T clamp(T v, T lo)");
+        assert_eq!(clamp.calls[0].callee_name, "max");
+
+        let main_fn = get("main");
+        assert!(main_fn.is_entry);
+        assert_eq!(main_fn.calls[0].raw, "s.start");
+
+        assert_eq!(entry.types.len(), 1);
+        assert_eq!(entry.types[0].qualified_name, "net::Server");
+        assert_eq!(
+            entry.types[0].methods,
+            vec!["Server", "start", "port", "listen"]
+        );
+    }
+
+    #[cfg(feature = "lang-cpp")]
+    #[test]
+    fn test_cpp_types() {
+        let source = "struct Point { int x; };\nenum class Color { Red };\ntypedef struct { int n; } Count;\n\
+                      template <typename T> class Box { T get() { return v; } T v; };\n";
+        let entry = CppParser::new().parse_file(source, "shapes.hpp").unwrap();
+        let kinds: Vec<(&str, TypeKind)> = entry
+            .types
+            .iter()
+            .map(|t| (t.qualified_name.as_str(), t.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("Point", TypeKind::Struct),
+                ("Color", TypeKind::Enum),
+                ("Box", TypeKind::Struct)
+            ]
+        );
+        // Class members are private by default
+        assert_eq!(
+            (
+                entry.functions[0].qualified_name.as_str(),
+                entry.functions[0].scope
+            ),
+            ("Box::get", Scope::Internal)
+        );
+    }
+
+    #[test]
+    fn test_cpp_segments() {
+        assert_eq!(
+            cpp_segments("net::Server<T>::start"),
+            vec!["net", "Server", "start"]
+        );
+        assert_eq!(cpp_segments("::global"), vec!["global"]);
+        assert_eq!(cpp_segments("Vec::operator<<"), vec!["Vec", "operator<<"]);
+        assert_eq!(cpp_segments("~Server"), vec!["~Server"]);
+    }
+
+    // ========================================================================
+    // Parser Registry Tests
+    // ========================================================================
+
+    #[test]
+    fn test_configured_registry_rejects_unknown_language() {
+        let languages = BTreeMap::from([("golang".to_string(), vec![".go".to_string()])]);
+        let err = ParserRegistry::configured(&languages, &IndexConfig::default()).err().unwrap();
+        assert!(err.starts_with("[languages] lists unknown language 'golang' (expected go, rust, c,"), "{err}");
+    }
+
+    #[cfg(all(feature = "lang-go", feature = "lang-rust"))]
+    #[test]
+    fn test_configured_registry_maps_and_disables() {
+        let languages = BTreeMap::from([
+            ("go".to_string(), vec![".go".to_string(), ".gno".to_string()]),
+            ("rust".to_string(), vec![]),
+        ]);
+        let registry = ParserRegistry::configured(&languages, &IndexConfig::default()).unwrap();
+        assert_eq!(registry.language_of(Path::new("./realm/counter.gno")), Some("go"));
+        assert_eq!(registry.language_of(Path::new("./main.go")), Some("go"));
+        assert_eq!(registry.language_of(Path::new("./build.rs")), None);
+        assert_eq!(ParserRegistry::new().language_of(Path::new("./realm/counter.gno")), None);
+    }
 }
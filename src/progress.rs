@@ -0,0 +1,125 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Environment variable that turns on JSON progress events when set to "json"
+pub const ENV_VAR: &str = "ARIA_PROGRESS";
+
+static JSON: AtomicBool = AtomicBool::new(false);
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Events recorded instead of printed, for tests
+    static CAPTURED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// One line of the `--progress-json` stream on stderr
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// A phase began; `total` is the number of items when known up front
+    PhaseStart {
+        phase: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<usize>,
+    },
+    /// `current` of the phase's `total` items are done
+    Progress { phase: &'a str, current: usize, total: usize },
+    /// A phase finished after handling `count` items
+    PhaseEnd { phase: &'a str, count: usize, elapsed_ms: u64 },
+    /// Something was skipped or failed without stopping the command
+    Warning { code: &'a str, message: &'a str },
+    /// Final outcome of the command, with the numbers its human summary prints
+    Result {
+        command: &'a str,
+        #[serde(flatten)]
+        details: serde_json::Value,
+    },
+}
+
+/// Emit JSON events instead of human progress output on stderr for the rest of the run
+pub fn enable_json() {
+    JSON.store(true, Ordering::SeqCst);
+}
+
+/// Print how long each phase took on stderr for the rest of the run
+pub fn enable_verbose() {
+    VERBOSE.store(true, Ordering::SeqCst);
+}
+
+/// With `--verbose`, print how long a phase took as soon as it is done
+pub fn report_timing(phase: &str, elapsed_ms: u64) {
+    if VERBOSE.load(Ordering::SeqCst) && !is_json() {
+        eprintln!("timing: {phase} {elapsed_ms} ms");
+    }
+}
+
+/// Whether progress is reported as JSON events, so human progress on stderr should be left out
+pub fn is_json() -> bool {
+    JSON.load(Ordering::SeqCst) || CAPTURED.with(|c| c.borrow().is_some())
+}
+
+/// Write one event line to stderr when JSON progress is on
+pub fn emit(event: &Event) {
+    if !is_json() {
+        return;
+    }
+    let Ok(line) = serde_json::to_string(event) else {
+        return;
+    };
+    let captured = CAPTURED.with(|c| c.borrow_mut().as_mut().map(|events| events.push(line.clone())).is_some());
+    if !captured {
+        eprintln!("{line}");
+    }
+}
+
+/// Report a warning as an event, or as a "warning: ..." line without JSON progress
+pub fn warn(code: &str, message: &str) {
+    if is_json() {
+        emit(&Event::Warning { code, message });
+    } else {
+        eprintln!("warning: {message}");
+    }
+}
+
+/// A running phase that emits its start now and its end when finished
+pub struct Phase {
+    name: &'static str,
+    total: Option<usize>,
+    started: Instant,
+}
+
+impl Phase {
+    pub fn start(name: &'static str, total: Option<usize>) -> Self {
+        emit(&Event::PhaseStart { phase: name, total });
+        Self { name, total, started: Instant::now() }
+    }
+
+    /// Report `current` items done; needs a total from `start`
+    pub fn progress(&self, current: usize) {
+        if let Some(total) = self.total {
+            emit(&Event::Progress { phase: self.name, current, total });
+        }
+    }
+
+    /// End the phase after `count` items, returning how long it took in milliseconds
+    pub fn finish(self, count: usize) -> u64 {
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        emit(&Event::PhaseEnd { phase: self.name, count, elapsed_ms });
+        report_timing(self.name, elapsed_ms);
+        elapsed_ms
+    }
+}
+
+/// Run `f` with events recorded on this thread instead of printed, returning them as parsed JSON
+#[cfg(test)]
+#[cfg_attr(not(all(feature = "lang-go", feature = "lang-rust")), allow(dead_code))]
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<serde_json::Value>) {
+    CAPTURED.with(|c| *c.borrow_mut() = Some(Vec::new()));
+    let result = f();
+    let lines = CAPTURED.with(|c| c.borrow_mut().take()).unwrap_or_default();
+    let events = lines.iter().map(|l| serde_json::from_str(l).expect("event is valid JSON")).collect();
+    (result, events)
+}
@@ -1,17 +1,36 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
 
-use crate::externals::ExternalDb;
-use crate::index::{ExternalEntry, FileEntry, Index};
+use crate::externals::{ExternalDb, Language, external_name};
+use crate::index::{CallSite, ExternalEntry, FileEntry, ImplLink, Import, Index, Scope, TypeDef, TypeKind};
+
+/// Ambiguous calls with more matches than this are treated as unresolved
+const MAX_CANDIDATES: usize = 5;
+
+/// Deepest chain of Go embedded structs followed to find a promoted method
+const MAX_EMBEDDING_DEPTH: usize = 4;
+
+/// `CallSite::resolved_by` for calls resolved through the type of a local variable
+pub const RESOLVED_BY_LOCAL_TYPE: &str = "local_type";
 
 /// Resolves call targets to qualified names and populates called_by relationships
 pub struct Resolver {
-    /// Maps function names to their qualified names and file paths
+    /// Maps function names to the functions they may refer to
     /// Key: simple name (e.g., "Foo") or receiver.name (e.g., "Server.Start")
-    /// Value: Vec of (qualified_name, file_path) for potential matches
-    symbol_table: HashMap<String, Vec<(String, String)>>,
+    /// Value: every function registered under that key
+    symbol_table: HashMap<String, Vec<Symbol>>,
 
     /// Maps qualified names to their file paths
     qualified_to_file: HashMap<String, String>,
+
+    /// Maps Go functions returning a single value to that value's type text (e.g., "*Client")
+    return_types: HashMap<String, String>,
+
+    /// Go interfaces declaring at least one method, with the methods implementing them
+    interfaces: Vec<GoInterface>,
+
+    /// Go struct names mapped to (package, names of the types it embeds) for each struct of that name
+    embeds: HashMap<String, Vec<(String, Vec<String>)>>,
 }
 
 impl Resolver {
@@ -19,6 +38,9 @@ impl Resolver {
         Self {
             symbol_table: HashMap::new(),
             qualified_to_file: HashMap::new(),
+            return_types: HashMap::new(),
+            interfaces: Vec::new(),
+            embeds: HashMap::new(),
         }
     }
 
@@ -26,39 +48,113 @@ impl Resolver {
     pub fn build_symbol_table(&mut self, files: &HashMap<String, FileEntry>) {
         self.symbol_table.clear();
         self.qualified_to_file.clear();
+        self.return_types.clear();
+        self.interfaces = go_interfaces(files);
+        self.embeds.clear();
 
         for (file_path, entry) in files {
+            let lang = Language::of_file(file_path);
+            let package = entry
+                .functions
+                .first()
+                .map(|f| extract_package(&f.qualified_name))
+                .unwrap_or_default();
+
+            if lang == Language::Go {
+                for t in &entry.types {
+                    let embedded: Vec<String> = t
+                        .fields
+                        .iter()
+                        .filter(|f| f.embedded)
+                        .filter_map(|f| base_type_name(&f.type_name))
+                        .map(String::from)
+                        .collect();
+                    if !embedded.is_empty() {
+                        let type_package = t.qualified_name.rsplit_once('.').map_or("", |(p, _)| p).to_string();
+                        self.embeds.entry(t.name.clone()).or_default().push((type_package, embedded));
+                    }
+                }
+            }
+
             for func in &entry.functions {
                 // Map qualified name to file
                 self.qualified_to_file
                     .insert(func.qualified_name.clone(), file_path.clone());
+                let result = match lang {
+                    Language::Go => go_result_type(&func.signature),
+                    // `Self` is the impl type, named by the method's qualified name without the method
+                    Language::Rust => rust_result_type(&func.signature).map(|result| match result {
+                        "Self" => func.qualified_name.rsplit_once("::").map_or(result, |(impl_type, _)| impl_type),
+                        _ => result,
+                    }),
+                    _ => None,
+                };
+                if let Some(result) = result {
+                    self.return_types.insert(func.qualified_name.clone(), result.to_string());
+                }
 
-                // Add to symbol table by simple name
-                self.symbol_table
-                    .entry(func.name.clone())
-                    .or_default()
-                    .push((func.qualified_name.clone(), file_path.clone()));
+                let symbol = Symbol {
+                    qualified_name: func.qualified_name.clone(),
+                    // Trait impl methods carry no `pub` but are visible wherever the trait is
+                    scope: if lang == Language::Rust && func.receiver.is_some() { Scope::Public } else { func.scope },
+                    lang,
+                    package: match lang {
+                        Language::Rust | Language::Cpp => rust_module(&func.qualified_name, func.receiver.as_deref()).to_string(),
+                        _ => package.clone(),
+                    },
+                    // Indexes written before takes_self was recorded still typed `self`
+                    takes_self: (lang == Language::Rust && func.receiver.is_some())
+                        .then(|| func.takes_self || func.param_types.contains_key("self")),
+                };
 
                 // Also add by receiver.name for methods (e.g., "Server.Start")
                 if let Some(ref receiver) = func.receiver {
                     let method_key = format!("{}.{}", receiver, func.name);
-                    self.symbol_table
-                        .entry(method_key)
-                        .or_default()
-                        .push((func.qualified_name.clone(), file_path.clone()));
+                    self.symbol_table.entry(method_key).or_default().push(symbol.clone());
                 }
+
+                // Add to symbol table by simple name
+                self.symbol_table.entry(func.name.clone()).or_default().push(symbol);
             }
         }
     }
 
     /// Resolve all calls in the index and populate called_by
     pub fn resolve(&self, index: &mut Index) {
-        let external_db = ExternalDb::new();
+        self.resolve_where(index, |_, _| true);
+    }
+
+    /// Re-resolve after the files in `changed` were added, replaced, or removed.
+    ///
+    /// `previous` is the index before the change. A call can only bind to functions
+    /// whose simple name is the call's callee name, so besides every call in the
+    /// changed files, only calls elsewhere naming a function that the changed files
+    /// added or removed need resolving again. Go structs in the changed files can also
+    /// gain or lose promoted methods, so calls naming a method of a type they embed,
+    /// before or after the change, are resolved again too. Reverse edges and externals
+    /// are then rebuilt from the forward edges.
+    pub fn resolve_changed(&self, index: &mut Index, previous: &Index, changed: &HashSet<String>) {
+        let mut affected_names: HashSet<String> = changed
+            .iter()
+            .flat_map(|path| previous.files.get(path).into_iter().chain(index.files.get(path)))
+            .flat_map(|entry| {
+                let interface_methods = entry.types.iter().filter(|t| t.kind == TypeKind::Interface).flat_map(|t| &t.methods);
+                entry.functions.iter().map(|f| &f.name).chain(interface_methods).cloned()
+            })
+            .collect();
+        affected_names.extend(embedded_method_names(previous, changed));
+        affected_names.extend(embedded_method_names(index, changed));
+
+        // Calls resolved through a local's constructor depend on that constructor's return type too
+        self.resolve_where(index, |file_path, call| {
+            changed.contains(file_path) || affected_names.contains(callee_of(call)) || call.resolved_by.is_some()
+        });
+    }
 
-        // First pass: resolve call targets
-        let mut calls_to_targets: HashMap<String, Vec<String>> = HashMap::new();
-        // Track external references: name -> (kind, summary, count)
-        let mut external_refs: HashMap<String, (String, Option<&'static str>, u32)> = HashMap::new();
+    /// Resolve the calls selected by `should_resolve(file_path, call)`, keep the
+    /// others as they are, then rebuild reverse edges, externals, and type uses
+    fn resolve_where(&self, index: &mut Index, should_resolve: impl Fn(&str, &CallSite) -> bool) {
+        let external_db = ExternalDb::new();
 
         for (file_path, entry) in index.files.iter_mut() {
             // Extract package from file path or first function's qualified name
@@ -69,65 +165,115 @@ impl Resolver {
                 .unwrap_or_default();
 
             for func in &mut entry.functions {
-                for call in &mut func.calls {
-                    let target = self.resolve_call(&call.raw, &package, file_path);
-
-                    if target == "[unresolved]" {
-                        // Categorize the external call
-                        let (kind, summary) = external_db.categorize(&call.raw);
-                        let formatted = format!("[{}:{}]", kind.as_str(), call.raw);
-                        call.target = formatted;
-
-                        // Track external reference
-                        external_refs
-                            .entry(call.raw.clone())
-                            .and_modify(|(_, _, count)| *count += 1)
-                            .or_insert((kind.as_str().to_string(), summary, 1));
-                    } else {
-                        call.target = target.clone();
-
-                        // Track for called_by population
-                        calls_to_targets
-                            .entry(target)
-                            .or_default()
-                            .push(func.qualified_name.clone());
+                let caller = Caller {
+                    qualified_name: &func.qualified_name,
+                    param_types: &func.param_types,
+                    local_types: &func.local_types,
+                    receiver: func.receiver.as_deref(),
+                    package: &package,
+                    imports: &entry.imports,
+                    lang: Language::of_file(file_path),
+                };
+                for call in func.calls.iter_mut().filter(|call| should_resolve(file_path, call)) {
+                    self.resolve_call_site(call, &caller, &external_db);
+                }
+                // A function used as a value is only worth an edge when it is indexed
+                for reference in func.references.iter_mut().filter(|r| should_resolve(file_path, r)) {
+                    self.resolve_call_site(reference, &caller, &external_db);
+                    if reference.target.starts_with('[') {
+                        reference.target = "[unresolved]".to_string();
+                        reference.candidates.clear();
                     }
                 }
             }
         }
 
-        // Second pass: populate called_by
-        for entry in index.files.values_mut() {
-            for func in &mut entry.functions {
-                if let Some(callers) = calls_to_targets.get(&func.qualified_name) {
-                    func.called_by = callers.clone();
-                    func.called_by.sort();
-                    func.called_by.dedup();
+        rebuild_reverse_edges(index);
+        rebuild_externals(index, &external_db);
+        resolve_type_uses(index);
+        resolve_global_uses(index);
+        resolve_implementations(index);
+    }
+
+    /// Set a call's target: a qualified name, "[ambiguous:raw]" or "[iface:Interface.Method]"
+    /// with candidates, or "[kind:raw]" for externals
+    fn resolve_call_site(&self, call: &mut CallSite, caller: &Caller, external_db: &ExternalDb) {
+        call.resolved_by = None;
+
+        // cgo: `C.name` calls the C function name, categorized as a C file's call would be
+        if let Some(name) = cgo_callee(call, caller) {
+            let (kind, _) = external_db.categorize(name, Language::C);
+            call.target = format!("[{}:{name}]", kind.as_str());
+            call.candidates.clear();
+            return;
+        }
+
+        let target = match self.resolve_typed_receiver(call, caller).or_else(|| self.resolve_implicit_this(call, caller)) {
+            Some(target) => target,
+            None => match self.resolve_local_receiver(call, caller) {
+                Some(target) => {
+                    call.resolved_by = Some(RESOLVED_BY_LOCAL_TYPE.to_string());
+                    target
                 }
-            }
+                None => self.resolve_call(&call.raw, caller),
+            },
+        };
+        call.candidates.clear();
+
+        if target != "[unresolved]" {
+            call.target = target;
+            return;
         }
 
-        // Populate externals in index
-        for (name, (kind, summary, count)) in external_refs {
-            index.externals.insert(
-                name,
-                ExternalEntry {
-                    kind,
-                    summary: summary.map(String::from),
-                    references: count,
-                },
-            );
+        // A Go interface method: every implementation may be the one called
+        if let Some((iface, implementations)) = self.resolve_interface_call(call, caller) {
+            call.target = format!("[iface:{}.{}]", iface, call.callee_name);
+            call.candidates = implementations;
+            return;
+        }
+
+        // Several indexed functions match: record them, but confirm none
+        let mut candidates = self.ambiguous_candidates(&call.raw, caller);
+        if candidates.is_empty() {
+            candidates = self.promoted_candidates(call, caller);
         }
+        if !candidates.is_empty() {
+            call.target = format!("[ambiguous:{}]", call.raw);
+            call.candidates = candidates;
+            return;
+        }
+
+        let (kind, _) = external_db.categorize(&call.raw, caller.lang);
+        call.target = format!("[{}:{}]", kind.as_str(), call.raw);
     }
 
     /// Resolve a single call expression to a qualified name
-    fn resolve_call(&self, raw: &str, package: &str, _file_path: &str) -> String {
+    fn resolve_call(&self, raw: &str, caller: &Caller) -> String {
+        if caller.lang == Language::Rust && !raw.contains('.') {
+            return self.resolve_rust_use(raw, caller, CallForm::Path);
+        }
+        self.resolve_call_as_written(raw, caller)
+    }
+
+    /// Resolve a call expression without regard to Rust `use` items
+    fn resolve_call_as_written(&self, raw: &str, caller: &Caller) -> String {
+        if raw.contains("->") {
+            return self.resolve_call(&raw.replace("->", "."), caller);
+        }
+        if caller.lang == Language::Cpp && !raw.contains('.') {
+            return self.resolve_cpp_name(raw, caller);
+        }
+        if raw.contains("::") {
+            return self.resolve_rust_path(raw, caller, CallForm::Path);
+        }
+
         // Handle different call patterns:
         // 1. Simple function call: "foo" -> look up in same package first
         // 2. Package-qualified: "pkg.Foo" -> look up pkg.Foo
         // 3. Method on receiver: "s.Method" or "obj.Method" -> harder to resolve without type info
         // 4. Chained calls: "s.logger.Info" -> extract final method
 
+        let package = caller.package;
         let parts: Vec<&str> = raw.split('.').collect();
 
         match parts.len() {
@@ -137,17 +283,38 @@ impl Resolver {
                 let same_pkg_qualified = format!("{}.{}", package, name);
 
                 if self.qualified_to_file.contains_key(&same_pkg_qualified) {
-                    same_pkg_qualified
-                } else {
-                    // Try finding any match
-                    self.find_single_match(name)
+                    return same_pkg_qualified;
+                }
+                // A build-constrained file calls the variant built alongside it
+                if let Some(constraint) = caller.build_constraint() {
+                    let variant = format!("{same_pkg_qualified}@{constraint}");
+                    if self.qualified_to_file.contains_key(&variant) {
+                        return variant;
+                    }
                 }
+                // Dot imports bring a package's names into the file
+                let dot_imported = caller
+                    .imports
+                    .iter()
+                    .filter(|import| import.alias == ".")
+                    .find_map(|import| self.resolve_import(&import.path, name, caller));
+                // Else try finding any match
+                dot_imported.unwrap_or_else(|| self.find_single_match(name, caller))
             }
             2 => {
                 // Could be pkg.Func or receiver.Method
                 let first = parts[0];
                 let second = parts[1];
 
+                // An imported Go package is the package the file names, indexed or not
+                if caller.lang == Language::Go
+                    && let Some(import) = caller.imports.iter().find(|import| import.alias == first)
+                {
+                    return self
+                        .resolve_import(&import.path, second, caller)
+                        .unwrap_or_else(|| "[unresolved]".to_string());
+                }
+
                 // Try as package.Function first
                 let as_pkg_func = format!("{}.{}", first, second);
                 if self.qualified_to_file.contains_key(&as_pkg_func) {
@@ -162,180 +329,2208 @@ impl Resolver {
 
                 // Try finding method by Type.Method pattern
                 let type_method = format!("{}.{}", first, second);
-                self.find_single_match(&type_method)
+                self.find_single_match(&type_method, caller)
             }
             _ => {
                 // Chained: s.logger.Info -> try to resolve last segment
                 // This is a simplification; proper resolution needs type inference
                 let last_two = format!("{}.{}", parts[parts.len() - 2], parts[parts.len() - 1]);
-                self.find_single_match(&last_two)
+                self.find_single_match(&last_two, caller)
             }
         }
     }
 
-    /// Find a single match in symbol table, return [unresolved] if none or ambiguous
-    fn find_single_match(&self, key: &str) -> String {
-        match self.symbol_table.get(key) {
-            Some(matches) if matches.len() == 1 => matches[0].0.clone(),
-            _ => "[unresolved]".to_string(),
+    /// The function `name` of the indexed Go package `import_path` refers to: the one whose
+    /// directory is the longest suffix of the import path, since the module path is not indexed.
+    /// Of several build-constrained variants, only one sharing the caller's constraint is picked.
+    fn resolve_import(&self, import_path: &str, name: &str, caller: &Caller) -> Option<String> {
+        let matches: Vec<&Symbol> = self
+            .visible_matches(name, caller)
+            .filter(|symbol| {
+                let unconstrained = symbol.qualified_name.split('@').next().unwrap_or_default();
+                symbol.lang == Language::Go && unconstrained == format!("{}.{name}", symbol.package)
+            })
+            .filter(|symbol| import_path == symbol.package || import_path.ends_with(&format!("/{}", symbol.package)))
+            .collect();
+        let longest = matches.iter().map(|symbol| symbol.package.len()).max()?;
+        let matches: Vec<&Symbol> = matches.into_iter().filter(|symbol| symbol.package.len() == longest).collect();
+        match matches.as_slice() {
+            [symbol] => Some(symbol.qualified_name.clone()),
+            _ => {
+                let constraint = caller.build_constraint()?;
+                let variant = matches.iter().find(|symbol| symbol.qualified_name.ends_with(&format!("@{constraint}")))?;
+                Some(variant.qualified_name.clone())
+            }
         }
     }
-}
 
-impl Default for Resolver {
-    fn default() -> Self {
-        Self::new()
+    /// Resolve a Rust path call. `crate::`, `self::`, `super::` and `Self::` are rewritten
+    /// against the caller's module; other paths are tried as absolute, then relative to
+    /// the caller's module, then as the unique function whose qualified name ends with them.
+    fn resolve_rust_path(&self, raw: &str, caller: &Caller, form: CallForm) -> String {
+        let path = raw.split("::<").next().unwrap_or(raw);
+        let module = caller.rust_module();
+
+        if let Some(absolute) = absolute_rust_path(path, module, caller.receiver) {
+            if self.qualified_to_file.contains_key(&absolute) {
+                return absolute;
+            }
+            return self.find_path_suffix(&absolute, caller, form);
+        }
+
+        if self.qualified_to_file.contains_key(path) {
+            return path.to_string();
+        }
+        if !module.is_empty() {
+            let relative = format!("{module}::{path}");
+            if self.qualified_to_file.contains_key(&relative) {
+                return relative;
+            }
+        }
+        self.find_path_suffix(path, caller, form)
     }
-}
 
-/// Extract package prefix from qualified name
-/// e.g., "internal/foo/bar.Func" -> "internal/foo/bar"
-/// e.g., "main.Foo" -> "main"
-/// e.g., "pkg.Type.Method" -> "pkg"
-fn extract_package(qualified_name: &str) -> String {
-    // Find the last component that's a path or package name (before any type/function names)
-    // The pattern is: path/segments.TypeOrFunc or path/segments.Type.Method
-    if let Some(dot_pos) = qualified_name.rfind('.') {
-        // Check if there's a type prefix (path/pkg.Type.Method)
-        let prefix = &qualified_name[..dot_pos];
-        if let Some(second_dot) = prefix.rfind('.') {
-            // Could be path/pkg.Type - take everything before second dot
-            // But only if what's after second dot starts with uppercase (a type)
-            let potential_type = &prefix[second_dot + 1..];
-            if potential_type.chars().next().is_some_and(|c| c.is_uppercase()) {
-                return prefix[..second_dot].to_string();
+    /// Resolve a Rust call through the file's `use` items: a first segment the file imports
+    /// is replaced by the imported path. Otherwise the call resolves as written, then under
+    /// each glob import.
+    fn resolve_rust_use(&self, raw: &str, caller: &Caller, form: CallForm) -> String {
+        let path = raw.split("::<").next().unwrap_or(raw);
+        let (first, rest) = path.split_once("::").map_or((path, None), |(first, rest)| (first, Some(rest)));
+        if rest.is_none()
+            && form == CallForm::Path
+            && let Some(qn) = self.enclosing_function_item(path, caller)
+        {
+            return qn;
+        }
+        if let Some(import) = caller.imports.iter().find(|import| import.alias == first) {
+            let expanded = match rest {
+                // A package's lib at the repository root has no root segment
+                Some(rest) if import.path.is_empty() => rest.to_string(),
+                Some(rest) => format!("{}::{rest}", import.path),
+                None => import.path.clone(),
+            };
+            return self.resolve_rust_path(&expanded, caller, form);
+        }
+
+        let direct = match form {
+            CallForm::Path => self.resolve_call_as_written(path, caller),
+            CallForm::Method => self.resolve_rust_path(path, caller, form),
+        };
+        if direct != "[unresolved]" {
+            return direct;
+        }
+        caller
+            .imports
+            .iter()
+            .filter(|import| import.alias == "*")
+            .map(|import| self.resolve_rust_path(&format!("{}::{path}", import.path), caller, form))
+            .find(|qn| qn != "[unresolved]")
+            .unwrap_or(direct)
+    }
+
+    /// A function or closure declared inside the caller, or inside a function enclosing it,
+    /// which is in scope there before any module-level name
+    fn enclosing_function_item(&self, name: &str, caller: &Caller) -> Option<String> {
+        let mut scope = caller.qualified_name;
+        while self.qualified_to_file.contains_key(scope) {
+            let candidate = format!("{scope}::{name}");
+            if self.qualified_to_file.contains_key(&candidate) {
+                return Some(candidate);
             }
+            scope = scope.rsplit_once("::")?.0;
         }
-        prefix.to_string()
-    } else {
-        qualified_name.to_string()
+        None
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::index::{CallSite, Function, Scope};
+    /// Resolve a C++ name as unqualified lookup does: in the caller's namespace, then each
+    /// enclosing one out to the global namespace, then as the unique function whose
+    /// qualified name ends with it
+    fn resolve_cpp_name(&self, raw: &str, caller: &Caller) -> String {
+        let path = raw.trim_start_matches("::");
+        let path = path.split('<').next().unwrap_or(path);
+        let mut module = caller.rust_module();
+        loop {
+            let candidate = if module.is_empty() { path.to_string() } else { format!("{module}::{path}") };
+            if self.qualified_to_file.contains_key(&candidate) {
+                return candidate;
+            }
+            if module.is_empty() {
+                break;
+            }
+            module = module.rsplit_once("::").map_or("", |(outer, _)| outer);
+        }
+        self.find_path_suffix(path, caller, CallForm::Path)
+    }
 
-    fn make_function(name: &str, qualified: &str, calls: Vec<CallSite>) -> Function {
-        Function {
-            name: name.to_string(),
-            qualified_name: qualified.to_string(),
-            ast_hash: "0000000000000000".to_string(),
-            line_start: 1,
-            line_end: 10,
-            signature: format!("func {}()", name),
-            summary: None,
-            receiver: None,
-            scope: Scope::Public,
-            calls,
-            called_by: Vec::new(),
+    /// The only function callable as `form` whose qualified name is `path` or ends with `::path`
+    fn find_path_suffix(&self, path: &str, caller: &Caller, form: CallForm) -> String {
+        let name = path.rsplit("::").next().unwrap_or(path);
+        let suffix = format!("::{path}");
+        let candidates: Vec<&Symbol> = self
+            .visible_matches(name, caller)
+            .filter(|symbol| symbol.qualified_name == path || symbol.qualified_name.ends_with(&suffix))
+            .collect();
+        let mut matches: Vec<&String> =
+            candidates.iter().filter(|symbol| symbol.callable_as(form)).map(|symbol| &symbol.qualified_name).collect();
+        // `Type::method(&x)` is still a valid way to call a method when nothing else fits
+        if matches.is_empty() && form == CallForm::Path {
+            matches = candidates.iter().map(|symbol| &symbol.qualified_name).collect();
+        }
+        match matches.as_slice() {
+            [qn] => qn.to_string(),
+            _ => "[unresolved]".to_string(),
         }
     }
 
-    fn make_call(raw: &str) -> CallSite {
-        CallSite {
-            target: "[unresolved]".to_string(),
-            raw: raw.to_string(),
-            line: 1,
+    /// Resolve `recv.Method()` where `recv` is a parameter or receiver whose
+    /// declared type has an indexed method of that name
+    fn resolve_typed_receiver(&self, call: &CallSite, caller: &Caller) -> Option<String> {
+        let receiver = call.receiver_expr.as_deref()?;
+        let type_name = match caller.param_types.get(receiver) {
+            Some(type_text) => base_type_name(type_text)?,
+            None => caller.unrecorded_receiver_type(receiver)?,
+        };
+        self.resolve_method_of(type_name, &call.callee_name, caller)
+    }
+
+    /// Resolve a bare `method()` in a Java or C++ method to a method of the same class
+    fn resolve_implicit_this(&self, call: &CallSite, caller: &Caller) -> Option<String> {
+        if !matches!(caller.lang, Language::Java | Language::Cpp) || call.receiver_expr.is_some() {
+            return None;
         }
+        self.resolve_method_of(caller.receiver?, &call.callee_name, caller)
     }
 
-    #[test]
-    fn test_resolve_same_package_call() {
-        let mut index = Index::new();
+    /// Resolve `local.Method()` where `local` was bound once to a composite literal
+    /// or to the result of an indexed function with a single return type
+    fn resolve_local_receiver(&self, call: &CallSite, caller: &Caller) -> Option<String> {
+        let type_text = caller.local_types.get(call.receiver_expr.as_deref()?)?;
+        let type_text = match type_text.strip_suffix("()") {
+            Some(constructor) => self.return_types.get(&self.resolve_constructor(constructor, caller)?)?,
+            None => type_text,
+        };
+        // A Rust constructor returning `Self` records the qualified impl type
+        let qualified = format!("{type_text}::{}", call.callee_name);
+        if caller.lang == Language::Rust && self.qualified_to_file.contains_key(&qualified) {
+            return Some(qualified);
+        }
+        self.resolve_method_of(base_type_name(type_text)?, &call.callee_name, caller)
+    }
 
-        // Using path-based qualified names: cmd/app.foo
-        let foo = make_function("foo", "cmd/app.foo", vec![]);
-        let bar = make_function("bar", "cmd/app.bar", vec![make_call("foo")]);
+    /// Resolve a constructor call like "NewClient" or "client.New". Package-qualified
+    /// calls the file's imports do not cover also match the only visible function of
+    /// that name in a package whose path ends with the qualifier.
+    fn resolve_constructor(&self, raw: &str, caller: &Caller) -> Option<String> {
+        let qn = self.resolve_call(raw, caller);
+        if self.qualified_to_file.contains_key(&qn) {
+            return Some(qn);
+        }
+        let (pkg, name) = raw.split_once('.')?;
+        let suffix = format!("/{pkg}");
+        let matches: Vec<&Symbol> = self
+            .visible_matches(name, caller)
+            .filter(|symbol| symbol.package == pkg || symbol.package.ends_with(&suffix))
+            .collect();
+        match matches.as_slice() {
+            [symbol] => Some(symbol.qualified_name.clone()),
+            _ => None,
+        }
+    }
 
-        index.files.insert(
-            "./cmd/app/main.go".to_string(),
-            FileEntry {
-                ast_hash: "abc".to_string(),
-                functions: vec![foo, bar],
-                types: vec![],
-                variables: vec![],
+    /// The indexed method `method` of type `type_name`, declared on the type or promoted
+    /// from a Go struct it embeds
+    fn resolve_method_of(&self, type_name: &str, method: &str, caller: &Caller) -> Option<String> {
+        self.declared_method_of(type_name, method, caller).or_else(|| {
+            let mut promoted = self.promoted_methods_of(type_name, method, caller);
+            if promoted.len() == 1 { promoted.pop() } else { None }
+        })
+    }
+
+    /// Promoted methods a Go `recv.Method()` call could mean when two embedded types both
+    /// provide `Method` at the same depth, which a name-based resolver cannot tell apart
+    fn promoted_candidates(&self, call: &CallSite, caller: &Caller) -> Vec<String> {
+        let Some(receiver) = call.receiver_expr.as_deref().filter(|_| caller.lang == Language::Go) else {
+            return Vec::new();
+        };
+        let type_text = caller.param_types.get(receiver).or_else(|| caller.local_types.get(receiver));
+        let Some(type_name) = type_text.and_then(|t| base_type_name(t)) else {
+            return Vec::new();
+        };
+        let promoted = self.promoted_methods_of(type_name, &call.callee_name, caller);
+        if promoted.len() > 1 { promoted } else { Vec::new() }
+    }
+
+    /// The methods `method` a Go struct gets from its embedded types, searched one embedding
+    /// level at a time as Go does: the matches at the shallowest level that has any, sorted.
+    fn promoted_methods_of(&self, type_name: &str, method: &str, caller: &Caller) -> Vec<String> {
+        let mut visited: HashSet<&str> = HashSet::from([type_name]);
+        let mut level: Vec<&str> = vec![type_name];
+        for _ in 0..MAX_EMBEDDING_DEPTH {
+            let mut embedded: Vec<&str> = level.iter().flat_map(|t| self.embedded_types(t, caller)).collect();
+            embedded.retain(|t| visited.insert(t));
+            if embedded.is_empty() {
+                break;
+            }
+
+            let mut matches: Vec<String> =
+                embedded.iter().filter_map(|t| self.declared_method_of(t, method, caller)).collect();
+            if !matches.is_empty() {
+                matches.sort();
+                matches.dedup();
+                return matches;
+            }
+            level = embedded;
+        }
+        Vec::new()
+    }
+
+    /// Names of the types the Go struct `type_name` embeds, preferring a struct in the caller's package
+    fn embedded_types(&self, type_name: &str, caller: &Caller) -> Vec<&str> {
+        let structs = self.embeds.get(type_name).map(Vec::as_slice).unwrap_or_default();
+        let embedded = match structs.iter().find(|(package, _)| package == caller.package) {
+            Some((_, embedded)) => embedded,
+            None => match structs {
+                [(_, embedded)] => embedded,
+                _ => return Vec::new(),
             },
-        );
+        };
+        embedded.iter().map(String::as_str).collect()
+    }
 
-        let mut resolver = Resolver::new();
-        resolver.build_symbol_table(&index.files);
-        resolver.resolve(&mut index);
+    /// The indexed method `method` declared on type `type_name`, preferring the caller's package
+    fn declared_method_of(&self, type_name: &str, method: &str, caller: &Caller) -> Option<String> {
+        // Type.Method in the same package
+        let same_pkg = format!("{}.{}.{}", caller.package, type_name, method);
+        if self.qualified_to_file.contains_key(&same_pkg) {
+            return Some(same_pkg);
+        }
+        // A Rust type may be named through the file's `use` items
+        if caller.lang == Language::Rust {
+            let qn = self.resolve_rust_use(&format!("{type_name}::{method}"), caller, CallForm::Method);
+            if qn != "[unresolved]" {
+                return Some(qn);
+            }
+        }
 
-        let entry = index.files.get("./cmd/app/main.go").unwrap();
-        let bar = entry.functions.iter().find(|f| f.name == "bar").unwrap();
-        assert_eq!(bar.calls[0].target, "cmd/app.foo");
+        match self.find_single_match(&format!("{}.{}", type_name, method), caller) {
+            qn if qn == "[unresolved]" => None,
+            qn => Some(qn),
+        }
+    }
 
-        let foo = entry.functions.iter().find(|f| f.name == "foo").unwrap();
-        assert_eq!(foo.called_by, vec!["cmd/app.bar"]);
+    /// The interface a Go `recv.Method()` call goes through and the methods implementing it.
+    /// The interface is the receiver's declared type when that is an interface declaring
+    /// the method, preferring the caller's package, else the only interface declaring it.
+    fn resolve_interface_call(&self, call: &CallSite, caller: &Caller) -> Option<(&str, Vec<String>)> {
+        if caller.lang != Language::Go {
+            return None;
+        }
+        let receiver = call.receiver_expr.as_deref()?;
+        let declaring: Vec<&GoInterface> =
+            self.interfaces.iter().filter(|iface| iface.implementations.contains_key(&call.callee_name)).collect();
+
+        let declared_type = caller.param_types.get(receiver).or_else(|| caller.local_types.get(receiver));
+        let typed: Vec<&GoInterface> = match declared_type.and_then(|t| base_type_name(t)) {
+            Some(type_name) => declaring.iter().copied().filter(|iface| iface.name == type_name).collect(),
+            None => Vec::new(),
+        };
+        let iface = match (typed.as_slice(), declaring.as_slice()) {
+            ([iface], _) | ([], [iface]) => iface,
+            ([], _) => return None,
+            (typed, _) => typed.iter().find(|iface| iface.package == caller.package)?,
+        };
+
+        let implementations = &iface.implementations[&call.callee_name];
+        (!implementations.is_empty()).then(|| (iface.name.as_str(), implementations.clone()))
     }
 
-    #[test]
-    fn test_resolve_cross_package_call() {
-        let mut index = Index::new();
+    /// Candidate qualified names for a call whose symbol table key matches more than once.
+    /// Empty if the call matches at most once or too many times to be useful.
+    fn ambiguous_candidates(&self, raw: &str, caller: &Caller) -> Vec<String> {
+        // Same key the fallback lookups in resolve_call use: "name" or "Type.name"
+        let parts: Vec<&str> = raw.split('.').collect();
+        let key = parts[parts.len().saturating_sub(2)..].join(".");
 
-        // Note: cross-package calls use import alias, so "utils.Helper" in source
-        // This won't resolve because we don't track imports yet
-        let helper = make_function("Helper", "internal/utils.Helper", vec![]);
-        let main_fn = make_function("main", "cmd/app.main", vec![make_call("Helper")]);
+        let matches: Vec<&Symbol> = self.visible_matches(&key, caller).collect();
+        if matches.len() > 1 && matches.len() <= MAX_CANDIDATES {
+            let mut candidates: Vec<String> = matches.iter().map(|symbol| symbol.qualified_name.clone()).collect();
+            candidates.sort();
+            candidates.dedup();
+            candidates
+        } else {
+            Vec::new()
+        }
+    }
 
-        index.files.insert(
-            "./internal/utils/helper.go".to_string(),
-            FileEntry {
-                ast_hash: "abc".to_string(),
-                functions: vec![helper],
-                types: vec![],
-                variables: vec![],
-            },
-        );
-        index.files.insert(
-            "./cmd/app/main.go".to_string(),
-            FileEntry {
-                ast_hash: "def".to_string(),
-                functions: vec![main_fn],
-                types: vec![],
-                variables: vec![],
-            },
-        );
+    /// Find the single visible match in symbol table, return [unresolved] if none or ambiguous
+    fn find_single_match(&self, key: &str, caller: &Caller) -> String {
+        let matches: Vec<&Symbol> = self.visible_matches(key, caller).collect();
+        match matches.as_slice() {
+            [symbol] => symbol.qualified_name.clone(),
+            _ => "[unresolved]".to_string(),
+        }
+    }
 
-        let mut resolver = Resolver::new();
-        resolver.build_symbol_table(&index.files);
-        resolver.resolve(&mut index);
+    /// Symbol table entries under `key` that `caller` is allowed to call
+    fn visible_matches<'a>(&'a self, key: &str, caller: &Caller) -> impl Iterator<Item = &'a Symbol> {
+        let caller_module = match caller.lang {
+            Language::Rust | Language::Cpp => caller.rust_module(),
+            _ => caller.package,
+        };
+        self.symbol_table
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter(move |symbol| symbol.visible_from(caller.lang, caller_module))
+    }
+}
 
-        let entry = index.files.get("./cmd/app/main.go").unwrap();
-        let main_fn = entry.functions.iter().find(|f| f.name == "main").unwrap();
-        // Should resolve via simple name lookup since Helper is unique
-        assert_eq!(main_fn.calls[0].target, "internal/utils.Helper");
+/// How a call names its function: by path (`Type::f()`, `f()`) or on a value (`x.f()`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallForm {
+    Path,
+    Method,
+}
+
+/// A function the symbol table can bind a call to
+#[derive(Clone)]
+struct Symbol {
+    qualified_name: String,
+    scope: Scope,
+    lang: Language,
+    /// Go package, Rust module or C++ namespace, TypeScript module, or Java package the function is defined in
+    package: String,
+    /// For a Rust impl function, whether it takes `self` (see Function::takes_self)
+    takes_self: Option<bool>,
+}
+
+impl Symbol {
+    /// Whether a call written as `form` can mean this function: a Rust method is called on a
+    /// value and an associated function by path. A path that matches no associated function
+    /// may still name a method, as in `Type::method(&x)`.
+    fn callable_as(&self, form: CallForm) -> bool {
+        match (form, self.takes_self) {
+            (_, None) => true,
+            (CallForm::Method, Some(takes_self)) => takes_self,
+            (CallForm::Path, Some(takes_self)) => !takes_self,
+        }
     }
 
-    #[test]
-    fn test_unresolved_external_call() {
-        let mut index = Index::new();
+    /// Whether code in `module` (a Go package or Rust module) can call this function.
+    /// Unexported Go functions are only visible within their package, private Rust
+    /// functions within their module and its submodules.
+    fn visible_from(&self, lang: Language, module: &str) -> bool {
+        if self.scope != Scope::Internal || lang != self.lang {
+            return true;
+        }
+        match self.lang {
+            // Unexported TypeScript declarations are local to their file, which is its module
+            // Java's private and package-private methods are both kept to the package
+            Language::Go | Language::TypeScript | Language::Java => module == self.package,
+            Language::Rust => {
+                self.package.is_empty()
+                    || module == self.package
+                    || module.strip_prefix(self.package.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            }
+            // Python privacy is a naming convention only
+            Language::C | Language::Python => true,
+            // Private members are marked but not enforced; callers outside the class are rare
+            Language::Cpp => true,
+        }
+    }
+}
 
-        let main_fn = make_function("main", "cmd/app.main", vec![make_call("fmt.Println")]);
+/// A Go interface as the resolver binds calls through it
+struct GoInterface {
+    name: String,
+    package: String,
+    /// Each declared method, with the sorted qualified names of the methods implementing it
+    implementations: BTreeMap<String, Vec<String>>,
+}
 
-        index.files.insert(
-            "./cmd/app/main.go".to_string(),
-            FileEntry {
-                ast_hash: "abc".to_string(),
-                functions: vec![main_fn],
-                types: vec![],
-                variables: vec![],
-            },
-        );
+/// Names of the methods declared on the types that the Go structs in the `changed` files
+/// of `index` embed, directly or through a chain of embedded structs anywhere in the index
+fn embedded_method_names(index: &Index, changed: &HashSet<String>) -> HashSet<String> {
+    fn go_types<'a>(path: &str, entry: &'a FileEntry) -> &'a [TypeDef] {
+        if Language::of_file(path) == Language::Go { &entry.types } else { &[] }
+    }
+    fn embeds_of(t: &TypeDef) -> impl Iterator<Item = String> + '_ {
+        t.fields.iter().filter(|f| f.embedded).filter_map(|f| base_type_name(&f.type_name)).map(String::from)
+    }
 
-        let mut resolver = Resolver::new();
-        resolver.build_symbol_table(&index.files);
-        resolver.resolve(&mut index);
+    // Embedded type names by struct name, across packages as embedded_types may pick any of them
+    let mut embeds: HashMap<String, Vec<String>> = HashMap::new();
+    for t in index.files.iter().flat_map(|(path, entry)| go_types(path, entry)) {
+        embeds.entry(t.name.clone()).or_default().extend(embeds_of(t));
+    }
 
-        let entry = index.files.get("./cmd/app/main.go").unwrap();
-        let main_fn = entry.functions.iter().find(|f| f.name == "main").unwrap();
-        // fmt.Println is external, should be categorized
-        assert_eq!(main_fn.calls[0].target, "[external:fmt.Println]");
+    let mut pending: Vec<String> = changed
+        .iter()
+        .filter_map(|path| index.files.get_key_value(path))
+        .flat_map(|(path, entry)| go_types(path, entry))
+        .flat_map(embeds_of)
+        .collect();
+    let mut embedded: HashSet<String> = HashSet::new();
+    while let Some(name) = pending.pop() {
+        if let Some(next) = embeds.get(name.as_str()).filter(|_| !embedded.contains(&name)) {
+            pending.extend(next.iter().cloned());
+        }
+        embedded.insert(name);
+    }
 
-        // Should be tracked in externals
-        assert!(index.externals.contains_key("fmt.Println"));
-        assert_eq!(index.externals.get("fmt.Println").unwrap().kind, "external");
+    index
+        .files
+        .values()
+        .flat_map(|entry| &entry.functions)
+        .filter(|f| f.receiver.as_deref().and_then(base_type_name).is_some_and(|r| embedded.contains(r)))
+        .map(|f| f.name.clone())
+        .collect()
+}
+
+/// Go interfaces declaring methods, each with its implementations: methods of the same name
+/// on types having every method the interface declares (names only, as in resolve_implementations)
+fn go_interfaces(files: &HashMap<String, FileEntry>) -> Vec<GoInterface> {
+    let go_files = || files.iter().filter(|(file_path, _)| file_path.ends_with(".go")).map(|(_, entry)| entry);
+
+    // Go method sets by (package, receiver type), each method mapped to its qualified name
+    let mut method_sets: HashMap<(String, &str), HashMap<&str, &str>> = HashMap::new();
+    for func in go_files().flat_map(|entry| &entry.functions) {
+        if let Some(receiver) = &func.receiver {
+            let key = (extract_package(&func.qualified_name), receiver.as_str());
+            method_sets.entry(key).or_default().insert(&func.name, &func.qualified_name);
+        }
+    }
+
+    let mut interfaces: Vec<GoInterface> = go_files()
+        .flat_map(|entry| &entry.types)
+        .filter(|t| t.kind == TypeKind::Interface && !t.methods.is_empty())
+        .map(|t| {
+            let mut implementations: BTreeMap<String, Vec<String>> =
+                t.methods.iter().map(|m| (m.clone(), Vec::new())).collect();
+            for methods in method_sets.values().filter(|methods| t.methods.iter().all(|m| methods.contains_key(m.as_str()))) {
+                for (method, impls) in &mut implementations {
+                    impls.push(methods[method.as_str()].to_string());
+                }
+            }
+            for impls in implementations.values_mut() {
+                impls.sort();
+            }
+            GoInterface {
+                name: t.name.clone(),
+                package: t.qualified_name.rsplit_once('.').map_or("", |(p, _)| p).to_string(),
+                implementations,
+            }
+        })
+        .collect();
+    interfaces.sort_by(|a, b| (&a.package, &a.name).cmp(&(&b.package, &b.name)));
+    interfaces
+}
+
+/// What the resolver knows about the function a call is made from
+struct Caller<'a> {
+    qualified_name: &'a str,
+    param_types: &'a BTreeMap<String, String>,
+    local_types: &'a BTreeMap<String, String>,
+    /// Receiver type of a method (e.g., "Server")
+    receiver: Option<&'a str>,
+    /// Go package of the caller's file
+    package: &'a str,
+    /// Go imports of the caller's file
+    imports: &'a [Import],
+    lang: Language,
+}
+
+impl Caller<'_> {
+    /// Rust module path of the caller, e.g. "commands::index" for
+    /// "commands::index::run" or "commands::index::ReadyQueue::pop"
+    fn rust_module(&self) -> &str {
+        rust_module(self.qualified_name, self.receiver)
+    }
+
+    /// Build constraint a Go caller's qualified name carries (e.g., "linux" for "pkg.poll@linux")
+    fn build_constraint(&self) -> Option<&str> {
+        self.qualified_name.split_once('@').map(|(_, constraint)| constraint)
+    }
+
+    /// The caller's receiver type for a bare identifier receiver, when the receiver
+    /// variable itself was not recorded in param_types (indexes written before
+    /// parameter types were, or receivers the parser could not name)
+    fn unrecorded_receiver_type(&self, receiver: &str) -> Option<&str> {
+        let receiver_type = self.receiver?;
+        let is_ident = !receiver.is_empty() && receiver.chars().all(|c| c.is_alphanumeric() || c == '_');
+        let recorded = self.param_types.values().any(|t| base_type_name(t) == Some(receiver_type));
+        (is_ident && !recorded).then_some(receiver_type)
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rust module path of a function from its qualified name and receiver type
+fn rust_module<'a>(qualified_name: &'a str, receiver: Option<&str>) -> &'a str {
+    let Some((scope, _)) = qualified_name.rsplit_once("::") else {
+        return "";
+    };
+    match receiver {
+        Some(receiver) if scope == receiver => "",
+        Some(receiver) => scope.strip_suffix(&format!("::{receiver}")).unwrap_or(scope),
+        // A binary's entry point is "crate::main" but lives in the root module
+        None if scope == "crate" => "",
+        None => scope,
+    }
+}
+
+/// Rewrite a path starting with `crate::`, `self::`, `super::` or `Self::` to an absolute
+/// module path, e.g. "super::helpers::parse" from "commands::index" -> "commands::helpers::parse".
+/// None for other paths, or when `super::` climbs above the crate root.
+fn absolute_rust_path(path: &str, module: &str, receiver: Option<&str>) -> Option<String> {
+    let mut base: Vec<&str> = module.split("::").filter(|s| !s.is_empty()).collect();
+    let rest = if let Some(rest) = path.strip_prefix("crate::") {
+        base.clear();
+        rest
+    } else if let Some(rest) = path.strip_prefix("self::") {
+        rest
+    } else if let Some(rest) = path.strip_prefix("Self::") {
+        base.push(receiver?);
+        rest
+    } else if path.starts_with("super::") {
+        let mut rest = path;
+        while let Some(r) = rest.strip_prefix("super::") {
+            base.pop()?;
+            rest = r;
+        }
+        rest
+    } else {
+        return None;
+    };
+
+    base.push(rest);
+    Some(base.join("::"))
+}
+
+/// Populate called_by (confirmed) and possibly_called_by (ambiguous) from the forward edges;
+/// a function that references another as a value counts as its caller
+fn rebuild_reverse_edges(index: &mut Index) {
+    let mut callers: HashMap<String, Vec<String>> = HashMap::new();
+    let mut possible: HashMap<String, Vec<String>> = HashMap::new();
+
+    for entry in index.files.values() {
+        for func in &entry.functions {
+            for call in func.calls.iter().chain(&func.references) {
+                if !call.target.starts_with('[') {
+                    callers.entry(call.target.clone()).or_default().push(func.qualified_name.clone());
+                }
+                for candidate in &call.candidates {
+                    possible.entry(candidate.clone()).or_default().push(func.qualified_name.clone());
+                }
+            }
+        }
+    }
+
+    for entry in index.files.values_mut() {
+        for func in &mut entry.functions {
+            let mut called_by = callers.remove(&func.qualified_name).unwrap_or_default();
+            called_by.sort();
+            called_by.dedup();
+
+            let mut possibly_called_by: Vec<String> = possible
+                .remove(&func.qualified_name)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|c| !called_by.contains(c))
+                .collect();
+            possibly_called_by.sort();
+            possibly_called_by.dedup();
+
+            func.called_by = called_by;
+            func.possibly_called_by = possibly_called_by;
+        }
+    }
+}
+
+/// Rebuild the externals table from calls whose target is "[kind:raw]"
+pub fn rebuild_externals(index: &mut Index, external_db: &ExternalDb) {
+    let mut externals: HashMap<String, ExternalEntry> = HashMap::new();
+
+    for entry in index.files.values() {
+        for func in &entry.functions {
+            for call in &func.calls {
+                let Some((kind, _)) = call.target.strip_prefix('[').and_then(|t| t.split_once(':')) else {
+                    continue;
+                };
+                if kind == "ambiguous" || kind == "iface" {
+                    continue;
+                }
+                let name = external_name(&call.target);
+                externals
+                    .entry(name.to_string())
+                    .and_modify(|ext| ext.references += 1)
+                    .or_insert_with(|| ExternalEntry {
+                        kind: kind.to_string(),
+                        summary: external_db.describe(kind, name).map(String::from),
+                        references: 1,
+                    });
+            }
+        }
+    }
+
+    index.externals = externals;
+}
+
+/// Map every function's type_refs to qualified names of indexed types (`uses_types`).
+/// Name-based: a qualified ref must match the end of the type's qualified name, and a
+/// name shared by several types picks the one in the same file, then the same module.
+fn resolve_type_uses(index: &mut Index) {
+    let mut types: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for (file_path, entry) in &index.files {
+        for t in &entry.types {
+            types.entry(t.name.clone()).or_default().push((t.qualified_name.clone(), file_path.clone()));
+        }
+    }
+
+    for (file_path, entry) in index.files.iter_mut() {
+        let is_go = Language::of_file(file_path) == Language::Go;
+        for func in &mut entry.functions {
+            let mut uses: Vec<String> = func
+                .type_refs
+                .iter()
+                .filter_map(|r| {
+                    match is_go.then(|| resolve_go_imported_type(&types, &r.name, &entry.imports)).flatten() {
+                        Some(resolved) => resolved,
+                        None => resolve_type_ref(&types, &r.name, file_path, &func.qualified_name),
+                    }
+                })
+                .collect();
+            uses.sort();
+            uses.dedup();
+            func.uses_types = uses;
+        }
+    }
+}
+
+/// Map every function's global_refs to qualified names of indexed variables and statics
+/// (`reads_globals`, `writes_globals`). Name-based like type uses; a Go name without a package
+/// qualifier only matches its own package, and a C static only its own file.
+fn resolve_global_uses(index: &mut Index) {
+    let mut globals: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut defined_in: HashMap<String, (String, bool)> = HashMap::new();
+    for (file_path, entry) in &index.files {
+        for var in &entry.variables {
+            globals.entry(var.name.clone()).or_default().push((var.qualified_name.clone(), file_path.clone()));
+            defined_in.insert(var.qualified_name.clone(), (file_path.clone(), var.scope == Scope::Static));
+        }
+    }
+
+    for (file_path, entry) in index.files.iter_mut() {
+        for func in &mut entry.functions {
+            let mut reads = Vec::new();
+            let mut writes = Vec::new();
+            for r in &func.global_refs {
+                let Some(qn) = resolve_global_ref(&globals, &defined_in, &r.name, file_path, &func.qualified_name)
+                else {
+                    continue;
+                };
+                if r.write {
+                    writes.push(qn);
+                } else {
+                    reads.push(qn);
+                }
+            }
+            for list in [&mut reads, &mut writes] {
+                list.sort();
+                list.dedup();
+            }
+            func.reads_globals = reads;
+            func.writes_globals = writes;
+        }
+    }
+}
+
+fn resolve_global_ref(
+    globals: &HashMap<String, Vec<(String, String)>>,
+    defined_in: &HashMap<String, (String, bool)>,
+    name: &str,
+    file_path: &str,
+    func_qualified_name: &str,
+) -> Option<String> {
+    let same_dir = |other: &str| Path::new(other).parent() == Path::new(file_path).parent();
+
+    // Go "config.Default" is another package's variable; "cfg.Port" a field of the variable cfg
+    let root = name.split_once('.').map(|(root, _)| root);
+    for attempt in std::iter::once(name).chain(root) {
+        let Some(qn) = resolve_type_ref(globals, attempt, file_path, func_qualified_name) else {
+            continue;
+        };
+        let Some((var_file, is_static)) = defined_in.get(&qn) else {
+            continue;
+        };
+        let visible = if *is_static {
+            var_file == file_path
+        } else if file_path.ends_with(".go") && !attempt.contains('.') {
+            same_dir(var_file)
+        } else {
+            true
+        };
+        if visible {
+            return Some(qn);
+        }
+    }
+    None
+}
+
+/// Link types to the traits and interfaces they implement, in both directions.
+///
+/// Rust `impl Trait for Type` blocks are resolved by name; a foreign trait or type,
+/// or a blanket impl over a type parameter, is kept as written and flagged unresolved.
+/// A Go type implements an interface when it has a method with every name the
+/// interface declares directly (names only, signatures are not compared).
+fn resolve_implementations(index: &mut Index) {
+    let mut traits: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut concrete: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for (file_path, entry) in &index.files {
+        for t in &entry.types {
+            let table = if t.kind == TypeKind::Interface { &mut traits } else { &mut concrete };
+            table.entry(t.name.clone()).or_default().push((t.qualified_name.clone(), file_path.clone()));
+        }
+    }
+
+    let mut implements: HashMap<String, Vec<ImplLink>> = HashMap::new();
+    let mut implemented_by: HashMap<String, Vec<ImplLink>> = HashMap::new();
+
+    for (file_path, entry) in &index.files {
+        let context = entry.functions.first().map_or("", |f| f.qualified_name.as_str());
+        for imp in &entry.impls {
+            let type_path = imp.type_name.trim_start_matches('&').trim_start_matches("mut ").trim();
+            let type_path = type_path.split('<').next().unwrap_or(type_path);
+            let trait_qn = resolve_type_ref(&traits, &imp.trait_name, file_path, context);
+            let type_qn = resolve_type_ref(&concrete, type_path, file_path, context);
+
+            if let Some(type_qn) = &type_qn {
+                implements.entry(type_qn.clone()).or_default().push(ImplLink {
+                    name: trait_qn.clone().unwrap_or_else(|| imp.trait_name.clone()),
+                    file: file_path.clone(),
+                    line: imp.line,
+                    unresolved: trait_qn.is_none(),
+                });
+            }
+            if let Some(trait_qn) = trait_qn {
+                implemented_by.entry(trait_qn).or_default().push(ImplLink {
+                    name: type_qn.clone().unwrap_or_else(|| imp.type_name.clone()),
+                    file: file_path.clone(),
+                    line: imp.line,
+                    unresolved: type_qn.is_none(),
+                });
+            }
+        }
+    }
+
+    // Go method sets by (package, receiver type)
+    let mut method_sets: HashMap<(String, &str), HashSet<&str>> = HashMap::new();
+    for (file_path, entry) in &index.files {
+        if !file_path.ends_with(".go") {
+            continue;
+        }
+        for func in &entry.functions {
+            if let Some(receiver) = &func.receiver {
+                let key = (extract_package(&func.qualified_name), receiver.as_str());
+                method_sets.entry(key).or_default().insert(func.name.as_str());
+            }
+        }
+    }
+
+    let interfaces: Vec<_> = index
+        .files
+        .iter()
+        .filter(|(file_path, _)| file_path.ends_with(".go"))
+        .flat_map(|(file_path, entry)| entry.types.iter().map(move |t| (file_path, t)))
+        .filter(|(_, t)| t.kind == TypeKind::Interface && !t.methods.is_empty())
+        .collect();
+
+    for (file_path, entry) in &index.files {
+        if !file_path.ends_with(".go") {
+            continue;
+        }
+        for t in entry.types.iter().filter(|t| t.kind != TypeKind::Interface) {
+            let package = t.qualified_name.rsplit_once('.').map_or("", |(p, _)| p);
+            let Some(methods) = method_sets.get(&(package.to_string(), t.name.as_str())) else {
+                continue;
+            };
+            for (iface_file, iface) in &interfaces {
+                if !iface.methods.iter().all(|m| methods.contains(m.as_str())) {
+                    continue;
+                }
+                implements.entry(t.qualified_name.clone()).or_default().push(ImplLink {
+                    name: iface.qualified_name.clone(),
+                    file: iface_file.to_string(),
+                    line: iface.line_start,
+                    unresolved: false,
+                });
+                implemented_by.entry(iface.qualified_name.clone()).or_default().push(ImplLink {
+                    name: t.qualified_name.clone(),
+                    file: file_path.clone(),
+                    line: t.line_start,
+                    unresolved: false,
+                });
+            }
+        }
+    }
+
+    for entry in index.files.values_mut() {
+        for t in &mut entry.types {
+            t.implements = sorted_links(implements.get(&t.qualified_name));
+            t.implemented_by = sorted_links(implemented_by.get(&t.qualified_name));
+        }
+    }
+}
+
+fn sorted_links(links: Option<&Vec<ImplLink>>) -> Vec<ImplLink> {
+    let mut links = links.cloned().unwrap_or_default();
+    links.sort();
+    links.dedup();
+    links
+}
+
+fn resolve_type_ref(
+    types: &HashMap<String, Vec<(String, String)>>,
+    name: &str,
+    file_path: &str,
+    func_qualified_name: &str,
+) -> Option<String> {
+    let name = name.strip_prefix("crate::").or_else(|| name.strip_prefix("self::")).unwrap_or(name);
+    let name = name.trim_start_matches("super::");
+    let simple = name.rsplit(['.', ':']).next().unwrap_or(name);
+
+    let mut candidates: Vec<&(String, String)> = types
+        .get(simple)?
+        .iter()
+        .filter(|(qn, _)| {
+            simple == name || qn == name || qn.ends_with(&format!("/{name}")) || qn.ends_with(&format!("::{name}"))
+        })
+        .collect();
+
+    if candidates.len() > 1 {
+        let same_file: Vec<_> = candidates.iter().copied().filter(|(_, f)| f == file_path).collect();
+        candidates = if same_file.is_empty() {
+            candidates
+                .into_iter()
+                .filter(|(qn, _)| {
+                    let module = qn.trim_end_matches(simple).trim_end_matches(['.', ':']);
+                    func_qualified_name.starts_with(module)
+                })
+                .collect()
+        } else {
+            same_file
+        };
+    }
+
+    match candidates.as_slice() {
+        [(qn, _)] => Some(qn.clone()),
+        _ => None,
+    }
+}
+
+/// A Go `pkg.Type` reference whose qualifier is one of the file's imports, by its alias or
+/// package name. The type is looked up in the imported package as resolve_import does for
+/// calls: the package whose directory is the longest suffix of the import path. None when
+/// the qualifier is not an import; Some(None) when the imported package has no such type.
+fn resolve_go_imported_type(
+    types: &HashMap<String, Vec<(String, String)>>,
+    name: &str,
+    imports: &[Import],
+) -> Option<Option<String>> {
+    let (qualifier, simple) = name.split_once('.')?;
+    let import = imports.iter().find(|import| import.alias == qualifier)?;
+    let in_package = |qn: &&String| {
+        let package = qn.rsplit_once('.').map_or("", |(package, _)| package);
+        import.path == package || import.path.ends_with(&format!("/{package}"))
+    };
+    let matches: Vec<&String> = types.get(simple).into_iter().flatten().map(|(qn, _)| qn).filter(in_package).collect();
+    let longest = matches.iter().map(|qn| qn.len()).max();
+    let matches: Vec<&String> = matches.into_iter().filter(|qn| Some(qn.len()) == longest).collect();
+    Some(match matches.as_slice() {
+        [qn] => Some((*qn).clone()),
+        _ => None,
+    })
+}
+
+/// Simple name a call invokes, e.g. "Get" for "s.store.Get"
+fn callee_of(call: &CallSite) -> &str {
+    if !call.callee_name.is_empty() {
+        return &call.callee_name;
+    }
+    // Indexes written before callee_name was recorded
+    call.raw.rsplit(['.', ':']).next().unwrap_or(&call.raw)
+}
+
+/// The C function a Go call like `C.malloc` invokes, in a file that imports "C" for cgo
+fn cgo_callee<'a>(call: &'a CallSite, caller: &Caller) -> Option<&'a str> {
+    let is_cgo = caller.lang == Language::Go
+        && call.receiver_expr.as_deref() == Some("C")
+        && caller.imports.iter().any(|import| import.path == "C");
+    is_cgo.then(|| callee_of(call))
+}
+
+/// Bare type name from declared type text
+/// e.g., "*Server" -> "Server", "&mut store::Store<T>" -> "Store", "*db.Conn" -> "Conn"
+fn base_type_name(type_text: &str) -> Option<&str> {
+    let mut t = type_text.trim().trim_start_matches(['*', '&']);
+    t = t.strip_prefix("mut ").unwrap_or(t).trim_start();
+    let t = t.split(['<', '[']).next().unwrap_or(t);
+    let name = t.rsplit(['.', ':']).next().unwrap_or(t);
+    let is_ident = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    is_ident.then_some(name)
+}
+
+/// Result type of a Go signature ("func New(addr string) *Client" -> "*Client"), when
+/// the function returns exactly one unnamed value
+fn go_result_type(signature: &str) -> Option<&str> {
+    let start = signature.find('(')?;
+    let mut depth = 0;
+    for (i, c) in signature[start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            let result = signature[start + i + 1..].trim();
+            return (!result.is_empty() && !result.starts_with('(')).then_some(result);
+        }
+    }
+    None
+}
+
+/// Return type of a Rust signature ("fn new() -> Self" -> "Self"), when it is written
+/// after the parameters rather than inside a closure type among them
+fn rust_result_type(signature: &str) -> Option<&str> {
+    let (_, result) = signature.rsplit_once(" -> ")?;
+    let result = result.trim();
+    (!result.contains(')')).then_some(result)
+}
+
+/// Extract package prefix from qualified name
+/// e.g., "internal/foo/bar.Func" -> "internal/foo/bar"
+/// e.g., "main.Foo" -> "main"
+/// e.g., "pkg.Type.Method" -> "pkg"
+fn extract_package(qualified_name: &str) -> String {
+    // Find the last component that's a path or package name (before any type/function names)
+    // The pattern is: path/segments.TypeOrFunc or path/segments.Type.Method
+    if let Some(dot_pos) = qualified_name.rfind('.') {
+        // Check if there's a type prefix (path/pkg.Type.Method)
+        let prefix = &qualified_name[..dot_pos];
+        if let Some(second_dot) = prefix.rfind('.') {
+            // Could be path/pkg.Type - take everything before second dot
+            // But only if what's after second dot starts with uppercase (a type)
+            let potential_type = &prefix[second_dot + 1..];
+            if potential_type.chars().next().is_some_and(|c| c.is_uppercase()) {
+                return prefix[..second_dot].to_string();
+            }
+        }
+        prefix.to_string()
+    } else {
+        qualified_name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Function;
+
+    fn make_function(name: &str, qualified: &str, calls: Vec<CallSite>) -> Function {
+        Function {
+            name: name.to_string(),
+            qualified_name: qualified.to_string(),
+            ast_hash: "0000000000000000".to_string(),
+            line_start: 1,
+            line_end: 10,
+            signature: format!("func {}()", name),
+            calls,
+            ..Default::default()
+        }
+    }
+
+    fn make_call(raw: &str) -> CallSite {
+        let (receiver_expr, callee_name) = match raw.rsplit_once('.') {
+            Some((recv, name)) => (Some(recv.to_string()), name.to_string()),
+            None => (None, raw.to_string()),
+        };
+        CallSite {
+            target: "[unresolved]".to_string(),
+            raw: raw.to_string(),
+            callee_name,
+            receiver_expr,
+            line: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_same_package_call() {
+        let mut index = Index::new();
+
+        // Using path-based qualified names: cmd/app.foo
+        let foo = make_function("foo", "cmd/app.foo", vec![]);
+        let bar = make_function("bar", "cmd/app.bar", vec![make_call("foo")]);
+
+        index.files.insert(
+            "./cmd/app/main.go".to_string(),
+            FileEntry {
+                ast_hash: "abc".to_string(),
+                functions: vec![foo, bar],
+                ..Default::default()
+            },
+        );
+
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let entry = index.files.get("./cmd/app/main.go").unwrap();
+        let bar = entry.functions.iter().find(|f| f.name == "bar").unwrap();
+        assert_eq!(bar.calls[0].target, "cmd/app.foo");
+
+        let foo = entry.functions.iter().find(|f| f.name == "foo").unwrap();
+        assert_eq!(foo.called_by, vec!["cmd/app.bar"]);
+    }
+
+    #[test]
+    fn test_resolve_cross_package_call() {
+        let mut index = Index::new();
+
+        // Without imports recorded, a bare call falls back to the globally unique name
+        let helper = make_function("Helper", "internal/utils.Helper", vec![]);
+        let main_fn = make_function("main", "cmd/app.main", vec![make_call("Helper")]);
+
+        index.files.insert(
+            "./internal/utils/helper.go".to_string(),
+            FileEntry {
+                ast_hash: "abc".to_string(),
+                functions: vec![helper],
+                ..Default::default()
+            },
+        );
+        index.files.insert(
+            "./cmd/app/main.go".to_string(),
+            FileEntry {
+                ast_hash: "def".to_string(),
+                functions: vec![main_fn],
+                ..Default::default()
+            },
+        );
+
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let entry = index.files.get("./cmd/app/main.go").unwrap();
+        let main_fn = entry.functions.iter().find(|f| f.name == "main").unwrap();
+        // Should resolve via simple name lookup since Helper is unique
+        assert_eq!(main_fn.calls[0].target, "internal/utils.Helper");
+    }
+
+    #[test]
+    fn test_unresolved_external_call() {
+        let mut index = Index::new();
+
+        let main_fn = make_function("main", "cmd/app.main", vec![make_call("fmt.Println")]);
+
+        index.files.insert(
+            "./cmd/app/main.go".to_string(),
+            FileEntry {
+                ast_hash: "abc".to_string(),
+                functions: vec![main_fn],
+                ..Default::default()
+            },
+        );
+
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let entry = index.files.get("./cmd/app/main.go").unwrap();
+        let main_fn = entry.functions.iter().find(|f| f.name == "main").unwrap();
+        // fmt.Println is external, should be categorized
+        assert_eq!(main_fn.calls[0].target, "[external:fmt.Println]");
+
+        // Should be tracked in externals
+        assert!(index.externals.contains_key("fmt.Println"));
+        assert_eq!(index.externals.get("fmt.Println").unwrap().kind, "external");
+    }
+
+    #[test]
+    fn test_resolve_param_method_call() {
+        let mut index = Index::new();
+
+        // Two Get methods make "Get" and the raw "st.Get" ambiguous without type info
+        let mut store_get = make_function("Get", "internal/store.Store.Get", vec![]);
+        store_get.receiver = Some("Store".to_string());
+        let mut cache_get = make_function("Get", "internal/cache.Cache.Get", vec![]);
+        cache_get.receiver = Some("Cache".to_string());
+
+        let mut handler = make_function("handle", "cmd/app.handle", vec![make_call("st.Get"), make_call("c.Get")]);
+        handler.param_types.insert("st".to_string(), "*store.Store".to_string());
+
+        index.files.insert(
+            "./internal/store/store.go".to_string(),
+            FileEntry {
+                ast_hash: "abc".to_string(),
+                functions: vec![store_get],
+                ..Default::default()
+            },
+        );
+        index.files.insert(
+            "./internal/cache/cache.go".to_string(),
+            FileEntry {
+                ast_hash: "def".to_string(),
+                functions: vec![cache_get],
+                ..Default::default()
+            },
+        );
+        index.files.insert(
+            "./cmd/app/main.go".to_string(),
+            FileEntry {
+                ast_hash: "ghi".to_string(),
+                functions: vec![handler],
+                ..Default::default()
+            },
+        );
+
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let entry = index.files.get("./cmd/app/main.go").unwrap();
+        let handler = &entry.functions[0];
+        assert_eq!(handler.calls[0].target, "internal/store.Store.Get");
+        // No declared type for "c", so it stays unresolved
+        assert!(handler.calls[1].target.starts_with("[external:"));
+    }
+
+    #[test]
+    fn test_resolve_local_variable_method_call() {
+        let mut index = Index::new();
+
+        let mut store_get = make_function("Get", "internal/store.Store.Get", vec![]);
+        store_get.receiver = Some("Store".to_string());
+        let mut cache_get = make_function("Get", "internal/cache.Cache.Get", vec![]);
+        cache_get.receiver = Some("Cache".to_string());
+        let mut new_store = make_function("New", "internal/store.New", vec![]);
+        new_store.signature = "func New(path string) *Store".to_string();
+        let mut open = make_function("Open", "internal/store.Open", vec![]);
+        open.signature = "func Open(path string) (*Store, error)".to_string();
+
+        let mut handler = make_function(
+            "handle",
+            "cmd/app.handle",
+            vec![make_call("st.Get"), make_call("c.Get"), make_call("o.Get"), make_call("again.Get")],
+        );
+        for (local, type_text) in [("st", "store.New()"), ("c", "&cache.Cache"), ("o", "store.Open()")] {
+            handler.local_types.insert(local.to_string(), type_text.to_string());
+        }
+
+        let entry = |functions| FileEntry {
+            functions,
+            ..Default::default()
+        };
+        index.files.insert("./internal/store/store.go".to_string(), entry(vec![store_get, new_store, open]));
+        index.files.insert("./internal/cache/cache.go".to_string(), entry(vec![cache_get]));
+        index.files.insert("./cmd/app/main.go".to_string(), entry(vec![handler]));
+
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let calls = &index.files["./cmd/app/main.go"].functions[0].calls;
+        // Constructor with a single return type
+        assert_eq!(calls[0].target, "internal/store.Store.Get");
+        assert_eq!(calls[0].resolved_by.as_deref(), Some(RESOLVED_BY_LOCAL_TYPE));
+        // Composite literal
+        assert_eq!(calls[1].target, "internal/cache.Cache.Get");
+        assert_eq!(calls[1].resolved_by.as_deref(), Some(RESOLVED_BY_LOCAL_TYPE));
+        // Multiple return values, and a local the parser dropped (e.g. reassigned): unresolved
+        for call in &calls[2..] {
+            assert!(call.target.starts_with("[external:"), "{}", call.target);
+            assert_eq!(call.resolved_by, None);
+        }
+        let store_get = &index.files["./internal/store/store.go"].functions[0];
+        assert_eq!(store_get.called_by, vec!["cmd/app.handle"]);
+    }
+
+    #[test]
+    fn test_go_result_type() {
+        assert_eq!(go_result_type("func New(addr string) *Client"), Some("*Client"));
+        assert_eq!(go_result_type("func Wrap(f func(int) error) Handler"), Some("Handler"));
+        assert_eq!(go_result_type("func Open() (*DB, error)"), None);
+        assert_eq!(go_result_type("func Close()"), None);
+    }
+
+    #[test]
+    fn test_base_type_name() {
+        assert_eq!(base_type_name("*Server"), Some("Server"));
+        assert_eq!(base_type_name("*db.Conn"), Some("Conn"));
+        assert_eq!(base_type_name("&mut store::Store<T>"), Some("Store"));
+        assert_eq!(base_type_name("Cache[K, V]"), Some("Cache"));
+        assert_eq!(base_type_name("func(int) error"), None);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_ambiguous_call_feeds_possibly_called_by() {
+        let mut index = Index::new();
+
+        // "helper" exists in two other packages, so the call from main can't be confirmed
+        let helper_a = make_function("helper", "pkg/a.helper", vec![]);
+        let helper_b = make_function("helper", "pkg/b.helper", vec![]);
+        let only = make_function("only", "pkg/b.only", vec![]);
+        let main_fn = make_function("main", "cmd/app.main", vec![make_call("helper"), make_call("only")]);
+
+        for (path, func) in [
+            ("./pkg/a/a.go", helper_a),
+            ("./pkg/b/b.go", helper_b),
+            ("./pkg/b/only.go", only),
+            ("./cmd/app/main.go", main_fn),
+        ] {
+            index.files.insert(
+                path.to_string(),
+                FileEntry {
+                    ast_hash: path.to_string(),
+                    functions: vec![func],
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let main_fn = &index.files["./cmd/app/main.go"].functions[0];
+        assert_eq!(main_fn.calls[0].target, "[ambiguous:helper]");
+        assert_eq!(main_fn.calls[0].candidates, vec!["pkg/a.helper", "pkg/b.helper"]);
+        assert_eq!(main_fn.calls[1].target, "pkg/b.only");
+
+        for path in ["./pkg/a/a.go", "./pkg/b/b.go"] {
+            let helper = &index.files[path].functions[0];
+            assert!(helper.called_by.is_empty());
+            assert_eq!(helper.possibly_called_by, vec!["cmd/app.main"]);
+        }
+        let only = &index.files["./pkg/b/only.go"].functions[0];
+        assert_eq!(only.called_by, vec!["cmd/app.main"]);
+        assert!(only.possibly_called_by.is_empty());
+
+        // Ambiguous calls are not externals
+        assert!(!index.externals.contains_key("helper"));
+    }
+
+    /// Parse and fully resolve a set of Go files from scratch
+    #[cfg(feature = "lang-go")]
+    fn index_from_sources(sources: &BTreeMap<&str, &str>) -> Index {
+        let mut parser = crate::parser::GoParser::new();
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+        index
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_imports() {
+        let sources = BTreeMap::from([
+            ("./internal/utils/utils.go", "package utils\nfunc Helper() {}\nfunc Only() {}\n"),
+            ("./pkg/utils/utils.go", "package utils\nfunc Helper() {}\n"),
+            ("./internal/other/other.go", "package other\nfunc Helper() {}\nfunc Only() {}\n"),
+            (
+                "./cmd/app/main.go",
+                "package main\n\nimport (\n\t\"fmt\"\n\t\"example.com/app/internal/utils\"\n\to \"example.com/app/internal/other\"\n\t. \"example.com/app/pkg/utils\"\n)\n\n\
+                 func main() { utils.Helper(); o.Helper(); Helper(); fmt.Println(); utils.Missing() }\n",
+            ),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let main_fn = &index.files["./cmd/app/main.go"].functions[0];
+        let targets: Vec<&str> = main_fn.calls.iter().map(|c| c.target.as_str()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                "internal/utils.Helper",
+                "internal/other.Helper",
+                "pkg/utils.Helper",
+                "[external:fmt.Println]",
+                "[external:utils.Missing]"
+            ]
+        );
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_var_func_literals() {
+        let sources = BTreeMap::from([(
+            "./web/web.go",
+            "package web\nvar handler = func() { doWork() }\nvar routes = map[string]func(){\"x\": doX}\nfunc doWork() {}\nfunc doX() {}\n",
+        )]);
+        let index = index_from_sources(&sources);
+
+        let called_by = |name: &str| {
+            let entry = &index.files["./web/web.go"];
+            entry.functions.iter().find(|f| f.name == name).unwrap().called_by.clone()
+        };
+        assert_eq!(called_by("doWork"), vec!["web.handler"]);
+        assert_eq!(called_by("doX"), vec!["web.routes"]);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_incremental_resolve_matches_full_resolve() {
+        // Each file cycles through variants that add, remove, duplicate, and rename
+        // functions so that edges in untouched files gain and lose targets
+        let variants: &[(&str, &[Option<&str>])] = &[
+            ("./a/a.go", &[
+                Some("package a\nfunc Helper() {}\nfunc Run() { Helper(); Shared(); fmt.Println() }\n"),
+                Some("package a\nfunc Run() { Helper(); Shared() }\n"),
+                Some("package a\nfunc Shared() {}\nfunc Run() { Only() }\n"),
+                None,
+            ]),
+            ("./b/b.go", &[
+                Some("package b\nfunc Shared() { Helper() }\n"),
+                Some("package b\nfunc Helper() {}\nfunc Only() { Shared() }\n"),
+                Some("package b\ntype S struct{}\nfunc (s *S) Shared() { s.Only() }\nfunc (s *S) Only() {}\n"),
+                None,
+            ]),
+            ("./c/c.go", &[
+                Some("package c\nfunc Main() { Helper(); Only(); Run(); os.Exit(1) }\n"),
+                Some("package c\nfunc Main(s *S) { s.Shared(); Missing() }\n"),
+                None,
+            ]),
+            // Changing what Server embeds, directly or through a chain, moves x.Log() in run.go
+            ("./s/base.go", &[
+                Some("package s\ntype A struct{}\ntype B struct{}\nfunc (a *A) Log() {}\nfunc (b *B) Log() {}\n"),
+                Some("package s\ntype A struct{ *B }\ntype B struct{}\nfunc (b *B) Log() {}\n"),
+            ]),
+            ("./s/server.go", &[
+                Some("package s\ntype Server struct{ *A }\n"),
+                Some("package s\ntype Server struct{ *B }\n"),
+                Some("package s\ntype Server struct{ Mid }\ntype Mid struct{ *A }\n"),
+                None,
+            ]),
+            ("./s/run.go", &[Some("package s\nfunc Run(x *Server) { x.Log() }\n")]),
+        ];
+
+        let mut current: BTreeMap<&str, &str> = BTreeMap::new();
+        for (path, options) in variants {
+            current.insert(path, options[0].unwrap());
+        }
+        let mut incremental = index_from_sources(&current);
+        let mut parser = crate::parser::GoParser::new();
+
+        // Deterministic LCG so failures are reproducible
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: usize| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound
+        };
+
+        for step in 0..200 {
+            let (path, options) = variants[next(variants.len())];
+            let choice = options[next(options.len())];
+
+            let previous = incremental.clone();
+            match choice {
+                Some(source) => {
+                    current.insert(path, source);
+                    incremental.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+                }
+                None => {
+                    current.remove(path);
+                    incremental.files.remove(path);
+                }
+            }
+
+            let mut resolver = Resolver::new();
+            resolver.build_symbol_table(&incremental.files);
+            resolver.resolve_changed(&mut incremental, &previous, &HashSet::from([path.to_string()]));
+
+            let full = index_from_sources(&current);
+            assert_eq!(
+                serde_json::to_value(&incremental.files).unwrap(),
+                serde_json::to_value(&full.files).unwrap(),
+                "files differ after step {step} ({path})"
+            );
+            assert_eq!(
+                serde_json::to_value(&incremental.externals).unwrap(),
+                serde_json::to_value(&full.externals).unwrap(),
+                "externals differ after step {step} ({path})"
+            );
+        }
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_resolve_type_uses() {
+        let mut parser = crate::parser::RustParser::new();
+        let sources = [
+            ("./src/index.rs", "pub struct FileEntry {}\npub struct Index {}\nimpl Index {\n    pub fn new() -> Self { Index {} }\n}\n"),
+            ("./src/other.rs", "pub struct FileEntry {}\n"),
+            (
+                "./src/commands/build.rs",
+                "use crate::index::{FileEntry, Index};\nfn build(entry: &index::FileEntry) -> Vec<Index> {\n    let index = Index::new();\n    let e = crate::index::FileEntry {};\n    vec![]\n}\nfn local() -> Option<Unknown> { None }\n",
+            ),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let build = &index.files["./src/commands/build.rs"].functions[0];
+        assert_eq!(build.uses_types, vec!["index::FileEntry", "index::Index"]);
+        let constructed: Vec<(&str, u32)> =
+            build.type_refs.iter().filter(|r| r.constructs).map(|r| (r.name.as_str(), r.line)).collect();
+        assert_eq!(constructed, vec![("Index", 3), ("crate::index::FileEntry", 4)]);
+
+        // Self resolves to the impl type
+        let new_fn = &index.files["./src/index.rs"].functions[0];
+        assert_eq!(new_fn.uses_types, vec!["index::Index"]);
+
+        assert!(index.files["./src/commands/build.rs"].functions[1].uses_types.is_empty());
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_type_uses_through_import_aliases() {
+        let sources = BTreeMap::from([
+            ("./internal/store/store.go", "package store\ntype Getter interface { Get() }\n"),
+            ("./pkg/store/store.go", "package store\ntype Getter interface { Get() }\n"),
+            (
+                "./cmd/app/main.go",
+                "package main\n\nimport (\n\tst \"example.com/app/internal/store\"\n\t\"example.com/app/pkg/store\"\n)\n\n\
+                 func aliased(g st.Getter) {}\nfunc plain(g store.Getter) {}\nfunc missing(g st.Putter) {}\n",
+            ),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let functions = &index.files["./cmd/app/main.go"].functions;
+        assert_eq!(functions[0].uses_types, vec!["internal/store.Getter"]);
+        assert_eq!(functions[1].uses_types, vec!["pkg/store.Getter"]);
+        assert!(functions[2].uses_types.is_empty());
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_go_composite_literal_type_use() {
+        let sources = BTreeMap::from([
+            ("./store/store.go", "package store\ntype Entry struct{}\nfunc Make(n int) *Entry { return &Entry{} }\n"),
+            ("./cmd/app/main.go", "package main\nfunc main() { e := store.Entry{}; use(e) }\nfunc show(e []*store.Entry) {}\n"),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let make = &index.files["./store/store.go"].functions[0];
+        assert_eq!(make.uses_types, vec!["store.Entry"]);
+        assert!(make.type_refs.iter().any(|r| r.constructs && r.line == 3));
+
+        let main_fns = &index.files["./cmd/app/main.go"].functions;
+        assert_eq!(main_fns[0].uses_types, vec!["store.Entry"]);
+        assert!(main_fns[0].type_refs[0].constructs);
+        assert_eq!(main_fns[1].uses_types, vec!["store.Entry"]);
+        assert!(!main_fns[1].type_refs[0].constructs);
+    }
+
+    #[cfg(all(feature = "lang-go", feature = "lang-c"))]
+    #[test]
+    fn test_resolve_global_uses() {
+        let sources = BTreeMap::from([
+            ("./config/vars.go", "package config
+var Default = Config{}
+var hits int
+"),
+            ("./config/load.go", "package config
+func Load() {
+	hits++
+	use(Default.Port)
+}
+"),
+            ("./app/app.go", "package app
+func Start() {
+	config.Default.Port = 1
+	hits = 2
+}
+"),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let load = &index.files["./config/load.go"].functions[0];
+        assert_eq!(load.reads_globals, vec!["config.Default"]);
+        assert_eq!(load.writes_globals, vec!["config.hits"]);
+
+        // A bare name from another Go package is not that package's variable
+        let start = &index.files["./app/app.go"].functions[0];
+        assert!(start.reads_globals.is_empty());
+        assert_eq!(start.writes_globals, vec!["config.Default"]);
+
+        // A C static is only visible in its own file
+        let mut parser = crate::parser::CParser::new();
+        let mut index = Index::new();
+        for (path, source) in [
+            ("./a.c", "static int total;
+int shared;
+void add(void) { total++; }
+"),
+            ("./b.c", "void other(void) { total = 1; shared = 2; }
+"),
+        ] {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+        assert_eq!(index.files["./a.c"].functions[0].writes_globals, vec!["a.total"]);
+        assert_eq!(index.files["./b.c"].functions[0].writes_globals, vec!["a.shared"]);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_receiver_variable_method_call() {
+        let sources = BTreeMap::from([
+            (
+                "./server/server.go",
+                "package server\ntype Server struct{}\nfunc (srv *Server) Start() error { srv.init(); return nil }\nfunc (srv *Server) init() {}\n",
+            ),
+            ("./worker/worker.go", "package worker\ntype Worker struct{}\nfunc (w *Worker) init() {}\n"),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let start = &index.files["./server/server.go"].functions[0];
+        assert_eq!(start.calls[0].target, "server.Server.init");
+    }
+
+    #[test]
+    fn test_resolve_receiver_without_param_types() {
+        // Entries from indexes written before param_types was recorded
+        let mut start = make_function("Start", "server.Server.Start", vec![make_call("self_.init"), make_call("other.init")]);
+        start.receiver = Some("Server".to_string());
+        start.calls[0].receiver_expr = Some("self_".to_string());
+        start.calls[0].callee_name = "init".to_string();
+        start.calls[1].receiver_expr = Some("other".to_string());
+        start.calls[1].callee_name = "init".to_string();
+        let mut init = make_function("init", "server.Server.init", vec![]);
+        init.receiver = Some("Server".to_string());
+        let mut worker_init = make_function("init", "worker.Worker.init", vec![]);
+        worker_init.receiver = Some("Worker".to_string());
+
+        let mut index = Index::new();
+        for (path, functions) in [("./server/server.go", vec![start, init]), ("./worker/worker.go", vec![worker_init])] {
+            index.files.insert(
+                path.to_string(),
+                FileEntry {
+                    functions,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let start = &index.files["./server/server.go"].functions[0];
+        assert_eq!(start.calls[0].target, "server.Server.init");
+
+        // With the receiver recorded, other identifiers are not assumed to be it
+        let mut index = index.clone();
+        let start = &mut index.files.get_mut("./server/server.go").unwrap().functions[0];
+        start.param_types.insert("s".to_string(), "*Server".to_string());
+        start.calls[0].receiver_expr = Some("other".to_string());
+        resolver.resolve(&mut index);
+        assert!(!index.files["./server/server.go"].functions[0].calls[0].target.starts_with("server."));
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_resolve_rust_trait_impls() {
+        let mut parser = crate::parser::RustParser::new();
+        let sources = [
+            ("./src/handler.rs", "pub trait Handler {\n    fn handle(&self);\n}\n"),
+            (
+                "./src/server.rs",
+                "pub struct Server {}\nimpl crate::handler::Handler for Server {\n    fn handle(&self) {}\n}\nimpl fmt::Display for Server {\n    fn fmt(&self) {}\n}\nmod inner {\n    impl<T: Clone> super::Handler for T {\n        fn handle(&self) {}\n    }\n}\n",
+            ),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let handler = &index.files["./src/handler.rs"].types[0];
+        let by: Vec<(&str, u32, bool)> =
+            handler.implemented_by.iter().map(|l| (l.name.as_str(), l.line, l.unresolved)).collect();
+        assert_eq!(by, vec![("T", 9, true), ("server::Server", 2, false)]);
+
+        let server = &index.files["./src/server.rs"].types[0];
+        let implements: Vec<(&str, bool)> = server.implements.iter().map(|l| (l.name.as_str(), l.unresolved)).collect();
+        assert_eq!(implements, vec![("fmt::Display", true), ("handler::Handler", false)]);
+
+        // Trait methods lead to every implementation, whatever path the impl names the trait by
+        let impls: Vec<&str> =
+            crate::index::find_functions(&index, "Handler::handle").iter().map(|(_, f)| f.qualified_name.as_str()).collect();
+        assert_eq!(impls, vec!["server::Server::handle", "server::inner::T::handle"]);
+        let lines = crate::commands::types::render(&index, "./src/handler.rs", handler);
+        assert!(lines.contains(&"    handle: server::Server::handle, server::inner::T::handle".to_string()), "{lines:?}");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_interface_implementations() {
+        let sources = BTreeMap::from([
+            ("./store/store.go", "package store\ntype Store interface {\n\tGet(k string) string\n\tPut(k, v string)\n}\ntype Empty interface{}\n"),
+            (
+                "./mem/mem.go",
+                "package mem\ntype Mem struct{}\nfunc (m *Mem) Get(k string) string { return \"\" }\nfunc (m *Mem) Put(k, v string) {}\ntype ReadOnly struct{}\nfunc (r ReadOnly) Get(k string) string { return \"\" }\n",
+            ),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let store = &index.files["./store/store.go"].types[0];
+        assert_eq!(store.methods, vec!["Get", "Put"]);
+        let by: Vec<(&str, &str, u32)> =
+            store.implemented_by.iter().map(|l| (l.name.as_str(), l.file.as_str(), l.line)).collect();
+        assert_eq!(by, vec![("mem.Mem", "./mem/mem.go", 2)]);
+
+        // Every type satisfies an empty interface, so it is not linked
+        assert!(index.files["./store/store.go"].types[1].implemented_by.is_empty());
+
+        let mem = &index.files["./mem/mem.go"].types[0];
+        assert_eq!(mem.implements.len(), 1);
+        assert_eq!(mem.implements[0].name, "store.Store");
+        assert!(index.files["./mem/mem.go"].types[1].implements.is_empty());
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_promoted_methods() {
+        let sources = BTreeMap::from([
+            (
+                "./server/base.go",
+                "package server\ntype BaseHandler struct{}\nfunc (b *BaseHandler) Log(msg string) {}\n\
+                 type Reader struct{}\nfunc (r Reader) Close() {}\ntype Writer struct{}\nfunc (w Writer) Close() {}\n",
+            ),
+            (
+                "./server/server.go",
+                "package server\ntype Server struct {\n\t*BaseHandler\n\tname string\n}\ntype App struct {\n\tServer\n}\n\
+                 type Pipe struct {\n\tReader\n\tWriter\n}\ntype Loop struct {\n\t*Loop\n}\n\
+                 func (s *Server) Start() { s.Log(\"start\") }\n\
+                 func Run(a *App, p Pipe, l *Loop) {\n\ta.Log(\"run\")\n\tp.Close()\n\tl.Spin()\n}\n",
+            ),
+        ]);
+        let index = index_from_sources(&sources);
+        let functions = &index.files["./server/server.go"].functions;
+
+        // One level: Server embeds *BaseHandler
+        assert_eq!(functions[0].calls[0].target, "server.BaseHandler.Log");
+        // Two levels: App embeds Server, which embeds *BaseHandler
+        assert_eq!(functions[1].calls[0].target, "server.BaseHandler.Log");
+        // Reader and Writer both promote Close: ambiguous, so neither is confirmed
+        assert!(!functions[1].calls[1].target.starts_with("server."));
+        assert_eq!(functions[1].calls[1].candidates, vec!["server.Reader.Close", "server.Writer.Close"]);
+        // A struct embedding itself does not loop
+        assert!(!functions[1].calls[2].target.starts_with("server."));
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_build_constrained_variants() {
+        let sources = BTreeMap::from([
+            ("./events/poll_linux.go", "package events\nfunc readEvents() {}\nfunc Poll() { readEvents() }\n"),
+            ("./events/poll_darwin.go", "package events\nfunc readEvents() {}\nfunc Poll() { readEvents() }\n"),
+            ("./events/watch.go", "package events\nfunc Watch() { readEvents() }\n"),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let linux_poll = &index.files["./events/poll_linux.go"].functions[1];
+        assert_eq!(linux_poll.qualified_name, "events.Poll@linux");
+        assert_eq!(linux_poll.calls[0].target, "events.readEvents@linux");
+
+        // Unconstrained code may call either variant
+        let watch = &index.files["./events/watch.go"].functions[0];
+        assert_eq!(watch.calls[0].target, "[ambiguous:readEvents]");
+        assert_eq!(watch.calls[0].candidates, vec!["events.readEvents@darwin", "events.readEvents@linux"]);
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_function_references() {
+        let sources = BTreeMap::from([
+            (
+                "./web/web.go",
+                "package web\nimport \"net/http\"\n\
+                 type Route struct { Handle func() }\n\
+                 func handleX() {}\nfunc handleY() {}\nfunc process() {}\nfunc worker(f func()) { f() }\n\
+                 func Serve(mux *http.ServeMux) {\n\
+                 \thttp.HandleFunc(\"/x\", handleX)\n\
+                 \tgo worker(process)\n\
+                 \troutes := []Route{{Handle: handleY}}\n\
+                 \ttable := map[string]func(){\"x\": handleX}\n\
+                 \tvar count int\n\
+                 \tfmt.Println(routes, table, count, nil)\n\
+                 }\n",
+            ),
+        ]);
+        let index = index_from_sources(&sources);
+        let funcs = &index.files["./web/web.go"].functions;
+
+        let resolved: Vec<(&str, u32)> = funcs[4]
+            .references
+            .iter()
+            .filter(|r| !r.target.starts_with('['))
+            .map(|r| (r.target.as_str(), r.line))
+            .collect();
+        assert_eq!(resolved, vec![("web.handleX", 9), ("web.process", 10), ("web.handleY", 11), ("web.handleX", 12)]);
+        // Locals and unindexed names never become references to indexed functions
+        assert!(funcs[4].references.iter().all(|r| !matches!(r.raw.as_str(), "routes" | "count" | "nil")));
+
+        for func in &funcs[..4] {
+            assert_eq!(func.called_by, vec!["web.Serve"], "{}", func.qualified_name);
+        }
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_cgo_calls() {
+        let sources = BTreeMap::from([
+            (
+                "./native/alloc.go",
+                "package native\nimport \"C\"\nfunc Alloc(n int) { p := C.malloc(n); C.free(p); C.my_helper() }\n",
+            ),
+            ("./app/app.go", "package app\nfunc Run(c Conn) { c.malloc() }\n"),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let targets: Vec<&str> =
+            index.files["./native/alloc.go"].functions[0].calls.iter().map(|c| c.target.as_str()).collect();
+        assert_eq!(targets, vec!["[libc:malloc]", "[libc:free]", "[external:my_helper]"]);
+        assert_eq!(index.externals["malloc"].kind, "libc");
+        assert!(index.externals["malloc"].summary.is_some());
+        // Without import "C", a receiver's method stays a Go external
+        assert_eq!(index.files["./app/app.go"].functions[0].calls[0].target, "[external:c.malloc]");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_resolve_go_interface_method_calls() {
+        let sources = BTreeMap::from([
+            ("./store/store.go", "package store\ntype Store interface {\n\tSave(k string)\n\tLoad(k string) string\n}\n"),
+            (
+                "./pg/pg.go",
+                "package pg\ntype PgStore struct{}\nfunc (p *PgStore) Save(k string) {}\nfunc (p *PgStore) Load(k string) string { return \"\" }\n",
+            ),
+            (
+                "./mem/mem.go",
+                "package mem\ntype MemStore struct{}\nfunc (m *MemStore) Save(k string) {}\nfunc (m *MemStore) Load(k string) string { return \"\" }\n\
+                 type Log struct{}\nfunc (l Log) Save(k string) {}\n",
+            ),
+            (
+                "./app/app.go",
+                "package app\nimport \"example.com/store\"\nfunc Run(s store.Store, other Unknown) {\n\ts.Save(\"k\")\n\tother.Load(\"k\")\n\tother.Missing()\n}\n",
+            ),
+        ]);
+        let index = index_from_sources(&sources);
+
+        let run = &index.files["./app/app.go"].functions[0];
+        // The declared interface picks Store even though Log has a Save too
+        assert_eq!(run.calls[0].target, "[iface:Store.Save]");
+        assert_eq!(run.calls[0].candidates, vec!["mem.MemStore.Save", "pg.PgStore.Save"]);
+        // Only Store declares Load, so the receiver's type does not matter
+        assert_eq!(run.calls[1].target, "[iface:Store.Load]");
+        assert_eq!(run.calls[1].candidates, vec!["mem.MemStore.Load", "pg.PgStore.Load"]);
+        assert!(run.calls[2].candidates.is_empty());
+
+        let pg_save = &index.files["./pg/pg.go"].functions[0];
+        assert!(pg_save.called_by.is_empty());
+        assert_eq!(pg_save.possibly_called_by, vec!["app.Run"]);
+        assert!(index.files["./mem/mem.go"].functions[2].possibly_called_by.is_empty());
+        assert!(!index.externals.contains_key("s.Save"));
+    }
+
+    #[cfg(feature = "lang-python")]
+    #[test]
+    fn test_resolve_python_calls() {
+        let mut parser = crate::parser::PythonParser::new();
+        let sources = [
+            (
+                "./pkg/store.py",
+                "def load(path):\n    data = read(path)\n    return len(data)\n\n\
+                 def read(path):\n    return open(path)\n\n\
+                 class Store:\n    def check(self):\n        return self._valid()\n\n    def _valid(self):\n        return True\n",
+            ),
+            ("./pkg/other.py", "def read():\n    pass\n"),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let targets = |qualified: &str| -> Vec<String> {
+            let func = index.files["./pkg/store.py"].functions.iter().find(|f| f.qualified_name == qualified).unwrap();
+            func.calls.iter().map(|c| c.target.clone()).collect()
+        };
+
+        // Same-module calls win over a same-named function elsewhere; builtins are external
+        assert_eq!(targets("pkg/store.load"), vec!["pkg/store.read", "[external:len]"]);
+        assert_eq!(targets("pkg/store.read"), vec!["[external:open]"]);
+        // self is typed as the enclosing class
+        assert_eq!(targets("pkg/store.Store.check"), vec!["pkg/store.Store._valid"]);
+    }
+
+    #[cfg(feature = "lang-typescript")]
+    #[test]
+    fn test_resolve_typescript_calls() {
+        let mut parser = crate::parser::TypeScriptParser::new();
+        let sources = [
+            (
+                "./src/api/users.ts",
+                "export class UserService {\n  get(id: string) { return this.load(id); }\n  private load(id: string) { return format(id); }\n}\n\
+                 function format(id: string) { return id; }\n",
+            ),
+            ("./src/api/orders.ts", "function format(id: string) { return id; }\nexport function list() { return format(\"x\"); }\n"),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let targets = |file: &str, qualified: &str| -> Vec<String> {
+            let func = index.files[file].functions.iter().find(|f| f.qualified_name == qualified).unwrap();
+            func.calls.iter().map(|c| c.target.clone()).collect()
+        };
+
+        assert_eq!(targets("./src/api/users.ts", "api/users.UserService.get"), vec!["api/users.UserService.load"]);
+        // Each file's unexported format is only visible in that file
+        assert_eq!(targets("./src/api/users.ts", "api/users.UserService.load"), vec!["api/users.format"]);
+        assert_eq!(targets("./src/api/orders.ts", "api/orders.list"), vec!["api/orders.format"]);
+    }
+
+    #[cfg(feature = "lang-java")]
+    #[test]
+    fn test_resolve_java_calls() {
+        let mut parser = crate::parser::JavaParser::new();
+        let sources = [
+            (
+                "./svc/UserService.java",
+                "package svc;\nclass UserService {\n  void create(UserRepo repo) { check(); repo.save(); this.check(); }\n  private void check() {}\n}\n",
+            ),
+            ("./svc/UserRepo.java", "package svc;\nclass UserRepo {\n  void save() {}\n  void check() {}\n}\n"),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let create = &index.files["./svc/UserService.java"].functions[0];
+        let targets: Vec<&str> = create.calls.iter().map(|c| c.target.as_str()).collect();
+        // A bare call is a call on this, even though UserRepo also has a check
+        assert_eq!(targets, vec!["svc.UserService.check", "svc.UserRepo.save", "svc.UserService.check"]);
+    }
+
+    #[cfg(feature = "lang-cpp")]
+    #[test]
+    fn test_resolve_cpp_calls() {
+        let mut parser = crate::parser::CppParser::new();
+        let sources = [
+            (
+                "./net/server.cpp",
+                "namespace net {\nvoid helper() {}\nclass Server {\n  void check() {}\n  void start(Conn* conn) { check(); this->check(); conn->close(); helper(); util::log(); malloc(4); }\n};\n}\n",
+            ),
+            (
+                "./net/conn.cpp",
+                "namespace net {\nclass Conn { public: void close() {} void check() {} };\nnamespace util { void log() {} }\n}\n",
+            ),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index
+                .files
+                .insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let start = index.files["./net/server.cpp"]
+            .functions
+            .iter()
+            .find(|f| f.qualified_name == "net::Server::start")
+            .unwrap();
+        let targets: Vec<&str> = start.calls.iter().map(|c| c.target.as_str()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                "net::Server::check",
+                "net::Server::check",
+                "net::Conn::close",
+                "net::helper",
+                "net::util::log",
+                "[libc:malloc]"
+            ]
+        );
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_resolve_rust_method_calls() {
+        let mut parser = crate::parser::RustParser::new();
+        let sources = [
+            (
+                "./src/store.rs",
+                "pub struct Store {}\nimpl Store {\n    pub fn new() -> Self { Self::empty() }\n    fn empty() -> Self { Store {} }\n\
+                 pub fn save(&mut self, x: u32) { self.validate(x); self.flush(); }\n    fn validate(&self, x: u32) {}\n    fn flush(&self) {}\n}\n",
+            ),
+            (
+                "./src/app.rs",
+                "use crate::store::Store;\npub struct App {}\nimpl App {\n\
+                 pub fn run(&mut self, store: &mut Store) {\n\
+                 store.save(1);\n\
+                 let mut fresh = Store::new();\n\
+                 fresh.save(2);\n\
+                 let literal = Store {};\n\
+                 literal.flush();\n\
+                 let declared: Store = make();\n\
+                 declared.flush();\n\
+                 self.tick();\n\
+                 }\n    fn tick(&self) {}\n}\nfn make() -> Store { Store::new() }\n",
+            ),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let targets = |file: &str, qualified: &str| -> Vec<String> {
+            let func = index.files[file].functions.iter().find(|f| f.qualified_name == qualified).unwrap();
+            func.calls.iter().map(|c| c.target.clone()).collect()
+        };
+
+        // self.method() resolves against the impl type, Type::func() against module::Type::func
+        assert_eq!(targets("./src/store.rs", "store::Store::save"), vec!["store::Store::validate", "store::Store::flush"]);
+        assert_eq!(targets("./src/store.rs", "store::Store::new"), vec!["store::Store::empty"]);
+        assert_eq!(
+            targets("./src/app.rs", "app::App::run"),
+            vec![
+                "store::Store::save",
+                "store::Store::new",
+                "store::Store::save",
+                "store::Store::flush",
+                "app::make",
+                "store::Store::flush",
+                "app::App::tick",
+            ]
+        );
+        let run = index.files["./src/app.rs"].functions.iter().find(|f| f.name == "run").unwrap();
+        assert_eq!(run.local_types["fresh"], "Store::new()");
+        assert_eq!(run.calls[2].resolved_by.as_deref(), Some(RESOLVED_BY_LOCAL_TYPE));
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_resolve_rust_nested_functions() {
+        use crate::parser::LanguageParser;
+        let mut parser = crate::parser::RustParser::new();
+        parser.configure(&crate::config::IndexConfig { closure_min_lines: Some(3), ..Default::default() });
+        let source = "fn helper() {}\n\
+                      pub fn run(items: &[u32]) {\n    fn helper() { inner() }\n    fn inner() {}\n    items.iter().for_each(|x| {\n        helper();\n    });\n}\n";
+        let mut index = Index::new();
+        index.files.insert("./src/ops.rs".to_string(), parser.parse_file(source, "./src/ops.rs").unwrap());
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let functions = &index.files["./src/ops.rs"].functions;
+        let target = |qn: &str| functions.iter().find(|f| f.qualified_name == qn).unwrap().calls[0].target.clone();
+        // A function declared in a body shadows the module's own, in nested bodies too
+        assert_eq!(target("ops::run::helper"), "ops::run::inner");
+        assert_eq!(target("ops::run::{closure@L5}"), "ops::run::helper");
+        assert_eq!(functions[1].references[0].target, "ops::run::{closure@L5}");
+        assert!(functions[0].called_by.is_empty());
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_resolve_rust_trait_default_methods() {
+        let mut parser = crate::parser::RustParser::new();
+        let sources = [
+            (
+                "./src/handler.rs",
+                "pub trait Handler {\n    fn label(&self) -> String { String::new() }\n    fn describe(&self) -> String { self.label() }\n}\n",
+            ),
+            ("./src/server.rs", "pub fn show(s: &Server) {\n    Handler::describe(s);\n}\n"),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let handler = &index.files["./src/handler.rs"];
+        assert_eq!(handler.functions[1].qualified_name, "handler::Handler::describe");
+        assert_eq!(handler.functions[1].calls[0].target, "handler::Handler::label");
+        assert_eq!(handler.functions[0].called_by, vec!["handler::Handler::describe"]);
+
+        // A method called by path, as `Trait::method(x)`, still resolves
+        assert_eq!(index.files["./src/server.rs"].functions[0].calls[0].target, "handler::Handler::describe");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_resolve_rust_methods_and_associated_functions() {
+        let mut parser = crate::parser::RustParser::new();
+        let sources = [
+            ("./src/lru.rs", "pub struct Cache<T> { items: Vec<T> }\nimpl<T: Clone> Cache<T> {\n    pub fn get(&self, k: usize) -> T { self.items[k].clone() }\n}\n"),
+            ("./src/disk.rs", "pub struct Cache;\nimpl Cache<String> {\n    pub fn get() -> Self { Cache }\n}\n"),
+            ("./src/app.rs", "pub fn run(cache: &Cache<u32>) {\n    Cache::get();\n    cache.get(0);\n}\n"),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        // The same name on two types: the call's form decides which one is meant
+        let targets: Vec<&str> = index.files["./src/app.rs"].functions[0].calls.iter().map(|c| c.target.as_str()).collect();
+        assert_eq!(targets, vec!["disk::Cache::get", "lru::Cache::get"]);
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_resolve_rust_relative_paths() {
+        let mut parser = crate::parser::RustParser::new();
+        let sources = [
+            ("./src/main.rs", "fn main() {\n    commands::index::run();\n    super::main();\n}\n"),
+            ("./src/util.rs", "pub fn fmt() {}\n"),
+            ("./src/commands/helpers.rs", "pub fn parse() {}\n"),
+            (
+                "./src/commands/index.rs",
+                "pub fn run() {\n    crate::util::fmt();\n    super::helpers::parse();\n    self::local();\n    self::inner::deep();\n    Builder::new();\n}\n\
+                 fn local() {}\n\
+                 mod inner {\n    pub fn deep() { super::local(); super::super::helpers::parse(); }\n}\n\
+                 struct Builder;\nimpl Builder {\n    pub fn new() -> Self { Self::make() }\n    fn make() -> Self { Builder }\n}\n",
+            ),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let targets = |file: &str, qualified: &str| -> Vec<String> {
+            let func = index.files[file].functions.iter().find(|f| f.qualified_name == qualified).unwrap();
+            func.calls.iter().map(|c| c.target.clone()).collect()
+        };
+
+        assert_eq!(
+            targets("./src/commands/index.rs", "commands::index::run"),
+            vec![
+                "util::fmt",
+                "commands::helpers::parse",
+                "commands::index::local",
+                "commands::index::inner::deep",
+                "commands::index::Builder::new",
+            ]
+        );
+        assert_eq!(
+            targets("./src/commands/index.rs", "commands::index::inner::deep"),
+            vec!["commands::index::local", "commands::helpers::parse"]
+        );
+        assert_eq!(
+            targets("./src/commands/index.rs", "commands::index::Builder::new"),
+            vec!["commands::index::Builder::make"]
+        );
+
+        // Absolute paths resolve from the crate root; super:: cannot climb above it
+        let main_calls = targets("./src/main.rs", "crate::main");
+        assert_eq!(main_calls[0], "commands::index::run");
+        assert_ne!(main_calls[1], "crate::main");
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_unexported_functions_scoped_to_package() {
+        let sources = BTreeMap::from([
+            ("./a/a.go", "package a\nfunc parse() {}\nfunc (c *codec) encode() {}\n"),
+            ("./b/b.go", "package b\nfunc Run() {\n\tparse()\n}\nfunc parse() {}\n"),
+            ("./c/c.go", "package c\nfunc Load() {\n\tparse()\n\tc.encode()\n}\n"),
+        ]);
+        let index = index_from_sources(&sources);
+        let targets = |file: &str| -> Vec<(String, Vec<String>)> {
+            let calls = index.files[file].functions.iter().flat_map(|f| &f.calls);
+            calls.map(|c| (c.target.clone(), c.candidates.clone())).collect()
+        };
+
+        // a's parse no longer makes b's own call ambiguous
+        assert_eq!(targets("./b/b.go"), vec![("b.parse".to_string(), vec![])]);
+
+        // Neither unexported parse, nor a's unexported method, is callable from c
+        let load = targets("./c/c.go");
+        assert!(load.iter().all(|(target, candidates)| !target.starts_with("a.") && candidates.is_empty()));
+        assert!(!load[0].0.starts_with("b.") && !load[0].0.starts_with("[ambiguous"));
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_private_rust_functions_scoped_to_module_tree() {
+        let mut parser = crate::parser::RustParser::new();
+        let sources = [
+            ("./src/store.rs", "fn parse() {}\npub fn open() { parse(); }\nmod disk {\n    fn read() { parse(); }\n}\n"),
+            ("./src/config.rs", "pub fn load() { parse(); }\n"),
+            ("./src/cli/args.rs", "fn parse() {}\n"),
+            ("./src/cli/run.rs", "pub fn start() { parse(); }\n"),
+        ];
+        let mut index = Index::new();
+        for (path, source) in sources {
+            index.files.insert(path.to_string(), parser.parse_file(source, path).unwrap());
+        }
+        let mut resolver = Resolver::new();
+        resolver.build_symbol_table(&index.files);
+        resolver.resolve(&mut index);
+
+        let target = |file: &str, qualified: &str| -> String {
+            let func = index.files[file].functions.iter().find(|f| f.qualified_name == qualified).unwrap();
+            func.calls[0].target.clone()
+        };
+
+        // Visible from the defining module and its submodules
+        assert_eq!(target("./src/store.rs", "store::open"), "store::parse");
+        assert_eq!(target("./src/store.rs", "store::disk::read"), "store::parse");
+
+        // Private to sibling modules: not a candidate, and never ambiguous between the two
+        assert!(!target("./src/config.rs", "config::load").contains("::parse"));
+        assert!(!target("./src/cli/run.rs", "cli::run::start").starts_with("[ambiguous"));
     }
 }
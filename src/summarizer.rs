@@ -1,20 +1,98 @@
-use std::io::Write;
-use std::process::{Command, Stdio};
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{mpsc, Arc};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::Duration;
+
+use crate::config::LlmConfig;
+#[cfg(unix)]
+use crate::gateway::Gateway;
+use crate::interrupt;
+use crate::progress;
 
 #[derive(Debug)]
 pub struct Summarizer {
+    provider: Provider,
     batch_size: usize,
     parallel: usize,
+    max_fallback_requests: usize,
     debug: bool,
 }
 
-#[derive(Debug)]
+/// Where prompts are sent, from `llm.provider`
+#[derive(Debug, Clone)]
+enum Provider {
+    /// The `claude` CLI in print mode
+    Cli,
+    /// An LLM gateway on a Unix socket (see `gateway`)
+    #[cfg(unix)]
+    Gateway { socket_path: PathBuf, model: String, timeout: Duration },
+}
+
+impl Provider {
+    fn from_config(llm: &LlmConfig) -> Result<Self, String> {
+        if llm.provider != "gateway" {
+            return Ok(Provider::Cli);
+        }
+        #[cfg(unix)]
+        {
+            let socket_path = llm.socket_path.clone().ok_or("llm.provider is \"gateway\" but llm.socket_path is not set")?;
+            Ok(Provider::Gateway { socket_path, model: llm.model.clone(), timeout: Duration::from_secs(llm.timeout_secs) })
+        }
+        #[cfg(not(unix))]
+        Err("gateway provider requires a Unix socket".to_string())
+    }
+
+    /// A client for one worker; a gateway client keeps its connection between requests
+    fn client(&self) -> Client {
+        match self {
+            Provider::Cli => Client::Cli,
+            #[cfg(unix)]
+            Provider::Gateway { socket_path, model, timeout } => {
+                Client::Gateway(Gateway::new(socket_path.clone(), model.clone(), *timeout))
+            }
+        }
+    }
+}
+
+/// Canned provider for tests: answers each prompt with the closure's result
+#[cfg(test)]
+type MockComplete = Box<dyn FnMut(&str) -> Result<String, SummarizerError> + Send>;
+
+enum Client {
+    Cli,
+    #[cfg(unix)]
+    Gateway(Gateway),
+    #[cfg(test)]
+    Mock(MockComplete),
+}
+
+impl Client {
+    fn complete(&mut self, prompt: &str) -> Result<String, SummarizerError> {
+        match self {
+            Client::Cli => call_claude(prompt),
+            #[cfg(unix)]
+            Client::Gateway(gateway) => gateway.complete(prompt),
+            #[cfg(test)]
+            Client::Mock(complete) => complete(prompt),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum SummarizerError {
     CommandFailed(String),
     IoError(String),
+    /// The LLM gateway could not be reached or answered with an error
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Gateway(String),
+    /// A batch response left out this function's summary
+    MissingFromBatch,
+    /// Ctrl-C was pressed before or while the request ran
+    Interrupted,
 }
 
 impl std::fmt::Display for SummarizerError {
@@ -22,6 +100,9 @@ impl std::fmt::Display for SummarizerError {
         match self {
             Self::CommandFailed(msg) => write!(f, "claude command failed: {msg}"),
             Self::IoError(msg) => write!(f, "IO error: {msg}"),
+            Self::Gateway(msg) => write!(f, "gateway error: {msg}"),
+            Self::MissingFromBatch => write!(f, "missing from the batch response"),
+            Self::Interrupted => write!(f, "interrupted"),
         }
     }
 }
@@ -42,6 +123,20 @@ pub struct SummaryRequest {
     pub body: String,
     /// Summaries of callees to include as context (callee_name -> summary)
     pub callee_context: Vec<(String, String)>,
+    /// Callees without a summary (unresolved, external, or not yet summarized), as written in source
+    pub raw_callees: Vec<String>,
+    /// The function's doc comment, shown as a hint
+    pub doc: Option<String>,
+}
+
+/// A package to roll up from its function summaries
+#[derive(Debug, Clone)]
+pub struct PackageRequest {
+    /// Package directory, e.g. "./internal/store"
+    pub name: String,
+    pub files: Vec<String>,
+    /// Function names and summaries, public API first
+    pub functions: Vec<(String, String)>,
 }
 
 /// Result of summarization
@@ -49,109 +144,189 @@ pub struct SummaryRequest {
 pub struct SummaryResult {
     pub id: usize,
     pub summary: Result<String, SummarizerError>,
+    /// Asked for on its own after its batch failed twice
+    pub fallback: bool,
 }
 
 impl Summarizer {
-    pub fn new(batch_size: usize, parallel: usize, debug: bool) -> Self {
-        Self {
-            batch_size: batch_size.max(1),
-            parallel: parallel.max(1),
+    /// Summarizer for the provider in `llm`, or an error if its settings are incomplete
+    pub fn new(llm: &LlmConfig, debug: bool) -> Result<Self, String> {
+        Ok(Self {
+            provider: Provider::from_config(llm)?,
+            batch_size: llm.batch_size.max(1),
+            parallel: llm.parallel.max(1),
+            max_fallback_requests: llm.max_fallback_requests,
             debug,
-        }
+        })
     }
 
-    /// Summarize multiple functions with batching and parallelism
-    pub fn summarize_batch(&self, requests: Vec<SummaryRequest>) -> Vec<SummaryResult> {
-        if requests.is_empty() {
-            return Vec::new();
-        }
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
 
-        // Group requests into batches
-        let batches: Vec<Vec<SummaryRequest>> = requests
-            .chunks(self.batch_size)
-            .map(|chunk| chunk.to_vec())
-            .collect();
+    pub fn parallel(&self) -> usize {
+        self.parallel
+    }
 
-        let total_batches = batches.len();
+    /// Start `parallel` workers that summarize batches as they are submitted
+    pub fn start(&self) -> SummaryPool {
+        let (batch_tx, batch_rx) = mpsc::channel::<Vec<SummaryRequest>>();
+        let (result_tx, result_rx) = mpsc::channel();
+        let batch_rx = Arc::new(Mutex::new(batch_rx));
         let completed_batches = Arc::new(AtomicUsize::new(0));
+        let fallback_budget = Arc::new(AtomicUsize::new(self.max_fallback_requests));
 
-        // Process batches in parallel using threads
-        let (tx, rx) = mpsc::channel();
-        let mut handles = Vec::new();
-        let debug = self.debug;
-
-        // Semaphore-like behavior: process `parallel` batches at a time
-        for batch_chunk in batches.chunks(self.parallel) {
-            let batch_chunk: Vec<Vec<SummaryRequest>> = batch_chunk.to_vec();
-
-            for batch in batch_chunk {
-                let tx = tx.clone();
+        let workers = (0..self.parallel)
+            .map(|_| {
+                let batch_rx = Arc::clone(&batch_rx);
+                let result_tx = result_tx.clone();
                 let completed = Arc::clone(&completed_batches);
-                let handle = thread::spawn(move || {
-                    let results = process_batch(batch, debug, completed, total_batches);
-                    for result in results {
-                        let _ = tx.send(result);
+                let fallback_budget = Arc::clone(&fallback_budget);
+                let debug = self.debug;
+                let mut client = self.provider.client();
+                thread::spawn(move || {
+                    loop {
+                        // Hold the lock only while waiting for the next batch
+                        let next = batch_rx.lock().map(|rx| rx.recv());
+                        let Ok(Ok(batch)) = next else {
+                            break;
+                        };
+                        let results = if interrupt::is_interrupted() {
+                            interrupted_results(&batch)
+                        } else {
+                            process_batch(&mut client, batch, debug, &completed, &fallback_budget)
+                        };
+                        for result in results {
+                            let _ = result_tx.send(result);
+                        }
                     }
-                });
-                handles.push(handle);
-            }
+                })
+            })
+            .collect();
 
-            // Wait for this chunk of parallel batches to complete
-            for handle in handles.drain(..) {
-                let _ = handle.join();
-            }
+        SummaryPool { batches: Some(batch_tx), results: result_rx, workers }
+    }
+
+    /// Summarize one package from its files and function summaries
+    pub fn summarize_package(&self, req: &PackageRequest) -> Result<String, SummarizerError> {
+        if interrupt::is_interrupted() {
+            return Err(SummarizerError::Interrupted);
         }
+        let prompt = build_package_prompt(req);
+        let result = self.provider.client().complete(&prompt);
+        if self.debug {
+            let response_str = match &result {
+                Ok(resp) => resp.clone(),
+                Err(e) => format!("[ERROR] {}", e),
+            };
+            eprintln!(
+                "\n{}\n[Package {}]\nPROMPT:\n{}\n{}\nRESPONSE:\n{}\n{}\n",
+                "=".repeat(60),
+                req.name,
+                prompt,
+                "-".repeat(40),
+                response_str,
+                "=".repeat(60),
+            );
+        }
+        result
+    }
+}
+
+/// Running summarization workers: batches go in, per-function results come out
+pub struct SummaryPool {
+    batches: Option<mpsc::Sender<Vec<SummaryRequest>>>,
+    results: mpsc::Receiver<SummaryResult>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl SummaryPool {
+    /// Queue a batch for the next idle worker
+    pub fn submit(&self, batch: Vec<SummaryRequest>) {
+        if let Some(batches) = &self.batches {
+            let _ = batches.send(batch);
+        }
+    }
+
+    /// Wait for the next result; None once all workers have exited
+    pub fn recv(&self) -> Option<SummaryResult> {
+        self.results.recv().ok()
+    }
 
-        drop(tx);
+    /// Next result if one is already available
+    pub fn try_recv(&self) -> Option<SummaryResult> {
+        self.results.try_recv().ok()
+    }
 
-        // Collect all results
-        rx.into_iter().collect()
+    /// Stop accepting batches and wait for the workers to exit
+    pub fn finish(mut self) {
+        self.batches.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
     }
 }
 
-/// Process a batch of functions, returning individual results
+fn interrupted_results(batch: &[SummaryRequest]) -> Vec<SummaryResult> {
+    batch
+        .iter()
+        .map(|req| SummaryResult { id: req.id, summary: Err(SummarizerError::Interrupted), fallback: false })
+        .collect()
+}
+
+/// Process a batch of functions, returning individual results. A failed request is sent once
+/// more; members of a batch still without a summary then get a prompt of their own, while
+/// `fallback_budget` lasts, so a provider outage does not multiply the requests sent.
 fn process_batch(
+    client: &mut Client,
     batch: Vec<SummaryRequest>,
     debug: bool,
-    completed: Arc<AtomicUsize>,
-    total_batches: usize,
+    completed: &AtomicUsize,
+    fallback_budget: &AtomicUsize,
 ) -> Vec<SummaryResult> {
     let batch_num = completed.fetch_add(1, Ordering::SeqCst) + 1;
 
     if batch.len() == 1 {
         // Single function - simple prompt
         let req = &batch[0];
-        let prompt = build_single_prompt(&req.signature, &req.body, &req.callee_context);
-        let result = call_claude(&prompt);
-
-        if debug {
-            let response_str = match &result {
-                Ok(resp) => resp.clone(),
-                Err(e) => format!("[ERROR] {}", e),
-            };
-            eprintln!(
-                "\n{}\n[Batch {}/{}]\n{}\n{}\n{}\n{}\n",
-                "=".repeat(60),
-                batch_num,
-                total_batches,
-                format!("PROMPT (single):\n{}", prompt),
-                "-".repeat(40),
-                format!("RESPONSE:\n{}", response_str),
-                "=".repeat(60),
-            );
-        } else {
-            eprint!("\r  Batch {}/{}", batch_num, total_batches);
+        let prompt = build_single_prompt(&req.signature, &req.body, req.doc.as_deref(), &req.callee_context, &req.raw_callees);
+        let mut summary = send(client, &prompt, debug, batch_num, "single");
+        if is_retryable(&summary) {
+            summary = send(client, &prompt, debug, batch_num, "single, retry");
         }
-
-        return vec![SummaryResult {
-            id: req.id,
-            summary: result,
-        }];
+        return vec![SummaryResult { id: req.id, summary, fallback: false }];
     }
 
     // Multiple functions - batch prompt with structured output
     let prompt = build_batch_prompt(&batch);
-    let result = call_claude(&prompt);
+    let response = send(client, &prompt, debug, batch_num, &format!("batch of {}", batch.len()));
+    let mut results = batch_results(&batch, response);
+    if results.iter().all(|r| is_retryable(&r.summary)) {
+        let response = send(client, &prompt, debug, batch_num, &format!("batch of {}, retry", batch.len()));
+        results = batch_results(&batch, response);
+    }
+
+    for (req, result) in batch.iter().zip(&mut results) {
+        let take_budget = |n: usize| n.checked_sub(1);
+        if !is_retryable(&result.summary) || fallback_budget.fetch_update(Ordering::SeqCst, Ordering::SeqCst, take_budget).is_err() {
+            continue;
+        }
+        let prompt = build_single_prompt(&req.signature, &req.body, req.doc.as_deref(), &req.callee_context, &req.raw_callees);
+        result.summary = send(client, &prompt, debug, batch_num, "single, fallback");
+        result.fallback = true;
+    }
+    results
+}
+
+/// Send one prompt, printing it with the response in debug mode
+fn send(
+    client: &mut Client,
+    prompt: &str,
+    debug: bool,
+    batch_num: usize,
+    kind: &str,
+) -> Result<String, SummarizerError> {
+    let result = client.complete(prompt);
 
     if debug {
         let response_str = match &result {
@@ -159,35 +334,47 @@ fn process_batch(
             Err(e) => format!("[ERROR] {}", e),
         };
         eprintln!(
-            "\n{}\n[Batch {}/{}]\n{}\n{}\n{}\n{}\n",
+            "\n{}\n[Batch {}]\nPROMPT ({}):\n{}\n{}\nRESPONSE:\n{}\n{}\n",
             "=".repeat(60),
             batch_num,
-            total_batches,
-            format!("PROMPT (batch of {}):\n{}", batch.len(), prompt),
+            kind,
+            prompt,
             "-".repeat(40),
-            format!("RESPONSE:\n{}", response_str),
+            response_str,
             "=".repeat(60),
         );
-    } else {
-        eprint!("\r  Batch {}/{}", batch_num, total_batches);
+    } else if !progress::is_json() {
+        eprint!("\r  Batch {}", batch_num);
     }
+    result
+}
 
-    match result {
-        Ok(response) => parse_batch_response(&batch, &response),
+/// Per-function results of a batch response; a failed request fails every member
+fn batch_results(batch: &[SummaryRequest], response: Result<String, SummarizerError>) -> Vec<SummaryResult> {
+    match response {
+        Ok(response) => parse_batch_response(batch, &response),
         Err(e) => {
             // If batch fails, return error for all
             batch
                 .iter()
-                .map(|req| SummaryResult {
-                    id: req.id,
-                    summary: Err(SummarizerError::CommandFailed(e.to_string())),
-                })
+                .map(|req| SummaryResult { id: req.id, summary: Err(e.clone()), fallback: false })
                 .collect()
         }
     }
 }
 
-fn build_single_prompt(signature: &str, body: &str, callee_context: &[(String, String)]) -> String {
+/// Whether a failed request is worth sending again; an interrupted one is not
+fn is_retryable(result: &Result<String, SummarizerError>) -> bool {
+    matches!(result, Err(e) if !matches!(e, SummarizerError::Interrupted)) && !interrupt::is_interrupted()
+}
+
+pub fn build_single_prompt(
+    signature: &str,
+    body: &str,
+    doc: Option<&str>,
+    callee_context: &[(String, String)],
+    raw_callees: &[String],
+) -> String {
     let mut prompt = String::from(
         "Summarize what this function does in 1-2 sentences. \
          Focus on behavior, not implementation details. \
@@ -195,16 +382,34 @@ fn build_single_prompt(signature: &str, body: &str, callee_context: &[(String, S
          Reply with ONLY the summary, no preamble.\n\n",
     );
 
+    push_doc(&mut prompt, doc);
+    push_callees(&mut prompt, callee_context, raw_callees);
+
+    prompt.push_str(&format!("Function: {signature}\nBody:\n{body}"));
+    prompt
+}
+
+/// The doc comment as background: the summary should say what it leaves out, not restate it
+fn push_doc(prompt: &mut String, doc: Option<&str>) {
+    if let Some(doc) = doc {
+        prompt.push_str(&format!("Its doc comment (context only):\n{doc}\n\n"));
+    }
+}
+
+/// Callee summaries, then the names of callees without one so the model still sees what is invoked
+fn push_callees(prompt: &mut String, callee_context: &[(String, String)], raw_callees: &[String]) {
     if !callee_context.is_empty() {
         prompt.push_str("This function calls:\n");
         for (name, summary) in callee_context {
             prompt.push_str(&format!("- {name}(): \"{summary}\"\n"));
         }
+    }
+    if !raw_callees.is_empty() {
+        prompt.push_str(&format!("Also calls: {} (no summary available)\n", raw_callees.join(", ")));
+    }
+    if !callee_context.is_empty() || !raw_callees.is_empty() {
         prompt.push('\n');
     }
-
-    prompt.push_str(&format!("Function: {signature}\nBody:\n{body}"));
-    prompt
 }
 
 fn build_batch_prompt(batch: &[SummaryRequest]) -> String {
@@ -220,13 +425,8 @@ fn build_batch_prompt(batch: &[SummaryRequest]) -> String {
     for (i, req) in batch.iter().enumerate() {
         prompt.push_str(&format!("=== Function {} ===\n", i + 1));
 
-        if !req.callee_context.is_empty() {
-            prompt.push_str("This function calls:\n");
-            for (name, summary) in &req.callee_context {
-                prompt.push_str(&format!("- {name}(): \"{summary}\"\n"));
-            }
-            prompt.push('\n');
-        }
+        push_doc(&mut prompt, req.doc.as_deref());
+        push_callees(&mut prompt, &req.callee_context, &req.raw_callees);
 
         prompt.push_str(&format!("{}\n{}\n\n", req.signature, req.body));
     }
@@ -234,6 +434,25 @@ fn build_batch_prompt(batch: &[SummaryRequest]) -> String {
     prompt
 }
 
+fn build_package_prompt(req: &PackageRequest) -> String {
+    let mut prompt = String::from(
+        "Summarize what this package is responsible for in 2-4 sentences, \
+         so a reader new to the codebase knows when to look here. \
+         Focus on its role and main entry points, not individual functions. \
+         Reply with ONLY the summary, no preamble.\n\n",
+    );
+
+    prompt.push_str(&format!("Package: {}\nFiles:\n", req.name));
+    for file in &req.files {
+        prompt.push_str(&format!("- {file}\n"));
+    }
+    prompt.push_str("\nFunctions:\n");
+    for (name, summary) in &req.functions {
+        prompt.push_str(&format!("- {name}(): \"{summary}\"\n"));
+    }
+    prompt
+}
+
 fn parse_batch_response(batch: &[SummaryRequest], response: &str) -> Vec<SummaryResult> {
     let mut results = Vec::new();
 
@@ -243,11 +462,13 @@ fn parse_batch_response(batch: &[SummaryRequest], response: &str) -> Vec<Summary
             .lines()
             .find(|line| line.starts_with(&marker))
             .map(|line| line[marker.len()..].trim().to_string())
-            .unwrap_or_else(|| format!("(failed to parse summary for function {})", i + 1));
+            .filter(|summary| !summary.is_empty())
+            .ok_or(SummarizerError::MissingFromBatch);
 
         results.push(SummaryResult {
             id: req.id,
-            summary: Ok(summary),
+            summary,
+            fallback: false,
         });
     }
 
@@ -268,23 +489,53 @@ fn call_claude(prompt: &str) -> Result<String, SummarizerError> {
         stdin.write_all(prompt.as_bytes())?;
     }
 
-    let output = child.wait_with_output()?;
+    // Drain output on background threads so the child never blocks on a full pipe
+    let stdout = spawn_reader(child.stdout.take());
+    let stderr = spawn_reader(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if interrupt::is_interrupted() {
+            kill_child(&mut child);
+            return Err(SummarizerError::Interrupted);
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    if status.success() {
+        Ok(String::from_utf8_lossy(&stdout).trim().to_string())
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(SummarizerError::CommandFailed(stderr.to_string()))
+        Err(SummarizerError::CommandFailed(String::from_utf8_lossy(&stderr).to_string()))
     }
 }
 
+fn spawn_reader<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn kill_child(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_build_single_prompt() {
-        let prompt = build_single_prompt("func Foo(x int) int", "{ return x * 2 }", &[]);
+        let prompt = build_single_prompt("func Foo(x int) int", "{ return x * 2 }", None, &[], &[]);
         assert!(prompt.contains("func Foo"));
         assert!(prompt.contains("return x * 2"));
         assert!(!prompt.contains("This function calls"));
@@ -296,12 +547,37 @@ mod tests {
             ("helper".to_string(), "Does a helper thing".to_string()),
             ("util".to_string(), "Utility function".to_string()),
         ];
-        let prompt = build_single_prompt("func Foo(x int) int", "{ return x * 2 }", &context);
+        let prompt = build_single_prompt("func Foo(x int) int", "{ return x * 2 }", None, &context, &[]);
         assert!(prompt.contains("This function calls:"));
         assert!(prompt.contains("helper(): \"Does a helper thing\""));
         assert!(prompt.contains("util(): \"Utility function\""));
     }
 
+    #[test]
+    fn test_build_single_prompt_with_raw_callees() {
+        let context = vec![("helper".to_string(), "Does a helper thing".to_string())];
+        let raw = vec!["s.store.Get".to_string(), "validate_token".to_string()];
+        let prompt = build_single_prompt("func Foo()", "{}", None, &context, &raw);
+        assert!(prompt.contains("helper(): \"Does a helper thing\"\nAlso calls: s.store.Get, validate_token (no summary available)\n\n"));
+
+        let prompt = build_single_prompt("func Foo()", "{}", None, &[], &raw);
+        assert!(!prompt.contains("This function calls"));
+        assert!(prompt.contains("Also calls: s.store.Get, validate_token"));
+    }
+
+    #[test]
+    fn test_prompts_include_doc_comment() {
+        let prompt = build_single_prompt("func Foo()", "{}", Some("Foo frobs."), &[], &[]);
+        assert!(prompt.contains("Do not repeat documentation comments."));
+        assert!(prompt.contains("Its doc comment (context only):\nFoo frobs.\n\nFunction: func Foo()"));
+
+        let mut req = request(0, "Bar");
+        req.doc = Some("Bar bars.".to_string());
+        let prompt = build_batch_prompt(&[req, request(1, "Baz")]);
+        assert_eq!(prompt.matches("Its doc comment").count(), 1);
+        assert!(prompt.contains("=== Function 1 ===\nIts doc comment (context only):\nBar bars.\n\nfunc Bar()"));
+    }
+
     #[test]
     fn test_build_batch_prompt() {
         let batch = vec![
@@ -310,12 +586,16 @@ mod tests {
                 signature: "func A()".to_string(),
                 body: "{}".to_string(),
                 callee_context: vec![],
+                raw_callees: vec![],
+                doc: None,
             },
             SummaryRequest {
                 id: 1,
                 signature: "func B()".to_string(),
                 body: "{}".to_string(),
                 callee_context: vec![("helper".to_string(), "Helps".to_string())],
+                raw_callees: vec![],
+                doc: None,
             },
         ];
         let prompt = build_batch_prompt(&batch);
@@ -325,6 +605,19 @@ mod tests {
         assert!(prompt.contains("helper(): \"Helps\""));
     }
 
+    #[test]
+    fn test_build_package_prompt() {
+        let req = PackageRequest {
+            name: "./internal/store".to_string(),
+            files: vec!["./internal/store/store.go".to_string()],
+            functions: vec![("store.Open".to_string(), "Opens the store".to_string())],
+        };
+        let prompt = build_package_prompt(&req);
+        assert!(prompt.contains("Package: ./internal/store"));
+        assert!(prompt.contains("- ./internal/store/store.go"));
+        assert!(prompt.contains("store.Open(): \"Opens the store\""));
+    }
+
     #[test]
     fn test_parse_batch_response() {
         let batch = vec![
@@ -333,12 +626,16 @@ mod tests {
                 signature: "func A()".to_string(),
                 body: "{}".to_string(),
                 callee_context: vec![],
+                raw_callees: vec![],
+                doc: None,
             },
             SummaryRequest {
                 id: 1,
                 signature: "func B()".to_string(),
                 body: "{}".to_string(),
                 callee_context: vec![],
+                raw_callees: vec![],
+                doc: None,
             },
         ];
         let response = "[1]: Does thing A\n[2]: Does thing B";
@@ -350,4 +647,80 @@ mod tests {
         assert_eq!(results[1].id, 1);
         assert_eq!(results[1].summary.as_ref().unwrap(), "Does thing B");
     }
+
+    fn request(id: usize, name: &str) -> SummaryRequest {
+        SummaryRequest {
+            id,
+            signature: format!("func {name}()"),
+            body: "{}".to_string(),
+            callee_context: vec![],
+            raw_callees: vec![],
+            doc: None,
+        }
+    }
+
+    /// A provider that answers batch prompts with `batch_reply` and single prompts with a summary
+    fn mock_client(batch_reply: Result<&'static str, ()>, prompts: Arc<Mutex<Vec<String>>>) -> Client {
+        Client::Mock(Box::new(move |prompt: &str| {
+            prompts.lock().unwrap().push(prompt.to_string());
+            if prompt.contains("=== Function 1 ===") {
+                return batch_reply.map(String::from).map_err(|_| SummarizerError::Gateway("timed out".to_string()));
+            }
+            let name = prompt.split("Function: func ").nth(1).and_then(|s| s.split('(').next()).unwrap();
+            Ok(format!("Summary of {name}."))
+        }))
+    }
+
+    #[test]
+    fn test_failed_batch_retried_then_falls_back_to_singles() {
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let mut client = mock_client(Err(()), Arc::clone(&prompts));
+        let batch = vec![request(0, "A"), request(1, "B"), request(2, "C")];
+        let budget = AtomicUsize::new(2);
+
+        let results = process_batch(&mut client, batch, false, &AtomicUsize::new(0), &budget);
+        let summaries: Vec<_> = results.iter().map(|r| (r.summary.as_deref().ok(), r.fallback)).collect();
+        assert_eq!(summaries, vec![(Some("Summary of A."), true), (Some("Summary of B."), true), (None, false)]);
+        assert_eq!(results[2].summary.as_ref().unwrap_err().to_string(), "gateway error: timed out");
+
+        // The batch went out twice, then one single prompt per function the budget allowed
+        let prompts = prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 4);
+        assert!(prompts[0].contains("=== Function 1 ===") && prompts[1] == prompts[0]);
+        assert_eq!(budget.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_members_missing_from_batch_response_fall_back() {
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let mut client = mock_client(Ok("[1]: Does A\n[3]:"), Arc::clone(&prompts));
+        let batch = vec![request(0, "A"), request(1, "B"), request(2, "C")];
+
+        let results = process_batch(&mut client, batch, false, &AtomicUsize::new(0), &AtomicUsize::new(10));
+        let summaries: Vec<_> = results.iter().map(|r| (r.summary.as_deref().unwrap(), r.fallback)).collect();
+        assert_eq!(summaries, vec![("Does A", false), ("Summary of B.", true), ("Summary of C.", true)]);
+        // A partly parsed response is not sent again
+        assert_eq!(prompts.lock().unwrap().len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_gateway_provider_needs_socket_path() {
+        let mut llm = LlmConfig { provider: "gateway".to_string(), ..Default::default() };
+        assert_eq!(
+            Provider::from_config(&llm).unwrap_err(),
+            "llm.provider is \"gateway\" but llm.socket_path is not set"
+        );
+
+        llm.socket_path = Some(PathBuf::from("/run/llm.sock"));
+        assert!(matches!(Provider::from_config(&llm), Ok(Provider::Gateway { .. })));
+        assert!(matches!(Provider::from_config(&LlmConfig::default()), Ok(Provider::Cli)));
+    }
+
+    #[cfg(not(unix))]
+    #[test]
+    fn test_gateway_provider_needs_unix() {
+        let llm = LlmConfig { provider: "gateway".to_string(), socket_path: Some("llm.sock".into()), ..Default::default() };
+        assert_eq!(Provider::from_config(&llm).unwrap_err(), "gateway provider requires a Unix socket");
+    }
 }
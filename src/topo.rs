@@ -21,6 +21,33 @@ pub fn hierarchy(
     group_functions_by_level(&funcs, &func_to_scc, &scc_levels)
 }
 
+/// Returns every function from which one of `targets` is reachable, targets included.
+///
+/// Walks the reversed call graph breadth-first, so cycles are visited once.
+pub fn reaching(
+    targets: &HashSet<String>,
+    calls: &HashMap<String, HashSet<String>>,
+) -> HashSet<String> {
+    let mut callers: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (caller, callees) in calls {
+        for callee in callees {
+            callers.entry(callee.as_str()).or_default().push(caller.as_str());
+        }
+    }
+
+    let mut seen: HashSet<String> = targets.clone();
+    let mut queue: VecDeque<&str> = targets.iter().map(String::as_str).collect();
+    while let Some(func) = queue.pop_front() {
+        for &caller in callers.get(func).into_iter().flatten() {
+            if seen.insert(caller.to_string()) {
+                queue.push_back(caller);
+            }
+        }
+    }
+
+    seen
+}
+
 // ---------------------------------------------------------------------------
 // Internal helpers
 // ---------------------------------------------------------------------------
@@ -212,8 +239,8 @@ fn assign_scc_levels(num_sccs: usize, dag: &SccDag) -> Vec<usize> {
     let mut levels: Vec<usize> = vec![0; num_sccs];
     let mut queue: VecDeque<usize> = VecDeque::new();
 
-    for scc_idx in 0..num_sccs {
-        if out_degree[scc_idx] == 0 {
+    for (scc_idx, &degree) in out_degree.iter().enumerate() {
+        if degree == 0 {
             queue.push_back(scc_idx);
         }
     }
@@ -386,6 +413,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reaching_diamond() {
+        // A -> B, A -> C, B -> D, C -> D, C -> E
+        let c = edges(&[
+            ("A", &["B", "C"]),
+            ("B", &["D"]),
+            ("C", &["D", "E"]),
+        ]);
+
+        assert_eq!(reaching(&funcs(&["D"]), &c), funcs(&["A", "B", "C", "D"]));
+        assert_eq!(reaching(&funcs(&["E"]), &c), funcs(&["A", "C", "E"]));
+        assert_eq!(reaching(&funcs(&["A"]), &c), funcs(&["A"]));
+    }
+
+    #[test]
+    fn test_reaching_through_cycles() {
+        // A -> B -> C -> B, C -> D, E -> E, F -> G
+        let c = edges(&[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("C", &["B", "D"]),
+            ("E", &["E"]),
+            ("F", &["G"]),
+        ]);
+
+        assert_eq!(reaching(&funcs(&["D"]), &c), funcs(&["A", "B", "C", "D"]));
+        assert_eq!(reaching(&funcs(&["B"]), &c), funcs(&["A", "B", "C"]));
+        assert_eq!(reaching(&funcs(&["E"]), &c), funcs(&["E"]));
+        assert_eq!(reaching(&funcs(&["D", "G"]), &c), funcs(&["A", "B", "C", "D", "F", "G"]));
+    }
+
     #[test]
     fn test_calls_to_unknown_functions_ignored() {
         // B calls Z which isn't in the function set